@@ -0,0 +1,217 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of the frontend's `user_provider`, such as
+//! `static_user_provider:file:/etc/greptimedb/users`.
+
+use crate::common::CheckDetail;
+use std::collections::HashSet;
+
+const STATIC_PROVIDER: &str = "static_user_provider";
+const WATCH_FILE_PROVIDER: &str = "watch_file_user_provider";
+
+/// Check that the user provider names a supported provider and that its users parse with
+/// non-empty passwords; a user file must also not be readable by other users
+pub fn check_user_provider(provider: &str) -> Vec<CheckDetail> {
+    let item = "User Provider".to_string();
+    let (kind, source) = provider.split_once(':').unwrap_or((provider, ""));
+    let source = match kind {
+        STATIC_PROVIDER => source.split_once(':'),
+        WATCH_FILE_PROVIDER => Some(("file", source)),
+        _ => {
+            return vec![CheckDetail::fail(
+                item,
+                format!("Unknown user provider '{}'", kind),
+                None,
+                Some(format!("Use {}:file:<path>, {}:cmd:<user>=<password> or {}:<path>", STATIC_PROVIDER, STATIC_PROVIDER, WATCH_FILE_PROVIDER)),
+            )];
+        }
+    };
+
+    match source {
+        Some(("file", path)) if !path.is_empty() => check_user_file(path),
+        Some(("cmd", users)) if !users.is_empty() => {
+            let mut details = check_users(&item, users.split(',').map(str::trim).enumerate(), "entry");
+            details.push(CheckDetail::warning(
+                "User Provider Source".to_string(),
+                "Passwords given on the command line are visible to every user through the process list".to_string(),
+                None,
+                Some(format!("Move the users to a file and use {}:file:<path>", STATIC_PROVIDER)),
+            ));
+            details
+        }
+        _ => vec![CheckDetail::fail(
+            item,
+            format!("'{}' does not name a user source", provider),
+            None,
+            Some(format!("Use {}:file:<path> or {}:cmd:<user>=<password>", STATIC_PROVIDER, STATIC_PROVIDER)),
+        )],
+    }
+}
+
+fn check_user_file(path: &str) -> Vec<CheckDetail> {
+    let item = format!("User File ({})", path);
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            return vec![CheckDetail::fail(
+                item,
+                format!("Cannot read {}: {}", path, e),
+                None,
+                Some("Create the user file, or fix the path in user_provider".to_string()),
+            )];
+        }
+    };
+
+    let lines = content
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let mut details = check_users(&item, lines, "line");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Ok(metadata) = std::fs::metadata(path) {
+            let mode = metadata.permissions().mode();
+            // Unlike other credential files, a password file should not be shared with the group either
+            if mode & 0o077 != 0 {
+                details.push(CheckDetail::warning(
+                    "User File Permissions".to_string(),
+                    format!("{} holds passwords and grants access to its group or all users (mode {:o})", path, mode & 0o777),
+                    None,
+                    Some(format!("Restrict it with: chmod 600 {}", path)),
+                ));
+            } else {
+                details.push(CheckDetail::pass(
+                    "User File Permissions".to_string(),
+                    format!("{} is only accessible to its owner", path),
+                    None,
+                ));
+            }
+        }
+    }
+    details
+}
+
+/// Check `user=password` entries, where the user may carry a permission suffix such as
+/// `reader:ro`. `entries` yields each entry with its zero-based position.
+fn check_users<'a>(item: &str, entries: impl Iterator<Item = (usize, &'a str)>, unit: &str) -> Vec<CheckDetail> {
+    let mut details = Vec::new();
+    let mut users = HashSet::new();
+
+    for (index, entry) in entries {
+        let Some((user, password)) = entry.split_once('=') else {
+            details.push(CheckDetail::fail(
+                item.to_string(),
+                format!("{} {} is not in user=password form", unit, index + 1),
+                None,
+                Some("Write one user per entry as user=password".to_string()),
+            ));
+            continue;
+        };
+        let user = user.split_once(':').map_or(user, |(name, _)| name).trim();
+        let password = password.trim();
+        crate::redact::register_secret(password);
+
+        if user.is_empty() {
+            details.push(CheckDetail::fail(
+                item.to_string(),
+                format!("{} {} has an empty user name", unit, index + 1),
+                None,
+                Some("Give every entry a user name".to_string()),
+            ));
+        } else if password.is_empty() {
+            details.push(CheckDetail::fail(
+                item.to_string(),
+                format!("User '{}' has an empty password", user),
+                None,
+                Some(format!("Set a password for '{}'", user)),
+            ));
+        } else if !users.insert(user.to_string()) {
+            details.push(CheckDetail::warning(
+                item.to_string(),
+                format!("User '{}' is defined more than once", user),
+                None,
+                Some("Remove the duplicate; only one of the passwords takes effect".to_string()),
+            ));
+        }
+    }
+
+    if users.is_empty() && details.is_empty() {
+        details.push(CheckDetail::fail(
+            item.to_string(),
+            "No users defined, so no client can log in".to_string(),
+            None,
+            Some("Add at least one user=password entry".to_string()),
+        ));
+    } else if details.is_empty() {
+        details.push(CheckDetail::pass(item.to_string(), format!("{} users defined", users.len()), None));
+    }
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_user_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users");
+        std::fs::write(&path, "# users\nadmin=s3cr3t-admin\nreader:ro=\n\nadmin=other\nbroken\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        let provider = format!("static_user_provider:file:{}", path.display());
+        let messages = check_user_provider(&provider)
+            .into_iter()
+            .map(|detail| (detail.status, detail.message))
+            .collect::<Vec<_>>();
+        assert_eq!(messages[0], (CheckStatus::Fail, "User 'reader' has an empty password".to_string()));
+        assert_eq!(messages[1], (CheckStatus::Warning, "User 'admin' is defined more than once".to_string()));
+        assert_eq!(messages[2], (CheckStatus::Fail, "line 6 is not in user=password form".to_string()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            assert_eq!(messages[3].0, CheckStatus::Warning);
+            assert!(messages[3].1.contains("grants access to its group or all users (mode 644)"), "{}", messages[3].1);
+            // Group access alone is reported too
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+            let details = check_user_provider(&provider);
+            assert_eq!(details.last().unwrap().status, CheckStatus::Warning);
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+            let details = check_user_provider(&provider);
+            assert_eq!(details.last().unwrap().status, CheckStatus::Pass);
+        }
+    }
+
+    #[test]
+    fn test_provider_forms() {
+        let details = check_user_provider("static_user_provider:cmd:admin=s3cr3t,reader=r3ad3r");
+        assert_eq!(details[0].status, CheckStatus::Pass);
+        assert_eq!(details[1].status, CheckStatus::Warning);
+
+        let details = check_user_provider("watch_file_user_provider:/nonexistent/users");
+        assert_eq!(details[0].status, CheckStatus::Fail);
+
+        let details = check_user_provider("ldap_user_provider:ldap://host");
+        assert_eq!(details[0].message, "Unknown user provider 'ldap_user_provider'");
+    }
+}
@@ -0,0 +1,562 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks across the configs of one cluster, run when `check-dir` or `k8s-values` sees metasrv
+//! together with frontends or datanodes.
+//!
+//! Each file can be valid on its own and still disagree with the others, e.g. datanodes that
+//! heartbeat less often than metasrv's failure detector expects.
+
+use crate::common::{CheckDetail, CheckResult};
+use crate::config::{DatanodeConfig, FrontendConfig, GrpcConfig, HeartbeatConfig, MetasrvConfig};
+use crate::datanode::providers;
+use crate::lint::{DEFAULT_HEARTBEAT_INTERVAL, FRONTEND_HEARTBEAT_FACTOR, parse_duration, parse_size};
+use crate::network;
+use itertools::Itertools;
+use std::time::Duration;
+
+/// Metasrv defaults for `failure_detector.acceptable_heartbeat_pause` and `node_max_idle_time`
+const DEFAULT_ACCEPTABLE_HEARTBEAT_PAUSE: Duration = Duration::from_secs(10);
+const DEFAULT_NODE_MAX_IDLE_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default for both `grpc.max_recv_message_size` and `grpc.max_send_message_size`
+const DEFAULT_GRPC_MESSAGE_SIZE: u64 = 512 << 20;
+
+/// GreptimeDB releases whose config format and APIs stepstone knows: 0.12 up to any 1.x
+const MIN_SUPPORTED_VERSION: (u64, u64) = (0, 12);
+const MAX_SUPPORTED_MAJOR: u64 = 1;
+
+const STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// gRPC address a datanode binds, and advertises, when `grpc` leaves it unset
+const DEFAULT_DATANODE_GRPC_ADDR: &str = "127.0.0.1:3001";
+
+/// The component configs of one cluster, each with the file it came from
+#[derive(Debug, Default)]
+pub struct ClusterConfigs {
+    metasrvs: Vec<(String, MetasrvConfig)>,
+    frontends: Vec<(String, FrontendConfig)>,
+    datanodes: Vec<(String, DatanodeConfig)>,
+    probe_datanodes: bool,
+    deployed: bool,
+}
+
+impl ClusterConfigs {
+    /// Create an empty cluster. With `probe_datanodes`, the advertised gRPC address of each
+    /// datanode is also connected to, which is meaningful when run on the metasrv host.
+    /// `deployed` marks configs rendered for a deployment tool such as the Helm chart, which
+    /// passes the gRPC addresses on the command line, so unset addresses are not checked.
+    pub fn new(probe_datanodes: bool, deployed: bool) -> Self {
+        Self {
+            probe_datanodes,
+            deployed,
+            ..Default::default()
+        }
+    }
+
+    /// Add a component config. One that does not parse is skipped, since its own check already
+    /// reports why.
+    pub fn add(&mut self, source: &str, component: &str, value: &toml::Value) {
+        let source = source.to_string();
+        match component {
+            "metasrv" => self.metasrvs.extend(value.clone().try_into().ok().map(|config| (source, config))),
+            "frontend" => self.frontends.extend(value.clone().try_into().ok().map(|config| (source, config))),
+            "datanode" => self.datanodes.extend(value.clone().try_into().ok().map(|config| (source, config))),
+            _ => {}
+        }
+    }
+
+    /// Whether metasrv and at least one node it manages are present, so there is something to
+    /// compare
+    pub fn is_cluster(&self) -> bool {
+        !self.metasrvs.is_empty() && !(self.frontends.is_empty() && self.datanodes.is_empty())
+    }
+
+    /// Run the cross-component checks
+    pub async fn check(&self) -> CheckResult {
+        let mut details = Vec::new();
+        details.extend(self.check_heartbeats());
+        details.extend(self.check_message_sizes());
+        details.extend(self.check_shared_storage());
+        details.extend(self.check_datanode_addresses().await);
+        details.extend(self.check_versions().await);
+        CheckResult::from_details(details)
+    }
+
+    /// Heartbeats of every node must arrive well within what metasrv tolerates: datanodes within
+    /// the failure detector's acceptable pause, frontends within `node_max_idle_time`
+    fn check_heartbeats(&self) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+
+        for (_, metasrv) in &self.metasrvs {
+            let pause = metasrv
+                .failure_detector
+                .as_ref()
+                .and_then(|detector| detector.acceptable_heartbeat_pause.as_deref())
+                .and_then(parse_duration)
+                .unwrap_or(DEFAULT_ACCEPTABLE_HEARTBEAT_PAUSE);
+            for (source, datanode) in &self.datanodes {
+                let (interval, retry) = heartbeat_timing(datanode.heartbeat.as_ref(), DEFAULT_HEARTBEAT_INTERVAL);
+                let item = format!("Heartbeat vs Failure Detector ({})", source);
+                if interval >= pause {
+                    details.push(CheckDetail::fail(
+                        item,
+                        format!(
+                            "heartbeat.interval ({:?}) is not shorter than metasrv's failure_detector.acceptable_heartbeat_pause ({:?})",
+                            interval, pause
+                        ),
+                        None,
+                        Some("Metasrv would consider the datanode's regions failed during normal operation; lower heartbeat.interval or raise the acceptable pause".to_string()),
+                    ));
+                } else if interval + retry >= pause {
+                    details.push(CheckDetail::warning(
+                        item,
+                        format!(
+                            "heartbeat.interval ({:?}) plus retry_interval ({:?}) reaches metasrv's acceptable heartbeat pause ({:?})",
+                            interval, retry, pause
+                        ),
+                        None,
+                        Some("A single failed heartbeat could trigger region failover; shorten the interval or retry_interval".to_string()),
+                    ));
+                }
+            }
+
+            let max_idle = metasrv
+                .node_max_idle_time
+                .as_deref()
+                .and_then(parse_duration)
+                .unwrap_or(DEFAULT_NODE_MAX_IDLE_TIME);
+            for (source, frontend) in &self.frontends {
+                let (interval, _) = heartbeat_timing(frontend.heartbeat.as_ref(), DEFAULT_HEARTBEAT_INTERVAL * FRONTEND_HEARTBEAT_FACTOR);
+                if interval >= max_idle {
+                    details.push(CheckDetail::fail(
+                        format!("Heartbeat vs Node Idle Time ({})", source),
+                        format!(
+                            "heartbeat.interval ({:?}) is not shorter than metasrv's node_max_idle_time ({:?})",
+                            interval, max_idle
+                        ),
+                        None,
+                        Some("Metasrv would drop the frontend from the cluster between heartbeats; lower heartbeat.interval".to_string()),
+                    ));
+                }
+            }
+        }
+
+        if details.is_empty() {
+            details.push(CheckDetail::pass(
+                "Heartbeat Timing".to_string(),
+                format!(
+                    "Heartbeats of {} datanode(s) and {} frontend(s) fit metasrv's failure detection",
+                    self.datanodes.len(),
+                    self.frontends.len()
+                ),
+                None,
+            ));
+        }
+        details
+    }
+
+    /// What a frontend may send must fit what datanodes and metasrv accept, and what a datanode
+    /// may send back must fit what the frontend accepts. A mismatch only surfaces later, as
+    /// "message too large" errors on big writes or queries.
+    fn check_message_sizes(&self) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+        let peers = self
+            .datanodes
+            .iter()
+            .map(|(source, config)| ("datanode", source, config.grpc.as_ref()))
+            .chain(self.metasrvs.iter().map(|(source, config)| ("metasrv", source, config.grpc.as_ref())));
+
+        for (peer, peer_source, peer_grpc) in peers {
+            let (peer_recv, peer_send) = message_sizes(peer_grpc);
+            for (source, frontend) in &self.frontends {
+                let (recv, send) = message_sizes(frontend.grpc.as_ref());
+                if send > peer_recv {
+                    details.push(CheckDetail::warning(
+                        format!("gRPC Message Size ({} -> {})", source, peer_source),
+                        format!(
+                            "frontend grpc.max_send_message_size ({}) exceeds the {} grpc.max_recv_message_size ({})",
+                            format_size(send), peer, format_size(peer_recv)
+                        ),
+                        None,
+                        Some(format!("Large requests would be rejected by the {} as too large; raise its max_recv_message_size or lower the frontend's max_send_message_size", peer)),
+                    ));
+                }
+                if peer == "datanode" && peer_send > recv {
+                    details.push(CheckDetail::warning(
+                        format!("gRPC Message Size ({} -> {})", peer_source, source),
+                        format!(
+                            "datanode grpc.max_send_message_size ({}) exceeds the frontend grpc.max_recv_message_size ({})",
+                            format_size(peer_send), format_size(recv)
+                        ),
+                        None,
+                        Some("Large query results would be rejected by the frontend; align the two limits".to_string()),
+                    ));
+                }
+            }
+        }
+
+        if details.is_empty() && !self.frontends.is_empty() {
+            details.push(CheckDetail::pass(
+                "gRPC Message Size".to_string(),
+                "Frontend gRPC message size limits fit those of datanodes and metasrv".to_string(),
+                None,
+            ));
+        }
+        details
+    }
+
+    /// Datanodes sharing an object storage bucket and root must keep their WAL remote. With a
+    /// local WAL, a region reopened on another datanode replays nothing and both may write the
+    /// same files; with a remote WAL, shared storage is what lets regions move between datanodes.
+    fn check_shared_storage(&self) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+        let shared = self
+            .datanodes
+            .iter()
+            .filter_map(|(source, datanode)| {
+                let storage = datanode.storage.as_ref()?;
+                // Local directories are per host, so they are never shared
+                let location = providers::location(storage);
+                (location.0 != "file").then_some((location, (source, datanode)))
+            })
+            .into_group_map();
+
+        for ((_, _, bucket, root), datanodes) in shared.into_iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
+            if datanodes.len() < 2 {
+                continue;
+            }
+            let sources = datanodes.iter().map(|(source, _)| source.as_str()).join(", ");
+            let item = format!("Shared Storage ({})", sources);
+            let local_wal = datanodes
+                .iter()
+                .filter(|(_, datanode)| {
+                    let provider = datanode.wal.as_ref().and_then(|wal| wal.provider.as_deref());
+                    !matches!(provider, Some("kafka"))
+                })
+                .map(|(source, _)| source.as_str())
+                .collect::<Vec<_>>();
+            if local_wal.is_empty() {
+                details.push(CheckDetail::info(
+                    item,
+                    format!("{} datanodes share bucket '{}' root '/{}' with a remote WAL, so regions can move between them", datanodes.len(), bucket, root),
+                    None,
+                ));
+            } else {
+                details.push(CheckDetail::warning(
+                    item,
+                    format!(
+                        "{} datanodes share bucket '{}' root '/{}' but {} use a local WAL",
+                        datanodes.len(),
+                        bucket,
+                        root,
+                        local_wal.join(", ")
+                    ),
+                    None,
+                    Some("Use a remote WAL (wal.provider = \"kafka\") with shared storage, or give each datanode its own root; otherwise datanodes can overwrite each other's objects".to_string()),
+                ));
+            }
+        }
+        details
+    }
+
+    /// Metasrv hands each datanode's advertised gRPC address to frontends and other datanodes,
+    /// so it must be reachable from other hosts. A datanode that registers 127.0.0.1 looks healthy
+    /// on its own host and is unreachable from everywhere else.
+    async fn check_datanode_addresses(&self) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+        for (source, datanode) in &self.datanodes {
+            let grpc = datanode.grpc.as_ref();
+            let configured = grpc.and_then(|grpc| grpc.server_addr.as_deref().or(grpc.addr.as_deref()));
+            let addr = match configured {
+                Some(addr) => addr,
+                None if self.deployed => continue,
+                None => DEFAULT_DATANODE_GRPC_ADDR,
+            };
+            let item = format!("Datanode Advertise Address ({})", source);
+            let (host, port) = match network::parse_address(addr) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    details.push(CheckDetail::fail(item, format!("Invalid gRPC address '{}': {}", addr, e), None, None));
+                    continue;
+                }
+            };
+
+            if network::is_wildcard_host(&host) || network::is_loopback_host(&host) {
+                details.push(CheckDetail::fail(
+                    item,
+                    format!("The datanode registers '{}' with metasrv, which other hosts cannot reach", addr),
+                    None,
+                    Some("Set grpc.server_addr to the datanode's routable IP or hostname, e.g. its pod DNS name".to_string()),
+                ));
+                continue;
+            }
+            if !self.probe_datanodes {
+                details.push(CheckDetail::pass(item, format!("The datanode advertises '{}'", addr), None));
+                continue;
+            }
+
+            let start = std::time::Instant::now();
+            match tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::TcpStream::connect((host.as_str(), port))).await {
+                Ok(Ok(_)) => details.push(CheckDetail::pass(
+                    item,
+                    format!("The advertised address '{}' is reachable from this host", addr),
+                    Some(start.elapsed()),
+                )),
+                Ok(Err(e)) => details.push(CheckDetail::fail(
+                    item,
+                    format!("Cannot connect to the advertised address '{}': {}", addr, network::describe_connect_error(&e)),
+                    Some(start.elapsed()),
+                    Some(network::connect_error_suggestion(&e, "datanode")),
+                )),
+                Err(_) => details.push(CheckDetail::fail(
+                    item,
+                    format!("Connecting to the advertised address '{}' timed out", addr),
+                    Some(start.elapsed()),
+                    Some("Check firewalls between metasrv and the datanode".to_string()),
+                )),
+            }
+        }
+        details
+    }
+
+    /// Versions of the components that are already running, read from the `/status` endpoint of
+    /// their HTTP servers. Components that do not answer are skipped.
+    async fn check_versions(&self) -> Vec<CheckDetail> {
+        let servers = self
+            .metasrvs
+            .iter()
+            .map(|(source, config)| ("metasrv", source, config.http.as_ref()))
+            .chain(self.frontends.iter().map(|(source, config)| ("frontend", source, config.http.as_ref())))
+            .chain(self.datanodes.iter().map(|(source, config)| ("datanode", source, config.http.as_ref())))
+            .filter_map(|(component, source, http)| Some((component, source, http?.addr.as_deref()?)));
+
+        let mut details = Vec::new();
+        let mut running = Vec::new();
+        for (component, source, addr) in servers {
+            let Some(version) = fetch_version(addr).await else {
+                continue;
+            };
+            let item = format!("Version ({})", source);
+            match parse_version(&version) {
+                Some(parsed) if !is_supported(parsed) => details.push(CheckDetail::warning(
+                    item,
+                    format!("{} runs GreptimeDB {}, outside the range stepstone supports", component, version),
+                    None,
+                    Some(format!(
+                        "Results may be inaccurate; stepstone supports GreptimeDB {}.{} up to {}.x",
+                        MIN_SUPPORTED_VERSION.0, MIN_SUPPORTED_VERSION.1, MAX_SUPPORTED_MAJOR
+                    )),
+                )),
+                Some(parsed) => {
+                    details.push(CheckDetail::pass(item, format!("{} runs GreptimeDB {}", component, version), None));
+                    running.push((component, source, version, parsed));
+                }
+                None => details.push(CheckDetail::warning(
+                    item,
+                    format!("{} reports an unrecognized version \"{}\"", component, version),
+                    None,
+                    None,
+                )),
+            }
+        }
+
+        let releases = running.iter().map(|(_, _, _, (major, minor, _))| (*major, *minor)).unique().count();
+        if releases > 1 {
+            details.push(CheckDetail::warning(
+                "Version Mismatch".to_string(),
+                format!(
+                    "Components run different GreptimeDB releases: {}",
+                    running.iter().map(|(_, source, version, _)| format!("{} {}", source, version)).join(", ")
+                ),
+                None,
+                Some("Mixed releases are only supported during a rolling upgrade; finish upgrading all components".to_string()),
+            ));
+        }
+
+        // Metasrv is upgraded first, so a node newer than every metasrv is out of order
+        let oldest_metasrv = running.iter().filter(|(component, ..)| *component == "metasrv").map(|(.., parsed)| *parsed).min();
+        if let Some(metasrv) = oldest_metasrv {
+            for (component, source, version, parsed) in &running {
+                if *component != "metasrv" && (parsed.0, parsed.1) > (metasrv.0, metasrv.1) {
+                    details.push(CheckDetail::warning(
+                        format!("Upgrade Order ({})", source),
+                        format!("{} runs GreptimeDB {}, newer than metasrv {}.{}", component, version, metasrv.0, metasrv.1),
+                        None,
+                        Some("Upgrade metasrv before datanodes and frontends; a newer node may send requests the older metasrv cannot handle".to_string()),
+                    ));
+                }
+            }
+        }
+        details
+    }
+}
+
+/// The version a running component reports on its HTTP `/status` endpoint; a wildcard bind
+/// address is asked on loopback
+async fn fetch_version(addr: &str) -> Option<String> {
+    let (host, port) = network::parse_address(addr).ok()?;
+    let host = if network::is_wildcard_host(&host) {
+        "127.0.0.1".to_string()
+    } else if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host
+    };
+    let client = reqwest::Client::builder().timeout(STATUS_TIMEOUT).build().ok()?;
+    let response = client.get(format!("http://{}:{}/status", host, port)).send().await.ok()?;
+    let status: serde_json::Value = serde_json::from_str(&response.text().await.ok()?).ok()?;
+    status.get("version")?.as_str().map(str::to_string)
+}
+
+/// Parse `major.minor.patch` out of a version such as `v0.15.2` or `0.16.0-nightly-20250801`
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+fn is_supported((major, minor, _): (u64, u64, u64)) -> bool {
+    (major, minor) >= MIN_SUPPORTED_VERSION && major <= MAX_SUPPORTED_MAJOR
+}
+
+/// A gRPC server's receive and send message size limits, with defaults filled in
+fn message_sizes(grpc: Option<&GrpcConfig>) -> (u64, u64) {
+    let size = |value: Option<&String>| value.and_then(|value| parse_size(value)).unwrap_or(DEFAULT_GRPC_MESSAGE_SIZE);
+    (
+        size(grpc.and_then(|grpc| grpc.max_recv_message_size.as_ref())),
+        size(grpc.and_then(|grpc| grpc.max_send_message_size.as_ref())),
+    )
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes % (1 << 20) == 0 {
+        format!("{}MiB", bytes >> 20)
+    } else {
+        format!("{} bytes", bytes)
+    }
+}
+
+/// A node's heartbeat interval and retry interval, with defaults filled in
+fn heartbeat_timing(heartbeat: Option<&HeartbeatConfig>, default_interval: Duration) -> (Duration, Duration) {
+    let parse = |value: Option<&String>| value.and_then(|value| parse_duration(value));
+    let interval = parse(heartbeat.and_then(|h| h.interval.as_ref())).unwrap_or(default_interval);
+    let retry = parse(heartbeat.and_then(|h| h.retry_interval.as_ref())).unwrap_or(interval);
+    (interval, retry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    fn cluster(metasrv: &str, datanode: &str, frontend: &str) -> ClusterConfigs {
+        let mut cluster = ClusterConfigs::new(false, false);
+        for (source, component, content) in [
+            ("metasrv.toml", "metasrv", metasrv),
+            ("datanode.toml", "datanode", datanode),
+            ("frontend.toml", "frontend", frontend),
+        ] {
+            cluster.add(source, component, &toml::from_str(content).unwrap());
+        }
+        cluster
+    }
+
+    #[test]
+    fn test_cluster_heartbeats() {
+        let defaults = cluster("backend = \"etcd_store\"\nstore_addrs = []", "", "");
+        assert!(defaults.is_cluster());
+        let details = defaults.check_heartbeats();
+        assert_eq!(details[0].item, "Heartbeat Timing");
+        assert_eq!(details[0].status, CheckStatus::Pass);
+
+        let slow = cluster(
+            "backend = \"etcd_store\"\nstore_addrs = []\nnode_max_idle_time = \"10s\"\n[failure_detector]\nacceptable_heartbeat_pause = \"5s\"",
+            "[heartbeat]\ninterval = \"3s\"\nretry_interval = \"3s\"",
+            "[heartbeat]\ninterval = \"18s\"",
+        );
+        let details = slow.check_heartbeats();
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].item, "Heartbeat vs Failure Detector (datanode.toml)");
+        assert_eq!(details[0].status, CheckStatus::Warning);
+        assert_eq!(details[1].status, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_cluster_datanode_addresses() {
+        let metasrv = "backend = \"etcd_store\"\nstore_addrs = []";
+        let details = cluster(metasrv, "", "").check_datanode_addresses().await;
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].status, CheckStatus::Fail);
+        assert!(details[0].message.contains("'127.0.0.1:3001'"));
+
+        let details = cluster(metasrv, "[grpc]\nbind_addr = \"0.0.0.0:3001\"\nserver_addr = \"10.0.0.5:3001\"", "")
+            .check_datanode_addresses()
+            .await;
+        assert_eq!(details[0].status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_cluster_shared_storage() {
+        let metasrv = "backend = \"etcd_store\"\nstore_addrs = []";
+        let storage = "[storage]\ntype = \"S3\"\nbucket = \"greptime\"\nroot = \"/data\"\n";
+        let mut shared = cluster(metasrv, storage, "");
+        shared.add("datanode-2.toml", "datanode", &toml::from_str(storage).unwrap());
+        let details = shared.check_shared_storage();
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].item, "Shared Storage (datanode.toml, datanode-2.toml)");
+        assert_eq!(details[0].status, CheckStatus::Warning);
+
+        let remote = format!("{}[wal]\nprovider = \"kafka\"\n", storage);
+        let mut shared = cluster(metasrv, &remote, "");
+        shared.add("datanode-2.toml", "datanode", &toml::from_str(&remote).unwrap());
+        assert_eq!(shared.check_shared_storage()[0].status, CheckStatus::Info);
+
+        let mut separate = cluster(metasrv, storage, "");
+        separate.add("datanode-2.toml", "datanode", &toml::from_str(&storage.replace("/data", "/data-2")).unwrap());
+        assert!(separate.check_shared_storage().is_empty());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v0.15.2"), Some((0, 15, 2)));
+        assert_eq!(parse_version("0.16.0-nightly-20250801"), Some((0, 16, 0)));
+        assert_eq!(parse_version("1.0"), Some((1, 0, 0)));
+        assert_eq!(parse_version("unknown"), None);
+
+        assert!(is_supported((0, 15, 2)));
+        assert!(is_supported((1, 3, 0)));
+        assert!(!is_supported((0, 9, 5)));
+        assert!(!is_supported((2, 0, 0)));
+    }
+
+    #[test]
+    fn test_cluster_message_sizes() {
+        let defaults = cluster("backend = \"etcd_store\"\nstore_addrs = []", "", "");
+        assert_eq!(defaults.check_message_sizes()[0].status, CheckStatus::Pass);
+
+        let mismatched = cluster(
+            "backend = \"etcd_store\"\nstore_addrs = []",
+            "[grpc]\nmax_recv_message_size = \"64MB\"\nmax_send_message_size = \"1GB\"",
+            "[grpc]\nmax_send_message_size = \"128MB\"",
+        );
+        let details = mismatched.check_message_sizes();
+        let items = details.iter().map(|d| d.item.as_str()).collect::<Vec<_>>();
+        assert_eq!(items, ["gRPC Message Size (frontend.toml -> datanode.toml)", "gRPC Message Size (datanode.toml -> frontend.toml)"]);
+        assert!(details[0].message.contains("(128MiB) exceeds the datanode grpc.max_recv_message_size (64MiB)"));
+    }
+}
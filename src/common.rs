@@ -10,10 +10,59 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::i18n;
 use async_trait::async_trait;
 use colored::*;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+static RUN_STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// Mark the start of the run, from which reports measure their wall-clock time
+pub fn mark_run_start() {
+    RUN_STARTED.get_or_init(Instant::now);
+}
+
+/// Wall-clock time since [`mark_run_start`], None when it was not called
+pub fn run_elapsed() -> Option<Duration> {
+    RUN_STARTED.get().map(Instant::elapsed)
+}
+
+/// A service a component needs before it can start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dependency {
+    /// The metasrv of `meta_client.metasrv_addrs`
+    Metasrv,
+    /// Object storage of the `[storage]` section
+    Storage,
+    /// Metasrv's metadata backend: etcd, PostgreSQL or MySQL
+    Backend,
+}
+
+impl std::str::FromStr for Dependency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "metasrv" => Ok(Self::Metasrv),
+            "storage" => Ok(Self::Storage),
+            "backend" => Ok(Self::Backend),
+            other => Err(format!("unknown dependency '{}', expected metasrv, storage or backend", other)),
+        }
+    }
+}
+
+impl Dependency {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Metasrv => "metasrv",
+            Self::Storage => "storage",
+            Self::Backend => "backend",
+        }
+    }
+}
 
 /// Common trait for all component checkers
 #[async_trait]
@@ -23,6 +72,24 @@ pub trait ComponentChecker {
     
     /// Get the name of the component being checked
     fn component_name(&self) -> &'static str;
+
+    /// Run [`check`](Self::check), recording how long it took on the wall clock
+    async fn run(&self) -> CheckResult {
+        let started_at = SystemTime::now();
+        let start = Instant::now();
+        let result = self.check().await;
+        CheckResult { started_at: Some(started_at), ..result.with_wall_duration(start.elapsed()) }
+    }
+
+    /// Services the component needs before it can start, see `stepstone wait`
+    fn dependencies(&self) -> &'static [Dependency] {
+        &[]
+    }
+
+    /// Check only the service `dependency`, None when the component does not need it
+    async fn check_dependency(&self, _dependency: Dependency) -> Option<CheckResult> {
+        None
+    }
 }
 
 /// Result of a component check
@@ -34,8 +101,15 @@ pub struct CheckResult {
     pub message: String,
     /// Detailed results for individual check items
     pub details: Vec<CheckDetail>,
-    /// Total duration of all checks
-    pub total_duration: Option<Duration>,
+    /// Sum of the durations of the timed checks; more than the run took when checks overlap
+    pub cumulative_duration: Option<Duration>,
+    /// Wall-clock time from the start to the end of the run, when it was measured
+    #[serde(default)]
+    pub wall_duration: Option<Duration>,
+    /// When the run started, when it was measured; with `wall_duration`, places the run on a
+    /// trace timeline
+    #[serde(skip)]
+    pub started_at: Option<SystemTime>,
 }
 
 /// Detailed result for a specific check item
@@ -51,6 +125,26 @@ pub struct CheckDetail {
     pub duration: Option<Duration>,
     /// Optional suggestion for fixing issues
     pub suggestion: Option<String>,
+    /// Stable code of the finding, e.g. `STEP-S3-0004`, for support and knowledge bases
+    #[serde(default)]
+    pub error_code: Option<String>,
+    /// Documentation of the finding
+    #[serde(default)]
+    pub doc_url: Option<String>,
+    /// Area the check belongs to, e.g. `storage-performance`, as listed by `stepstone list-checks`
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Free-form labels, e.g. `performance` or `security`, selected with `--tag-filter`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Time per phase of a check made of several operations, e.g. `client_build`, `tls`,
+    /// `first_byte` and `total`; empty for single-operation checks
+    #[serde(default)]
+    pub timings: BTreeMap<String, Duration>,
+    /// When the check started and finished, for checks that recorded both; places the check on
+    /// a trace timeline
+    #[serde(skip)]
+    pub span: Option<(SystemTime, SystemTime)>,
 }
 
 /// Status of a check item
@@ -62,12 +156,14 @@ pub enum CheckStatus {
     Fail,
     /// Check passed with warnings
     Warning,
+    /// Finding that needs no action, such as a suppressed warning
+    Info,
 }
 
 impl CheckResult {
     /// Create a new successful check result
     pub fn success(message: String, details: Vec<CheckDetail>) -> Self {
-        let total_duration = details
+        let cumulative_duration = details
             .iter()
             .filter_map(|d| d.duration)
             .reduce(|acc, d| acc + d);
@@ -76,13 +172,15 @@ impl CheckResult {
             success: true,
             message,
             details,
-            total_duration,
+            cumulative_duration,
+            wall_duration: None,
+            started_at: None,
         }
     }
 
     /// Create a new failed check result
     pub fn failure(message: String, details: Vec<CheckDetail>) -> Self {
-        let total_duration = details
+        let cumulative_duration = details
             .iter()
             .filter_map(|d| d.duration)
             .reduce(|acc, d| acc + d);
@@ -91,13 +189,15 @@ impl CheckResult {
             success: false,
             message,
             details,
-            total_duration,
+            cumulative_duration,
+            wall_duration: None,
+            started_at: None,
         }
     }
 
     /// Create a mixed result based on the details
     pub fn from_details(details: Vec<CheckDetail>) -> Self {
-        let success = details.iter().all(|d| matches!(d.status, CheckStatus::Pass | CheckStatus::Warning));
+        let success = details.iter().all(|d| d.status != CheckStatus::Fail);
         let has_warnings = details.iter().any(|d| d.status == CheckStatus::Warning);
         let failed_count = details.iter().filter(|d| d.status == CheckStatus::Fail).count();
         let passed_count = details.iter().filter(|d| d.status == CheckStatus::Pass).count();
@@ -113,7 +213,7 @@ impl CheckResult {
             format!("Some checks failed ({} passed, {} warnings, {} failed)", passed_count, warning_count, failed_count)
         };
 
-        let total_duration = details
+        let cumulative_duration = details
             .iter()
             .filter_map(|d| d.duration)
             .reduce(|acc, d| acc + d);
@@ -122,21 +222,51 @@ impl CheckResult {
             success,
             message,
             details,
-            total_duration,
+            cumulative_duration,
+            wall_duration: None,
+            started_at: None,
         }
     }
 
+    /// This result with `wall_duration` as the time its run took
+    pub fn with_wall_duration(self, wall_duration: Duration) -> Self {
+        Self { wall_duration: Some(wall_duration), ..self }
+    }
+
+    /// A result of `details` from the same run, keeping its wall-clock time
+    pub fn with_details(self, details: Vec<CheckDetail>) -> Self {
+        Self { wall_duration: self.wall_duration, started_at: self.started_at, ..Self::from_details(details) }
+    }
+
+    /// Wall-clock time of the run, or the time of its checks added up when it was not measured
+    pub fn total_duration(&self) -> Option<Duration> {
+        self.wall_duration.or(self.cumulative_duration)
+    }
+
     /// Print the result in a human-readable format
     pub fn print_human_readable(&self, component_name: &str, config_file: Option<&str>) {
-        println!("\n{}", "GreptimeDB Self-Test Report".bold().blue());
-        println!("{}", "===========================".blue());
+        let title = i18n::label("report_title", "GreptimeDB Self-Test Report");
+        println!("\n{}", title.bold().blue());
+        println!("{}", "=".repeat(title.chars().count()).blue());
         println!();
-        println!("{}: {}", "Component".bold(), component_name);
+        println!("{}: {}", i18n::label("component", "Component").bold(), component_name);
         if let Some(config) = config_file {
-            println!("{}: {}", "Configuration".bold(), config);
+            println!("{}: {}", i18n::label("configuration", "Configuration").bold(), config);
         }
-        if let Some(duration) = self.total_duration {
-            println!("{}: {:?}", "Total Duration".bold(), duration);
+        println!("{}: {}", i18n::label("mode", "Mode").bold(), crate::mode::current().as_str());
+        if let Some(duration) = self.total_duration() {
+            println!("{}: {:?}", i18n::label("total_duration", "Total Duration").bold(), duration);
+        }
+        if let Some(duration) = self.cumulative_duration.filter(|_| self.wall_duration.is_some()) {
+            println!("{}: {:?}", i18n::label("cumulative_duration", "Time in Checks").bold(), duration);
+        }
+        if let Some(host) = crate::host::current() {
+            println!("{}: {}", i18n::label("host", "Host").bold(), host.summary());
+            println!("{}: {}", i18n::label("stepstone_version", "Stepstone Version").bold(), host.stepstone_version);
+            if !host.tags.is_empty() {
+                let tags = host.tags.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>();
+                println!("{}: {}", i18n::label("tags", "Tags").bold(), tags.join(", "));
+            }
         }
         println!();
 
@@ -145,12 +275,14 @@ impl CheckResult {
                 CheckStatus::Pass => "✓".green(),
                 CheckStatus::Fail => "✗".red(),
                 CheckStatus::Warning => "⚠".yellow(),
+                CheckStatus::Info => "ℹ".blue(),
             };
 
             let status_text = match detail.status {
-                CheckStatus::Pass => "[PASS]".green(),
-                CheckStatus::Fail => "[FAIL]".red(),
-                CheckStatus::Warning => "[WARN]".yellow(),
+                CheckStatus::Pass => format!("[{}]", i18n::label("pass", "PASS")).green(),
+                CheckStatus::Fail => format!("[{}]", i18n::label("fail", "FAIL")).red(),
+                CheckStatus::Warning => format!("[{}]", i18n::label("warn", "WARN")).yellow(),
+                CheckStatus::Info => format!("[{}]", i18n::label("info", "INFO")).blue(),
             };
 
             let duration_text = if let Some(duration) = detail.duration {
@@ -161,39 +293,107 @@ impl CheckResult {
 
             println!("{} {:<30} {} {} - {}", 
                 status_symbol, 
-                detail.item, 
+                i18n::item(&detail.item),
                 status_text, 
                 duration_text,
-                detail.message
+                i18n::text(&detail.message)
             );
 
             if let Some(suggestion) = &detail.suggestion {
-                println!("    💡 {}: {}", "Suggestion".yellow(), suggestion);
+                println!("    💡 {}: {}", i18n::label("suggestion", "Suggestion").yellow(), i18n::text(suggestion));
+            }
+            match (&detail.error_code, &detail.doc_url) {
+                (Some(code), Some(url)) => println!("    📖 {}: {}", code, url),
+                (Some(code), None) => println!("    📖 {} (stepstone explain {})", code, code),
+                (None, Some(url)) => println!("    📖 {}", url),
+                (None, None) => {}
             }
         }
 
+        self.print_timing_summary(component_name);
+
         println!();
+        let overall = i18n::label("overall_result", "Overall Result");
         let overall_status = if self.success {
-            format!("Overall Result: {}", "PASS".green().bold())
+            format!("{}: {}", overall, i18n::label("pass", "PASS").green().bold())
         } else {
-            format!("Overall Result: {}", "FAIL".red().bold())
+            format!("{}: {}", overall, i18n::label("fail", "FAIL").red().bold())
         };
         println!("{}", overall_status);
         println!();
     }
 
+    /// The `limit` slowest timed checks, slowest first
+    pub fn slowest_checks(&self, limit: usize) -> Vec<&CheckDetail> {
+        let mut timed = self.details.iter().filter(|d| d.duration.is_some()).collect::<Vec<_>>();
+        timed.sort_by(|a, b| b.duration.cmp(&a.duration));
+        timed.truncate(limit);
+        timed
+    }
+
+    /// Time spent per check category, with the number of timed checks, most time first
+    pub fn time_by_category(&self, component_name: &str) -> Vec<(&'static str, usize, Duration)> {
+        let mut categories: Vec<(&'static str, usize, Duration)> = Vec::new();
+        for detail in &self.details {
+            let Some(duration) = detail.duration else { continue };
+            let category = crate::checks::category(component_name, &detail.item);
+            match categories.iter_mut().find(|(name, _, _)| *name == category) {
+                Some((_, count, total)) => {
+                    *count += 1;
+                    *total += duration;
+                }
+                None => categories.push((category, 1, duration)),
+            }
+        }
+        categories.sort_by(|a, b| b.2.cmp(&a.2));
+        categories
+    }
+
+    /// Where the time of the run went, so a slow run points at S3, etcd or the network
+    fn print_timing_summary(&self, component_name: &str) {
+        let slowest = self.slowest_checks(10);
+        if slowest.is_empty() {
+            return;
+        }
+
+        println!();
+        println!("{}", i18n::label("slowest_checks", "Top 10 Slowest Checks").bold());
+        for (rank, detail) in slowest.iter().enumerate() {
+            println!("{:>3}. {:<40} {:>10.2?}", rank + 1, i18n::item(&detail.item), detail.duration.unwrap_or_default());
+        }
+
+        let categories = self.time_by_category(component_name);
+        let timed: Duration = categories.iter().map(|(_, _, total)| *total).sum();
+        println!();
+        println!("{}", i18n::label("time_by_category", "Time by Category").bold());
+        for (category, count, total) in categories {
+            let share = if timed.is_zero() { 0.0 } else { total.as_secs_f64() / timed.as_secs_f64() * 100.0 };
+            println!("     {:<22} {:>4} {:>10.2?} {:>5.1}%", category, count, total, share);
+        }
+    }
+
     /// Convert the result to JSON format
     pub fn to_json(&self, component_name: &str, config_file: Option<&str>) -> serde_json::Result<String> {
-        let json_result = serde_json::json!({
+        serde_json::to_string_pretty(&self.to_json_value(component_name, config_file))
+    }
+
+    /// Build the JSON document of the result
+    pub(crate) fn to_json_value(&self, component_name: &str, config_file: Option<&str>) -> serde_json::Value {
+        serde_json::json!({
+            "schema_version": crate::schema::SCHEMA_VERSION,
             "component": component_name,
             "config_file": config_file,
             "timestamp": chrono::Utc::now().to_rfc3339(),
+            "host": crate::host::current(),
+            "mode": crate::mode::current().as_str(),
             "overall_result": if self.success { "PASS" } else { "FAIL" },
             "total_checks": self.details.len(),
             "passed_checks": self.details.iter().filter(|d| d.status == CheckStatus::Pass).count(),
             "failed_checks": self.details.iter().filter(|d| d.status == CheckStatus::Fail).count(),
             "warning_checks": self.details.iter().filter(|d| d.status == CheckStatus::Warning).count(),
-            "total_duration_ms": self.total_duration.map(|d| d.as_millis()),
+            "info_checks": self.details.iter().filter(|d| d.status == CheckStatus::Info).count(),
+            "total_duration_ms": self.cumulative_duration.map(|d| d.as_millis()),
+            "wall_duration_ms": self.wall_duration.map(|d| d.as_millis()),
             "message": self.message,
             "details": self.details.iter().map(|d| serde_json::json!({
                 "item": d.item,
@@ -201,14 +401,122 @@ impl CheckResult {
                     CheckStatus::Pass => "PASS",
                     CheckStatus::Fail => "FAIL",
                     CheckStatus::Warning => "WARNING",
+                    CheckStatus::Info => "INFO",
                 },
                 "message": d.message,
                 "duration_ms": d.duration.map(|dur| dur.as_millis()),
                 "suggestion": d.suggestion,
+                "error_code": d.error_code,
+                "doc_url": d.doc_url,
+                "category": d.category,
+                "tags": d.tags,
+                "timings": d.timings.iter().map(|(phase, duration)| (phase.clone(), duration.as_secs_f64() * 1000.0)).collect::<BTreeMap<_, _>>(),
             })).collect::<Vec<_>>()
+        })
+    }
+}
+
+/// Results of several config files checked in one run
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// One section per checked file
+    pub sections: Vec<ReportSection>,
+}
+
+/// Result of one file within a [`Report`]
+#[derive(Debug, Clone)]
+pub struct ReportSection {
+    /// Name of the component the file was checked as
+    pub component: String,
+    /// Path of the checked file
+    pub config_file: String,
+    /// Result of the check
+    pub result: CheckResult,
+}
+
+impl Report {
+    /// Add the result of one file
+    pub fn push(&mut self, component: &str, config_file: &str, result: CheckResult) {
+        self.sections.push(ReportSection {
+            component: component.to_string(),
+            config_file: config_file.to_string(),
+            result,
         });
+    }
 
-        serde_json::to_string_pretty(&json_result)
+    /// Whether every section passed
+    pub fn success(&self) -> bool {
+        self.sections.iter().all(|section| section.result.success)
+    }
+
+    /// Print each section followed by a one-line-per-file summary
+    pub fn print_human_readable(&self) {
+        for section in &self.sections {
+            section.result.print_human_readable(&section.component, Some(&section.config_file));
+        }
+
+        let summary = i18n::label("summary", "Summary");
+        println!("{}", summary.bold().blue());
+        println!("{}", "=".repeat(summary.chars().count()).blue());
+        for section in &self.sections {
+            let status = if section.result.success {
+                format!("[{}]", i18n::label("pass", "PASS")).green()
+            } else {
+                format!("[{}]", i18n::label("fail", "FAIL")).red()
+            };
+            let duration = section.result.total_duration().map(|d| format!("{:.2}s", d.as_secs_f64())).unwrap_or_default();
+            println!(
+                "{} {:<40} {:<10} {:>8}  {}",
+                status, section.config_file, section.component, duration, section.result.message
+            );
+        }
+
+        println!();
+        let passed = self.sections.iter().filter(|section| section.result.success).count();
+        let overall = if self.success() {
+            i18n::label("pass", "PASS").green().bold()
+        } else {
+            i18n::label("fail", "FAIL").red().bold()
+        };
+        let files = i18n::label("files_passed", "{passed} of {total} files passed")
+            .replace("{passed}", &passed.to_string())
+            .replace("{total}", &self.sections.len().to_string());
+        println!("{}: {} ({})", i18n::label("overall_result", "Overall Result"), overall, files);
+        if let Some(duration) = run_elapsed() {
+            println!(
+                "{}: {:.2}s ({}: {:.2}s)",
+                i18n::label("total_duration", "Total Duration"),
+                duration.as_secs_f64(),
+                i18n::label("cumulative_duration", "Time in Checks"),
+                self.cumulative_duration().as_secs_f64()
+            );
+        }
+        println!();
+    }
+
+    /// Time of every check of every section added up
+    pub fn cumulative_duration(&self) -> Duration {
+        self.sections.iter().filter_map(|section| section.result.cumulative_duration).sum()
+    }
+
+    /// Convert the report to JSON format, embedding the JSON of each section
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let json_report = serde_json::json!({
+            "schema_version": crate::schema::SCHEMA_VERSION,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "host": crate::host::current(),
+            "mode": crate::mode::current().as_str(),
+            "overall_result": if self.success() { "PASS" } else { "FAIL" },
+            "total_files": self.sections.len(),
+            "failed_files": self.sections.iter().filter(|section| !section.result.success).count(),
+            "total_duration_ms": self.cumulative_duration().as_millis(),
+            "wall_duration_ms": run_elapsed().map(|d| d.as_millis()),
+            "files": self.sections.iter()
+                .map(|section| section.result.to_json_value(&section.component, Some(&section.config_file)))
+                .collect::<Vec<_>>(),
+        });
+
+        serde_json::to_string_pretty(&json_report)
     }
 }
 
@@ -221,6 +529,12 @@ impl CheckDetail {
             message,
             duration,
             suggestion: None,
+            error_code: None,
+            doc_url: None,
+            category: None,
+            tags: Vec::new(),
+            timings: BTreeMap::new(),
+            span: None,
         }
     }
 
@@ -232,6 +546,12 @@ impl CheckDetail {
             message,
             duration,
             suggestion,
+            error_code: None,
+            doc_url: None,
+            category: None,
+            tags: Vec::new(),
+            timings: BTreeMap::new(),
+            span: None,
         }
     }
 
@@ -243,14 +563,147 @@ impl CheckDetail {
             message,
             duration,
             suggestion,
+            error_code: None,
+            doc_url: None,
+            category: None,
+            tags: Vec::new(),
+            timings: BTreeMap::new(),
+            span: None,
+        }
+    }
+
+    /// This detail with the phase breakdown of a [`PhaseTimer`], ending now
+    pub fn with_timer(self, timer: &PhaseTimer) -> Self {
+        Self { timings: timer.finish(), ..self.spanning(timer.started_at) }
+    }
+
+    /// This detail as a check that started at `started_at` and ends now
+    pub fn spanning(self, started_at: SystemTime) -> Self {
+        Self { span: Some((started_at, SystemTime::now())), ..self }
+    }
+
+    /// Create a new informational check detail
+    pub fn info(item: String, message: String, duration: Option<Duration>) -> Self {
+        Self {
+            item,
+            status: CheckStatus::Info,
+            message,
+            duration,
+            suggestion: None,
+            error_code: None,
+            doc_url: None,
+            category: None,
+            tags: Vec::new(),
+            timings: BTreeMap::new(),
+            span: None,
         }
     }
 }
 
+/// Times the phases of a check made of several operations, for [`CheckDetail::timings`]
+#[derive(Debug)]
+pub struct PhaseTimer {
+    start: Instant,
+    started_at: SystemTime,
+    phase_start: Instant,
+    phases: BTreeMap<String, Duration>,
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self { start: now, started_at: SystemTime::now(), phase_start: now, phases: BTreeMap::new() }
+    }
+
+    /// End the phase `name`, which began when the previous phase ended
+    pub fn phase(&mut self, name: &str) {
+        let now = Instant::now();
+        self.phases.insert(name.to_string(), now - self.phase_start);
+        self.phase_start = now;
+    }
+
+    /// Time since the timer started
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// The phases ended so far, with `total` for the time since the timer started
+    pub fn finish(&self) -> BTreeMap<String, Duration> {
+        let mut phases = self.phases.clone();
+        phases.insert("total".to_string(), self.elapsed());
+        phases
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_timing_summary() {
+        let details = vec![
+            CheckDetail::pass("S3 64MB File Write Performance".to_string(), "ok".to_string(), Some(Duration::from_millis(900))),
+            CheckDetail::pass("S3 PUT Operation".to_string(), "ok".to_string(), Some(Duration::from_millis(40))),
+            CheckDetail::pass("S3 Concurrent Operations".to_string(), "ok".to_string(), Some(Duration::from_millis(300))),
+            CheckDetail::pass("Storage Type".to_string(), "S3".to_string(), None),
+            CheckDetail::pass("Custom".to_string(), "ok".to_string(), Some(Duration::from_millis(5))),
+        ];
+        let result = CheckResult::from_details(details);
+
+        let slowest = result.slowest_checks(2).iter().map(|d| d.item.as_str()).collect::<Vec<_>>();
+        assert_eq!(slowest, vec!["S3 64MB File Write Performance", "S3 Concurrent Operations"]);
+
+        let categories = result.time_by_category("Datanode");
+        assert_eq!(
+            categories,
+            vec![
+                ("storage-performance", 2, Duration::from_millis(1200)),
+                ("storage", 1, Duration::from_millis(40)),
+                ("other", 1, Duration::from_millis(5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wall_and_cumulative_duration() {
+        let details = vec![
+            CheckDetail::pass("A".to_string(), "ok".to_string(), Some(Duration::from_millis(800))),
+            CheckDetail::pass("B".to_string(), "ok".to_string(), Some(Duration::from_millis(700))),
+            CheckDetail::pass("Untimed".to_string(), "ok".to_string(), None),
+        ];
+        let result = CheckResult::from_details(details);
+        assert_eq!(result.total_duration(), Some(Duration::from_millis(1500)));
+
+        // The two checks overlapped, so the run took less than their sum
+        let result = result.with_wall_duration(Duration::from_millis(900));
+        assert_eq!(result.total_duration(), Some(Duration::from_millis(900)));
+        let json = result.to_json_value("Datanode", None);
+        assert_eq!(json["total_duration_ms"], 1500);
+        assert_eq!(json["wall_duration_ms"], 900);
+
+        let first = result.details[..1].to_vec();
+        let kept = result.with_details(first);
+        assert_eq!(kept.wall_duration, Some(Duration::from_millis(900)));
+        assert_eq!(kept.cumulative_duration, Some(Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn test_phase_timer() {
+        let mut timer = PhaseTimer::start();
+        timer.phase("client_build");
+        std::thread::sleep(Duration::from_millis(5));
+        timer.phase("first_byte");
+        let detail = CheckDetail::pass("Call".to_string(), "ok".to_string(), Some(timer.elapsed())).with_timer(&timer);
+
+        assert_eq!(detail.timings.keys().collect::<Vec<_>>(), vec!["client_build", "first_byte", "total"]);
+        let (started, finished) = detail.span.unwrap();
+        assert!(finished.duration_since(started).unwrap() >= Duration::from_millis(5));
+        assert!(detail.timings["first_byte"] >= Duration::from_millis(5));
+        assert!(detail.timings["total"] >= detail.timings["client_build"] + detail.timings["first_byte"]);
+        let json = CheckResult::from_details(vec![detail]).to_json_value("Datanode", None);
+        assert!(json["details"][0]["timings"]["total"].as_f64().unwrap() >= 5.0);
+    }
+
     #[test]
     fn test_check_result_success() {
         let details = vec![
@@ -315,4 +768,28 @@ mod tests {
         assert!(json_str.contains("/test/config.toml"));
         assert!(json_str.contains("PASS"));
     }
+
+    #[test]
+    fn test_report() {
+        let mut report = Report::default();
+        report.push(
+            "Datanode",
+            "configs/datanode.toml",
+            CheckResult::from_details(vec![CheckDetail::pass("Test 1".to_string(), "Passed".to_string(), None)]),
+        );
+        assert!(report.success());
+
+        report.push(
+            "Frontend",
+            "configs/frontend.toml",
+            CheckResult::from_details(vec![CheckDetail::fail("Test 2".to_string(), "Failed".to_string(), None, None)]),
+        );
+        assert!(!report.success());
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["overall_result"], "FAIL");
+        assert_eq!(json["failed_files"], 1);
+        assert_eq!(json["files"][1]["config_file"], "configs/frontend.toml");
+        assert_eq!(json["files"][1]["overall_result"], "FAIL");
+    }
 }
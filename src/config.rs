@@ -10,6 +10,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use std::collections::HashMap;
@@ -17,7 +18,7 @@ use std::fs;
 use std::path::Path;
 
 /// Configuration for Metasrv component (matches actual GreptimeDB format)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MetasrvConfig {
     /// Data home directory
     pub data_home: Option<String>,
@@ -39,37 +40,106 @@ pub struct MetasrvConfig {
     pub use_memory_store: Option<bool>,
     /// Enable region failover
     pub enable_region_failover: Option<bool>,
+    /// Delay before region failure detection starts after metasrv becomes leader
+    pub region_failure_detector_initialization_delay: Option<String>,
+    /// Allow region failover when the datanode uses a local WAL
+    pub allow_region_failover_on_local_wal: Option<bool>,
+    /// Idle time after which a node is removed from the cluster
+    pub node_max_idle_time: Option<String>,
+    /// Enable telemetry
+    pub enable_telemetry: Option<bool>,
+    /// Runtime configuration
+    pub runtime: Option<RuntimeConfig>,
     /// gRPC server configuration
     pub grpc: Option<GrpcConfig>,
     /// HTTP server configuration
     pub http: Option<HttpConfig>,
     /// Backend TLS configuration
     pub backend_tls: Option<TlsConfig>,
+    /// Username for an etcd backend with authentication enabled
+    pub backend_username: Option<String>,
+    /// Password for an etcd backend with authentication enabled
+    pub backend_password: Option<String>,
+    /// Maximum number of operations metasrv puts in a single etcd transaction
+    pub max_txn_ops: Option<usize>,
+    /// Procedure configuration
+    pub procedure: Option<ProcedureConfig>,
+    /// Failure detector configuration
+    pub failure_detector: Option<FailureDetectorConfig>,
+    /// Datanode client configuration
+    pub datanode: Option<DatanodeClientsConfig>,
+    /// WAL configuration
+    pub wal: Option<WalConfig>,
+    /// Event recorder configuration
+    pub event_recorder: Option<EventRecorderConfig>,
+    /// Region statistics persistence configuration
+    pub stats_persistence: Option<StatsPersistenceConfig>,
+    /// Logging configuration
+    pub logging: Option<LoggingConfig>,
+    /// Self-monitoring metrics export configuration
+    pub export_metrics: Option<ExportMetricsConfig>,
+    /// Tracing configuration
+    pub tracing: Option<TracingConfig>,
+    /// Memory profiling configuration
+    pub memory: Option<MemoryConfig>,
 }
 
 /// Configuration for Frontend component (matches actual GreptimeDB format)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FrontendConfig {
     /// Data home directory
     pub data_home: Option<String>,
     /// Default timezone
     pub default_timezone: Option<String>,
+    /// User provider, e.g. `static_user_provider:file:/etc/greptimedb/users`
+    pub user_provider: Option<String>,
+    /// Maximum bytes of in-flight write requests
+    pub max_in_flight_write_bytes: Option<String>,
+    /// Runtime configuration
+    pub runtime: Option<RuntimeConfig>,
     /// HTTP server configuration
     pub http: Option<HttpConfig>,
     /// gRPC server configuration
     pub grpc: Option<GrpcConfig>,
+    /// Internal gRPC server configuration
+    pub internal_grpc: Option<GrpcConfig>,
+    /// MySQL server configuration
+    pub mysql: Option<SqlServerConfig>,
+    /// PostgreSQL server configuration
+    pub postgres: Option<SqlServerConfig>,
+    /// OpenTSDB protocol configuration
+    pub opentsdb: Option<ProtocolConfig>,
+    /// InfluxDB protocol configuration
+    pub influxdb: Option<ProtocolConfig>,
+    /// Jaeger protocol configuration
+    pub jaeger: Option<ProtocolConfig>,
     /// Metasrv client configuration
     pub meta_client: Option<MetaClientConfig>,
     /// Heartbeat configuration
     pub heartbeat: Option<HeartbeatConfig>,
-    /// Prometheus configuration
+    /// Prometheus remote storage configuration
+    #[serde(alias = "prom_store")]
     pub prometheus: Option<PrometheusConfig>,
+    /// Query configuration
+    pub query: Option<QueryConfig>,
+    /// Datanode client configuration
+    pub datanode: Option<DatanodeClientsConfig>,
     /// Logging configuration
     pub logging: Option<LoggingConfig>,
+    /// Slow query log configuration
+    pub slow_query: Option<SlowQueryConfig>,
+    /// Self-monitoring metrics export configuration
+    pub export_metrics: Option<ExportMetricsConfig>,
+    /// Tracing configuration
+    pub tracing: Option<TracingConfig>,
+    /// Memory profiling configuration
+    pub memory: Option<MemoryConfig>,
+    /// Event recorder configuration
+    pub event_recorder: Option<EventRecorderConfig>,
 }
 
 /// Configuration for Datanode component (matches actual GreptimeDB format)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DatanodeConfig {
     /// Datanode identifier
     pub node_id: Option<u64>,
@@ -83,6 +153,8 @@ pub struct DatanodeConfig {
     pub max_concurrent_queries: Option<u32>,
     /// Enable telemetry
     pub enable_telemetry: Option<bool>,
+    /// Runtime configuration
+    pub runtime: Option<RuntimeConfig>,
     /// HTTP server configuration
     pub http: Option<HttpConfig>,
     /// gRPC server configuration
@@ -97,8 +169,23 @@ pub struct DatanodeConfig {
     pub storage: Option<DatanodeStorageConfig>,
     /// Query configuration
     pub query: Option<QueryConfig>,
+    /// Region engine configurations
+    pub region_engine: Option<Vec<RegionEngineConfig>>,
     /// Logging configuration
     pub logging: Option<LoggingConfig>,
+    /// Self-monitoring metrics export configuration
+    pub export_metrics: Option<ExportMetricsConfig>,
+    /// Tracing configuration
+    pub tracing: Option<TracingConfig>,
+    /// Memory profiling configuration
+    pub memory: Option<MemoryConfig>,
+}
+
+impl DatanodeConfig {
+    /// The mito engine configuration, if any `[[region_engine]]` entry defines one
+    pub fn mito(&self) -> Option<&MitoConfig> {
+        self.region_engine.as_ref()?.iter().find_map(|engine| engine.mito.as_ref())
+    }
 }
 
 /// Store configuration for Metasrv
@@ -129,10 +216,20 @@ pub struct ServerConfig {
     pub grpc_addr: Option<String>,
 }
 
+/// Runtime configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    /// Number of threads for the global runtime
+    pub global_rt_size: Option<u32>,
+    /// Number of threads for the compaction runtime
+    pub compact_rt_size: Option<u32>,
+}
+
 /// gRPC server configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GrpcConfig {
     /// Bind address
+    #[serde(alias = "bind_addr")]
     pub addr: Option<String>,
     /// Server address
     pub server_addr: Option<String>,
@@ -142,10 +239,14 @@ pub struct GrpcConfig {
     pub max_recv_message_size: Option<String>,
     /// Max send message size
     pub max_send_message_size: Option<String>,
+    /// Compression mode for Arrow Flight responses
+    pub flight_compression: Option<String>,
+    /// TLS configuration
+    pub tls: Option<TlsConfig>,
 }
 
 /// HTTP server configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HttpConfig {
     /// HTTP address
     pub addr: Option<String>,
@@ -155,10 +256,42 @@ pub struct HttpConfig {
     pub body_limit: Option<String>,
     /// Max connections
     pub max_connections: Option<u32>,
+    /// Enable CORS
+    pub enable_cors: Option<bool>,
+    /// Allowed CORS origins
+    pub cors_allowed_origins: Option<Vec<String>>,
+    /// Validation mode for Prometheus remote write requests
+    pub prom_validation_mode: Option<String>,
+}
+
+/// MySQL or PostgreSQL protocol server configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SqlServerConfig {
+    /// Enable the server
+    pub enable: Option<bool>,
+    /// Bind address
+    pub addr: Option<String>,
+    /// Runtime size
+    pub runtime_size: Option<u32>,
+    /// TCP keep-alive duration
+    pub keep_alive: Option<String>,
+    /// Prepared statement cache size (MySQL only)
+    pub prepared_stmt_cache_size: Option<u32>,
+    /// TLS configuration
+    pub tls: Option<TlsConfig>,
+}
+
+/// Toggle for a protocol served over HTTP
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProtocolConfig {
+    /// Enable the protocol
+    pub enable: Option<bool>,
+    /// Dedicated bind address, for releases that serve the protocol on its own port
+    pub addr: Option<String>,
 }
 
 /// Metasrv client configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MetaClientConfig {
     /// Metasrv addresses
     pub metasrv_addrs: Vec<String>,
@@ -172,10 +305,18 @@ pub struct MetaClientConfig {
     pub connect_timeout: Option<String>,
     /// TCP nodelay
     pub tcp_nodelay: Option<bool>,
+    /// Maximum number of entries in the metadata cache
+    pub metadata_cache_max_capacity: Option<u64>,
+    /// Time to live of metadata cache entries
+    pub metadata_cache_ttl: Option<String>,
+    /// Time to idle of metadata cache entries
+    pub metadata_cache_tti: Option<String>,
+    /// Client TLS for connections to metasrv; without it the node connects in plaintext
+    pub tls: Option<TlsConfig>,
 }
 
 /// Heartbeat configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HeartbeatConfig {
     /// Heartbeat interval
     pub interval: Option<String>,
@@ -184,7 +325,7 @@ pub struct HeartbeatConfig {
 }
 
 /// Prometheus configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PrometheusConfig {
     /// Enable prometheus
     pub enable: Option<bool>,
@@ -193,16 +334,161 @@ pub struct PrometheusConfig {
 }
 
 /// Logging configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LoggingConfig {
     /// Log level
     pub level: Option<String>,
     /// Log directory
     pub dir: Option<String>,
+    /// Enable OTLP tracing export
+    pub enable_otlp_tracing: Option<bool>,
+    /// OTLP tracing endpoint
+    pub otlp_endpoint: Option<String>,
+    /// OTLP export protocol: "grpc" or "http"
+    pub otlp_export_protocol: Option<String>,
+    /// Extra headers sent with OTLP exports
+    pub otlp_headers: Option<HashMap<String, String>>,
+    /// Also write logs to stdout
+    pub append_stdout: Option<bool>,
+    /// Log format: "text" or "json"
+    pub log_format: Option<String>,
+    /// Maximum number of log files kept
+    pub max_log_files: Option<u32>,
+    /// Tracing sample ratio
+    pub tracing_sample_ratio: Option<TracingSampleRatio>,
+}
+
+/// Tracing sample ratio configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TracingSampleRatio {
+    /// Ratio of traces sampled by default
+    pub default_ratio: Option<f64>,
+    /// Per-protocol overrides of the default ratio
+    pub rules: Option<Vec<TracingSampleRule>>,
+}
+
+/// Sample ratio for the requests of one protocol
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TracingSampleRule {
+    /// Protocol the rule applies to, e.g. "http" or "grpc"
+    pub protocol: Option<String>,
+    /// Request types the rule applies to; all when empty
+    pub request_types: Option<Vec<String>>,
+    /// Ratio of matching traces sampled
+    pub ratio: Option<f64>,
+}
+
+/// Slow query log configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlowQueryConfig {
+    /// Enable the slow query log
+    pub enable: Option<bool>,
+    /// Where slow queries are recorded: "system_table" or "log"
+    pub record_type: Option<String>,
+    /// Queries slower than this are recorded
+    pub threshold: Option<String>,
+    /// Ratio of slow queries recorded
+    pub sample_ratio: Option<f64>,
+    /// Time to live of the slow query system table
+    pub ttl: Option<String>,
+}
+
+/// Self-monitoring metrics export configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportMetricsConfig {
+    /// Enable metrics export
+    pub enable: Option<bool>,
+    /// Export interval
+    pub write_interval: Option<String>,
+    /// Prometheus remote write target
+    pub remote_write: Option<RemoteWriteConfig>,
+}
+
+/// Prometheus remote write target
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteWriteConfig {
+    /// Remote write URL
+    pub url: Option<String>,
+    /// Extra HTTP headers
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// Tracing configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TracingConfig {
+    /// tokio-console address
+    pub tokio_console_addr: Option<String>,
+}
+
+/// Memory profiling configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Enable heap profiling
+    pub enable_heap_profiling: Option<bool>,
+}
+
+/// Event recorder configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventRecorderConfig {
+    /// Time to live of recorded events
+    pub ttl: Option<String>,
+}
+
+/// Region statistics persistence configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsPersistenceConfig {
+    /// Time to live of persisted statistics
+    pub ttl: Option<String>,
+    /// Persistence interval
+    pub interval: Option<String>,
+}
+
+/// Procedure configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcedureConfig {
+    /// Maximum retry times
+    pub max_retry_times: Option<u32>,
+    /// Initial retry delay
+    pub retry_delay: Option<String>,
+    /// Maximum size of a single metadata value
+    pub max_metadata_value_size: Option<String>,
+    /// Maximum number of procedures running at once
+    pub max_running_procedures: Option<u32>,
+}
+
+/// Phi accrual failure detector configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FailureDetectorConfig {
+    /// Phi threshold above which a region is considered failed
+    pub threshold: Option<f64>,
+    /// Minimum standard deviation of heartbeat intervals
+    pub min_std_deviation: Option<String>,
+    /// Heartbeat pause tolerated before failure is suspected
+    pub acceptable_heartbeat_pause: Option<String>,
+    /// Estimate of the first heartbeat interval
+    pub first_heartbeat_estimate: Option<String>,
+}
+
+/// Datanode client section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatanodeClientsConfig {
+    /// Datanode client configuration
+    pub client: Option<DatanodeClientConfig>,
+}
+
+/// Datanode client configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatanodeClientConfig {
+    /// Operation timeout
+    pub timeout: Option<String>,
+    /// Connect timeout
+    pub connect_timeout: Option<String>,
+    /// TCP nodelay
+    pub tcp_nodelay: Option<bool>,
 }
 
 /// WAL configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WalConfig {
     /// WAL provider
     pub provider: Option<String>,
@@ -218,10 +504,77 @@ pub struct WalConfig {
     pub read_batch_size: Option<u32>,
     /// Sync write
     pub sync_write: Option<bool>,
+    /// Reuse purged log files
+    pub enable_log_recycle: Option<bool>,
+    /// Pre-allocate log files on startup
+    pub prefill_log_files: Option<bool>,
+    /// Sync period when sync_write is disabled
+    pub sync_period: Option<String>,
+    /// Parallelism of WAL recovery
+    pub recovery_parallelism: Option<u32>,
+    /// Kafka broker endpoints
+    pub broker_endpoints: Option<Vec<String>>,
+    /// Maximum size of a single Kafka produce batch
+    pub max_batch_bytes: Option<String>,
+    /// Kafka consumer wait timeout
+    pub consumer_wait_timeout: Option<String>,
+    /// Build a WAL index for Kafka topics
+    pub create_index: Option<bool>,
+    /// Interval for dumping the WAL index
+    pub dump_index_interval: Option<String>,
+    /// Ignore missing entries when replaying the WAL
+    pub overwrite_entry_start_id: Option<bool>,
+    /// Create Kafka topics automatically (metasrv)
+    pub auto_create_topics: Option<bool>,
+    /// Interval for pruning obsolete WAL entries (metasrv)
+    pub auto_prune_interval: Option<String>,
+    /// Parallelism of WAL pruning (metasrv)
+    pub auto_prune_parallelism: Option<u32>,
+    /// Topic size that triggers a region flush (metasrv)
+    pub flush_trigger_size: Option<String>,
+    /// Topic size that triggers a region checkpoint (metasrv)
+    pub checkpoint_trigger_size: Option<String>,
+    /// Number of Kafka topics (metasrv)
+    pub num_topics: Option<u32>,
+    /// Topic selector type (metasrv)
+    pub selector_type: Option<String>,
+    /// Topic name prefix (metasrv)
+    pub topic_name_prefix: Option<String>,
+    /// Topic replication factor (metasrv)
+    pub replication_factor: Option<i16>,
+    /// Topic creation timeout (metasrv)
+    pub create_topic_timeout: Option<String>,
+    /// Kafka SASL configuration
+    pub sasl: Option<KafkaSaslConfig>,
+    /// Kafka TLS configuration
+    pub tls: Option<KafkaTlsConfig>,
+}
+
+/// Kafka SASL configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KafkaSaslConfig {
+    /// SASL mechanism: "PLAIN", "SCRAM-SHA-256" or "SCRAM-SHA-512"
+    #[serde(rename = "type")]
+    pub mechanism: Option<String>,
+    /// Username
+    pub username: Option<String>,
+    /// Password
+    pub password: Option<String>,
+}
+
+/// Kafka TLS configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KafkaTlsConfig {
+    /// Server CA certificate path
+    pub server_ca_cert_path: Option<String>,
+    /// Client certificate path
+    pub client_cert_path: Option<String>,
+    /// Client private key path
+    pub client_key_path: Option<String>,
 }
 
 /// Datanode storage configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DatanodeStorageConfig {
     /// Data home directory
     pub data_home: Option<String>,
@@ -244,10 +597,63 @@ pub struct DatanodeStorageConfig {
     pub endpoint: Option<String>,
     /// Region
     pub region: Option<String>,
+    /// Use virtual-host-style S3 requests
+    pub enable_virtual_host_style: Option<bool>,
+    /// S3 storage class of written objects, e.g. STANDARD, INTELLIGENT_TIERING or EXPRESS_ONEZONE
+    pub storage_class: Option<String>,
+    /// OSS access key secret
+    pub access_key_secret: Option<String>,
+    /// Azure Blob container
+    pub container: Option<String>,
+    /// Azure Blob account name
+    pub account_name: Option<String>,
+    /// Azure Blob account key
+    pub account_key: Option<String>,
+    /// Azure Blob SAS token
+    pub sas_token: Option<String>,
+    /// GCS scope
+    pub scope: Option<String>,
+    /// GCS credential file path
+    pub credential_path: Option<String>,
+    /// GCS base64-encoded credential
+    pub credential: Option<String>,
+    /// WebHDFS delegation token, for namenodes requiring Kerberos
+    pub delegation: Option<String>,
+    /// WebHDFS user for simple authentication, the namenode's static web user when unset
+    pub user_name: Option<String>,
+    /// Object storage HTTP client configuration
+    pub http_client: Option<HttpClientConfig>,
+    /// Additional object storage providers
+    pub providers: Option<Vec<ObjectStoreProviderConfig>>,
+}
+
+/// Object storage HTTP client configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// Maximum idle connections per host
+    pub pool_max_idle_per_host: Option<u32>,
+    /// Connect timeout
+    pub connect_timeout: Option<String>,
+    /// Request timeout
+    pub timeout: Option<String>,
+    /// Idle connection timeout
+    pub pool_idle_timeout: Option<String>,
+    /// Skip TLS certificate validation
+    pub skip_ssl_validation: Option<bool>,
+}
+
+/// Additional object storage provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectStoreProviderConfig {
+    /// Provider name referenced by table options
+    pub name: Option<String>,
+    /// Provider storage settings
+    #[serde(flatten)]
+    pub storage: DatanodeStorageConfig,
 }
 
 /// Query configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct QueryConfig {
     /// Query parallelism
     pub parallelism: Option<u32>,
@@ -255,10 +661,147 @@ pub struct QueryConfig {
     pub allow_query_fallback: Option<bool>,
 }
 
+/// A single `[[region_engine]]` entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegionEngineConfig {
+    /// Mito engine configuration
+    pub mito: Option<MitoConfig>,
+    /// File engine configuration
+    pub file: Option<FileEngineConfig>,
+    /// Metric engine configuration
+    pub metric: Option<MetricEngineConfig>,
+}
+
+/// Mito engine configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MitoConfig {
+    /// Number of region workers
+    pub num_workers: Option<u32>,
+    /// Request channel size of each worker
+    pub worker_channel_size: Option<u32>,
+    /// Maximum requests a worker handles in one batch
+    pub worker_request_batch_size: Option<u32>,
+    /// Manifest versions between checkpoints
+    pub manifest_checkpoint_distance: Option<u64>,
+    /// Removed files kept in the manifest
+    pub experimental_manifest_keep_removed_file_count: Option<u32>,
+    /// How long removed files are kept in the manifest
+    pub experimental_manifest_keep_removed_file_ttl: Option<String>,
+    /// Compress manifest files
+    pub compress_manifest: Option<bool>,
+    /// Maximum concurrent flush jobs
+    pub max_background_flushes: Option<u32>,
+    /// Maximum concurrent compaction jobs
+    pub max_background_compactions: Option<u32>,
+    /// Maximum concurrent purge jobs
+    pub max_background_purges: Option<u32>,
+    /// Interval to auto flush regions
+    pub auto_flush_interval: Option<String>,
+    /// Global write buffer size that triggers flushes
+    pub global_write_buffer_size: Option<String>,
+    /// Global write buffer size at which writes are rejected
+    pub global_write_buffer_reject_size: Option<String>,
+    /// SST metadata cache size
+    pub sst_meta_cache_size: Option<String>,
+    /// Vector cache size
+    pub vector_cache_size: Option<String>,
+    /// Page cache size
+    pub page_cache_size: Option<String>,
+    /// Selector result cache size
+    pub selector_result_cache_size: Option<String>,
+    /// Enable the write cache for object storage
+    pub enable_write_cache: Option<bool>,
+    /// Write cache directory
+    pub write_cache_path: Option<String>,
+    /// Write cache capacity
+    pub write_cache_size: Option<String>,
+    /// Time to live of write cache entries
+    pub write_cache_ttl: Option<String>,
+    /// Buffer size for SST writing
+    pub sst_write_buffer_size: Option<String>,
+    /// Capacity of the parallel scan channel
+    pub parallel_scan_channel_size: Option<u32>,
+    /// Maximum SST files scanned concurrently
+    pub max_concurrent_scan_files: Option<u32>,
+    /// Allow stale WAL entries on replay
+    pub allow_stale_entries: Option<bool>,
+    /// Minimum interval between compactions of a region
+    pub min_compaction_interval: Option<String>,
+    /// Index configuration
+    pub index: Option<MitoIndexConfig>,
+    /// Inverted index configuration
+    pub inverted_index: Option<SecondaryIndexConfig>,
+    /// Full-text index configuration
+    pub fulltext_index: Option<SecondaryIndexConfig>,
+    /// Bloom filter index configuration
+    pub bloom_filter_index: Option<SecondaryIndexConfig>,
+    /// Memtable configuration
+    pub memtable: Option<MemtableConfig>,
+}
+
+/// Mito index configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MitoIndexConfig {
+    /// Auxiliary directory for intermediate index files
+    pub aux_path: Option<String>,
+    /// Staging directory capacity
+    pub staging_size: Option<String>,
+    /// Time to live of staging files
+    pub staging_ttl: Option<String>,
+    /// Index metadata cache size
+    pub metadata_cache_size: Option<String>,
+    /// Index content cache size
+    pub content_cache_size: Option<String>,
+    /// Index content cache page size
+    pub content_cache_page_size: Option<String>,
+    /// Index result cache size
+    pub result_cache_size: Option<String>,
+}
+
+/// Inverted, full-text or bloom filter index configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecondaryIndexConfig {
+    /// Build the index on flush: "auto" or "disable"
+    pub create_on_flush: Option<String>,
+    /// Build the index on compaction: "auto" or "disable"
+    pub create_on_compaction: Option<String>,
+    /// Use the index on query: "auto" or "disable"
+    pub apply_on_query: Option<String>,
+    /// Memory threshold for index creation: "auto", "unlimited" or a size
+    pub mem_threshold_on_create: Option<String>,
+    /// Intermediate directory (deprecated, use `index.aux_path`)
+    pub intermediate_path: Option<String>,
+}
+
+/// Memtable configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemtableConfig {
+    /// Memtable type: "time_series" or "partition_tree"
+    #[serde(rename = "type")]
+    pub memtable_type: Option<String>,
+    /// Maximum keys in an index shard
+    pub index_max_keys_per_shard: Option<u32>,
+    /// Rows before a data part is frozen
+    pub data_freeze_threshold: Option<u32>,
+    /// Maximum dictionary bytes before forking
+    pub fork_dictionary_bytes: Option<String>,
+}
+
+/// File engine configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileEngineConfig {}
+
+/// Metric engine configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricEngineConfig {
+    /// Use sparse primary key encoding
+    pub experimental_sparse_primary_key_encoding: Option<bool>,
+}
+
 /// Storage configuration for Datanode
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
-    /// Storage type: "S3", "Oss", "Azblob", "Gcs", "File"
+    /// Storage type: "S3", "Oss", "Azblob", "Gcs", "Webhdfs", "File"
     pub storage_type: String,
     /// Storage configuration data
     #[serde(flatten)]
@@ -266,16 +809,23 @@ pub struct StorageConfig {
 }
 
 /// TLS configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TlsConfig {
     /// Certificate file path
+    #[serde(alias = "cert_path")]
     pub cert: Option<String>,
     /// Private key file path
+    #[serde(alias = "key_path")]
     pub key: Option<String>,
     /// CA certificate file path
+    #[serde(alias = "ca_cert_path")]
     pub ca: Option<String>,
     /// Server name for verification
     pub server_name: Option<String>,
+    /// TLS mode: "disable", "prefer", "require", "verify-ca" or "verify-full"
+    pub mode: Option<String>,
+    /// Reload certificates when the files change
+    pub watch: Option<bool>,
 }
 
 /// S3-compatible storage configuration
@@ -338,44 +888,388 @@ pub struct GcsConfig {
     pub service_account_path: Option<String>,
 }
 
+/// Config file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// TOML, the format GreptimeDB reads
+    Toml,
+    /// YAML, as rendered by some deployment tooling before templating to TOML
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a `.toml`, `.yaml` or `.yml` extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ConfigFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "toml" => Ok(Self::Toml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            other => Err(format!("unknown config format '{}', expected toml or yaml", other)),
+        }
+    }
+}
+
+/// Options controlling how a component config file is read
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// File format; detected from the extension when unset, falling back to TOML then YAML
+    pub format: Option<ConfigFormat>,
+    /// `key.path=value` overrides applied on top of the file
+    pub overrides: Vec<String>,
+}
+
 /// Configuration parser utility
 pub struct ConfigParser;
 
 impl ConfigParser {
-    /// Parse Metasrv configuration from TOML file
-    pub fn parse_metasrv_config<P: AsRef<Path>>(path: P) -> crate::error::Result<MetasrvConfig> {
+    /// Read a config file and resolve its `${VAR}` and `${VAR:-default}` placeholders
+    fn read_config<P: AsRef<Path>>(path: P) -> crate::error::Result<String> {
         let content = fs::read_to_string(&path).context(crate::error::FileSystemSnafu {
             message: format!("Failed to read config file: {:?}", path.as_ref()),
         })?;
 
-        toml::from_str(&content).context(crate::error::TomlParsingSnafu {
-            message: "Failed to parse metasrv TOML config".to_string(),
-        })
+        resolve_env_vars(&content)
     }
 
-    /// Parse Frontend configuration from TOML file
-    pub fn parse_frontend_config<P: AsRef<Path>>(path: P) -> crate::error::Result<FrontendConfig> {
-        let content = fs::read_to_string(&path).context(crate::error::FileSystemSnafu {
-            message: format!("Failed to read config file: {:?}", path.as_ref()),
-        })?;
+    /// Read a config file as a TOML value, converting YAML input to the same structure
+    fn read_value<P: AsRef<Path>>(path: P, format: Option<ConfigFormat>) -> crate::error::Result<toml::Value> {
+        let content = Self::read_config(&path)?;
+
+        let mut value = match format.or_else(|| ConfigFormat::from_path(&path)) {
+            Some(ConfigFormat::Toml) => toml::from_str(&content).context(crate::error::TomlParsingSnafu {
+                message: format!("Failed to parse {:?} as TOML", path.as_ref()),
+            })?,
+            Some(ConfigFormat::Yaml) => parse_yaml(&content)?,
+            None => toml::from_str(&content).or_else(|_| parse_yaml(&content))?,
+        };
+        crate::credentials::resolve_secret_refs(&mut value);
+        crate::redact::register_config_secrets(&value);
+        Ok(value)
+    }
+
+    /// Read a config file as an untyped value with the `key.path=value` overrides applied
+    pub fn parse_value<P: AsRef<Path>>(path: P, options: &ParseOptions) -> crate::error::Result<toml::Value> {
+        let mut value = Self::read_value(&path, options.format)?;
+        for assignment in &options.overrides {
+            apply_override(&mut value, assignment)?;
+        }
+        crate::credentials::resolve_secret_refs(&mut value);
+        crate::redact::register_config_secrets(&value);
+        Ok(value)
+    }
 
-        toml::from_str(&content).context(crate::error::TomlParsingSnafu {
-            message: "Failed to parse frontend TOML config".to_string(),
+    /// Parse a component configuration from file, applying `key.path=value` overrides
+    /// on top of the file before deserializing
+    fn parse_with_options<T, P>(path: P, options: &ParseOptions, component: &str) -> crate::error::Result<T>
+    where
+        T: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        Self::parse_value(path, options)?.try_into().context(crate::error::TomlParsingSnafu {
+            message: format!("Failed to parse {} config", component),
         })
     }
 
-    /// Parse Datanode configuration from TOML file
-    pub fn parse_datanode_config<P: AsRef<Path>>(path: P) -> crate::error::Result<DatanodeConfig> {
-        let content = fs::read_to_string(&path).context(crate::error::FileSystemSnafu {
-            message: format!("Failed to read config file: {:?}", path.as_ref()),
-        })?;
+    /// Parse Metasrv configuration from file
+    pub fn parse_metasrv_config<P: AsRef<Path>>(path: P, options: &ParseOptions) -> crate::error::Result<MetasrvConfig> {
+        Self::parse_with_options(path, options, "metasrv")
+    }
+
+    /// Parse Frontend configuration from file
+    pub fn parse_frontend_config<P: AsRef<Path>>(path: P, options: &ParseOptions) -> crate::error::Result<FrontendConfig> {
+        Self::parse_with_options(path, options, "frontend")
+    }
+
+    /// Parse Datanode configuration from file
+    pub fn parse_datanode_config<P: AsRef<Path>>(path: P, options: &ParseOptions) -> crate::error::Result<DatanodeConfig> {
+        Self::parse_with_options(path, options, "datanode")
+    }
+}
 
-        toml::from_str(&content).context(crate::error::TomlParsingSnafu {
-            message: "Failed to parse datanode TOML config".to_string(),
+impl ConfigParser {
+    /// Parse a standalone storage configuration from TOML file.
+    ///
+    /// Accepts either a full datanode file with a `[storage]` table or a minimal file
+    /// containing only the storage keys at the top level.
+    pub fn parse_storage_config<P: AsRef<Path>>(path: P) -> crate::error::Result<DatanodeStorageConfig> {
+        let value = Self::read_value(&path, None)?;
+        let storage = match value.get("storage") {
+            Some(storage) => storage.clone(),
+            None => value,
+        };
+
+        storage.try_into().context(crate::error::TomlParsingSnafu {
+            message: "Failed to parse storage TOML config".to_string(),
         })
     }
 }
 
+/// Parse YAML into the equivalent TOML value.
+///
+/// YAML nulls have no TOML counterpart and are dropped, the same as leaving the key unset.
+fn parse_yaml(content: &str) -> crate::error::Result<toml::Value> {
+    let value: serde_yaml::Value = serde_yaml::from_str(content).context(crate::error::YamlParsingSnafu {
+        message: "Failed to parse YAML config".to_string(),
+    })?;
+
+    match yaml_to_toml(value)? {
+        Some(value @ toml::Value::Table(_)) => Ok(value),
+        _ => crate::error::InvalidConfigSnafu {
+            message: "YAML config must be a mapping at the top level".to_string(),
+        }
+        .fail(),
+    }
+}
+
+fn yaml_to_toml(value: serde_yaml::Value) -> crate::error::Result<Option<toml::Value>> {
+    Ok(match value {
+        serde_yaml::Value::Null => None,
+        serde_yaml::Value::Bool(b) => Some(toml::Value::Boolean(b)),
+        serde_yaml::Value::Number(n) => match n.as_i64() {
+            Some(i) => Some(toml::Value::Integer(i)),
+            None => Some(toml::Value::Float(n.as_f64().unwrap_or(f64::NAN))),
+        },
+        serde_yaml::Value::String(s) => Some(toml::Value::String(s)),
+        serde_yaml::Value::Sequence(items) => Some(toml::Value::Array(
+            items.into_iter().filter_map(|item| yaml_to_toml(item).transpose()).collect::<crate::error::Result<_>>()?,
+        )),
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut table = toml::Table::new();
+            for (key, value) in mapping {
+                let key = match key {
+                    serde_yaml::Value::String(key) => key,
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    serde_yaml::Value::Bool(b) => b.to_string(),
+                    other => {
+                        return crate::error::InvalidConfigSnafu {
+                            message: format!("Unsupported YAML mapping key: {:?}", other),
+                        }
+                        .fail();
+                    }
+                };
+                if let Some(value) = yaml_to_toml(value)? {
+                    table.insert(key, value);
+                }
+            }
+            Some(toml::Value::Table(table))
+        }
+        serde_yaml::Value::Tagged(tagged) => yaml_to_toml(tagged.value)?,
+    })
+}
+
+/// Apply a single `key.path=value` assignment to a parsed config.
+///
+/// The value is read as a TOML literal when it parses as one (`2`, `true`, `["a", "b"]`) and as a
+/// plain string otherwise, so `storage.bucket=staging` needs no quoting. Missing tables along the
+/// path are created, and numeric segments index into arrays such as `region_engine.0.mito`.
+pub fn apply_override(config: &mut toml::Value, assignment: &str) -> crate::error::Result<()> {
+    let Some((key, raw)) = assignment.split_once('=') else {
+        return crate::error::InvalidConfigSnafu {
+            message: format!("Override '{}' must have the form key.path=value", assignment),
+        }
+        .fail();
+    };
+    let key = key.trim();
+    let raw = raw.trim();
+    if key.is_empty() || key.split('.').any(|segment| segment.is_empty()) {
+        return crate::error::InvalidConfigSnafu {
+            message: format!("Override '{}' has an invalid key path", assignment),
+        }
+        .fail();
+    }
+
+    let value = toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+
+    let segments = key.split('.').collect::<Vec<_>>();
+    let (last, parents) = segments.split_last().expect("key path is not empty");
+    let mut current = config;
+    for segment in parents {
+        current = match current {
+            toml::Value::Table(table) => table
+                .entry(segment.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new())),
+            toml::Value::Array(items) => match segment.parse::<usize>().ok().and_then(|index| items.get_mut(index)) {
+                Some(item) => item,
+                None => {
+                    return crate::error::InvalidConfigSnafu {
+                        message: format!("Override '{}': '{}' is not a valid index", assignment, segment),
+                    }
+                    .fail();
+                }
+            },
+            _ => {
+                return crate::error::InvalidConfigSnafu {
+                    message: format!("Override '{}': '{}' is not a table", assignment, segment),
+                }
+                .fail();
+            }
+        };
+    }
+
+    match current {
+        toml::Value::Table(table) => {
+            table.insert(last.to_string(), value);
+            Ok(())
+        }
+        _ => crate::error::InvalidConfigSnafu {
+            message: format!("Override '{}': parent of '{}' is not a table", assignment, last),
+        }
+        .fail(),
+    }
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` placeholders with values from the process environment
+pub fn resolve_env_vars(content: &str) -> crate::error::Result<String> {
+    substitute_env_vars(content, |name| std::env::var(name).ok())
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` placeholders with values from `lookup`.
+///
+/// The default is used when the variable is unset or empty. Comments are left untouched, so
+/// documentation such as `s3://${bucket}/${root}` never has to resolve. Values substituted into
+/// a `"..."` string are escaped, so a quote or backslash in a secret cannot break the file;
+/// defaults are TOML already and copied as written.
+fn substitute_env_vars<F>(content: &str, lookup: F) -> crate::error::Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(content.len());
+    let mut context = TomlContext::Bare;
+    let mut rest = content;
+
+    while let Some(c) = rest.chars().next() {
+        let len = match (context, c) {
+            (TomlContext::Comment, '\n') => {
+                context = TomlContext::Bare;
+                1
+            }
+            (TomlContext::Comment, _) => c.len_utf8(),
+            (TomlContext::Bare, '#') => {
+                context = TomlContext::Comment;
+                1
+            }
+            (TomlContext::Bare, '"') if rest.starts_with(r#"""""#) => {
+                context = TomlContext::MultiBasic;
+                3
+            }
+            (TomlContext::Bare, '"') => {
+                context = TomlContext::Basic;
+                1
+            }
+            (TomlContext::Bare, '\'') if rest.starts_with("'''") => {
+                context = TomlContext::MultiLiteral;
+                3
+            }
+            (TomlContext::Bare, '\'') => {
+                context = TomlContext::Literal;
+                1
+            }
+            // An escape sequence, so an escaped quote does not end the string
+            (TomlContext::Basic | TomlContext::MultiBasic, '\\') => 1 + rest[1..].chars().next().map_or(0, char::len_utf8),
+            (TomlContext::Basic, '"' | '\n') | (TomlContext::Literal, '\'' | '\n') => {
+                context = TomlContext::Bare;
+                1
+            }
+            (TomlContext::MultiBasic, '"') if rest.starts_with(r#"""""#) => {
+                context = TomlContext::Bare;
+                3
+            }
+            (TomlContext::MultiLiteral, '\'') if rest.starts_with("'''") => {
+                context = TomlContext::Bare;
+                3
+            }
+            (_, '$') if rest.starts_with("${") => {
+                let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                let Some(len) = line[2..].find('}') else {
+                    return crate::error::InvalidConfigSnafu {
+                        message: format!("Unterminated placeholder in config line: {}", line.trim_end()),
+                    }
+                    .fail();
+                };
+                let placeholder = &line[2..2 + len];
+                let (name, default) = match placeholder.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (placeholder, None),
+                };
+
+                match (lookup(name).filter(|value| !value.is_empty()), default) {
+                    (Some(value), _) => output.push_str(&encode_env_value(name, &value, context)?),
+                    (None, Some(default)) => output.push_str(default),
+                    (None, None) => {
+                        return crate::error::InvalidConfigSnafu {
+                            message: format!("Environment variable {} is not set and has no default", name),
+                        }
+                        .fail();
+                    }
+                }
+                rest = &rest[2 + len + 1..];
+                continue;
+            }
+            _ => c.len_utf8(),
+        };
+        output.push_str(&rest[..len]);
+        rest = &rest[len..];
+    }
+
+    Ok(output)
+}
+
+/// Where [`substitute_env_vars`] is in the TOML text
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TomlContext {
+    Bare,
+    Comment,
+    Basic,
+    MultiBasic,
+    Literal,
+    MultiLiteral,
+}
+
+/// Write an environment value so it reads back unchanged in `context`; literal strings have no
+/// escapes, so values they cannot hold are rejected
+fn encode_env_value(name: &str, value: &str, context: TomlContext) -> crate::error::Result<String> {
+    match context {
+        TomlContext::Basic | TomlContext::MultiBasic => {
+            let mut escaped = String::with_capacity(value.len());
+            for c in value.chars() {
+                match c {
+                    '"' => escaped.push_str(r#"\""#),
+                    '\\' => escaped.push_str(r"\\"),
+                    '\n' => escaped.push_str(r"\n"),
+                    '\r' => escaped.push_str(r"\r"),
+                    '\t' => escaped.push_str(r"\t"),
+                    c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+                    c => escaped.push(c),
+                }
+            }
+            Ok(escaped)
+        }
+        TomlContext::Literal if value.contains(['\'', '\n']) => crate::error::InvalidConfigSnafu {
+            message: format!("Environment variable {} holds a quote or line break, which a '...' string cannot; use \"${{{}}}\"", name, name),
+        }
+        .fail(),
+        TomlContext::MultiLiteral if value.contains("'''") => crate::error::InvalidConfigSnafu {
+            message: format!("Environment variable {} holds ''', which a '''...''' string cannot; use \"${{{}}}\"", name, name),
+        }
+        .fail(),
+        _ => Ok(value.to_string()),
+    }
+}
+
 impl StorageConfig {
     /// Convert to S3 configuration
     pub fn as_s3_config(&self) -> crate::error::Result<S3Config> {
@@ -439,15 +1333,27 @@ impl StorageConfig {
 
 impl ConfigParser {
     /// Try to parse configuration from different possible formats
-    pub fn parse_config_flexible<P: AsRef<Path>>(path: P) -> crate::error::Result<toml::Value> {
-        let content = fs::read_to_string(&path).map_err(|e| crate::error::Error::ConfigLoad {
-            message: format!("Failed to read config file: {:?}: {}", path.as_ref(), e),
-        })?;
+    pub fn parse_config_flexible<P: AsRef<Path>>(path: P, format: Option<ConfigFormat>) -> crate::error::Result<toml::Value> {
+        Self::read_value(path, format)
+    }
 
-        // Try TOML first
-        toml::from_str(&content).map_err(|e| crate::error::Error::ConfigLoad {
-            message: format!("Failed to parse config as TOML: {}", e),
-        })
+    /// Guess the component a config belongs to from top-level keys only that component uses
+    pub fn detect_component(value: &toml::Value) -> Option<&'static str> {
+        const METASRV_KEYS: &[&str] = &["backend", "store_addrs", "store_key_prefix", "selector", "enable_region_failover", "failure_detector"];
+        const DATANODE_KEYS: &[&str] = &["storage", "node_id", "region_engine", "require_lease_before_startup"];
+        const FRONTEND_KEYS: &[&str] = &["mysql", "postgres", "opentsdb", "influxdb", "prom_store", "jaeger", "internal_grpc"];
+
+        let table = value.as_table()?;
+        let has_any = |keys: &[&str]| keys.iter().any(|key| table.contains_key(*key));
+        if has_any(METASRV_KEYS) {
+            Some("metasrv")
+        } else if has_any(DATANODE_KEYS) {
+            Some("datanode")
+        } else if has_any(FRONTEND_KEYS) || table.contains_key("meta_client") {
+            Some("frontend")
+        } else {
+            None
+        }
     }
 
     /// Create a default metasrv config for testing
@@ -466,6 +1372,11 @@ impl ConfigParser {
             grpc: None,
             http: None,
             backend_tls: None,
+            backend_username: None,
+            backend_password: None,
+            max_txn_ops: None,
+            wal: None,
+            ..Default::default()
         }
     }
 
@@ -483,10 +1394,12 @@ impl ConfigParser {
                 ddl_timeout: Some("10s".to_string()),
                 connect_timeout: Some("1s".to_string()),
                 tcp_nodelay: Some(true),
+                ..Default::default()
             }),
             heartbeat: None,
             prometheus: None,
             logging: None,
+            ..Default::default()
         }
     }
 
@@ -504,6 +1417,7 @@ impl ConfigParser {
                 timeout: Some("30s".to_string()),
                 body_limit: None,
                 max_connections: None,
+                ..Default::default()
             }),
             grpc: Some(GrpcConfig {
                 addr: Some("127.0.0.1:4001".to_string()),
@@ -511,9 +1425,10 @@ impl ConfigParser {
                 runtime_size: Some(8),
                 max_recv_message_size: None,
                 max_send_message_size: None,
+                ..Default::default()
             }),
             heartbeat: Some(HeartbeatConfig {
-                interval: Some("18s".to_string()),
+                interval: Some("3s".to_string()),
                 retry_interval: Some("3s".to_string()),
             }),
             meta_client: Some(MetaClientConfig {
@@ -523,6 +1438,7 @@ impl ConfigParser {
                 ddl_timeout: Some("10s".to_string()),
                 connect_timeout: Some("1s".to_string()),
                 tcp_nodelay: Some(true),
+                ..Default::default()
             }),
             wal: None,
             storage: Some(DatanodeStorageConfig {
@@ -536,9 +1452,11 @@ impl ConfigParser {
                 secret_access_key: None,
                 endpoint: None,
                 region: None,
+                ..Default::default()
             }),
             query: None,
             logging: None,
+            ..Default::default()
         }
     }
 }
@@ -600,7 +1518,7 @@ addr = "0.0.0.0:3002"
         let mut temp_file = NamedTempFile::new().unwrap();
         temp_file.write_all(toml_content.as_bytes()).unwrap();
 
-        let config = ConfigParser::parse_metasrv_config(temp_file.path()).unwrap();
+        let config = ConfigParser::parse_metasrv_config(temp_file.path(), &ParseOptions::default()).unwrap();
         assert_eq!(config.backend, "etcd_store");
         assert_eq!(config.store_addrs, vec!["127.0.0.1:2379"]);
         assert_eq!(config.store_key_prefix, Some("/greptime".to_string()));
@@ -625,7 +1543,7 @@ addr = "0.0.0.0:4001"
         let mut temp_file = NamedTempFile::new().unwrap();
         temp_file.write_all(toml_content.as_bytes()).unwrap();
 
-        let config = ConfigParser::parse_frontend_config(temp_file.path()).unwrap();
+        let config = ConfigParser::parse_frontend_config(temp_file.path(), &ParseOptions::default()).unwrap();
         assert!(config.meta_client.is_some());
         let meta_client = config.meta_client.unwrap();
         assert_eq!(meta_client.metasrv_addrs, vec!["127.0.0.1:3002", "127.0.0.1:3003"]);
@@ -639,6 +1557,33 @@ addr = "0.0.0.0:4001"
         assert_eq!(grpc.addr, Some("0.0.0.0:4001".to_string()));
     }
 
+    #[test]
+    fn test_storage_config_parsing() {
+        let minimal = r#"
+type = "S3"
+bucket = "my-bucket"
+endpoint = "http://localhost:9000"
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(minimal.as_bytes()).unwrap();
+        let storage = ConfigParser::parse_storage_config(temp_file.path()).unwrap();
+        assert_eq!(storage.storage_type, Some("S3".to_string()));
+        assert_eq!(storage.bucket, Some("my-bucket".to_string()));
+
+        let nested = r#"
+node_id = 1
+
+[storage]
+type = "File"
+data_home = "/tmp/greptimedb"
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(nested.as_bytes()).unwrap();
+        let storage = ConfigParser::parse_storage_config(temp_file.path()).unwrap();
+        assert_eq!(storage.storage_type, Some("File".to_string()));
+        assert_eq!(storage.data_home, Some("/tmp/greptimedb".to_string()));
+    }
+
     #[test]
     fn test_datanode_config_parsing() {
         let toml_content = r#"
@@ -659,7 +1604,7 @@ region = "us-west-2"
         let mut temp_file = NamedTempFile::new().unwrap();
         temp_file.write_all(toml_content.as_bytes()).unwrap();
 
-        let config = ConfigParser::parse_datanode_config(temp_file.path()).unwrap();
+        let config = ConfigParser::parse_datanode_config(temp_file.path(), &ParseOptions::default()).unwrap();
         assert!(config.meta_client.is_some());
         let meta_client = config.meta_client.unwrap();
         assert_eq!(meta_client.metasrv_addrs, vec!["127.0.0.1:3002"]);
@@ -672,4 +1617,151 @@ region = "us-west-2"
         assert_eq!(storage.secret_access_key, Some("my-secret".to_string()));
         assert_eq!(storage.region, Some("us-west-2".to_string()));
     }
+
+    #[test]
+    fn test_example_configs_parsing() {
+        let datanode = ConfigParser::parse_datanode_config("datanode.example.toml", &ParseOptions::default()).unwrap();
+        let mito = datanode.mito().unwrap();
+        assert_eq!(mito.write_cache_size, Some("5GiB".to_string()));
+        assert_eq!(mito.index.as_ref().unwrap().staging_size, Some("2GB".to_string()));
+        assert_eq!(mito.memtable.as_ref().unwrap().memtable_type, Some("time_series".to_string()));
+        assert_eq!(datanode.region_engine.as_ref().unwrap().len(), 3);
+        assert_eq!(datanode.export_metrics.unwrap().write_interval, Some("30s".to_string()));
+        assert_eq!(datanode.grpc.unwrap().tls.unwrap().mode, Some("disable".to_string()));
+
+        let frontend = ConfigParser::parse_frontend_config("frontend.example.toml", &ParseOptions::default()).unwrap();
+        assert_eq!(frontend.mysql.unwrap().addr, Some("127.0.0.1:4002".to_string()));
+        assert_eq!(frontend.prometheus.unwrap().with_metric_engine, Some(true));
+        assert_eq!(frontend.slow_query.unwrap().threshold, Some("30s".to_string()));
+        assert_eq!(frontend.internal_grpc.unwrap().addr, Some("127.0.0.1:4010".to_string()));
+
+        let metasrv = ConfigParser::parse_metasrv_config("metasrv.example.toml", &ParseOptions::default()).unwrap();
+        assert_eq!(metasrv.failure_detector.unwrap().threshold, Some(8.0));
+        assert_eq!(metasrv.procedure.unwrap().max_metadata_value_size, Some("1500KiB".to_string()));
+        assert_eq!(metasrv.wal.unwrap().num_topics, Some(64));
+    }
+
+    #[test]
+    fn test_substitute_env_vars() {
+        let lookup = |name: &str| match name {
+            "S3_BUCKET" => Some("prod-bucket".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+
+        let content = "bucket = \"${S3_BUCKET}\"\nregion = \"${S3_REGION:-us-east-1}\"\nroot = \"${EMPTY:-data}\"\n";
+        let resolved = substitute_env_vars(content, lookup).unwrap();
+        assert_eq!(resolved, "bucket = \"prod-bucket\"\nregion = \"us-east-1\"\nroot = \"data\"\n");
+
+        let comment = "## stored under `s3://${bucket}/${root}`\nroot = \"data\" # or `${root`\n";
+        assert_eq!(substitute_env_vars(comment, lookup).unwrap(), comment);
+        // A `#` inside a string does not start a comment
+        assert_eq!(substitute_env_vars("root = \"#${S3_BUCKET}\"", lookup).unwrap(), "root = \"#prod-bucket\"");
+
+        // Values are escaped inside "..." strings and copied as they are elsewhere
+        let lookup = |name: &str| match name {
+            "SECRET" => Some("a\"b\\c\nd".to_string()),
+            "PORT" => Some("4000".to_string()),
+            _ => None,
+        };
+        let content = "secret = \"${SECRET}\"\nport = ${PORT}\npath = 'C:\\${PORT}'\n";
+        let value: toml::Value = toml::from_str(&substitute_env_vars(content, lookup).unwrap()).unwrap();
+        assert_eq!(value["secret"].as_str(), Some("a\"b\\c\nd"));
+        assert_eq!(value["port"].as_integer(), Some(4000));
+        assert_eq!(value["path"].as_str(), Some("C:\\4000"));
+        assert!(substitute_env_vars("secret = '${SECRET}'", lookup).is_err());
+
+        assert!(substitute_env_vars("bucket = \"${MISSING}\"", lookup).is_err());
+        assert!(substitute_env_vars("bucket = \"${S3_BUCKET\"", lookup).is_err());
+    }
+
+    #[test]
+    fn test_apply_override() {
+        let mut value: toml::Value = toml::from_str(
+            r#"
+node_id = 1
+
+[storage]
+type = "S3"
+bucket = "prod-bucket"
+
+[[region_engine]]
+[region_engine.mito]
+num_workers = 8
+"#,
+        )
+        .unwrap();
+
+        apply_override(&mut value, "storage.bucket=staging-bucket").unwrap();
+        apply_override(&mut value, "node_id=2").unwrap();
+        apply_override(&mut value, "meta_client.metasrv_addrs=[\"meta-0:3002\", \"meta-1:3002\"]").unwrap();
+        apply_override(&mut value, "region_engine.0.mito.num_workers=4").unwrap();
+
+        let config: DatanodeConfig = value.clone().try_into().unwrap();
+        assert_eq!(config.node_id, Some(2));
+        assert_eq!(config.storage.unwrap().bucket, Some("staging-bucket".to_string()));
+        assert_eq!(config.meta_client.unwrap().metasrv_addrs, vec!["meta-0:3002", "meta-1:3002"]);
+        assert_eq!(config.region_engine.unwrap()[0].mito.as_ref().unwrap().num_workers, Some(4));
+
+        assert!(apply_override(&mut value, "storage.bucket").is_err());
+        assert!(apply_override(&mut value, "storage..bucket=x").is_err());
+        assert!(apply_override(&mut value, "node_id.inner=x").is_err());
+        assert!(apply_override(&mut value, "region_engine.5.mito.num_workers=4").is_err());
+    }
+
+    #[test]
+    fn test_yaml_config_parsing() {
+        let yaml = r#"
+node_id: 1
+meta_client:
+  metasrv_addrs: ["127.0.0.1:3002"]
+  timeout: 3s
+storage:
+  type: S3
+  bucket: my-bucket
+  cache_capacity: ~
+region_engine:
+  - mito:
+      num_workers: 8
+      global_write_buffer_size: 1GB
+"#;
+        let mut temp_file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        temp_file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = ConfigParser::parse_datanode_config(temp_file.path(), &ParseOptions::default()).unwrap();
+        assert_eq!(config.node_id, Some(1));
+        assert_eq!(config.meta_client.unwrap().timeout, Some("3s".to_string()));
+        let storage = config.storage.as_ref().unwrap();
+        assert_eq!(storage.bucket, Some("my-bucket".to_string()));
+        assert_eq!(storage.cache_capacity, None);
+        assert_eq!(config.mito().unwrap().num_workers, Some(8));
+
+        // Without a telling extension the format can be given explicitly
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(yaml.as_bytes()).unwrap();
+        let options = ParseOptions {
+            format: Some(ConfigFormat::Yaml),
+            ..Default::default()
+        };
+        assert!(ConfigParser::parse_datanode_config(temp_file.path(), &options).is_ok());
+
+        assert_eq!(ConfigFormat::from_path("datanode.yml"), Some(ConfigFormat::Yaml));
+        assert_eq!(ConfigFormat::from_path("datanode.toml"), Some(ConfigFormat::Toml));
+        assert_eq!("YAML".parse::<ConfigFormat>(), Ok(ConfigFormat::Yaml));
+    }
+
+    #[test]
+    fn test_detect_component() {
+        for (path, component) in [
+            ("metasrv.example.toml", "metasrv"),
+            ("frontend.example.toml", "frontend"),
+            ("datanode.example.toml", "datanode"),
+        ] {
+            let value = ConfigParser::parse_config_flexible(path, None).unwrap();
+            assert_eq!(ConfigParser::detect_component(&value), Some(component));
+        }
+
+        let value: toml::Value = toml::from_str("[logging]\nlevel = \"info\"").unwrap();
+        assert_eq!(ConfigParser::detect_component(&value), None);
+    }
 }
@@ -10,23 +10,60 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::{CheckDetail, CheckResult, ComponentChecker};
+mod batch_delete;
+mod layers;
+mod listing;
+mod minio;
+pub mod provider;
+pub(crate) mod providers;
+mod stat;
+mod throttle;
+mod webhdfs;
+mod workload;
+
+use crate::common::{CheckDetail, CheckResult, ComponentChecker, Dependency};
 use crate::config::DatanodeConfig;
-use crate::error;
+use crate::credentials;
+use crate::export_metrics;
+use crate::grpc_health;
+use crate::lint;
+use crate::logging;
+use crate::mito;
+use crate::mode::{self, CheckMode};
+use crate::network;
+use crate::otlp;
+use crate::profile::Profile;
+use crate::query;
+use crate::request_rate;
+use crate::settings;
+use crate::tls;
+use crate::wal;
 use async_trait::async_trait;
+use layers::OperationMetrics;
 use opendal::services::S3;
 use opendal::Operator;
-use snafu::ResultExt;
+use provider::S3Provider;
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::net::TcpStream;
+use throttle::ThrottleStats;
 use tokio::time::timeout;
 use uuid::Uuid;
 
+/// GreptimeDB's defaults for the datanode's servers
+const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:3001";
+const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:4000";
+
 /// Datanode component checker
 pub struct DatanodeChecker {
     config: DatanodeConfig,
-    include_performance: bool,
+    profile: Profile,
+    /// Throttled requests of the object storage tests of the current run
+    throttle: Arc<ThrottleStats>,
+    /// Storage operation latencies of the current run
+    op_metrics: Arc<OperationMetrics>,
+    /// Resources of the host the datanode runs on; None when the config belongs to another machine
+    host: Option<query::Host>,
 }
 
 impl Debug for DatanodeChecker {
@@ -36,87 +73,25 @@ impl Debug for DatanodeChecker {
 }
 
 impl DatanodeChecker {
-    /// Create a new DatanodeChecker with the given configuration
-    pub fn new(config: DatanodeConfig, include_performance: bool) -> Self {
-        Self { config, include_performance }
+    /// Create a new DatanodeChecker running the checks of `profile`
+    pub fn new(config: DatanodeConfig, profile: Profile) -> Self {
+        Self { config, profile, throttle: Arc::default(), op_metrics: Arc::default(), host: query::Host::current() }
     }
 
-    /// Check connectivity to metasrv endpoints (reuse logic from frontend)
-    async fn check_metasrv_connectivity(&self) -> CheckResult {
-        let mut details = Vec::new();
-
-        let metasrv_addrs = if let Some(meta_client) = &self.config.meta_client {
-            &meta_client.metasrv_addrs
-        } else {
-            details.push(CheckDetail::fail(
-                "Metasrv Configuration".to_string(),
-                "No meta_client configuration found".to_string(),
-                None,
-                Some("Configure meta_client section in the configuration file".to_string()),
-            ));
-            return CheckResult::from_details(details);
-        };
-
-        if metasrv_addrs.is_empty() {
-            details.push(CheckDetail::fail(
-                "Metasrv Configuration".to_string(),
-                "No metasrv addresses configured".to_string(),
-                None,
-                Some("Configure metasrv_addrs in the meta_client section".to_string()),
-            ));
-            return CheckResult::from_details(details);
-        }
-
-        for (index, addr) in metasrv_addrs.iter().enumerate() {
-            let start = Instant::now();
-
-            // Parse address to extract host and port
-            let (host, port) = match self.parse_address(addr) {
-                Ok((h, p)) => (h, p),
-                Err(e) => {
-                    details.push(CheckDetail::fail(
-                        format!("Metasrv Address {} Parsing", index + 1),
-                        format!("Failed to parse address '{}': {}", addr, e),
-                        None,
-                        Some("Check address format (should be host:port)".to_string()),
-                    ));
-                    continue;
-                }
-            };
-
-            // Test TCP connectivity
-            match timeout(Duration::from_secs(10), TcpStream::connect((host.as_str(), port))).await {
-                Ok(Ok(_stream)) => {
-                    details.push(CheckDetail::pass(
-                        format!("Metasrv Connectivity {}", index + 1),
-                        format!("Successfully connected to metasrv at {}", addr),
-                        Some(start.elapsed()),
-                    ));
-                }
-                Ok(Err(e)) => {
-                    details.push(CheckDetail::fail(
-                        format!("Metasrv Connectivity {}", index + 1),
-                        format!("Failed to connect to metasrv at {}: {}", addr, e),
-                        Some(start.elapsed()),
-                        Some("Check if metasrv is running and accessible".to_string()),
-                    ));
-                }
-                Err(_) => {
-                    details.push(CheckDetail::fail(
-                        format!("Metasrv Connectivity {}", index + 1),
-                        format!("Connection to metasrv at {} timed out", addr),
-                        Some(start.elapsed()),
-                        Some("Check network connectivity and metasrv availability".to_string()),
-                    ));
-                }
-            }
-        }
+    /// Size the config against `host` instead of this one; None skips the sizing checks, for
+    /// configs of other machines
+    pub fn with_host(mut self, host: Option<query::Host>) -> Self {
+        self.host = host;
+        self
+    }
 
-        CheckResult::from_details(details)
+    /// Check connectivity to metasrv endpoints
+    async fn check_metasrv_connectivity(&self) -> CheckResult {
+        network::check_metasrv_connectivity(self.config.meta_client.as_ref()).await
     }
 
     /// Check object storage configuration and connectivity
-    async fn check_object_storage(&self) -> CheckResult {
+    pub(crate) async fn check_object_storage(&self) -> CheckResult {
         let storage_config = match &self.config.storage {
             Some(config) => config,
             None => {
@@ -132,12 +107,14 @@ impl DatanodeChecker {
             }
         };
 
+        let credential_details = credentials::check_storage_credentials(storage_config);
         let storage_type = storage_config.storage_type.as_deref().unwrap_or("File");
-        match storage_type {
+        let result = match storage_type {
             "S3" => self.check_s3_storage().await,
             "Oss" => self.check_oss_storage().await,
             "Azblob" => self.check_azblob_storage().await,
             "Gcs" => self.check_gcs_storage().await,
+            "Webhdfs" => self.check_webhdfs_storage().await,
             "File" => self.check_file_storage().await,
             unknown => CheckResult::failure(
                 format!("Unknown storage type: {}", unknown),
@@ -145,16 +122,89 @@ impl DatanodeChecker {
                     "Storage Type".to_string(),
                     format!("Unsupported storage type: {}", unknown),
                     None,
-                    Some("Use one of: S3, Oss, Azblob, Gcs, File".to_string()),
+                    Some("Use one of: S3, Oss, Azblob, Gcs, Webhdfs, File".to_string()),
                 )],
             ),
+        };
+
+        let providers = storage_config.providers.as_deref().unwrap_or_default();
+        if credential_details.is_empty() && providers.is_empty() {
+            return result;
+        }
+        let mut details = credential_details;
+        details.extend(result.details);
+        if !providers.is_empty() {
+            details.extend(self.check_storage_providers(providers).await);
         }
+        CheckResult::from_details(details)
+    }
+
+    /// Create the `stepstone-test/` prefix under the storage root, for `--fix` on stores that
+    /// refuse to list a prefix holding no objects
+    pub(crate) async fn create_s3_test_prefix(&self) -> Result<(), String> {
+        let op = layers::layered(self.s3_operator()?, &self.op_metrics, &self.throttle);
+        op.create_dir("stepstone-test/").await.map_err(|e| e.to_string())
+    }
+
+    /// The S3 operator of the storage config, without checking anything
+    fn s3_operator(&self) -> Result<Operator, String> {
+        Ok(Operator::new(self.s3_builder()?).map_err(|e| e.to_string())?.finish())
+    }
+
+    /// The S3 builder of the storage config
+    fn s3_builder(&self) -> Result<S3, String> {
+        let storage_config = self.config.storage.as_ref().ok_or("Storage configuration is missing")?;
+        let mut builder = S3::default()
+            .root(storage_config.root.as_deref().unwrap_or(""))
+            .bucket(storage_config.bucket.as_deref().ok_or("S3 bucket name is required")?)
+            .access_key_id(storage_config.access_key_id.as_deref().unwrap_or(""))
+            .secret_access_key(storage_config.secret_access_key.as_deref().unwrap_or(""))
+            .endpoint(storage_config.endpoint.as_deref().unwrap_or("https://s3.amazonaws.com"))
+            .region(storage_config.region.as_deref().unwrap_or("us-east-1"));
+        if let Some(class) = &storage_config.storage_class {
+            builder = builder.default_storage_class(&class.to_ascii_uppercase());
+        }
+        Ok(builder)
+    }
+
+    /// Whether object storage answers, by listing at most one entry of its root; `stepstone wait`
+    /// polls this rather than the storage checks, which write test objects
+    pub(crate) async fn probe_object_storage(&self) -> CheckResult {
+        let Some(storage_config) = &self.config.storage else {
+            return self.check_object_storage().await;
+        };
+        let start = Instant::now();
+        let probe = match storage_config.storage_type.as_deref().unwrap_or("File") {
+            "S3" => list_one(self.s3_operator()).await,
+            "Webhdfs" => list_one(webhdfs::operator(storage_config)).await,
+            "File" => {
+                let data_home = storage_config.data_home.as_deref().unwrap_or("./greptimedb_data");
+                std::fs::read_dir(data_home).map(|_| ()).map_err(|e| format!("{}: {}", data_home, e))
+            }
+            // The other types have no storage checks to spare
+            _ => return self.check_object_storage().await,
+        };
+        let detail = match probe {
+            Ok(()) => CheckDetail::pass(
+                "Storage Reachability".to_string(),
+                "The storage root can be listed".to_string(),
+                Some(start.elapsed()),
+            ),
+            Err(e) => CheckDetail::fail(
+                "Storage Reachability".to_string(),
+                format!("Listing the storage root failed: {}", e),
+                Some(start.elapsed()),
+                Some("Check the [storage] section, its credentials and network access to the store".to_string()),
+            ),
+        };
+        CheckResult::from_details(vec![detail])
     }
 
     /// Check S3-compatible storage
     async fn check_s3_storage(&self) -> CheckResult {
         let mut details = Vec::new();
-        let start = Instant::now();
+        self.throttle.reset();
+        self.op_metrics.reset();
 
         // Get S3 configuration from storage config
         let storage_config = self.config.storage.as_ref().unwrap();
@@ -177,18 +227,53 @@ impl DatanodeChecker {
         let endpoint = storage_config.endpoint.as_deref().unwrap_or("https://s3.amazonaws.com");
         let region = storage_config.region.as_deref().unwrap_or("us-east-1");
 
+        // Resolve the endpoint host so DNS problems are not mistaken for S3 errors
+        if let Ok((host, port)) = network::parse_endpoint_url(endpoint) {
+            let (dns_detail, _) = network::check_dns_resolution(
+                "S3 Endpoint DNS Resolution".to_string(),
+                &host,
+                port,
+            ).await;
+            details.push(dns_detail);
+        }
+
+        // Self-hosted endpoints may be MinIO, which has problems of its own
+        if storage_config.endpoint.is_some() {
+            if minio::detect(endpoint).await {
+                let credentials = minio::Credentials { access_key_id, secret_access_key, region };
+                details.extend(minio::check(endpoint, &credentials, storage_config.enable_virtual_host_style).await);
+            }
+        }
+        let provider = S3Provider::detect(endpoint);
+        if let Some(provider) = provider {
+            details.push(provider.compatibility(endpoint, region));
+            details.push(provider.features(storage_config.storage_class.as_deref()));
+            let part_size = self
+                .config
+                .mito()
+                .and_then(|mito| mito.sst_write_buffer_size.as_deref())
+                .and_then(lint::parse_size)
+                .unwrap_or(provider::DEFAULT_SST_PART_SIZE);
+            details.push(provider.multipart_limits(part_size));
+        }
+
         // Build S3 operator
-        let builder = S3::default()
+        let start = Instant::now();
+        let mut builder = S3::default()
             .root(storage_config.root.as_deref().unwrap_or(""))
             .bucket(bucket)
             .access_key_id(access_key_id)
             .secret_access_key(secret_access_key)
             .endpoint(endpoint)
             .region(region);
+        // Written objects get the configured class, so the PUT test shows the bucket accepts it
+        if let Some(class) = &storage_config.storage_class {
+            builder = builder.default_storage_class(&class.to_ascii_uppercase());
+        }
 
         match Operator::new(builder) {
             Ok(op) => {
-                let op = op.finish();
+                let op = layers::layered(op.finish(), &self.op_metrics, &self.throttle);
                 details.push(CheckDetail::pass(
                     "S3 Client Creation".to_string(),
                     "S3 client created successfully".to_string(),
@@ -198,6 +283,12 @@ impl DatanodeChecker {
                 // First, test bucket access permissions
                 self.test_s3_bucket_permissions(&op, &mut details).await;
 
+                // Listing is as far as the quick profile goes; nothing is written
+                if !self.profile.storage_operations {
+                    details.extend(self.op_metrics.detail());
+                    return CheckResult::from_details(details);
+                }
+
                 // Test basic operations
                 let test_key = format!("stepstone-test/{}", Uuid::new_v4());
                 let test_data = b"stepstone-test-data";
@@ -250,6 +341,20 @@ impl DatanodeChecker {
 
                                 // Performance tests
                                 self.test_s3_performance(&op, &mut details).await;
+                                self.test_s3_stat(&op, &mut details).await;
+                                if let Some(provider) = provider {
+                                    details.push(provider.test_multipart(&op).await);
+                                    match self.s3_builder().and_then(|builder| {
+                                        Operator::new(builder.disable_list_objects_v2()).map_err(|e| e.to_string())
+                                    }) {
+                                        Ok(op_v1) => details.push(provider.test_list_objects_v1(&op_v1.finish()).await),
+                                        Err(e) => details.push(CheckDetail::info(
+                                            "S3 ListObjectsV1".to_string(),
+                                            format!("Could not build a ListObjectsV1 client: {}", e),
+                                            None,
+                                        )),
+                                    }
+                                }
                             }
                             Err(e) => {
                                 details.push(CheckDetail::warning(
@@ -262,17 +367,45 @@ impl DatanodeChecker {
                         }
 
                         // Performance test if requested
-                        if self.include_performance {
+                        if self.profile.performance {
                             let perf_result = self.performance_test_s3(&op).await;
                             details.extend(perf_result.details);
                         }
+
+                        // Listing and batch delete of many objects if requested
+                        if self.profile.listing {
+                            self.test_s3_listing(&op, &mut details).await;
+                            self.test_s3_batch_delete(&op, &mut details).await;
+                        }
+
+                        // GreptimeDB-like concurrent load if requested
+                        if self.profile.workload_secs > 0 {
+                            let duration = Duration::from_secs(self.profile.workload_secs);
+                            self.test_s3_mixed_workload(duration, &mut details).await;
+                        }
+
+                        // Network bandwidth estimate if requested
+                        if self.profile.bandwidth {
+                            self.test_s3_bandwidth(&op, endpoint, &mut details).await;
+                        }
+
+                        details.extend(self.throttle.detail());
+                        details.extend(self.op_metrics.detail());
                     }
                     Err(e) => {
+                        let error = e.to_string();
+                        let suggestion = if let Some(explained) = provider.and_then(|provider| provider.explain_error(&error)) {
+                            explained
+                        } else if error.contains("InvalidStorageClass") {
+                            "The bucket rejects storage.storage_class; directory buckets only accept EXPRESS_ONEZONE and some S3-compatible stores only STANDARD"
+                        } else {
+                            "Check S3 credentials, bucket permissions, and network connectivity"
+                        };
                         details.push(CheckDetail::fail(
                             "S3 PUT Operation".to_string(),
                             format!("PUT operation failed: {}", e),
                             None,
-                            Some("Check S3 credentials, bucket permissions, and network connectivity".to_string()),
+                            Some(suggestion.to_string()),
                         ));
                     }
                 }
@@ -410,7 +543,9 @@ impl DatanodeChecker {
 
             // Write latency test
             let start = Instant::now();
-            match op.write(&test_key, test_data.clone()).await {
+            let written = op.write(&test_key, test_data.clone()).await;
+            self.throttle.record(&written);
+            match written {
                 Ok(_) => {
                     let write_latency = start.elapsed();
                     let write_throughput = (size as f64) / write_latency.as_secs_f64() / (1024.0 * 1024.0); // MB/s
@@ -423,7 +558,9 @@ impl DatanodeChecker {
 
                     // Read latency test
                     let start = Instant::now();
-                    match op.read(&test_key).await {
+                    let read = op.read(&test_key).await;
+                    self.throttle.record(&read);
+                    match read {
                         Ok(read_data) => {
                             let read_latency = start.elapsed();
                             let read_throughput = (read_data.len() as f64) / read_latency.as_secs_f64() / (1024.0 * 1024.0); // MB/s
@@ -446,7 +583,7 @@ impl DatanodeChecker {
                         Err(e) => {
                             details.push(CheckDetail::fail(
                                 format!("S3 Read Test ({})", size_name),
-                                format!("Read failed: {}", e),
+                                format!("Read failed: {}", throttle::describe(&e)),
                                 None,
                                 Some("Check S3 read permissions and connectivity".to_string()),
                             ));
@@ -459,7 +596,7 @@ impl DatanodeChecker {
                 Err(e) => {
                     details.push(CheckDetail::fail(
                         format!("S3 Write Test ({})", size_name),
-                        format!("Write failed: {}", e),
+                        format!("Write failed: {}", throttle::describe(&e)),
                         None,
                         Some("Check S3 write permissions and connectivity".to_string()),
                     ));
@@ -500,12 +637,12 @@ impl DatanodeChecker {
         let mut test_keys = Vec::new();
 
         for (handle, key) in handles {
-            match handle.await {
-                Ok(Ok(_)) => {
+            if let Ok(result) = handle.await {
+                self.throttle.record(&result);
+                if result.is_ok() {
                     successful_writes += 1;
                     test_keys.push(key);
                 }
-                Ok(Err(_)) | Err(_) => {}
             }
         }
 
@@ -524,7 +661,11 @@ impl DatanodeChecker {
                 "S3 Concurrent Write".to_string(),
                 format!("Only {}/{} concurrent writes succeeded", successful_writes, concurrent_count),
                 Some(concurrent_write_duration),
-                Some("Check S3 rate limits and connection pool settings".to_string()),
+                Some(if self.throttle.throttled() > 0 {
+                    throttle::THROTTLE_SUGGESTION.to_string()
+                } else {
+                    "Check S3 rate limits and connection pool settings".to_string()
+                }),
             ));
         }
 
@@ -536,56 +677,73 @@ impl DatanodeChecker {
         CheckResult::from_details(details)
     }
 
-    /// Parse address string into host and port (reuse from frontend)
-    fn parse_address(&self, addr: &str) -> error::Result<(String, u16)> {
-        // Handle different address formats
-        if addr.starts_with("http://") {
-            let addr = addr.strip_prefix("http://").unwrap();
-            self.parse_host_port(addr)
-        } else if addr.starts_with("https://") {
-            let addr = addr.strip_prefix("https://").unwrap();
-            self.parse_host_port(addr)
-        } else {
-            self.parse_host_port(addr)
-        }
+    /// The gRPC and HTTP bind addresses, with GreptimeDB's defaults filled in
+    fn servers(&self) -> [(&'static str, String); 2] {
+        let grpc = self.config.grpc.as_ref().and_then(|grpc| grpc.addr.clone());
+        let http = self.config.http.as_ref().and_then(|http| http.addr.clone());
+        [
+            ("grpc", grpc.unwrap_or_else(|| DEFAULT_GRPC_ADDR.to_string())),
+            ("http", http.unwrap_or_else(|| DEFAULT_HTTP_ADDR.to_string())),
+        ]
     }
 
-    /// Parse host:port format
-    fn parse_host_port(&self, addr: &str) -> error::Result<(String, u16)> {
-        if let Some(colon_pos) = addr.rfind(':') {
-            let host = addr[..colon_pos].to_string();
-            let port_str = &addr[colon_pos + 1..];
-
-            // Remove any path component
-            let port_str = if let Some(slash_pos) = port_str.find('/') {
-                &port_str[..slash_pos]
-            } else {
-                port_str
-            };
-
-            port_str.parse::<u16>()
-                .map(|port| (host, port))
-                .context(error::InvalidPortSnafu {
-                    address: addr.to_string(),
-                    port_str: port_str.to_string(),
-                })
-        } else {
-            error::MissingPortSnafu {
-                address: addr.to_string(),
-            }.fail()
+    /// Checks of the datanode's own servers in `mode`: before startup their ports must be free,
+    /// and once it runs its gRPC and HTTP servers must be healthy
+    pub async fn check_own_servers(&self, mode: CheckMode) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+        details.extend(grpc_health::check_running_server("gRPC Health", self.config.grpc.as_ref(), grpc_health::DATANODE_SERVICES, mode).await);
+        let [grpc, http] = self.servers();
+        details.extend(network::check_running_http("HTTP Health".to_string(), &http.1, mode).await);
+        if mode.checks_binds() {
+            details.extend(network::check_server_ports(
+                &[grpc, http],
+                "Give grpc.addr and http.addr their own ports; the defaults are 3001 and 4000",
+                mode,
+            ));
         }
+        details
     }
 }
 
 #[async_trait]
 impl ComponentChecker for DatanodeChecker {
     async fn check(&self) -> CheckResult {
-        let mut all_details = Vec::new();
+        // Validate relationships between config fields before touching the network
+        let mut all_details = lint::lint_datanode(&self.config);
+        all_details.extend(tls::check_datanode_tls(&self.config));
+        all_details.extend(logging::check_log_dir(self.config.logging.as_ref()));
+        let max_concurrent_queries = self.config.max_concurrent_queries.unwrap_or(0);
+        all_details.extend(query::check_query(self.config.query.as_ref(), Some(max_concurrent_queries), self.host));
+        if let Some(host) = self.host {
+            let data_home = self.config.storage.as_ref().and_then(|storage| storage.data_home.as_deref());
+            let write_mib_per_sec = settings::current().and_then(|settings| settings.capacity.write_mib_per_sec);
+            all_details.extend(wal::check_wal_disk(self.config.wal.as_ref(), data_home, write_mib_per_sec));
+            all_details.extend(mito::check_mito(self.config.mito(), host));
+        }
+        if let Some(settings) = settings::current() {
+            all_details.extend(request_rate::check_request_rate(&self.config, &settings.capacity));
+        }
+        all_details.extend(export_metrics::check_remote_write(self.config.export_metrics.as_ref()).await);
+        all_details.extend(otlp::check_otlp_tracing(self.config.logging.as_ref()).await);
+        all_details.extend(self.check_own_servers(mode::current()).await);
 
         // Check metasrv connectivity
         let metasrv_result = self.check_metasrv_connectivity().await;
         all_details.extend(metasrv_result.details);
 
+        // Check gRPC advertise address
+        if let Some(grpc_config) = &self.config.grpc {
+            if let Some(detail) = network::check_grpc_advertise_address(grpc_config) {
+                all_details.push(detail);
+            }
+
+            if self.profile.probe_advertise {
+                if let Some(server_addr) = grpc_config.server_addr.as_deref().or(grpc_config.addr.as_deref()) {
+                    all_details.push(network::probe_advertise_address(server_addr).await);
+                }
+            }
+        }
+
         // Check object storage
         let storage_result = self.check_object_storage().await;
         all_details.extend(storage_result.details);
@@ -596,6 +754,18 @@ impl ComponentChecker for DatanodeChecker {
     fn component_name(&self) -> &'static str {
         "Datanode"
     }
+
+    fn dependencies(&self) -> &'static [Dependency] {
+        &[Dependency::Metasrv, Dependency::Storage]
+    }
+
+    async fn check_dependency(&self, dependency: Dependency) -> Option<CheckResult> {
+        match dependency {
+            Dependency::Metasrv => Some(self.check_metasrv_connectivity().await),
+            Dependency::Storage => Some(self.probe_object_storage().await),
+            Dependency::Backend => None,
+        }
+    }
 }
 
 impl DatanodeChecker {
@@ -609,7 +779,11 @@ impl DatanodeChecker {
         let small_key = "stepstone_perf_test_64mb";
 
         let start = Instant::now();
-        match timeout(Duration::from_secs(120), op.write(small_key, small_data.clone())).await {
+        let written = timeout(Duration::from_secs(120), op.write(small_key, small_data.clone())).await;
+        if let Ok(result) = &written {
+            self.throttle.record(result);
+        }
+        match written {
             Ok(Ok(_)) => {
                 let write_duration = start.elapsed();
                 let throughput_mbps = 64.0 / write_duration.as_secs_f64();
@@ -623,7 +797,11 @@ impl DatanodeChecker {
 
                 // Test read performance
                 let start = Instant::now();
-                match timeout(Duration::from_secs(120), op.read(small_key)).await {
+                let read = timeout(Duration::from_secs(120), op.read(small_key)).await;
+                if let Ok(result) = &read {
+                    self.throttle.record(result);
+                }
+                match read {
                     Ok(Ok(data)) => {
                         let read_duration = start.elapsed();
                         let read_throughput_mbps = (data.len() as f64 / read_duration.as_secs_f64()) / (1024.0 * 1024.0);
@@ -638,7 +816,7 @@ impl DatanodeChecker {
                     Ok(Err(e)) => {
                         details.push(CheckDetail::warning(
                             "S3 64MB File Read Performance".to_string(),
-                            format!("Read test failed: {}", e),
+                            format!("Read test failed: {}", throttle::describe(&e)),
                             None,
                             Some("Performance test incomplete".to_string()),
                         ));
@@ -659,7 +837,7 @@ impl DatanodeChecker {
             Ok(Err(e)) => {
                 details.push(CheckDetail::warning(
                     "S3 64MB File Write Performance".to_string(),
-                    format!("Write test failed: {}", e),
+                    format!("Write test failed: {}", throttle::describe(&e)),
                     None,
                     Some("Performance test incomplete".to_string()),
                 ));
@@ -674,12 +852,29 @@ impl DatanodeChecker {
             }
         }
 
-        // Test larger file performance (1GB)
+        // Test larger file performance (1GB), only in profiles that ask for it
+        if self.profile.large_objects {
+            self.test_s3_large_object(op, details).await;
+        }
+
+        // Test concurrent operations
+        self.test_s3_concurrent_performance(op, details).await;
+    }
+
+    /// Test S3 write performance of a 1GB object
+    async fn test_s3_large_object(&self, op: &opendal::Operator, details: &mut Vec<CheckDetail>) {
+        use std::time::Instant;
+        use tokio::time::{timeout, Duration};
+
         let large_data = vec![0u8; 1024 * 1024 * 1024]; // 1GB
         let large_key = "stepstone_perf_test_1gb";
 
         let start = Instant::now();
-        match timeout(Duration::from_secs(300), op.write(large_key, large_data.clone())).await {
+        let written = timeout(Duration::from_secs(300), op.write(large_key, large_data.clone())).await;
+        if let Ok(result) = &written {
+            self.throttle.record(result);
+        }
+        match written {
             Ok(Ok(_)) => {
                 let write_duration = start.elapsed();
                 let throughput_mbps = 1024.0 / write_duration.as_secs_f64();
@@ -697,7 +892,7 @@ impl DatanodeChecker {
             Ok(Err(e)) => {
                 details.push(CheckDetail::warning(
                     "S3 1GB File Write Performance".to_string(),
-                    format!("1GB file write test failed: {}", e),
+                    format!("1GB file write test failed: {}", throttle::describe(&e)),
                     None,
                     Some("May indicate bandwidth or timeout issues".to_string()),
                 ));
@@ -711,9 +906,6 @@ impl DatanodeChecker {
                 ));
             }
         }
-
-        // Test concurrent operations
-        self.test_s3_concurrent_performance(op, details).await;
     }
 
     /// Test S3 concurrent operation performance
@@ -743,12 +935,13 @@ impl DatanodeChecker {
         let mut keys_to_cleanup = Vec::new();
 
         for (handle, key) in handles {
-            match timeout(Duration::from_secs(10), handle).await {
-                Ok(Ok(Ok(_))) => {
+            // Tasks that panicked or timed out are not counted as requests
+            if let Ok(Ok(result)) = timeout(Duration::from_secs(10), handle).await {
+                self.throttle.record(&result);
+                if result.is_ok() {
                     successful_ops += 1;
                     keys_to_cleanup.push(key);
                 }
-                _ => {} // Failed or timed out
             }
         }
 
@@ -762,6 +955,14 @@ impl DatanodeChecker {
                        concurrent_count, total_duration.as_millis(), ops_per_second),
                 Some(total_duration),
             ));
+        } else if self.throttle.throttled() > 0 {
+            details.push(CheckDetail::warning(
+                "S3 Concurrent Operations".to_string(),
+                format!("{}/{} concurrent writes succeeded: {:.2}ms ({:.1} ops/s); the store throttled requests",
+                       successful_ops, concurrent_count, total_duration.as_millis(), ops_per_second),
+                Some(total_duration),
+                Some(throttle::THROTTLE_SUGGESTION.to_string()),
+            ));
         } else {
             details.push(CheckDetail::warning(
                 "S3 Concurrent Operations".to_string(),
@@ -809,6 +1010,13 @@ impl DatanodeChecker {
                         Some(start.elapsed()),
                         Some("Create the bucket or check the bucket name in configuration".to_string()),
                     ));
+                } else if e.kind() == opendal::ErrorKind::NotFound {
+                    details.push(CheckDetail::warning(
+                        "S3 Storage Root".to_string(),
+                        format!("The storage root does not exist yet: {}", e),
+                        Some(start.elapsed()),
+                        Some("It is created by the first write; run with --fix to create it now".to_string()),
+                    ));
                 } else if error_msg.contains("InvalidAccessKeyId") {
                     details.push(CheckDetail::fail(
                         "S3 Access Key Validation".to_string(),
@@ -891,4 +1099,165 @@ impl DatanodeChecker {
             }
         }
     }
+
+    /// Estimate sustained network bandwidth to the S3 endpoint.
+    ///
+    /// Payloads are generated in memory and spread across parallel streams so the result
+    /// reflects the network path rather than local disk. The endpoint RTT and per-request
+    /// overhead are reported alongside, to tell a slow network from a slow store.
+    async fn test_s3_bandwidth(&self, op: &Operator, endpoint: &str, details: &mut Vec<CheckDetail>) {
+        const STREAMS: usize = 4;
+        const OBJECT_SIZE: usize = 16 * 1024 * 1024;
+
+        // Baseline network RTT to the endpoint
+        let mut network_rtt = None;
+        if let Ok((host, port)) = network::parse_endpoint_url(endpoint) {
+            if let Ok(Ok(addrs)) = timeout(Duration::from_secs(5), tokio::net::lookup_host((host.as_str(), port))).await {
+                let addrs: Vec<_> = addrs.collect();
+                network_rtt = network::sample_tcp_rtt(&addrs, 5).await.ok().map(|stats| stats.avg);
+            }
+        }
+
+        // Per-request overhead, measured with a tiny object so bandwidth does not matter
+        let probe_key = format!("stepstone-bandwidth-test/{}/probe", Uuid::new_v4());
+        let start = Instant::now();
+        let request_overhead = match op.write(&probe_key, vec![0u8; 1]).await {
+            Ok(_) => {
+                let overhead = start.elapsed();
+                let _ = op.delete(&probe_key).await;
+                Some(overhead)
+            }
+            Err(_) => None,
+        };
+
+        // Upload across parallel streams
+        let prefix = format!("stepstone-bandwidth-test/{}", Uuid::new_v4());
+        let payload = vec![0u8; OBJECT_SIZE];
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for i in 0..STREAMS {
+            let op_clone = op.clone();
+            let key = format!("{}/{}", prefix, i);
+            let data = payload.clone();
+            handles.push(tokio::spawn(async move {
+                op_clone.write(&key, data).await.map(|_| key)
+            }));
+        }
+
+        let mut uploaded_keys = Vec::new();
+        let mut last_error = None;
+        for handle in handles {
+            match timeout(Duration::from_secs(120), handle).await {
+                Ok(Ok(Ok(key))) => {
+                    self.throttle.record(&Ok(()));
+                    uploaded_keys.push(key);
+                }
+                Ok(Ok(Err(e))) => {
+                    last_error = Some(throttle::describe(&e));
+                    self.throttle.record::<()>(&Err(e));
+                }
+                Ok(Err(e)) => last_error = Some(e.to_string()),
+                Err(_) => last_error = Some("timed out (>120s)".to_string()),
+            }
+        }
+        let upload_duration = start.elapsed();
+
+        if uploaded_keys.is_empty() {
+            details.push(CheckDetail::warning(
+                "S3 Upload Bandwidth".to_string(),
+                format!("Bandwidth test uploads failed: {}", last_error.unwrap_or_default()),
+                Some(upload_duration),
+                Some("Check S3 write permissions and network connectivity".to_string()),
+            ));
+            return;
+        }
+
+        let upload_mbps = (uploaded_keys.len() * OBJECT_SIZE) as f64 / upload_duration.as_secs_f64() / (1024.0 * 1024.0);
+        details.push(CheckDetail::pass(
+            "S3 Upload Bandwidth".to_string(),
+            format!(
+                "{:.2} MB/s sustained across {} streams of {}MB",
+                upload_mbps,
+                uploaded_keys.len(),
+                OBJECT_SIZE / (1024 * 1024)
+            ),
+            Some(upload_duration),
+        ));
+
+        // Download the same objects across parallel streams
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for key in &uploaded_keys {
+            let op_clone = op.clone();
+            let key = key.clone();
+            handles.push(tokio::spawn(async move {
+                op_clone.read(&key).await.map(|data| data.len())
+            }));
+        }
+
+        let mut downloaded_bytes = 0;
+        for handle in handles {
+            match timeout(Duration::from_secs(120), handle).await {
+                Ok(Ok(Ok(len))) => {
+                    self.throttle.record(&Ok(()));
+                    downloaded_bytes += len;
+                }
+                Ok(Ok(Err(e))) => {
+                    last_error = Some(throttle::describe(&e));
+                    self.throttle.record::<()>(&Err(e));
+                }
+                Ok(Err(e)) => last_error = Some(e.to_string()),
+                Err(_) => last_error = Some("timed out (>120s)".to_string()),
+            }
+        }
+        let download_duration = start.elapsed();
+
+        if downloaded_bytes == 0 {
+            details.push(CheckDetail::warning(
+                "S3 Download Bandwidth".to_string(),
+                format!("Bandwidth test downloads failed: {}", last_error.unwrap_or_default()),
+                Some(download_duration),
+                Some("Check S3 read permissions and network connectivity".to_string()),
+            ));
+        } else {
+            let download_mbps = downloaded_bytes as f64 / download_duration.as_secs_f64() / (1024.0 * 1024.0);
+            details.push(CheckDetail::pass(
+                "S3 Download Bandwidth".to_string(),
+                format!("{:.2} MB/s sustained across {} streams", download_mbps, uploaded_keys.len()),
+                Some(download_duration),
+            ));
+        }
+
+        // Compare store-side request overhead with raw network RTT
+        if let (Some(overhead), Some(rtt)) = (request_overhead, network_rtt) {
+            let message = format!(
+                "Network RTT {:?}, per-request overhead {:?}, upload {:.2} MB/s",
+                rtt, overhead, upload_mbps
+            );
+            if overhead > rtt * 20 {
+                details.push(CheckDetail::warning(
+                    "S3 Bandwidth Analysis".to_string(),
+                    message,
+                    None,
+                    Some("Request overhead is far above network RTT, so slowness most likely comes from the store itself rather than the network".to_string()),
+                ));
+            } else {
+                details.push(CheckDetail::pass(
+                    "S3 Bandwidth Analysis".to_string(),
+                    message,
+                    None,
+                ));
+            }
+        }
+
+        // Cleanup
+        for key in uploaded_keys {
+            let _ = op.delete(&key).await;
+        }
+    }
+}
+
+/// List at most one entry of the root of `op`
+async fn list_one(op: Result<Operator, String>) -> Result<(), String> {
+    op?.list_with("/").limit(1).await.map(|_| ()).map_err(|e| e.to_string())
 }
@@ -0,0 +1,167 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Batch delete test of object storage.
+//!
+//! Compaction removes the SST files it replaced in one go. Stores without DeleteObjects make that
+//! one request per file, and stores that drop part of a batch without an error leave garbage behind
+//! that GreptimeDB believes deleted.
+
+use super::DatanodeChecker;
+use crate::common::CheckDetail;
+use opendal::Operator;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Objects deleted in one batch
+const BATCH_OBJECTS: usize = 100;
+
+/// Keys in the batch that were never written, which S3 deletes successfully
+const MISSING_OBJECTS: usize = 5;
+
+impl DatanodeChecker {
+    /// Delete a batch of objects in one call, measure deletes per second and check that nothing
+    /// the store failed to delete went unreported
+    pub(super) async fn test_s3_batch_delete(&self, op: &Operator, details: &mut Vec<CheckDetail>) {
+        let root = format!("stepstone-test/batch-delete-{}/", Uuid::new_v4());
+        let keys: Vec<String> = (0..BATCH_OBJECTS).map(|i| format!("{}{:04}.parquet", root, i)).collect();
+
+        let written = self.write_empty_objects(op, &keys).await;
+        if written.len() < keys.len() {
+            details.push(CheckDetail::warning(
+                "S3 Batch Delete".to_string(),
+                format!("Only {}/{} test objects could be written, batch delete was not tested", written.len(), keys.len()),
+                None,
+                Some("Check S3 rate limits and write permissions".to_string()),
+            ));
+            let _ = op.remove_all(&root).await;
+            return;
+        }
+
+        let capability = op.info().full_capability();
+        let mut batch = written.clone();
+        batch.extend((0..MISSING_OBJECTS).map(|i| format!("{}missing-{}.parquet", root, i)));
+
+        let start = Instant::now();
+        let result = op.remove(batch).await;
+        let elapsed = start.elapsed();
+
+        // Whatever is still there was dropped from the batch, reported or not
+        let remaining = op
+            .list_with(&root)
+            .recursive(true)
+            .await
+            .map(|entries| entries.iter().filter(|entry| !entry.path().ends_with('/')).count());
+
+        details.push(batch_delete_detail(&result, &remaining, capability.batch_delete, capability.batch_max_operations, elapsed));
+        if result.is_err() || !matches!(remaining, Ok(0)) {
+            let _ = op.remove_all(&root).await;
+        }
+    }
+}
+
+/// The verdict on one batch delete of [`BATCH_OBJECTS`] objects and [`MISSING_OBJECTS`] missing
+/// keys, given how many objects a listing found afterwards
+fn batch_delete_detail(
+    result: &opendal::Result<()>,
+    remaining: &opendal::Result<usize>,
+    batch_delete: bool,
+    batch_max_operations: Option<usize>,
+    elapsed: Duration,
+) -> CheckDetail {
+    let item = "S3 Batch Delete".to_string();
+    let rate = (BATCH_OBJECTS + MISSING_OBJECTS) as f64 / elapsed.as_secs_f64();
+    match (result, remaining) {
+        (Ok(()), Err(e)) => CheckDetail::warning(
+            item,
+            format!("Batch delete succeeded in {:.2?}, but listing the test prefix to verify it failed: {}", elapsed, e),
+            Some(elapsed),
+            Some("Check the s3:ListBucket permission on the storage root".to_string()),
+        ),
+        (Ok(()), Ok(remaining)) if *remaining > 0 => CheckDetail::fail(
+            item,
+            format!("Batch delete succeeded but {} of {} objects are still there", remaining, BATCH_OBJECTS),
+            Some(elapsed),
+            Some("The store drops part of a DeleteObjects batch without reporting it, so compaction leaves files behind; upgrade the store or report it to its vendor".to_string()),
+        ),
+        (Ok(()), Ok(_)) if !batch_delete => CheckDetail::warning(
+            item,
+            format!(
+                "Deleted {} objects and {} missing keys one by one in {:.2?} ({:.0} deletes/s); the store has no batch delete",
+                BATCH_OBJECTS, MISSING_OBJECTS, elapsed, rate
+            ),
+            Some(elapsed),
+            Some("Compaction deletes each file with its own request, which is slow and counts against request rate limits; prefer a store supporting DeleteObjects".to_string()),
+        ),
+        (Ok(()), Ok(_)) => CheckDetail::pass(
+            item,
+            format!(
+                "Deleted {} objects and {} missing keys in batches of up to {} in {:.2?} ({:.0} deletes/s)",
+                BATCH_OBJECTS,
+                MISSING_OBJECTS,
+                batch_max_operations.map_or("unlimited".to_string(), |max| max.to_string()),
+                elapsed,
+                rate
+            ),
+            Some(elapsed),
+        ),
+        (Err(e), Err(list_error)) => CheckDetail::fail(
+            item,
+            format!("Batch delete failed: {}; listing what is left failed too: {}", e, list_error),
+            Some(elapsed),
+            Some("Check the s3:DeleteObject and s3:ListBucket permissions on the storage root".to_string()),
+        ),
+        (Err(e), Ok(remaining)) => CheckDetail::fail(
+            item,
+            format!("Batch delete failed with {} of {} objects left: {}", remaining, BATCH_OBJECTS, e),
+            Some(elapsed),
+            Some(if *remaining == 0 {
+                "The store reports deleting a missing key as an error, unlike S3; GreptimeDB retries such deletes forever".to_string()
+            } else {
+                "Check the s3:DeleteObject permission on the storage root".to_string()
+            }),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use opendal::{Error, ErrorKind};
+
+    fn detail(result: opendal::Result<()>, remaining: opendal::Result<usize>, batch_delete: bool) -> CheckDetail {
+        batch_delete_detail(&result, &remaining, batch_delete, Some(1000), Duration::from_millis(500))
+    }
+
+    #[test]
+    fn test_batch_delete_detail() {
+        let passed = detail(Ok(()), Ok(0), true);
+        assert_eq!(passed.status, CheckStatus::Pass);
+        // All 105 keys count towards the rate
+        assert!(passed.message.contains("(210 deletes/s)"));
+
+        assert_eq!(detail(Ok(()), Ok(0), false).status, CheckStatus::Warning);
+
+        let dropped = detail(Ok(()), Ok(3), true);
+        assert_eq!(dropped.status, CheckStatus::Fail);
+        assert!(dropped.message.contains("3 of 100 objects are still there"));
+
+        let unverified = detail(Ok(()), Err(Error::new(ErrorKind::PermissionDenied, "denied")), true);
+        assert_eq!(unverified.status, CheckStatus::Warning);
+        assert!(unverified.message.contains("listing the test prefix"));
+
+        let missing_key_error = detail(Err(Error::new(ErrorKind::NotFound, "no such key")), Ok(0), true);
+        assert_eq!(missing_key_error.status, CheckStatus::Fail);
+        assert!(missing_key_error.suggestion.unwrap().contains("missing key"));
+    }
+}
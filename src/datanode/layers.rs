@@ -0,0 +1,303 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Layers every storage operator of the checks is wrapped in.
+//!
+//! Timeouts and retries behave like GreptimeDB's own object store client, so a transient error
+//! does not fail a check GreptimeDB would have retried. The metrics layer records the latency of
+//! each operation, so the report breaks down where storage time went without timing code in
+//! every check, and passes each operation on to the run trace.
+
+use super::throttle::ThrottleStats;
+use crate::common::CheckDetail;
+use crate::run_trace;
+use opendal::layers::{RetryInterceptor, RetryLayer, TimeoutLayer};
+use opendal::raw::*;
+use opendal::{Buffer, Operator, Result};
+use std::collections::BTreeMap;
+use std::future::poll_fn;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Attempts per operation, as in GreptimeDB
+const MAX_RETRIES: usize = 3;
+
+/// Limit of operations without data transfer, such as stat and delete
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Limit of each read or write of data; generous enough for 1 GB objects on slow links
+const IO_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Wrap `op` in timeouts, retries and a metrics layer recording into `metrics`; retried
+/// throttled requests are counted in `throttle`
+pub(crate) fn layered(op: Operator, metrics: &Arc<OperationMetrics>, throttle: &Arc<ThrottleStats>) -> Operator {
+    // The timeout applies to each attempt, so it goes below the retries
+    op.layer(TimeoutLayer::new().with_timeout(OPERATION_TIMEOUT).with_io_timeout(IO_TIMEOUT))
+        .layer(
+            RetryLayer::new()
+                .with_max_times(MAX_RETRIES)
+                .with_jitter()
+                .with_notify(RetryNotifier { metrics: metrics.clone(), throttle: throttle.clone() }),
+        )
+        .layer(MetricsLayer { metrics: metrics.clone() })
+}
+
+/// Latencies of the storage operations of one check run, by operation
+#[derive(Debug, Default)]
+pub(crate) struct OperationMetrics {
+    samples: Mutex<BTreeMap<&'static str, Vec<Duration>>>,
+    retries: AtomicUsize,
+}
+
+impl OperationMetrics {
+    /// Start recording a new run
+    pub(crate) fn reset(&self) {
+        self.samples.lock().unwrap().clear();
+        self.retries.store(0, Ordering::Relaxed);
+    }
+
+    fn record(&self, operation: &'static str, latency: Duration) {
+        self.samples.lock().unwrap().entry(operation).or_default().push(latency);
+    }
+
+    /// How often `operation` ran since the last reset
+    pub(crate) fn count(&self, operation: &str) -> usize {
+        self.samples.lock().unwrap().get(operation).map_or(0, Vec::len)
+    }
+
+    /// Record `operation` on `path`, started at `start`, and pass it on to the run trace
+    fn record_operation<T>(&self, operation: &'static str, path: &str, start: (Instant, SystemTime), result: &Result<T>) {
+        self.record(operation, start.0.elapsed());
+        run_trace::record_operation(span_name(operation), path, start.1, result.as_ref().err().map(ToString::to_string));
+    }
+
+    /// Latency percentiles of each operation, or nothing if no operation ran
+    pub(crate) fn detail(&self) -> Option<CheckDetail> {
+        let samples = self.samples.lock().unwrap();
+        if samples.is_empty() {
+            return None;
+        }
+        let mut parts = samples
+            .iter()
+            .map(|(operation, latencies)| {
+                let mut latencies = latencies.clone();
+                latencies.sort();
+                format!(
+                    "{} n={} p50 {:.1?} p95 {:.1?} max {:.1?}",
+                    operation,
+                    latencies.len(),
+                    percentile(&latencies, 50),
+                    percentile(&latencies, 95),
+                    latencies[latencies.len() - 1]
+                )
+            })
+            .collect::<Vec<_>>();
+        let retries = self.retries.load(Ordering::Relaxed);
+        if retries > 0 {
+            parts.push(format!("{} retried", retries));
+        }
+        Some(CheckDetail::pass("S3 Operation Latency".to_string(), parts.join("; "), None))
+    }
+}
+
+/// The `p`th percentile of sorted, non-empty `latencies`
+pub(super) fn percentile(latencies: &[Duration], p: usize) -> Duration {
+    latencies[(latencies.len() * p / 100).min(latencies.len() - 1)]
+}
+
+/// Counts the retries of the retry layer
+#[derive(Debug)]
+struct RetryNotifier {
+    metrics: Arc<OperationMetrics>,
+    throttle: Arc<ThrottleStats>,
+}
+
+impl RetryInterceptor for RetryNotifier {
+    fn intercept(&self, err: &opendal::Error, _dur: Duration) {
+        self.metrics.retries.fetch_add(1, Ordering::Relaxed);
+        self.throttle.record_retry(err);
+    }
+}
+
+/// Records the latency of every operation into [`OperationMetrics`]
+#[derive(Debug, Clone)]
+struct MetricsLayer {
+    metrics: Arc<OperationMetrics>,
+}
+
+impl<A: Access> Layer<A> for MetricsLayer {
+    type LayeredAccess = MetricsAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        MetricsAccessor { inner, metrics: self.metrics.clone() }
+    }
+}
+
+#[derive(Debug)]
+struct MetricsAccessor<A: Access> {
+    inner: A,
+    metrics: Arc<OperationMetrics>,
+}
+
+impl<A: Access> LayeredAccess for MetricsAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = MetricsWriter<A::Writer>;
+    type BlockingWriter = A::BlockingWriter;
+    type Lister = MetricsLister<A::Lister>;
+    type BlockingLister = A::BlockingLister;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    /// Time to the first byte; the body is read by the caller
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let start = (Instant::now(), SystemTime::now());
+        let result = self.inner.read(path, args).await;
+        self.metrics.record_operation("read", path, start, &result);
+        result
+    }
+
+    /// Timed from here until the writer is closed, when the object is complete
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let start = (Instant::now(), SystemTime::now());
+        let (rp, writer) = self.inner.write(path, args).await?;
+        Ok((rp, MetricsWriter { inner: writer, metrics: self.metrics.clone(), path: path.to_string(), start }))
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let start = (Instant::now(), SystemTime::now());
+        let result = self.inner.stat(path, args).await;
+        self.metrics.record_operation("stat", path, start, &result);
+        result
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let start = (Instant::now(), SystemTime::now());
+        let result = self.inner.delete(path, args).await;
+        self.metrics.record_operation("delete", path, start, &result);
+        result
+    }
+
+    /// Time to create the lister; each page it fetches is recorded as a `list_page`
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let start = (Instant::now(), SystemTime::now());
+        let result = self.inner.list(path, args).await;
+        self.metrics.record_operation("list", path, start, &result);
+        let (rp, lister) = result?;
+        Ok((rp, MetricsLister { inner: lister, metrics: self.metrics.clone(), path: path.to_string() }))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+struct MetricsWriter<W> {
+    inner: W,
+    metrics: Arc<OperationMetrics>,
+    path: String,
+    start: (Instant, SystemTime),
+}
+
+impl<W: oio::Write> oio::Write for MetricsWriter<W> {
+    async fn write(&mut self, bs: Buffer) -> Result<()> {
+        self.inner.write(bs).await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        let result = self.inner.close().await;
+        self.metrics.record_operation("write", &self.path, self.start, &result);
+        result
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.inner.abort().await
+    }
+}
+
+struct MetricsLister<L> {
+    inner: L,
+    metrics: Arc<OperationMetrics>,
+    path: String,
+}
+
+impl<L: oio::List> oio::List for MetricsLister<L> {
+    /// An entry of the current page is ready at once, so a `next` that does not complete on its
+    /// first poll is waiting for the store to return the next page
+    async fn next(&mut self) -> Result<Option<oio::Entry>> {
+        let start = (Instant::now(), SystemTime::now());
+        let mut fetched = false;
+        let mut next = pin!(self.inner.next());
+        let result = poll_fn(|cx| {
+            let poll = next.as_mut().poll(cx);
+            fetched |= poll.is_pending();
+            poll
+        })
+        .await;
+        if fetched {
+            self.metrics.record_operation("list_page", &self.path, start, &result);
+        }
+        result
+    }
+}
+
+/// Name of the trace span of `operation`
+fn span_name(operation: &'static str) -> &'static str {
+    match operation {
+        "read" => "opendal.read",
+        "write" => "opendal.write",
+        "stat" => "opendal.stat",
+        "delete" => "opendal.delete",
+        "list" => "opendal.list",
+        "list_page" => "opendal.list_page",
+        _ => "opendal",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_metrics() {
+        let metrics = OperationMetrics::default();
+        assert!(metrics.detail().is_none());
+
+        for ms in [40, 10, 20, 30] {
+            metrics.record("write", Duration::from_millis(ms));
+        }
+        metrics.record("read", Duration::from_millis(5));
+        metrics.retries.fetch_add(2, Ordering::Relaxed);
+
+        let detail = metrics.detail().unwrap();
+        assert_eq!(
+            detail.message,
+            "read n=1 p50 5.0ms p95 5.0ms max 5.0ms; write n=4 p50 30.0ms p95 40.0ms max 40.0ms; 2 retried"
+        );
+
+        metrics.reset();
+        assert!(metrics.detail().is_none());
+    }
+}
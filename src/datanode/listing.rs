@@ -0,0 +1,215 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Listing scalability and semantics test of object storage.
+//!
+//! GreptimeDB finds manifests and SST files by listing prefixes. Some S3-compatible stores page
+//! listings differently, ignore the delimiter or match prefixes that are not directories, which
+//! shows up as missing or foreign files in manifest handling rather than as an error.
+
+use super::DatanodeChecker;
+use crate::common::CheckDetail;
+use opendal::Operator;
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+/// Objects written under the listed prefix
+const LISTED_OBJECTS: usize = 300;
+
+/// Objects per listing page, so the listing takes several pages
+const PAGE_SIZE: usize = 100;
+
+/// Writes in flight at once
+const WRITE_CONCURRENCY: usize = 32;
+
+impl DatanodeChecker {
+    /// Write a few hundred objects, list them page by page, and check delimiter and prefix
+    /// semantics of the listing
+    pub(super) async fn test_s3_listing(&self, op: &Operator, details: &mut Vec<CheckDetail>) {
+        let root = format!("stepstone-test/listing-{}/", Uuid::new_v4());
+        let mut keys: Vec<String> = (0..LISTED_OBJECTS).map(|i| format!("{}sst/{:04}.parquet", root, i)).collect();
+        // A sibling whose name extends the directory name, and an object beside the directories
+        keys.push(format!("{}sst-old/0000.parquet", root));
+        keys.push(format!("{}manifest/_last_checkpoint", root));
+        keys.push(format!("{}CURRENT", root));
+
+        let start = Instant::now();
+        let written = self.write_empty_objects(op, &keys).await;
+        if written.len() < keys.len() {
+            details.push(CheckDetail::warning(
+                "S3 Listing Scalability".to_string(),
+                format!("Only {}/{} test objects could be written, listing was not tested", written.len(), keys.len()),
+                Some(start.elapsed()),
+                Some("Check S3 rate limits and write permissions".to_string()),
+            ));
+            let _ = op.remove_all(&root).await;
+            return;
+        }
+
+        // Recursive, paginated listing of the directory holding most objects
+        let start = Instant::now();
+        let pages_before = self.op_metrics.count("list_page");
+        let sst_dir = format!("{}sst/", root);
+        match op.list_with(&sst_dir).recursive(true).limit(PAGE_SIZE).await {
+            Ok(entries) => {
+                let elapsed = start.elapsed();
+                let listed = entries.iter().filter(|entry| entry.path() != sst_dir && !entry.path().ends_with('/')).count();
+                let pages = self.op_metrics.count("list_page") - pages_before;
+                let message = listing_message(listed, pages, elapsed);
+                if listed == LISTED_OBJECTS {
+                    details.push(CheckDetail::pass("S3 Listing Scalability".to_string(), message, Some(elapsed)));
+                } else {
+                    details.push(CheckDetail::fail(
+                        "S3 Listing Scalability".to_string(),
+                        format!("{}, expected {}", message, LISTED_OBJECTS),
+                        Some(elapsed),
+                        Some("The store loses objects across listing pages; GreptimeDB would miss SST files. Check the store's ListObjectsV2 continuation token support".to_string()),
+                    ));
+                }
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "S3 Listing Scalability".to_string(),
+                    format!("Paginated listing failed: {}", e),
+                    Some(start.elapsed()),
+                    Some("Check the s3:ListBucket permission on the storage root".to_string()),
+                ));
+            }
+        }
+
+        // Non-recursive listing groups by the delimiter and returns only direct children
+        let start = Instant::now();
+        match op.list(&root).await {
+            Ok(entries) => {
+                let listed: BTreeSet<String> = entries
+                    .iter()
+                    .filter(|entry| entry.path() != root)
+                    .map(|entry| entry.path().to_string())
+                    .collect();
+                let expected: BTreeSet<String> = ["sst/", "sst-old/", "manifest/", "CURRENT"]
+                    .iter()
+                    .map(|name| format!("{}{}", root, name))
+                    .collect();
+                match listing_mismatch(&listed, &expected) {
+                    None => details.push(CheckDetail::pass(
+                        "S3 Listing Semantics".to_string(),
+                        "Delimiter listing returned the direct children only, and prefixes match whole directories".to_string(),
+                        Some(start.elapsed()),
+                    )),
+                    Some(mismatch) => details.push(CheckDetail::fail(
+                        "S3 Listing Semantics".to_string(),
+                        format!("Delimiter listing differs from S3: {}", mismatch),
+                        Some(start.elapsed()),
+                        Some("The store does not implement the delimiter and prefix parameters of ListObjectsV2 like S3; GreptimeDB manifest handling relies on them".to_string()),
+                    )),
+                }
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "S3 Listing Semantics".to_string(),
+                    format!("Delimiter listing failed: {}", e),
+                    Some(start.elapsed()),
+                    Some("Check the s3:ListBucket permission on the storage root".to_string()),
+                ));
+            }
+        }
+
+        let _ = op.remove_all(&root).await;
+    }
+
+    /// Write empty objects at `keys`, returning the keys written
+    pub(super) async fn write_empty_objects(&self, op: &Operator, keys: &[String]) -> Vec<String> {
+        let mut written = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(WRITE_CONCURRENCY) {
+            let mut tasks = JoinSet::new();
+            for key in chunk {
+                let op = op.clone();
+                let key = key.clone();
+                tasks.spawn(async move { (op.write(&key, Vec::<u8>::new()).await, key) });
+            }
+            // A task that panicked wrote nothing the caller knows of, but the others still finish
+            while let Some(joined) = tasks.join_next().await {
+                let Ok((result, key)) = joined else {
+                    continue;
+                };
+                self.throttle.record(&result);
+                if result.is_ok() {
+                    written.push(key);
+                }
+            }
+        }
+        written
+    }
+}
+
+/// The result of listing `listed` objects in `pages` pages, as counted by the lister
+fn listing_message(listed: usize, pages: usize, elapsed: Duration) -> String {
+    if pages == 0 {
+        return format!("Listed {} objects in pages of up to {} in {:.2?}", listed, PAGE_SIZE, elapsed);
+    }
+    format!(
+        "Listed {} objects in {} pages of up to {} in {:.2?} ({:.2?} per page)",
+        listed,
+        pages,
+        PAGE_SIZE,
+        elapsed,
+        elapsed / pages as u32
+    )
+}
+
+/// What differs between the `listed` and `expected` paths, or nothing if they are the same
+fn listing_mismatch(listed: &BTreeSet<String>, expected: &BTreeSet<String>) -> Option<String> {
+    let missing: Vec<&str> = expected.difference(listed).map(String::as_str).collect();
+    let unexpected: Vec<&str> = listed.difference(expected).map(String::as_str).collect();
+    let mut parts = Vec::new();
+    if !missing.is_empty() {
+        parts.push(format!("missing {}", missing.join(", ")));
+    }
+    if !unexpected.is_empty() {
+        let shown = unexpected.iter().take(5).copied().collect::<Vec<_>>().join(", ");
+        if unexpected.len() > 5 {
+            parts.push(format!("unexpected {} and {} more", shown, unexpected.len() - 5));
+        } else {
+            parts.push(format!("unexpected {}", shown));
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_mismatch() {
+        let set = |paths: &[&str]| paths.iter().map(|path| path.to_string()).collect::<BTreeSet<_>>();
+        let expected = set(&["t/sst/", "t/sst-old/", "t/CURRENT"]);
+
+        assert_eq!(listing_mismatch(&expected.clone(), &expected), None);
+        // A store ignoring the delimiter returns the objects instead of their directories
+        assert_eq!(
+            listing_mismatch(&set(&["t/sst/0000.parquet", "t/sst-old/0000.parquet", "t/CURRENT"]), &expected).unwrap(),
+            "missing t/sst-old/, t/sst/; unexpected t/sst-old/0000.parquet, t/sst/0000.parquet"
+        );
+    }
+
+    #[test]
+    fn test_listing_message() {
+        assert_eq!(
+            listing_message(300, 4, Duration::from_millis(400)),
+            "Listed 300 objects in 4 pages of up to 100 in 400.00ms (100.00ms per page)"
+        );
+        assert_eq!(listing_message(300, 0, Duration::from_millis(400)), "Listed 300 objects in pages of up to 100 in 400.00ms");
+    }
+}
@@ -0,0 +1,369 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! MinIO diagnostics, run when the S3 endpoint turns out to be MinIO.
+//!
+//! MinIO is the most common self-hosted store under GreptimeDB. Its problems are rarely in the
+//! S3 API itself but in releases with known defects, drives offline or healing, and
+//! virtual-host-style requests without the matching DNS setup, none of which the generic S3
+//! tests can tell apart. Release and drive state come from the admin API, which is called with
+//! the configured keys, signed like any S3 request.
+
+use crate::common::CheckDetail;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Timeout of each request to MinIO
+const MINIO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Releases with known problems: the first affected release, the first fixed one, and why
+const PROBLEMATIC_RELEASES: &[(&str, &str, &str)] = &[
+    (
+        "",
+        "2022-10-29T06-21-33Z",
+        "it still ships the deprecated filesystem and gateway modes and is out of support",
+    ),
+    (
+        "2019-12-17T23-16-33Z",
+        "2023-03-20T20-16-18Z",
+        "a cluster's bootstrap API discloses its environment, root credentials included (CVE-2023-28432)",
+    ),
+    (
+        "2023-03-20T20-16-18Z",
+        "2024-01-31T20-20-33Z",
+        "users allowed to create service accounts can escalate their privileges (CVE-2024-24747)",
+    ),
+];
+
+/// SHA-256 of an empty payload
+const EMPTY_PAYLOAD_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Whether the `Server` header of `endpoint` names MinIO
+pub(super) async fn detect(endpoint: &str) -> bool {
+    let Ok(client) = reqwest::Client::builder().timeout(MINIO_TIMEOUT).build() else {
+        return false;
+    };
+    // Anonymous requests are denied, but the answer still names the server
+    let Ok(response) = client.head(endpoint).send().await else {
+        return false;
+    };
+    response
+        .headers()
+        .get(reqwest::header::SERVER)
+        .and_then(|server| server.to_str().ok())
+        .is_some_and(|server| server.to_ascii_lowercase().contains("minio"))
+}
+
+/// Keys and region the admin API is called with
+pub(super) struct Credentials<'a> {
+    pub(super) access_key_id: &'a str,
+    pub(super) secret_access_key: &'a str,
+    pub(super) region: &'a str,
+}
+
+/// The part of the admin API's server info the checks use
+#[derive(Debug, Default, Deserialize)]
+struct ServerInfo {
+    #[serde(default)]
+    servers: Vec<ServerProperties>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ServerProperties {
+    #[serde(default)]
+    endpoint: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    drives: Vec<Drive>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Drive {
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    healing: bool,
+}
+
+/// MinIO-specific checks of the server at `endpoint`
+pub(super) async fn check(endpoint: &str, credentials: &Credentials<'_>, virtual_host_style: Option<bool>) -> Vec<CheckDetail> {
+    let mut details = Vec::new();
+    match server_info(endpoint, credentials).await {
+        Ok(info) => {
+            details.push(version_detail(&info));
+            details.push(health_detail(&info));
+        }
+        Err(e) => {
+            for item in ["MinIO Version", "MinIO Cluster Health"] {
+                details.push(CheckDetail::info(
+                    item.to_string(),
+                    format!("The admin API did not answer with server info: {}; `mc admin info` shows release and drive state", e),
+                    None,
+                ));
+            }
+        }
+    }
+
+    if virtual_host_style == Some(true) {
+        details.push(CheckDetail::warning(
+            "MinIO Addressing".to_string(),
+            "enable_virtual_host_style is on, which needs MINIO_DOMAIN and wildcard DNS for every bucket".to_string(),
+            None,
+            Some("Set enable_virtual_host_style = false in [storage]; MinIO serves path-style requests without extra setup".to_string()),
+        ));
+    }
+
+    details
+}
+
+/// `GET /minio/admin/v3/info`, which needs the `admin:ServerInfo` permission
+async fn server_info(endpoint: &str, credentials: &Credentials<'_>) -> Result<ServerInfo, String> {
+    if credentials.access_key_id.is_empty() || credentials.secret_access_key.is_empty() {
+        return Err("no access keys are configured".to_string());
+    }
+    let url = reqwest::Url::parse(&format!("{}/minio/admin/v3/info", endpoint.trim_end_matches('/')))
+        .map_err(|e| e.to_string())?;
+    let client = reqwest::Client::builder().timeout(MINIO_TIMEOUT).build().map_err(|e| e.to_string())?;
+    let mut request = client.get(url.clone());
+    for (name, value) in sign_headers(&url, credentials, Utc::now()) {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.map_err(|e| e.without_url().to_string())?;
+    match response.status() {
+        status if status.is_success() => response.json::<ServerInfo>().await.map_err(|e| e.without_url().to_string()),
+        reqwest::StatusCode::FORBIDDEN => {
+            Err("the configured keys lack the admin:ServerInfo permission".to_string())
+        }
+        status => Err(format!("it answered {}", status)),
+    }
+}
+
+/// Headers signing a `GET` of `url` with AWS Signature Version 4, as MinIO expects of admin calls
+fn sign_headers(url: &reqwest::Url, credentials: &Credentials<'_>, now: DateTime<Utc>) -> [(&'static str, String); 3] {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "GET\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        url.path(),
+        url.query().unwrap_or_default(),
+        host,
+        EMPTY_PAYLOAD_SHA256,
+        amz_date,
+        signed_headers,
+        EMPTY_PAYLOAD_SHA256
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, credentials.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = [date.as_str(), credentials.region, "s3", "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", credentials.secret_access_key).into_bytes(), |key, part| hmac_sha256(&key, part));
+    let signature = hex(&hmac_sha256(&key, &string_to_sign));
+    [
+        ("x-amz-date", amz_date),
+        ("x-amz-content-sha256", EMPTY_PAYLOAD_SHA256.to_string()),
+        (
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                credentials.access_key_id, scope, signed_headers, signature
+            ),
+        ),
+    ]
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// A release as `2023-05-04T21-44-30Z`, from the admin API's `2023-05-04T21:44:30Z` or a
+/// `RELEASE.` tag; release names are timestamps, so they sort chronologically
+fn normalize_release(version: &str) -> String {
+    version.trim_start_matches("RELEASE.").replace(':', "-")
+}
+
+/// Known problems of `release`
+fn release_problems(release: &str) -> Vec<&'static str> {
+    PROBLEMATIC_RELEASES
+        .iter()
+        .filter(|(first, fixed, _)| *first <= release && release < *fixed)
+        .map(|(_, _, problem)| *problem)
+        .collect()
+}
+
+fn version_detail(info: &ServerInfo) -> CheckDetail {
+    let mut releases: Vec<String> = info
+        .servers
+        .iter()
+        .filter(|server| !server.version.is_empty())
+        .map(|server| normalize_release(&server.version))
+        .collect();
+    releases.sort();
+    releases.dedup();
+    let Some(oldest) = releases.first() else {
+        return CheckDetail::info(
+            "MinIO Version".to_string(),
+            "The admin API did not report a release; `mc admin info` shows it".to_string(),
+            None,
+        );
+    };
+
+    let problems: Vec<String> = releases
+        .iter()
+        .flat_map(|release| release_problems(release).into_iter().map(move |problem| format!("{}: {}", release, problem)))
+        .collect();
+    if !problems.is_empty() {
+        return CheckDetail::warning(
+            "MinIO Version".to_string(),
+            format!("MinIO RELEASE.{} has known problems: {}", oldest, problems.join("; ")),
+            None,
+            Some("Upgrade every MinIO server to a current release".to_string()),
+        );
+    }
+    if releases.len() > 1 {
+        return CheckDetail::warning(
+            "MinIO Version".to_string(),
+            format!("The servers run different releases: {}", releases.join(", ")),
+            None,
+            Some("Upgrade all servers together with `mc admin update`; mixed releases are not supported".to_string()),
+        );
+    }
+    CheckDetail::pass("MinIO Version".to_string(), format!("MinIO RELEASE.{}", oldest), None)
+}
+
+fn health_detail(info: &ServerInfo) -> CheckDetail {
+    let offline_servers: Vec<&str> = info
+        .servers
+        .iter()
+        .filter(|server| server.state != "online")
+        .map(|server| server.endpoint.as_str())
+        .collect();
+    let drives = info.servers.iter().flat_map(|server| &server.drives);
+    let total = drives.clone().count();
+    let offline = drives.clone().filter(|drive| drive.state != "ok").count();
+    let healing = drives.filter(|drive| drive.healing).count();
+
+    if !offline_servers.is_empty() || offline > 0 {
+        return CheckDetail::warning(
+            "MinIO Cluster Health".to_string(),
+            format!(
+                "{} of {} servers and {} of {} drives are offline{}",
+                offline_servers.len(),
+                info.servers.len(),
+                offline,
+                total,
+                if offline_servers.is_empty() { String::new() } else { format!(" ({})", offline_servers.join(", ")) }
+            ),
+            None,
+            Some("Bring offline drives or nodes back; an erasure set that loses write quorum rejects GreptimeDB's writes".to_string()),
+        );
+    }
+    if healing > 0 {
+        return CheckDetail::warning(
+            "MinIO Cluster Health".to_string(),
+            format!("{} of {} drives are healing", healing, total),
+            None,
+            Some("Let healing finish before load tests; `mc admin heal` shows its progress".to_string()),
+        );
+    }
+    CheckDetail::pass(
+        "MinIO Cluster Health".to_string(),
+        format!("All {} servers and {} drives are online", info.servers.len(), total),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    fn info(json: &str) -> ServerInfo {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_sign_headers() {
+        let url = reqwest::Url::parse("http://minio:9000/minio/admin/v3/info").unwrap();
+        let credentials = Credentials { access_key_id: "minioadmin", secret_access_key: "minioadmin", region: "us-east-1" };
+        let now = DateTime::parse_from_rfc3339("2024-01-02T03:04:05Z").unwrap().with_timezone(&Utc);
+        let [date, _, authorization] = sign_headers(&url, &credentials, now);
+        assert_eq!(date.1, "20240102T030405Z");
+        assert_eq!(
+            authorization.1,
+            "AWS4-HMAC-SHA256 Credential=minioadmin/20240102/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+             Signature=bfe08a7158b1882735197bc0258dd34dfbe108f54550e4f7cbb9048b65da5ea8"
+        );
+    }
+
+    #[test]
+    fn test_version_detail() {
+        assert_eq!(normalize_release("2023-05-04T21:44:30Z"), "2023-05-04T21-44-30Z");
+        assert_eq!(normalize_release("RELEASE.2023-05-04T21-44-30Z"), "2023-05-04T21-44-30Z");
+
+        let current = info(r#"{"servers": [{"state": "online", "version": "2024-06-13T22:53:53Z"}]}"#);
+        assert_eq!(version_detail(&current).status, CheckStatus::Pass);
+
+        let vulnerable = info(r#"{"servers": [{"state": "online", "version": "2023-02-27T18:10:45Z"}]}"#);
+        let detail = version_detail(&vulnerable);
+        assert_eq!(detail.status, CheckStatus::Warning);
+        assert!(detail.message.contains("CVE-2023-28432"));
+        assert!(!detail.message.contains("gateway"));
+
+        let mixed = info(
+            r#"{"servers": [{"version": "2024-06-13T22:53:53Z"}, {"version": "2024-07-04T14:25:45Z"}]}"#,
+        );
+        assert!(version_detail(&mixed).message.contains("different releases"));
+        assert_eq!(version_detail(&info("{}")).status, CheckStatus::Info);
+    }
+
+    #[test]
+    fn test_health_detail() {
+        let healthy = info(r#"{"servers": [{"state": "online", "drives": [{"state": "ok"}, {"state": "ok"}]}]}"#);
+        assert_eq!(health_detail(&healthy).message, "All 1 servers and 2 drives are online");
+
+        let degraded = info(
+            r#"{"servers": [
+                {"state": "online", "drives": [{"state": "ok"}]},
+                {"state": "offline", "endpoint": "minio-2:9000", "drives": [{"state": "offline"}]}
+            ]}"#,
+        );
+        let detail = health_detail(&degraded);
+        assert_eq!(detail.status, CheckStatus::Warning);
+        assert_eq!(detail.message, "1 of 2 servers and 1 of 2 drives are offline (minio-2:9000)");
+
+        let healing = info(r#"{"servers": [{"state": "online", "drives": [{"state": "ok", "healing": true}]}]}"#);
+        assert!(health_detail(&healing).message.contains("healing"));
+    }
+}
@@ -0,0 +1,315 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! S3-compatible providers with known differences from AWS, and endpoint presets for them.
+//!
+//! Cloudflare R2 and Backblaze B2 speak the S3 API with their own rules for regions, storage
+//! classes, multipart uploads and listing, and lack some S3 features. Recognizing them from the
+//! endpoint turns their generic S3 errors into findings that name the actual rule.
+
+use crate::common::CheckDetail;
+use opendal::Operator;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Smallest part of a multipart upload but the last, at R2 and B2 as at S3
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Largest part of a multipart upload
+const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Most parts of one multipart upload
+const MAX_PARTS: u64 = 10_000;
+
+/// Part size of SST uploads when `sst_write_buffer_size` is unset, as in GreptimeDB
+pub const DEFAULT_SST_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// An S3-compatible provider with its own rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum S3Provider {
+    /// Cloudflare R2
+    R2,
+    /// Backblaze B2
+    B2,
+}
+
+impl std::str::FromStr for S3Provider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "r2" | "cloudflare" => Ok(Self::R2),
+            "b2" | "backblaze" => Ok(Self::B2),
+            other => Err(format!("unknown provider preset '{}', expected r2 or b2", other)),
+        }
+    }
+}
+
+impl S3Provider {
+    fn name(self) -> &'static str {
+        match self {
+            S3Provider::R2 => "Cloudflare R2",
+            S3Provider::B2 => "Backblaze B2",
+        }
+    }
+
+    /// The provider serving `endpoint`, if it is one with its own rules
+    pub fn detect(endpoint: &str) -> Option<S3Provider> {
+        let host = crate::network::parse_endpoint_url(endpoint).ok()?.0.to_ascii_lowercase();
+        if host.ends_with(".r2.cloudflarestorage.com") {
+            Some(S3Provider::R2)
+        } else if host.ends_with(".backblazeb2.com") {
+            Some(S3Provider::B2)
+        } else {
+            None
+        }
+    }
+
+    /// Endpoint and region of the preset: R2 needs the Cloudflare account ID, B2 the bucket's
+    /// region such as `us-west-004`
+    pub fn preset(self, account_id: Option<&str>, region: Option<&str>) -> Result<(String, String), String> {
+        match self {
+            S3Provider::R2 => {
+                let account_id = account_id.ok_or("The r2 preset needs --account-id, the Cloudflare account ID")?;
+                Ok((format!("https://{}.r2.cloudflarestorage.com", account_id), "auto".to_string()))
+            }
+            S3Provider::B2 => {
+                let region = region.ok_or("The b2 preset needs --region, e.g. us-west-004 from the bucket's endpoint")?;
+                Ok((format!("https://s3.{}.backblazeb2.com", region), region.to_string()))
+            }
+        }
+    }
+
+    /// Whether `region` follows the provider's rules
+    pub fn compatibility(self, endpoint: &str, region: &str) -> CheckDetail {
+        let problem = match self {
+            S3Provider::R2 => {
+                (!matches!(region, "auto" | "us-east-1")).then(|| format!("R2 only accepts the region \"auto\", not \"{}\"", region))
+            }
+            S3Provider::B2 => {
+                let host = crate::network::parse_endpoint_url(endpoint).map(|(host, _)| host).unwrap_or_default();
+                let endpoint_region = host.strip_prefix("s3.").and_then(|rest| rest.strip_suffix(".backblazeb2.com"));
+                endpoint_region.filter(|endpoint_region| *endpoint_region != region).map(|endpoint_region| {
+                    format!(
+                        "region \"{}\" does not match the endpoint's region \"{}\"; B2 rejects the signature",
+                        region, endpoint_region
+                    )
+                })
+            }
+        };
+
+        match problem {
+            None => CheckDetail::pass(
+                "S3 Provider Compatibility".to_string(),
+                format!("{} endpoint; the region follows its rules", self.name()),
+                None,
+            ),
+            Some(problem) => CheckDetail::fail(
+                "S3 Provider Compatibility".to_string(),
+                format!("{}: {}", self.name(), problem),
+                None,
+                Some(self.config_suggestion().to_string()),
+            ),
+        }
+    }
+
+    /// S3 features the provider does not implement
+    fn unsupported_features(self) -> &'static [&'static str] {
+        match self {
+            S3Provider::R2 => &["object ACLs", "object tagging", "SSE-KMS", "storage classes other than STANDARD and STANDARD_IA"],
+            S3Provider::B2 => &["object tagging", "SSE-KMS", "storage classes"],
+        }
+    }
+
+    /// Whether the config needs a feature the provider lacks; of those, GreptimeDB's S3 settings
+    /// can only ask for a storage class
+    pub fn features(self, storage_class: Option<&str>) -> CheckDetail {
+        let unsupported_class = storage_class.filter(|class| match self {
+            S3Provider::R2 => !["STANDARD", "STANDARD_IA"].iter().any(|supported| class.eq_ignore_ascii_case(supported)),
+            S3Provider::B2 => true,
+        });
+        match unsupported_class {
+            None => CheckDetail::pass(
+                "S3 Provider Features".to_string(),
+                format!(
+                    "The config uses none of the S3 features {} lacks ({})",
+                    self.name(),
+                    self.unsupported_features().join(", ")
+                ),
+                None,
+            ),
+            Some(class) => CheckDetail::fail(
+                "S3 Provider Features".to_string(),
+                format!("{} does not support the storage class {}", self.name(), class),
+                None,
+                Some(match self {
+                    S3Provider::R2 => "Set storage_class to STANDARD or STANDARD_IA (R2 Infrequent Access), or remove it".to_string(),
+                    S3Provider::B2 => "B2 has no storage classes; remove storage_class".to_string(),
+                }),
+            ),
+        }
+    }
+
+    /// Whether SST uploads in parts of `part_size` stay within the provider's multipart limits
+    pub fn multipart_limits(self, part_size: u64) -> CheckDetail {
+        let limits = format!("{} allows parts of 5 MiB to 5 GiB and {} parts per upload", self.name(), MAX_PARTS);
+        if !(MIN_PART_SIZE..=MAX_PART_SIZE).contains(&part_size) {
+            return CheckDetail::fail(
+                "S3 Multipart Limits".to_string(),
+                format!("SST uploads use parts of {} bytes, but {}", part_size, limits),
+                None,
+                Some("Set [region_engine.mito] sst_write_buffer_size between 5MB and 5GB; GreptimeDB's default is 8MB".to_string()),
+            );
+        }
+        let mut message = format!(
+            "SST uploads use parts of {} MiB; {}, so SST files of up to {} GiB can be uploaded",
+            part_size / (1024 * 1024),
+            limits,
+            part_size * MAX_PARTS / (1024 * 1024 * 1024)
+        );
+        if self == S3Provider::R2 {
+            message.push_str("; R2 also requires all parts but the last to be the same size, as GreptimeDB writes them");
+        }
+        CheckDetail::pass("S3 Multipart Limits".to_string(), message, None)
+    }
+
+    /// Upload an object in two equal parts and a shorter last one, the way GreptimeDB uploads SST
+    /// files, and delete it
+    pub(crate) async fn test_multipart(self, op: &Operator) -> CheckDetail {
+        let key = format!("stepstone-test/multipart-{}", Uuid::new_v4());
+        let part_size = MIN_PART_SIZE as usize;
+        let start = Instant::now();
+        let result = async {
+            let mut writer = op.writer_with(&key).chunk(part_size).await?;
+            writer.write(vec![0u8; 2 * part_size + 1024 * 1024]).await?;
+            writer.close().await
+        }
+        .await;
+        let elapsed = start.elapsed();
+        let _ = op.delete(&key).await;
+
+        match result {
+            Ok(_) => CheckDetail::pass(
+                "S3 Multipart Upload".to_string(),
+                format!("Uploaded 11 MiB in 3 parts of up to 5 MiB to {} in {:.2?}", self.name(), elapsed),
+                Some(elapsed),
+            ),
+            Err(e) => CheckDetail::fail(
+                "S3 Multipart Upload".to_string(),
+                format!("Multipart upload to {} failed: {}", self.name(), e),
+                Some(elapsed),
+                Some(
+                    self.explain_error(&e.to_string())
+                        .unwrap_or("GreptimeDB uploads SST files in parts; check that the key may create and complete multipart uploads")
+                        .to_string(),
+                ),
+            ),
+        }
+    }
+
+    /// List the test prefix with ListObjectsV1 through `op_v1`, an operator built without
+    /// ListObjectsV2
+    pub(crate) async fn test_list_objects_v1(self, op_v1: &Operator) -> CheckDetail {
+        let start = Instant::now();
+        match op_v1.list_with("stepstone-test/").limit(10).await {
+            Ok(_) => CheckDetail::pass(
+                "S3 ListObjectsV1".to_string(),
+                format!("{} answers ListObjectsV1 as well as ListObjectsV2", self.name()),
+                Some(start.elapsed()),
+            ),
+            Err(e) => CheckDetail::info(
+                "S3 ListObjectsV1".to_string(),
+                format!(
+                    "{} rejects ListObjectsV1 ({}); GreptimeDB lists with ListObjectsV2, so only tools still using V1 are affected",
+                    self.name(),
+                    e
+                ),
+                Some(start.elapsed()),
+            ),
+        }
+    }
+
+    fn config_suggestion(self) -> &'static str {
+        match self {
+            S3Provider::R2 => "For R2 set endpoint = \"https://<account-id>.r2.cloudflarestorage.com\" and region = \"auto\"; `stepstone storage bench --preset r2 --account-id <id>` fills both",
+            S3Provider::B2 => "For B2 use the bucket's S3 endpoint, e.g. https://s3.us-west-004.backblazeb2.com, with the region from it; `stepstone storage bench --preset b2 --region <region>` fills both",
+        }
+    }
+
+    /// A suggestion naming the provider rule behind a failed request, if one is known
+    pub fn explain_error(self, error: &str) -> Option<&'static str> {
+        match self {
+            S3Provider::R2 if error.contains("InvalidRegionName") => {
+                Some("R2 rejects regions other than \"auto\"; set region = \"auto\"")
+            }
+            S3Provider::R2 if error.contains("NotImplemented") => {
+                Some("R2 does not implement this S3 operation, e.g. object ACLs, tagging or SSE-KMS; remove the setting that needs it")
+            }
+            S3Provider::B2 if error.contains("InvalidAccessKeyId") || error.contains("SignatureDoesNotMatch") => {
+                Some("B2 application keys only work in their bucket's region and, if restricted, for their bucket; check the key's bucket and the endpoint region")
+            }
+            S3Provider::B2 if error.contains("NotImplemented") || error.contains("InvalidStorageClass") => {
+                Some("B2 does not implement this S3 feature, e.g. storage classes or object tagging; remove the setting that needs it")
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_providers() {
+        assert_eq!(S3Provider::detect("https://abc123.r2.cloudflarestorage.com"), Some(S3Provider::R2));
+        assert_eq!(S3Provider::detect("https://s3.us-west-004.backblazeb2.com"), Some(S3Provider::B2));
+        assert_eq!(S3Provider::detect("https://s3.amazonaws.com"), None);
+
+        let (endpoint, region) = S3Provider::R2.preset(Some("abc123"), None).unwrap();
+        assert_eq!(endpoint, "https://abc123.r2.cloudflarestorage.com");
+        assert_eq!(region, "auto");
+        assert!(S3Provider::R2.preset(None, None).is_err());
+        assert_eq!(
+            S3Provider::B2.preset(None, Some("eu-central-003")).unwrap().0,
+            "https://s3.eu-central-003.backblazeb2.com"
+        );
+
+        assert_eq!(S3Provider::R2.compatibility(&endpoint, "auto").status, CheckStatus::Pass);
+        assert_eq!(S3Provider::R2.compatibility(&endpoint, "eu-west-1").status, CheckStatus::Fail);
+        let b2 = "https://s3.us-west-004.backblazeb2.com";
+        assert_eq!(S3Provider::B2.compatibility(b2, "us-west-004").status, CheckStatus::Pass);
+        assert_eq!(S3Provider::B2.compatibility(b2, "us-east-1").status, CheckStatus::Fail);
+
+        assert!(S3Provider::R2.explain_error("InvalidRegionName").is_some());
+        assert!(S3Provider::B2.explain_error("AccessDenied").is_none());
+    }
+
+    #[test]
+    fn test_features() {
+        assert_eq!(S3Provider::R2.features(None).status, CheckStatus::Pass);
+        // R2 Infrequent Access is STANDARD_IA in the S3 API
+        assert_eq!(S3Provider::R2.features(Some("standard_ia")).status, CheckStatus::Pass);
+        assert_eq!(S3Provider::R2.features(Some("GLACIER")).status, CheckStatus::Fail);
+        assert_eq!(S3Provider::B2.features(Some("STANDARD")).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_multipart_limits() {
+        let detail = S3Provider::B2.multipart_limits(DEFAULT_SST_PART_SIZE);
+        assert_eq!(detail.status, CheckStatus::Pass);
+        assert!(detail.message.contains("up to 78 GiB"));
+        assert!(S3Provider::R2.multipart_limits(DEFAULT_SST_PART_SIZE).message.contains("same size"));
+        assert_eq!(S3Provider::R2.multipart_limits(1024 * 1024).status, CheckStatus::Fail);
+    }
+}
@@ -0,0 +1,161 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Additional object store providers under `[[storage.providers]]`.
+//!
+//! Tables pick a provider by name, and each provider usually has credentials of its own, e.g. a
+//! role per bucket. Each is checked on its own so one provider's credentials cannot hide another's
+//! problem, and providers meant to be distinct must not share a location, or the tables of both
+//! would write into the same files.
+
+use super::DatanodeChecker;
+use crate::common::{CheckDetail, CheckResult, CheckStatus};
+use crate::config::{DatanodeStorageConfig, ObjectStoreProviderConfig};
+use crate::profile::Profile;
+
+impl DatanodeChecker {
+    /// Check every additional provider with its own credentials, and that no two locations overlap
+    pub(super) async fn check_storage_providers(&self, providers: &[ObjectStoreProviderConfig]) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+        for (index, provider) in providers.iter().enumerate() {
+            let name = provider_name(provider, index);
+            let mut config = self.config.clone();
+            config.storage = Some(DatanodeStorageConfig { providers: None, ..provider.storage.clone() });
+            // Listing proves the credentials; writes stay with the main storage
+            let checker = DatanodeChecker::new(config, Profile::QUICK);
+            let result = Box::pin(checker.check_object_storage()).await;
+            details.push(provider_detail(&name, &result));
+        }
+
+        if let Some(main) = &self.config.storage {
+            let mut locations = vec![("default storage".to_string(), main)];
+            locations.extend(providers.iter().enumerate().map(|(index, provider)| (provider_name(provider, index), &provider.storage)));
+            details.push(isolation_detail(&locations));
+        }
+        details
+    }
+}
+
+fn provider_name(provider: &ObjectStoreProviderConfig, index: usize) -> String {
+    provider.name.clone().unwrap_or_else(|| format!("provider #{}", index + 1))
+}
+
+/// One detail summarizing the isolated check of provider `name`
+fn provider_detail(name: &str, result: &CheckResult) -> CheckDetail {
+    let item = format!("Storage Provider ({})", name);
+    let failed = result.details.iter().find(|detail| detail.status == CheckStatus::Fail);
+    match failed {
+        Some(failed) => CheckDetail::fail(
+            item,
+            format!("{}: {}", failed.item, failed.message),
+            None,
+            failed.suggestion.clone().or_else(|| Some("Check this provider's credentials and bucket".to_string())),
+        ),
+        None => CheckDetail::pass(
+            item,
+            format!("Checked with its own credentials ({} checks)", result.details.len()),
+            None,
+        ),
+    }
+}
+
+/// Where a storage config puts its data: type, endpoint, bucket and root, normalized
+pub(crate) fn location(storage: &DatanodeStorageConfig) -> (String, String, String, String) {
+    let storage_type = storage.storage_type.as_deref().unwrap_or("File").to_ascii_lowercase();
+    let endpoint = storage.endpoint.as_deref().unwrap_or("").trim_end_matches('/').to_ascii_lowercase();
+    let bucket = match storage_type.as_str() {
+        "azblob" => storage.container.as_deref(),
+        "file" => storage.data_home.as_deref(),
+        _ => storage.bucket.as_deref(),
+    };
+    let root = storage.root.as_deref().unwrap_or("").trim_matches('/');
+    (storage_type, endpoint, bucket.unwrap_or("").to_string(), format!("{}/", root).trim_start_matches('/').to_string())
+}
+
+/// Fail when two of `locations` share a bucket with the same or nested roots
+fn isolation_detail(locations: &[(String, &DatanodeStorageConfig)]) -> CheckDetail {
+    let mut overlaps = Vec::new();
+    for (i, (first_name, first)) in locations.iter().enumerate() {
+        for (second_name, second) in &locations[i + 1..] {
+            let (first_type, first_endpoint, first_bucket, first_root) = location(first);
+            let (second_type, second_endpoint, second_bucket, second_root) = location(second);
+            if first_type != second_type || first_endpoint != second_endpoint || first_bucket != second_bucket {
+                continue;
+            }
+            if first_root.starts_with(&second_root) || second_root.starts_with(&first_root) {
+                overlaps.push(format!(
+                    "{} and {} both use bucket '{}' with roots '/{}' and '/{}'",
+                    first_name, second_name, first_bucket, first_root, second_root
+                ));
+            }
+        }
+    }
+
+    if overlaps.is_empty() {
+        CheckDetail::pass(
+            "Storage Provider Isolation".to_string(),
+            format!("{} storage locations do not overlap", locations.len()),
+            None,
+        )
+    } else {
+        CheckDetail::fail(
+            "Storage Provider Isolation".to_string(),
+            overlaps.join("; "),
+            None,
+            Some("Give each provider its own bucket or a root outside the others'; overlapping locations mix the data of their tables".to_string()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s3(bucket: &str, root: &str) -> DatanodeStorageConfig {
+        DatanodeStorageConfig {
+            storage_type: Some("S3".to_string()),
+            bucket: Some(bucket.to_string()),
+            root: Some(root.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_isolation_detail() {
+        let main = s3("greptime", "/data");
+        let archive = s3("greptime-archive", "/data");
+        let nested = s3("greptime", "data/tenant-a");
+        let sibling = s3("greptime", "/data-old");
+
+        let detail = isolation_detail(&[("default storage".to_string(), &main), ("archive".to_string(), &archive)]);
+        assert_eq!(detail.status, CheckStatus::Pass);
+        let detail = isolation_detail(&[("default storage".to_string(), &main), ("old".to_string(), &sibling)]);
+        assert_eq!(detail.status, CheckStatus::Pass);
+
+        let detail = isolation_detail(&[("default storage".to_string(), &main), ("tenant".to_string(), &nested)]);
+        assert_eq!(detail.status, CheckStatus::Fail);
+        assert!(detail.message.contains("default storage and tenant"), "{}", detail.message);
+        let detail = isolation_detail(&[("a".to_string(), &main), ("b".to_string(), &s3("greptime", "data/"))]);
+        assert_eq!(detail.status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_provider_detail() {
+        let result = CheckResult::from_details(vec![
+            CheckDetail::pass("S3 Client Creation".to_string(), "ok".to_string(), None),
+            CheckDetail::fail("S3 Bucket Access".to_string(), "AccessDenied".to_string(), None, None),
+        ]);
+        let detail = provider_detail("archive", &result);
+        assert_eq!(detail.item, "Storage Provider (archive)");
+        assert_eq!(detail.message, "S3 Bucket Access: AccessDenied");
+    }
+}
@@ -0,0 +1,158 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Object metadata consistency test.
+//!
+//! The datanode's caches key SST files on the size, modification time and ETag `stat` returns.
+//! A store reporting the wrong length, a clock far off, or an ETag that changes without the
+//! content changing causes cache misses or stale reads rather than errors.
+
+use super::DatanodeChecker;
+use crate::common::CheckDetail;
+use chrono::{DateTime, Utc};
+use opendal::Operator;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Size of the test object
+const STAT_OBJECT_SIZE: usize = 4096;
+
+/// Difference between the store's clock and ours tolerated in last-modified
+const CLOCK_SKEW_SECONDS: i64 = 300;
+
+impl DatanodeChecker {
+    /// Write an object twice with the same content and check what `stat` reports about it
+    pub(super) async fn test_s3_stat(&self, op: &Operator, details: &mut Vec<CheckDetail>) {
+        let key = format!("stepstone-test/stat-{}", Uuid::new_v4());
+        let content = vec![0x5au8; STAT_OBJECT_SIZE];
+
+        let start = Instant::now();
+        let before = Utc::now();
+        if let Err(e) = op.write(&key, content.clone()).await {
+            details.push(CheckDetail::warning(
+                "S3 Object Metadata".to_string(),
+                format!("Test object could not be written, metadata was not tested: {}", e),
+                None,
+                Some("Check S3 write permissions".to_string()),
+            ));
+            return;
+        }
+        let after = Utc::now();
+
+        let first = match op.stat(&key).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "S3 Object Metadata".to_string(),
+                    format!("stat of the written object failed: {}", e),
+                    Some(start.elapsed()),
+                    Some("Check that HeadObject is allowed; GreptimeDB stats every file it opens".to_string()),
+                ));
+                let _ = op.delete(&key).await;
+                return;
+            }
+        };
+
+        let mut problems = Vec::new();
+        if first.content_length() != STAT_OBJECT_SIZE as u64 {
+            problems.push(format!("content-length is {} instead of {}", first.content_length(), STAT_OBJECT_SIZE));
+        }
+        match first.last_modified() {
+            Some(last_modified) => problems.extend(last_modified_problem(last_modified, before, after)),
+            None => problems.push("last-modified is missing".to_string()),
+        }
+        if first.etag().is_none() {
+            problems.push("ETag is missing".to_string());
+        }
+
+        // The same object stat'ed again must look the same
+        if let Ok(again) = op.stat(&key).await {
+            if again.etag() != first.etag() {
+                problems.push(format!(
+                    "ETag changed between two stats without a write ({} then {})",
+                    first.etag().unwrap_or("none"),
+                    again.etag().unwrap_or("none")
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            details.push(CheckDetail::pass(
+                "S3 Object Metadata".to_string(),
+                "stat returned the written length, a current last-modified and a stable ETag".to_string(),
+                Some(start.elapsed()),
+            ));
+        } else {
+            details.push(CheckDetail::fail(
+                "S3 Object Metadata".to_string(),
+                format!("stat is inconsistent: {}", problems.join("; ")),
+                Some(start.elapsed()),
+                Some("Inconsistent object metadata breaks the datanode's file caches; check the store's HeadObject implementation and clock".to_string()),
+            ));
+        }
+
+        // Writing identical content again keeps the ETag on S3, unless the store encrypts with
+        // per-object keys or hashes differently, in which case caches only miss more often
+        let rewrite = op.write(&key, content).await;
+        if rewrite.is_ok() {
+            if let (Ok(second), Some(etag)) = (op.stat(&key).await, first.etag()) {
+                match second.etag() {
+                    Some(second_etag) if second_etag == etag => details.push(CheckDetail::pass(
+                        "S3 ETag Stability".to_string(),
+                        format!("Rewriting identical content kept the ETag {}", etag),
+                        None,
+                    )),
+                    other => details.push(CheckDetail::warning(
+                        "S3 ETag Stability".to_string(),
+                        format!("Rewriting identical content changed the ETag from {} to {}", etag, other.unwrap_or("none")),
+                        None,
+                        Some("Expected with SSE-KMS or stores not using the content MD5 as ETag; caches keyed on the ETag will miss after rewrites".to_string()),
+                    )),
+                }
+            }
+        }
+
+        let _ = op.delete(&key).await;
+    }
+}
+
+/// What is wrong with `last_modified` of an object written between `before` and `after`
+fn last_modified_problem(last_modified: DateTime<Utc>, before: DateTime<Utc>, after: DateTime<Utc>) -> Option<String> {
+    let skew = chrono::Duration::seconds(CLOCK_SKEW_SECONDS);
+    if last_modified < before - skew || last_modified > after + skew {
+        Some(format!(
+            "last-modified {} is more than {}s away from the write at {}",
+            last_modified.to_rfc3339(),
+            CLOCK_SKEW_SECONDS,
+            before.to_rfc3339()
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_modified_problem() {
+        let before = Utc::now();
+        let after = before + chrono::Duration::seconds(1);
+
+        // S3 truncates last-modified to seconds, so it may be slightly before the write
+        assert!(last_modified_problem(before - chrono::Duration::milliseconds(900), before, after).is_none());
+        assert!(last_modified_problem(after + chrono::Duration::seconds(60), before, after).is_none());
+        assert!(last_modified_problem(before - chrono::Duration::hours(1), before, after).is_some());
+        assert!(last_modified_problem(after + chrono::Duration::hours(1), before, after).is_some());
+    }
+}
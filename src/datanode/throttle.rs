@@ -0,0 +1,149 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Throttling detection for the object storage tests.
+//!
+//! Stores answer too many requests with 429 or 503 SlowDown instead of a permission or network
+//! error. Counting those separately tells a store at its request rate limit apart from a broken
+//! one, and points at GreptimeDB's concurrency settings rather than credentials.
+
+use crate::common::CheckDetail;
+use opendal::ErrorKind;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Error codes and texts stores use to ask clients to back off
+const THROTTLE_MARKERS: &[&str] = &[
+    "SlowDown",
+    "TooManyRequests",
+    "Too Many Requests",
+    "RequestLimitExceeded",
+    "Reduce your request rate",
+    "Retry-After",
+    "status: 429",
+    "status: 503",
+];
+
+/// Suggestion attached to throttled requests
+pub(crate) const THROTTLE_SUGGESTION: &str = "The store is rate limiting requests; lower max_background_flushes and max_background_compactions under [region_engine.mito] so fewer requests run at once, spread data over more prefixes, or ask the provider for a higher request rate";
+
+/// Whether the store rejected a request because of its request rate
+pub(crate) fn is_throttled(error: &opendal::Error) -> bool {
+    if error.kind() == ErrorKind::RateLimited {
+        return true;
+    }
+    let message = error.to_string();
+    THROTTLE_MARKERS.iter().any(|marker| message.contains(marker))
+}
+
+/// `error` with throttling called out, for failure messages
+pub(crate) fn describe(error: &opendal::Error) -> String {
+    if is_throttled(error) {
+        format!("throttled by the store (429/503 SlowDown): {}", error)
+    } else {
+        error.to_string()
+    }
+}
+
+/// Requests and throttled requests of one storage check run
+#[derive(Debug, Default)]
+pub(crate) struct ThrottleStats {
+    requests: AtomicUsize,
+    throttled: AtomicUsize,
+}
+
+impl ThrottleStats {
+    /// Start counting a new run
+    pub(crate) fn reset(&self) {
+        self.requests.store(0, Ordering::Relaxed);
+        self.throttled.store(0, Ordering::Relaxed);
+    }
+
+    /// Count the outcome of one request
+    pub(crate) fn record<T>(&self, result: &opendal::Result<T>) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if result.as_ref().is_err_and(is_throttled) {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count an attempt the retry layer retried, which the final result no longer shows
+    pub(crate) fn record_retry(&self, error: &opendal::Error) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if is_throttled(error) {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Throttled requests so far
+    pub(crate) fn throttled(&self) -> usize {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// The observed throttle rate, or nothing if no request was counted
+    pub(crate) fn detail(&self) -> Option<CheckDetail> {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let throttled = self.throttled();
+        if requests == 0 {
+            return None;
+        }
+        if throttled == 0 {
+            return Some(CheckDetail::pass(
+                "S3 Throttling".to_string(),
+                format!("None of {} test requests were throttled", requests),
+                None,
+            ));
+        }
+        Some(CheckDetail::warning(
+            "S3 Throttling".to_string(),
+            format!(
+                "{} of {} test requests ({:.1}%) were throttled with 429/503 SlowDown",
+                throttled,
+                requests,
+                throttled as f64 / requests as f64 * 100.0
+            ),
+            None,
+            Some(THROTTLE_SUGGESTION.to_string()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_throttle_stats() {
+        let slow_down = opendal::Error::new(ErrorKind::Unexpected, "<Code>SlowDown</Code><Message>Please reduce your request rate.</Message>");
+        let rate_limited = opendal::Error::new(ErrorKind::RateLimited, "too many requests");
+        let denied = opendal::Error::new(ErrorKind::PermissionDenied, "AccessDenied");
+        assert!(is_throttled(&slow_down));
+        assert!(is_throttled(&rate_limited));
+        assert!(!is_throttled(&denied));
+        assert!(describe(&slow_down).starts_with("throttled by the store"));
+
+        let stats = ThrottleStats::default();
+        assert!(stats.detail().is_none());
+        stats.record(&Ok(()));
+        assert_eq!(stats.detail().unwrap().status, CheckStatus::Pass);
+
+        stats.record::<()>(&Err(slow_down));
+        stats.record::<()>(&Err(denied));
+        stats.record_retry(&rate_limited);
+        let detail = stats.detail().unwrap();
+        assert_eq!(detail.status, CheckStatus::Warning);
+        assert!(detail.message.starts_with("2 of 4 test requests (50.0%)"), "{}", detail.message);
+
+        stats.reset();
+        assert!(stats.detail().is_none());
+    }
+}
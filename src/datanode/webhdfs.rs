@@ -0,0 +1,308 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! WebHDFS storage check, for on-prem deployments keeping region data in HDFS.
+//!
+//! The namenode is asked about the root first, so an unreachable namenode, a cluster requiring
+//! Kerberos without a delegation token and a missing root are reported as such instead of as a
+//! failed write. Append is tested separately because GreptimeDB's log-like files depend on it
+//! and HDFS clusters can disable it.
+
+use super::{DatanodeChecker, layers};
+use crate::common::{CheckDetail, CheckResult};
+use crate::config::DatanodeStorageConfig;
+use opendal::Operator;
+use opendal::services::Webhdfs;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Timeout of the namenode status request
+const NAMENODE_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl DatanodeChecker {
+    /// Check WebHDFS storage: namenode, authentication, write/read/delete and append
+    pub(super) async fn check_webhdfs_storage(&self) -> CheckResult {
+        let mut details = Vec::new();
+        self.throttle.reset();
+        self.op_metrics.reset();
+
+        let storage_config = self.config.storage.as_ref().unwrap();
+        let Some(endpoint) = storage_config.endpoint.as_deref() else {
+            details.push(CheckDetail::fail(
+                "WebHDFS Configuration".to_string(),
+                "endpoint of the namenode is required".to_string(),
+                None,
+                Some("Set endpoint = \"http://<namenode>:9870\" in [storage]".to_string()),
+            ));
+            return CheckResult::from_details(details);
+        };
+        let root = storage_config.root.as_deref().unwrap_or("/");
+        let delegation = storage_config.delegation.as_deref().filter(|token| !token.is_empty());
+        let user_name = storage_config.user_name.as_deref().filter(|user| !user.is_empty());
+
+        details.push(match delegation {
+            Some(_) => CheckDetail::info(
+                "WebHDFS Authentication".to_string(),
+                "Kerberos, through the configured delegation token".to_string(),
+                None,
+            ),
+            None => CheckDetail::info(
+                "WebHDFS Authentication".to_string(),
+                match user_name {
+                    Some(user) => format!("Simple authentication as user {}", user),
+                    None => "Simple authentication as the namenode's static web user".to_string(),
+                },
+                None,
+            ),
+        });
+
+        let start = Instant::now();
+        match namenode_status(endpoint, root, delegation, user_name).await {
+            Ok(status) => {
+                let detail = namenode_detail(status, endpoint, root, delegation.is_some(), start.elapsed());
+                let reachable = detail.status != crate::common::CheckStatus::Fail;
+                details.push(detail);
+                if !reachable {
+                    return CheckResult::from_details(details);
+                }
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "WebHDFS Namenode".to_string(),
+                    // The request URL carries the delegation token, keep it out of the report
+                    format!("Cannot reach the namenode at {}: {}", endpoint, e.without_url()),
+                    Some(start.elapsed()),
+                    Some("Check the endpoint, that the namenode runs with dfs.webhdfs.enabled, and firewalls to its HTTP port".to_string()),
+                ));
+                return CheckResult::from_details(details);
+            }
+        }
+
+        let op = match operator(storage_config) {
+            Ok(op) => layers::layered(op, &self.op_metrics, &self.throttle),
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "WebHDFS Configuration".to_string(),
+                    format!("WebHDFS client could not be created: {}", e),
+                    None,
+                    Some("Check endpoint and root in [storage]".to_string()),
+                ));
+                return CheckResult::from_details(details);
+            }
+        };
+
+        let key = format!("stepstone-test/{}", Uuid::new_v4());
+        let data = b"stepstone-test-data";
+        let start = Instant::now();
+        match op.write(&key, data.as_slice()).await {
+            Ok(_) => details.push(CheckDetail::pass(
+                "WebHDFS Write".to_string(),
+                "Test file written".to_string(),
+                Some(start.elapsed()),
+            )),
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "WebHDFS Write".to_string(),
+                    format!("Write failed: {}", e),
+                    Some(start.elapsed()),
+                    Some("Check HDFS permissions of the root for the authenticated user, and that datanodes are reachable from here: WebHDFS redirects writes to them".to_string()),
+                ));
+                return CheckResult::from_details(details);
+            }
+        }
+
+        let start = Instant::now();
+        details.push(match op.read(&key).await {
+            Ok(read) if read.to_vec() == data => CheckDetail::pass(
+                "WebHDFS Read".to_string(),
+                "Test file read back with the written content".to_string(),
+                Some(start.elapsed()),
+            ),
+            Ok(_) => CheckDetail::fail(
+                "WebHDFS Read".to_string(),
+                "Test file read back with different content".to_string(),
+                Some(start.elapsed()),
+                Some("Check the HDFS cluster for corrupt blocks with `hdfs fsck`".to_string()),
+            ),
+            Err(e) => CheckDetail::fail(
+                "WebHDFS Read".to_string(),
+                format!("Read failed: {}", e),
+                Some(start.elapsed()),
+                Some("WebHDFS redirects reads to datanodes; check they are reachable from here under the names the namenode reports".to_string()),
+            ),
+        });
+
+        details.push(self.test_webhdfs_append(&op, &key).await);
+
+        let start = Instant::now();
+        details.push(match op.delete(&key).await {
+            Ok(_) => CheckDetail::pass("WebHDFS Delete".to_string(), "Test file deleted".to_string(), Some(start.elapsed())),
+            Err(e) => CheckDetail::warning(
+                "WebHDFS Delete".to_string(),
+                format!("Delete failed: {}", e),
+                Some(start.elapsed()),
+                Some("Compaction deletes old files; check delete permissions on the root".to_string()),
+            ),
+        });
+
+        details.extend(self.op_metrics.detail());
+        CheckResult::from_details(details)
+    }
+
+    /// Append to the test file written at `key` and check both parts are there
+    async fn test_webhdfs_append(&self, op: &Operator, key: &str) -> CheckDetail {
+        if !op.info().full_capability().write_can_append {
+            return CheckDetail::warning(
+                "WebHDFS Append".to_string(),
+                "The WebHDFS client does not support append".to_string(),
+                None,
+                Some("Upgrade stepstone's storage client".to_string()),
+            );
+        }
+        let append_key = format!("{}.append", key);
+        let start = Instant::now();
+        let result = async {
+            op.write_with(&append_key, b"first-".as_slice()).append(true).await?;
+            op.write_with(&append_key, b"second".as_slice()).append(true).await?;
+            op.read(&append_key).await
+        }
+        .await;
+        let _ = op.delete(&append_key).await;
+
+        match result {
+            Ok(read) if read.to_vec() == b"first-second" => CheckDetail::pass(
+                "WebHDFS Append".to_string(),
+                "Appended data follows the existing content".to_string(),
+                Some(start.elapsed()),
+            ),
+            Ok(read) => CheckDetail::fail(
+                "WebHDFS Append".to_string(),
+                format!("After two appends the file holds {} bytes instead of 12", read.len()),
+                Some(start.elapsed()),
+                Some("Append replaced or lost data; check dfs.support.append and the HDFS version".to_string()),
+            ),
+            Err(e) => CheckDetail::fail(
+                "WebHDFS Append".to_string(),
+                format!("Append failed: {}", e),
+                Some(start.elapsed()),
+                Some("Enable append on the HDFS cluster (dfs.support.append) and check the user may write the file".to_string()),
+            ),
+        }
+    }
+}
+
+/// The WebHDFS operator of the storage config, without checking anything
+pub(super) fn operator(storage_config: &DatanodeStorageConfig) -> Result<Operator, String> {
+    let endpoint = storage_config.endpoint.as_deref().ok_or("endpoint of the namenode is required")?;
+    let mut builder = Webhdfs::default().endpoint(endpoint).root(storage_config.root.as_deref().unwrap_or("/"));
+    if let Some(token) = storage_config.delegation.as_deref().filter(|token| !token.is_empty()) {
+        builder = builder.delegation(token);
+    } else if let Some(user) = storage_config.user_name.as_deref().filter(|user| !user.is_empty()) {
+        builder = builder.user_name(user);
+    }
+    Ok(Operator::new(builder).map_err(|e| e.to_string())?.finish())
+}
+
+/// Status of `GETFILESTATUS` of `root` on the namenode at `endpoint`
+async fn namenode_status(
+    endpoint: &str,
+    root: &str,
+    delegation: Option<&str>,
+    user_name: Option<&str>,
+) -> reqwest::Result<reqwest::StatusCode> {
+    let client = reqwest::Client::builder().timeout(NAMENODE_TIMEOUT).build()?;
+    let url = format!(
+        "{}/webhdfs/v1/{}",
+        endpoint.trim_end_matches('/'),
+        root.trim_matches('/')
+    );
+    Ok(client.get(&url).query(&status_query(delegation, user_name)).send().await?.status())
+}
+
+/// Query of the status request: the delegation token on Kerberos clusters, otherwise the
+/// simple-authentication user when one is configured
+fn status_query<'a>(delegation: Option<&'a str>, user_name: Option<&'a str>) -> Vec<(&'static str, &'a str)> {
+    let mut query = vec![("op", "GETFILESTATUS")];
+    match (delegation, user_name) {
+        (Some(token), _) => query.push(("delegation", token)),
+        (None, Some(user)) => query.push(("user.name", user)),
+        (None, None) => {}
+    }
+    query
+}
+
+fn namenode_detail(status: reqwest::StatusCode, endpoint: &str, root: &str, has_token: bool, elapsed: Duration) -> CheckDetail {
+    match status.as_u16() {
+        200 => CheckDetail::pass(
+            "WebHDFS Namenode".to_string(),
+            format!("Namenode at {} serves root {}", endpoint, root),
+            Some(elapsed),
+        ),
+        // A missing root is created by the first write
+        404 => CheckDetail::warning(
+            "WebHDFS Namenode".to_string(),
+            format!("Namenode at {} answers, but root {} does not exist yet", endpoint, root),
+            Some(elapsed),
+            Some("The root is created on the first write; create it beforehand to set its owner and permissions".to_string()),
+        ),
+        401 if has_token => CheckDetail::fail(
+            "WebHDFS Namenode".to_string(),
+            "The namenode rejected the delegation token".to_string(),
+            Some(elapsed),
+            Some("Delegation tokens expire, by default after a day, or 7 days with renewals; fetch a new one with `hdfs dtutil` or the GETDELEGATIONTOKEN operation".to_string()),
+        ),
+        401 => CheckDetail::fail(
+            "WebHDFS Namenode".to_string(),
+            "The namenode requires Kerberos (SPNEGO) authentication".to_string(),
+            Some(elapsed),
+            Some("Set delegation in [storage] to a delegation token of a Kerberos principal allowed to write the root".to_string()),
+        ),
+        403 => CheckDetail::fail(
+            "WebHDFS Namenode".to_string(),
+            format!("The namenode denied access to {}", root),
+            Some(elapsed),
+            Some("Grant the authenticated user access to the root with `hdfs dfs -chown` or HDFS ACLs".to_string()),
+        ),
+        other => CheckDetail::fail(
+            "WebHDFS Namenode".to_string(),
+            format!("The namenode answered HTTP {}", other),
+            Some(elapsed),
+            Some("Check that the endpoint is the namenode's HTTP address and WebHDFS is enabled (dfs.webhdfs.enabled)".to_string()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_namenode_detail() {
+        let detail = |code, has_token| {
+            namenode_detail(reqwest::StatusCode::from_u16(code).unwrap(), "http://nn:9870", "/greptimedb", has_token, Duration::ZERO)
+        };
+        assert_eq!(detail(200, false).status, CheckStatus::Pass);
+        assert_eq!(detail(404, false).status, CheckStatus::Warning);
+        assert!(detail(401, false).message.contains("Kerberos"));
+        assert!(detail(401, true).message.contains("delegation token"));
+        assert_eq!(detail(403, true).status, CheckStatus::Fail);
+        assert_eq!(detail(500, false).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_status_query() {
+        assert_eq!(status_query(None, None), vec![("op", "GETFILESTATUS")]);
+        assert_eq!(status_query(None, Some("greptime"))[1], ("user.name", "greptime"));
+        assert_eq!(status_query(Some("tok"), Some("greptime")), vec![("op", "GETFILESTATUS"), ("delegation", "tok")]);
+    }
+}
@@ -0,0 +1,235 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mixed read/write workload against object storage.
+//!
+//! The single-operation tests run alone. A busy datanode writes small WAL and manifest files
+//! while queries read large SST files, and stores that are fine for either alone can stall one
+//! under the other. This runs both at once for the profile's `workload_secs` and reports the
+//! sustained throughput and tail latency of each side.
+
+use super::DatanodeChecker;
+use super::layers::{self, percentile, OperationMetrics};
+use crate::common::CheckDetail;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+/// Tasks writing small objects, like WAL and manifest writes
+const WRITERS: usize = 8;
+
+/// Size of a small write
+const SMALL_WRITE_SIZE: usize = 4 * 1024;
+
+/// Tasks reading large objects, like SST scans
+const READERS: usize = 4;
+
+/// Large objects the readers read
+const SST_OBJECTS: usize = 2;
+
+/// Size of a large object
+const SST_SIZE: usize = 16 * 1024 * 1024;
+
+/// Small write p99 above which the store stalls writes under read load
+const SLOW_WRITE_P99: Duration = Duration::from_secs(1);
+
+/// What one task of the workload saw
+#[derive(Debug, Default)]
+struct TaskStats {
+    latencies: Vec<Duration>,
+    bytes: usize,
+    errors: usize,
+}
+
+impl DatanodeChecker {
+    /// Run small writes and large reads concurrently for `duration`
+    pub(super) async fn test_s3_mixed_workload(&self, duration: Duration, details: &mut Vec<CheckDetail>) {
+        // The workload's operations are recorded apart, so its thousands of samples under load do
+        // not skew the latencies of the single-operation tests
+        let metrics = Arc::new(OperationMetrics::default());
+        let op = match self.s3_operator() {
+            Ok(op) => layers::layered(op, &metrics, &self.throttle),
+            Err(e) => {
+                details.push(CheckDetail::warning(
+                    "S3 Mixed Workload".to_string(),
+                    format!("The workload did not run: {}", e),
+                    None,
+                    None,
+                ));
+                return;
+            }
+        };
+        let root = format!("stepstone-test/workload-{}/", Uuid::new_v4());
+        let sst_keys: Vec<String> = (0..SST_OBJECTS).map(|i| format!("{}sst/{}.parquet", root, i)).collect();
+        for key in &sst_keys {
+            let result = op.write(key, vec![0u8; SST_SIZE]).await;
+            self.throttle.record(&result);
+            if let Err(e) = result {
+                details.push(CheckDetail::warning(
+                    "S3 Mixed Workload".to_string(),
+                    format!("SST-like test objects could not be written, the workload did not run: {}", e),
+                    None,
+                    Some("Check S3 write permissions".to_string()),
+                ));
+                let _ = op.remove_all(&root).await;
+                return;
+            }
+        }
+
+        let deadline = Instant::now() + duration;
+        let mut writers = JoinSet::new();
+        for writer in 0..WRITERS {
+            let op = op.clone();
+            let prefix = format!("{}wal/{}/", root, writer);
+            writers.spawn(async move {
+                let mut stats = TaskStats::default();
+                let mut sequence = 0u64;
+                while Instant::now() < deadline {
+                    let start = Instant::now();
+                    match op.write(&format!("{}{:020}", prefix, sequence), vec![0u8; SMALL_WRITE_SIZE]).await {
+                        Ok(_) => {
+                            stats.latencies.push(start.elapsed());
+                            stats.bytes += SMALL_WRITE_SIZE;
+                        }
+                        Err(_) => stats.errors += 1,
+                    }
+                    sequence += 1;
+                }
+                stats
+            });
+        }
+        let mut readers = JoinSet::new();
+        for reader in 0..READERS {
+            let op = op.clone();
+            let key = sst_keys[reader % sst_keys.len()].clone();
+            readers.spawn(async move {
+                let mut stats = TaskStats::default();
+                while Instant::now() < deadline {
+                    let start = Instant::now();
+                    match op.read(&key).await {
+                        Ok(data) => {
+                            stats.latencies.push(start.elapsed());
+                            stats.bytes += data.len();
+                        }
+                        Err(_) => stats.errors += 1,
+                    }
+                }
+                stats
+            });
+        }
+
+        let started = Instant::now();
+        let writes = collect(writers).await;
+        let reads = collect(readers).await;
+        let elapsed = started.elapsed();
+        let _ = op.remove_all(&root).await;
+
+        details.push(workload_detail(&writes, &reads, elapsed));
+    }
+}
+
+/// The stats of all tasks of `tasks` together, with latencies sorted
+async fn collect(mut tasks: JoinSet<TaskStats>) -> TaskStats {
+    let mut total = TaskStats::default();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(stats) => {
+                total.latencies.extend(stats.latencies);
+                total.bytes += stats.bytes;
+                total.errors += stats.errors;
+            }
+            Err(_) => total.errors += 1,
+        }
+    }
+    total.latencies.sort();
+    total
+}
+
+fn workload_detail(writes: &TaskStats, reads: &TaskStats, elapsed: Duration) -> CheckDetail {
+    let seconds = elapsed.as_secs_f64();
+    let side = |stats: &TaskStats| {
+        if stats.latencies.is_empty() {
+            return "none succeeded".to_string();
+        }
+        format!(
+            "p50 {:.1?} p99 {:.1?}",
+            percentile(&stats.latencies, 50),
+            percentile(&stats.latencies, 99)
+        )
+    };
+    let message = format!(
+        "Over {:.0?}: {:.0} small writes/s ({}), {:.1} MB/s of large reads ({}), {} errors",
+        elapsed,
+        writes.latencies.len() as f64 / seconds,
+        side(writes),
+        reads.bytes as f64 / seconds / (1024.0 * 1024.0),
+        side(reads),
+        writes.errors + reads.errors
+    );
+
+    if writes.latencies.is_empty() || reads.latencies.is_empty() {
+        return CheckDetail::fail(
+            "S3 Mixed Workload".to_string(),
+            message,
+            Some(elapsed),
+            Some("Writes or reads made no progress while the other ran; check S3 connectivity and rate limits".to_string()),
+        );
+    }
+    if writes.errors + reads.errors > 0 {
+        return CheckDetail::warning(
+            "S3 Mixed Workload".to_string(),
+            message,
+            Some(elapsed),
+            Some("Requests failed under concurrent load; check the S3 Throttling result and the store's connection limits".to_string()),
+        );
+    }
+    if percentile(&writes.latencies, 99) > SLOW_WRITE_P99 {
+        return CheckDetail::warning(
+            "S3 Mixed Workload".to_string(),
+            message,
+            Some(elapsed),
+            Some("Small writes stall behind large reads, which delays flushes and manifest updates; consider a write cache or a store with more throughput".to_string()),
+        );
+    }
+    CheckDetail::pass("S3 Mixed Workload".to_string(), message, Some(elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_workload_detail() {
+        let stats = |ms: &[u64], bytes, errors| TaskStats {
+            latencies: ms.iter().map(|ms| Duration::from_millis(*ms)).collect(),
+            bytes,
+            errors,
+        };
+        let reads = stats(&[200, 300], 32 * 1024 * 1024, 0);
+
+        let detail = workload_detail(&stats(&[10, 20, 30, 40], 16384, 0), &reads, Duration::from_secs(2));
+        assert_eq!(detail.status, CheckStatus::Pass);
+        assert!(detail.message.contains("2 small writes/s"), "{}", detail.message);
+        assert!(detail.message.contains("16.0 MB/s"), "{}", detail.message);
+
+        let detail = workload_detail(&stats(&[10, 2000], 8192, 0), &reads, Duration::from_secs(2));
+        assert_eq!(detail.status, CheckStatus::Warning);
+
+        let detail = workload_detail(&stats(&[10], 4096, 3), &reads, Duration::from_secs(2));
+        assert_eq!(detail.status, CheckStatus::Warning);
+
+        let detail = workload_detail(&stats(&[], 0, 5), &reads, Duration::from_secs(2));
+        assert_eq!(detail.status, CheckStatus::Fail);
+    }
+}
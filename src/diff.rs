@@ -0,0 +1,258 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::{CheckDetail, CheckResult, ComponentChecker};
+use crate::lint::{parse_duration, parse_size};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, Instant};
+
+/// Timeout for fetching the effective config from a running node
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Compare a local config file against the effective config served by a running node
+pub struct ConfigDiffChecker {
+    local: toml::Value,
+    url: String,
+}
+
+impl Debug for ConfigDiffChecker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ConfigDiffChecker")
+    }
+}
+
+impl ConfigDiffChecker {
+    /// Create a new ConfigDiffChecker for a parsed local config and the node's `/config` URL
+    pub fn new(local: toml::Value, url: String) -> Self {
+        Self { local, url }
+    }
+
+    /// Fetch the node's effective config, which GreptimeDB serves as TOML
+    async fn fetch_remote(&self) -> Result<toml::Value, String> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let body = client
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        toml::from_str(&body).map_err(|e| format!("response is not a TOML config: {}", e))
+    }
+}
+
+#[async_trait]
+impl ComponentChecker for ConfigDiffChecker {
+    async fn check(&self) -> CheckResult {
+        let start = Instant::now();
+        let remote = match self.fetch_remote().await {
+            Ok(remote) => remote,
+            Err(e) => {
+                return CheckResult::from_details(vec![CheckDetail::fail(
+                    "Config Fetch".to_string(),
+                    format!("Failed to fetch config from {}: {}", self.url, e),
+                    Some(start.elapsed()),
+                    Some("Check the node's HTTP address; the effective config is served at http://<node>:4000/config".to_string()),
+                )]);
+            }
+        };
+
+        let mut details = vec![CheckDetail::pass(
+            "Config Fetch".to_string(),
+            format!("Fetched effective config from {}", self.url),
+            Some(start.elapsed()),
+        )];
+        details.extend(diff_configs(&self.local, &remote));
+        CheckResult::from_details(details)
+    }
+
+    fn component_name(&self) -> &'static str {
+        "Config Diff"
+    }
+}
+
+/// Compare every field set in the local config with the running node's value.
+///
+/// Fields only present on the node are its defaults and are not reported. Sizes and durations
+/// are compared by value, so `"1GB"` matches `"1GiB"` and `"60s"` matches `"1m"`.
+pub fn diff_configs(local: &toml::Value, remote: &toml::Value) -> Vec<CheckDetail> {
+    let mut local_fields = BTreeMap::new();
+    flatten("", local, &mut local_fields);
+    let mut remote_fields = BTreeMap::new();
+    flatten("", remote, &mut remote_fields);
+
+    let mut details = Vec::new();
+    for (path, local_value) in &local_fields {
+        match remote_fields.get(path) {
+            Some(remote_value) if is_redacted(remote_value) || values_match(local_value, remote_value) => {}
+            Some(remote_value) => details.push(CheckDetail::fail(
+                format!("Config Drift ({})", path),
+                format!("local = {}, running = {}", local_value, remote_value),
+                None,
+                Some("Restart the node with the local file, or update the file to match the running node".to_string()),
+            )),
+            None => details.push(CheckDetail::warning(
+                format!("Config Drift ({})", path),
+                format!("local = {}, not present in the running config", local_value),
+                None,
+                Some("The running version may not know this field; check for typos or a version mismatch".to_string()),
+            )),
+        }
+    }
+
+    if details.is_empty() {
+        details.push(CheckDetail::pass(
+            "Config Drift".to_string(),
+            format!("All {} local fields match the running node", local_fields.len()),
+            None,
+        ));
+    }
+    details
+}
+
+/// Collect leaf values keyed by their dotted path. Arrays of tables such as
+/// `[[storage.providers]]` are walked by index, e.g. `storage.providers[0].bucket`; other arrays
+/// are compared as a whole.
+fn flatten(path: &str, value: &toml::Value, fields: &mut BTreeMap<String, toml::Value>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                flatten(&field, value, fields);
+            }
+        }
+        toml::Value::Array(items) if !items.is_empty() && items.iter().all(toml::Value::is_table) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(&format!("{}[{}]", path, index), item, fields);
+            }
+        }
+        _ => {
+            fields.insert(path.to_string(), value.clone());
+        }
+    }
+}
+
+/// GreptimeDB masks secrets such as `secret_access_key` in its `/config` output
+fn is_redacted(value: &toml::Value) -> bool {
+    value.as_str().is_some_and(|text| !text.is_empty() && text.chars().all(|c| c == '*'))
+}
+
+fn values_match(local: &toml::Value, remote: &toml::Value) -> bool {
+    if local == remote {
+        return true;
+    }
+    match (local.as_str(), remote.as_str()) {
+        (Some(local), Some(remote)) => {
+            matches!((parse_duration(local), parse_duration(remote)), (Some(l), Some(r)) if l == r)
+                || matches!((parse_size(local), parse_size(remote)), (Some(l), Some(r)) if l == r)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_diff_configs() {
+        let local: toml::Value = toml::from_str(
+            r#"
+node_id = 1
+
+[storage]
+type = "S3"
+bucket = "prod"
+secret_access_key = "secret"
+
+[wal]
+file_size = "128MB"
+purge_interval = "60s"
+unknown_field = true
+"#,
+        )
+        .unwrap();
+        let remote: toml::Value = toml::from_str(
+            r#"
+node_id = 1
+init_regions_parallelism = 16
+
+[storage]
+type = "S3"
+bucket = "staging"
+secret_access_key = "******"
+
+[wal]
+file_size = "128MiB"
+purge_interval = "1m"
+"#,
+        )
+        .unwrap();
+
+        let details = diff_configs(&local, &remote);
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].item, "Config Drift (storage.bucket)");
+        assert_eq!(details[0].status, CheckStatus::Fail);
+        assert_eq!(details[1].item, "Config Drift (wal.unknown_field)");
+        assert_eq!(details[1].status, CheckStatus::Warning);
+
+        let details = diff_configs(&remote, &remote);
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_diff_arrays_of_tables() {
+        let local: toml::Value = toml::from_str(
+            r#"
+[[storage.providers]]
+type = "S3"
+bucket = "prod"
+secret_access_key = "secret"
+
+[[storage.providers]]
+type = "Gcs"
+bucket = "archive"
+"#,
+        )
+        .unwrap();
+        let remote: toml::Value = toml::from_str(
+            r#"
+[[storage.providers]]
+type = "S3"
+bucket = "prod"
+secret_access_key = "******"
+cache_capacity = "1GiB"
+
+[[storage.providers]]
+type = "Gcs"
+bucket = "backup"
+"#,
+        )
+        .unwrap();
+
+        // Only the field that differs is reported, and the masked secret is not
+        let details = diff_configs(&local, &remote);
+        let items = details.iter().map(|detail| detail.item.as_str()).collect::<Vec<_>>();
+        assert_eq!(items, ["Config Drift (storage.providers[1].bucket)"]);
+        assert_eq!(diff_configs(&remote, &remote)[0].status, CheckStatus::Pass);
+    }
+}
@@ -115,6 +115,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Vault request failed: {}", message))]
+    Vault {
+        message: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("File system operation failed: {}", message))]
     FileSystem {
         message: String,
@@ -292,6 +299,25 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("TOML serialization failed: {}", message))]
+    TomlSerialization {
+        message: String,
+        #[snafu(source)]
+        error: toml::ser::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    // YAML parsing errors
+    #[snafu(display("YAML parsing failed: {}", message))]
+    YamlParsing {
+        message: String,
+        #[snafu(source)]
+        error: serde_yaml::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     // Performance test errors
     #[snafu(display("Performance test setup failed: {}", message))]
     PerformanceTestSetup {
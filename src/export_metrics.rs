@@ -0,0 +1,188 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Check of the Prometheus remote-write target used by `export_metrics`.
+//!
+//! A component whose self-metrics export fails only logs the error, so a wrong URL or expired
+//! token goes unnoticed until someone needs the dashboards. The check writes one sample the
+//! same way the component does: a protobuf `WriteRequest`, snappy-compressed, with the
+//! configured headers.
+
+use crate::common::{CheckDetail, PhaseTimer};
+use crate::config::ExportMetricsConfig;
+use std::time::Duration;
+
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Name of the sample written by the check
+const CHECK_METRIC: &str = "stepstone_remote_write_check";
+
+/// Check the remote-write target when metrics export is enabled
+pub async fn check_remote_write(config: Option<&ExportMetricsConfig>) -> Vec<CheckDetail> {
+    let Some(config) = config.filter(|config| config.enable == Some(true)) else {
+        return Vec::new();
+    };
+    let Some(remote_write) = config.remote_write.as_ref() else {
+        return Vec::new();
+    };
+    let item = "Metrics Remote Write".to_string();
+    let Some(url) = remote_write.url.as_deref().filter(|url| !url.is_empty()) else {
+        return vec![CheckDetail::fail(
+            item,
+            "export_metrics is enabled but remote_write.url is empty".to_string(),
+            None,
+            Some("Set export_metrics.remote_write.url, e.g. http://greptimedb:4000/v1/prometheus/write?db=greptime_metrics".to_string()),
+        )];
+    };
+
+    let mut timer = PhaseTimer::start();
+    let body = match snap::raw::Encoder::new().compress_vec(&encode_write_request(CHECK_METRIC, 1.0, chrono::Utc::now().timestamp_millis())) {
+        Ok(body) => body,
+        Err(e) => return vec![CheckDetail::fail(item, format!("Failed to compress the test sample: {}", e), None, None)],
+    };
+
+    let client = match reqwest::Client::builder().timeout(WRITE_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => return vec![CheckDetail::fail(item, format!("Failed to build HTTP client: {}", e), None, None)],
+    };
+    timer.phase("client_build");
+    let mut request = client
+        .post(url)
+        .header("Content-Type", "application/x-protobuf")
+        .header("Content-Encoding", "snappy")
+        .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+        .body(body);
+    for (name, value) in remote_write.headers.iter().flatten() {
+        request = request.header(name.as_str(), value.as_str());
+    }
+
+    let sent = request.send().await;
+    timer.phase("first_byte");
+    let response = match sent {
+        Ok(response) => response,
+        Err(e) => {
+            return vec![CheckDetail::fail(
+                item,
+                format!("Cannot reach {}: {}", url, e),
+                Some(timer.elapsed()),
+                Some("Check the remote-write URL and that the receiver is reachable from this host".to_string()),
+            )
+            .with_timer(&timer)];
+        }
+    };
+
+    let status = response.status();
+    let detail = if status.is_success() {
+        CheckDetail::pass(
+            item,
+            format!("{} accepted a test sample ({}) with the configured headers", url, CHECK_METRIC),
+            Some(timer.elapsed()),
+        )
+    } else {
+        let body = response.text().await.unwrap_or_default();
+        let body = body.trim().chars().take(200).collect::<String>();
+        let suggestion = match status.as_u16() {
+            401 | 403 => "Authentication was rejected; check the Authorization header in export_metrics.remote_write.headers",
+            404 | 405 => "The URL is not a remote-write endpoint; for GreptimeDB it is /v1/prometheus/write?db=<database>",
+            _ => "Check the receiver's logs for why the write was rejected",
+        };
+        CheckDetail::fail(
+            item,
+            format!("{} rejected the test sample with {}: {}", url, status, body),
+            Some(timer.elapsed()),
+            Some(suggestion.to_string()),
+        )
+    };
+    vec![detail.with_timer(&timer)]
+}
+
+/// Encode a Prometheus `WriteRequest` holding one series with one sample, labelled
+/// `__name__` and `job="stepstone"`
+fn encode_write_request(name: &str, value: f64, timestamp_ms: i64) -> Vec<u8> {
+    let mut series = Vec::new();
+    // Labels must be sorted by name; `__name__` sorts first
+    for (label, label_value) in [("__name__", name), ("job", "stepstone")] {
+        let mut encoded = Vec::new();
+        put_bytes(&mut encoded, 1, label.as_bytes());
+        put_bytes(&mut encoded, 2, label_value.as_bytes());
+        put_bytes(&mut series, 1, &encoded);
+    }
+
+    let mut sample = Vec::new();
+    put_varint(&mut sample, (1 << 3) | 1);
+    sample.extend_from_slice(&value.to_le_bytes());
+    put_varint(&mut sample, 2 << 3);
+    put_varint(&mut sample, timestamp_ms as u64);
+    put_bytes(&mut series, 2, &sample);
+
+    let mut request = Vec::new();
+    put_bytes(&mut request, 1, &series);
+    request
+}
+
+/// Append a length-delimited protobuf field
+pub(crate) fn put_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(buf, (field << 3) | 2);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Append a protobuf varint
+pub(crate) fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RemoteWriteConfig;
+
+    #[test]
+    fn test_encode_write_request() {
+        let mut varint = Vec::new();
+        put_varint(&mut varint, 300);
+        assert_eq!(varint, vec![0xac, 0x02]);
+
+        let request = encode_write_request("up", 1.0, 1);
+        #[rustfmt::skip]
+        let expected = [
+            // timeseries
+            0x0a, 0x2f,
+            0x0a, 0x0e, 0x0a, 0x08, b'_', b'_', b'n', b'a', b'm', b'e', b'_', b'_', 0x12, 0x02, b'u', b'p',
+            0x0a, 0x10, 0x0a, 0x03, b'j', b'o', b'b', 0x12, 0x09, b's', b't', b'e', b'p', b's', b't', b'o', b'n', b'e',
+            0x12, 0x0b, 0x09, 0, 0, 0, 0, 0, 0, 0xf0, 0x3f, 0x10, 0x01,
+        ];
+        assert_eq!(request, expected);
+    }
+
+    #[tokio::test]
+    async fn test_remote_write_without_url() {
+        let config = ExportMetricsConfig {
+            enable: Some(true),
+            remote_write: Some(RemoteWriteConfig::default()),
+            ..Default::default()
+        };
+        let details = check_remote_write(Some(&config)).await;
+        assert_eq!(details.len(), 1);
+        assert!(details[0].message.contains("remote_write.url is empty"));
+
+        let disabled = ExportMetricsConfig {
+            enable: Some(false),
+            ..config
+        };
+        assert!(check_remote_write(Some(&disabled)).await.is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Remediation of simple check failures for `--fix`.
+
+use crate::common::{CheckResult, CheckStatus};
+use crate::config::{DatanodeConfig, MetasrvConfig};
+use crate::datanode::DatanodeChecker;
+use crate::metasrv::MetasrvChecker;
+use crate::profile::Profile;
+use snafu::ResultExt;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Mode of directories created for GreptimeDB data: owner full access, group read
+#[cfg(unix)]
+const DIRECTORY_MODE: u32 = 0o750;
+
+/// A remediation for a failed check
+#[derive(Debug)]
+pub enum Fix {
+    /// Create a missing local directory
+    CreateDirectory(PathBuf),
+    /// Create the test prefix under the object storage root
+    CreateS3TestPrefix(Box<DatanodeConfig>),
+    /// Create the PostgreSQL schema named by `meta_schema_name`
+    CreatePostgresSchema(Box<MetasrvConfig>),
+    /// Create the PostgreSQL metadata table
+    CreatePostgresTable(Box<MetasrvConfig>),
+}
+
+impl Fix {
+    /// What the fix will do, shown before asking for confirmation
+    pub fn description(&self) -> String {
+        match self {
+            Fix::CreateDirectory(path) => format!("Create directory {}", path.display()),
+            Fix::CreateS3TestPrefix(config) => format!(
+                "Create prefix stepstone-test/ in bucket {}",
+                config
+                    .storage
+                    .as_ref()
+                    .and_then(|storage| storage.bucket.as_deref())
+                    .unwrap_or("<unset>")
+            ),
+            Fix::CreatePostgresSchema(config) => format!(
+                "Create PostgreSQL schema {}",
+                config.meta_schema_name.as_deref().unwrap_or("<unset>")
+            ),
+            Fix::CreatePostgresTable(config) => format!(
+                "Create PostgreSQL metadata table {}",
+                config.meta_table_name.as_deref().unwrap_or("greptime_metasrv")
+            ),
+        }
+    }
+
+    /// Apply the fix
+    pub async fn apply(&self) -> Result<(), String> {
+        match self {
+            Fix::CreateDirectory(path) => create_directory(path).map_err(|e| e.to_string()),
+            Fix::CreateS3TestPrefix(config) => {
+                DatanodeChecker::new(config.as_ref().clone(), Profile::QUICK)
+                    .create_s3_test_prefix()
+                    .await
+            }
+            Fix::CreatePostgresSchema(config) => {
+                MetasrvChecker::new(config.as_ref().clone(), Profile::STANDARD, 0)
+                    .create_postgres_schema()
+                    .await
+            }
+            Fix::CreatePostgresTable(config) => {
+                MetasrvChecker::new(config.as_ref().clone(), Profile::STANDARD, 0)
+                    .create_postgres_table()
+                    .await
+            }
+        }
+    }
+}
+
+/// Fixes for a datanode: missing data, WAL and cache directories, and the object storage
+/// test prefix when listing the root failed because it does not exist
+pub fn datanode_fixes(config: &DatanodeConfig, result: &CheckResult) -> Vec<Fix> {
+    let storage = config.storage.as_ref();
+    let mut directories = vec![storage.and_then(|storage| storage.data_home.as_deref()).unwrap_or("./greptimedb_data")];
+    if let Some(wal) = &config.wal {
+        if wal.provider.as_deref().unwrap_or("raft_engine") == "raft_engine" {
+            directories.extend(wal.dir.as_deref());
+        }
+    }
+    directories.extend(storage.and_then(|storage| storage.cache_path.as_deref()));
+    directories.extend(config.mito().and_then(|mito| mito.write_cache_path.as_deref()));
+
+    let mut fixes = Vec::new();
+    for directory in directories {
+        let path = PathBuf::from(directory);
+        if !path.exists() && !fixes.iter().any(|fix| matches!(fix, Fix::CreateDirectory(existing) if *existing == path)) {
+            fixes.push(Fix::CreateDirectory(path));
+        }
+    }
+
+    let root_missing = result
+        .details
+        .iter()
+        .any(|detail| detail.item == "S3 Storage Root" && detail.status != CheckStatus::Pass);
+    if root_missing {
+        fixes.push(Fix::CreateS3TestPrefix(Box::new(config.clone())));
+    }
+
+    fixes
+}
+
+/// Fixes for metasrv: the PostgreSQL schema and metadata table when they are missing
+pub fn metasrv_fixes(config: &MetasrvConfig, result: &CheckResult) -> Vec<Fix> {
+    let mut fixes = Vec::new();
+    if config.backend != "postgres_store" {
+        return fixes;
+    }
+
+    let failed = |item: &str| {
+        result
+            .details
+            .iter()
+            .any(|detail| detail.item == item && detail.status != CheckStatus::Pass)
+    };
+    // A missing schema ends the check before the table is looked at, but a new schema has no
+    // table either
+    let schema_missing = failed("Metadata Schema Existence");
+    if schema_missing {
+        fixes.push(Fix::CreatePostgresSchema(Box::new(config.clone())));
+    }
+    if schema_missing || failed("Metadata Table Existence") {
+        fixes.push(Fix::CreatePostgresTable(Box::new(config.clone())));
+    }
+    fixes
+}
+
+/// Ask for each fix on `output` (unless `assume_yes`) and apply the accepted ones.
+/// Returns whether any fix was applied, so the caller knows to re-run the checks.
+pub async fn apply_fixes<R: BufRead, W: Write>(
+    fixes: &[Fix],
+    assume_yes: bool,
+    mut input: R,
+    mut output: W,
+) -> crate::error::Result<bool> {
+    let context = || crate::error::FileSystemSnafu {
+        message: "Failed to prompt for fix".to_string(),
+    };
+
+    let mut applied = false;
+    for fix in fixes {
+        if !assume_yes {
+            write!(output, "Fix: {}? [y/N] ", fix.description()).context(context())?;
+            output.flush().context(context())?;
+            let mut answer = String::new();
+            input.read_line(&mut answer).context(context())?;
+            if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                continue;
+            }
+        }
+
+        match fix.apply().await {
+            Ok(()) => {
+                writeln!(output, "✓ {}", fix.description()).context(context())?;
+                applied = true;
+            }
+            Err(e) => writeln!(output, "✗ {}: {}", fix.description(), e).context(context())?,
+        }
+    }
+    Ok(applied)
+}
+
+fn create_directory(path: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(DIRECTORY_MODE);
+    }
+    builder.create(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckDetail;
+    use crate::config::{DatanodeStorageConfig, WalConfig};
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_datanode_directory_fixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_home = dir.path().join("data");
+        let wal_dir = dir.path().join("data/wal");
+        let config = DatanodeConfig {
+            storage: Some(DatanodeStorageConfig {
+                storage_type: Some("File".to_string()),
+                data_home: Some(data_home.display().to_string()),
+                cache_path: Some(dir.path().display().to_string()),
+                ..Default::default()
+            }),
+            wal: Some(WalConfig {
+                dir: Some(wal_dir.display().to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let fixes = datanode_fixes(&config, &CheckResult::from_details(Vec::new()));
+        assert_eq!(fixes.len(), 2);
+
+        // Declined fixes are not applied
+        let mut prompts = Vec::new();
+        assert!(!apply_fixes(&fixes, false, Cursor::new("n\n\n"), &mut prompts).await.unwrap());
+        assert!(!data_home.exists());
+
+        let mut prompts = Vec::new();
+        assert!(apply_fixes(&fixes, true, Cursor::new(""), &mut prompts).await.unwrap());
+        assert!(wal_dir.is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&wal_dir).unwrap().permissions().mode() & 0o777, DIRECTORY_MODE);
+        }
+        assert!(datanode_fixes(&config, &CheckResult::from_details(Vec::new())).is_empty());
+    }
+
+    #[test]
+    fn test_metasrv_fixes() {
+        // The check stops at a missing schema, so there is no table result
+        let result = CheckResult::from_details(vec![CheckDetail::fail(
+            "Metadata Schema Existence".to_string(),
+            "Schema 'meta' does not exist".to_string(),
+            None,
+            None,
+        )]);
+        let config = MetasrvConfig {
+            backend: "postgres_store".to_string(),
+            meta_schema_name: Some("meta".to_string()),
+            ..Default::default()
+        };
+
+        let fixes = metasrv_fixes(&config, &result);
+        let descriptions = fixes.iter().map(Fix::description).collect::<Vec<_>>();
+        assert_eq!(
+            descriptions,
+            vec!["Create PostgreSQL schema meta", "Create PostgreSQL metadata table greptime_metasrv"]
+        );
+
+        let table_missing = CheckResult::from_details(vec![
+            CheckDetail::pass("Metadata Schema Existence".to_string(), "Schema 'meta' exists".to_string(), None),
+            CheckDetail::warning("Metadata Table Existence".to_string(), "Missing".to_string(), None, None),
+        ]);
+        let fixes = metasrv_fixes(&config, &table_missing);
+        assert_eq!(fixes.iter().map(Fix::description).collect::<Vec<_>>(), vec!["Create PostgreSQL metadata table greptime_metasrv"]);
+
+        // A failed query is not a missing schema, whatever its message says
+        let query_failed = CheckResult::from_details(vec![CheckDetail::fail(
+            "Metadata Schema Check".to_string(),
+            "Failed to check schema existence: relation does not exist".to_string(),
+            None,
+            None,
+        )]);
+        assert!(metasrv_fixes(&config, &query_failed).is_empty());
+
+        let config = MetasrvConfig {
+            backend: "etcd_store".to_string(),
+            ..config
+        };
+        assert!(metasrv_fixes(&config, &result).is_empty());
+    }
+}
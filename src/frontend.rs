@@ -10,19 +10,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::{CheckDetail, CheckResult, ComponentChecker};
+use crate::auth;
+use crate::common::{CheckDetail, CheckResult, ComponentChecker, Dependency};
 use crate::config::FrontendConfig;
-use crate::error;
+use crate::export_metrics;
+use crate::grpc_health;
+use crate::lint;
+use crate::logging;
+use crate::mode::{self, CheckMode};
+use crate::network;
+use crate::otlp;
+use crate::query;
+use crate::tls;
 use async_trait::async_trait;
-use snafu::ResultExt;
 use std::fmt::{Debug, Formatter};
-use std::time::{Duration, Instant};
-use tokio::net::TcpStream;
-use tokio::time::timeout;
+
+/// Bind addresses GreptimeDB uses when a server section leaves `addr` unset
+const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:4000";
+const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:4001";
+const DEFAULT_MYSQL_ADDR: &str = "127.0.0.1:4002";
+const DEFAULT_POSTGRES_ADDR: &str = "127.0.0.1:4003";
 
 /// Frontend component checker
 pub struct FrontendChecker {
     config: FrontendConfig,
+    probe_advertise: bool,
+    /// Resources of the host the frontend runs on; None when the config belongs to another machine
+    host: Option<query::Host>,
 }
 
 impl Debug for FrontendChecker {
@@ -33,122 +47,76 @@ impl Debug for FrontendChecker {
 
 impl FrontendChecker {
     /// Create a new FrontendChecker with the given configuration
-    pub fn new(config: FrontendConfig) -> Self {
-        Self { config }
+    pub fn new(config: FrontendConfig, probe_advertise: bool) -> Self {
+        Self { config, probe_advertise, host: query::Host::current() }
+    }
+
+    /// Size the config against `host` instead of this one; None skips the sizing checks, for
+    /// configs of other machines
+    pub fn with_host(mut self, host: Option<query::Host>) -> Self {
+        self.host = host;
+        self
     }
 
     /// Check connectivity to metasrv endpoints
     async fn check_metasrv_connectivity(&self) -> CheckResult {
-        let mut details = Vec::new();
-
-        let metasrv_addrs = if let Some(meta_client) = &self.config.meta_client {
-            &meta_client.metasrv_addrs
-        } else {
-            details.push(CheckDetail::fail(
-                "Metasrv Configuration".to_string(),
-                "No meta_client configuration found".to_string(),
-                None,
-                Some("Configure meta_client section in the configuration file".to_string()),
-            ));
-            return CheckResult::from_details(details);
-        };
+        network::check_metasrv_connectivity(self.config.meta_client.as_ref()).await
+    }
 
-        if metasrv_addrs.is_empty() {
-            details.push(CheckDetail::fail(
-                "Metasrv Configuration".to_string(),
-                "No metasrv addresses configured".to_string(),
-                None,
-                Some("Configure metasrv_addrs in the meta_client section".to_string()),
-            ));
-            return CheckResult::from_details(details);
+    /// The enabled protocol servers and their bind addresses, with defaults filled in
+    fn protocol_servers(&self) -> Vec<(&'static str, String)> {
+        let config = &self.config;
+        let mut servers = vec![
+            (
+                "http",
+                config.http.as_ref().and_then(|http| http.addr.clone()).unwrap_or_else(|| DEFAULT_HTTP_ADDR.to_string()),
+            ),
+            (
+                "grpc",
+                config.grpc.as_ref().and_then(|grpc| grpc.addr.clone()).unwrap_or_else(|| DEFAULT_GRPC_ADDR.to_string()),
+            ),
+        ];
+        if let Some(addr) = config.internal_grpc.as_ref().and_then(|grpc| grpc.addr.clone()) {
+            servers.push(("internal_grpc", addr));
         }
-
-        for (index, addr) in metasrv_addrs.iter().enumerate() {
-            let start = Instant::now();
-
-            // Parse address to extract host and port
-            let (host, port) = match self.parse_address(addr) {
-                Ok((h, p)) => (h, p),
-                Err(e) => {
-                    details.push(CheckDetail::fail(
-                        format!("Metasrv Address {} Parsing", index + 1),
-                        format!("Failed to parse address '{}': {}", addr, e),
-                        None,
-                        Some("Check address format (should be host:port)".to_string()),
-                    ));
-                    continue;
-                }
-            };
-
-            // Test TCP connectivity
-            match timeout(Duration::from_secs(10), TcpStream::connect((host.as_str(), port))).await {
-                Ok(Ok(_stream)) => {
-                    details.push(CheckDetail::pass(
-                        format!("Metasrv Connectivity {}", index + 1),
-                        format!("Successfully connected to metasrv at {}", addr),
-                        Some(start.elapsed()),
-                    ));
-                }
-                Ok(Err(e)) => {
-                    details.push(CheckDetail::fail(
-                        format!("Metasrv Connectivity {}", index + 1),
-                        format!("Failed to connect to metasrv at {}: {}", addr, e),
-                        Some(start.elapsed()),
-                        Some("Check if metasrv is running and accessible".to_string()),
-                    ));
-                }
-                Err(_) => {
-                    details.push(CheckDetail::fail(
-                        format!("Metasrv Connectivity {}", index + 1),
-                        format!("Connection to metasrv at {} timed out", addr),
-                        Some(start.elapsed()),
-                        Some("Check network connectivity and metasrv availability".to_string()),
-                    ));
-                }
+        for (name, server, default_addr) in [
+            ("mysql", &config.mysql, DEFAULT_MYSQL_ADDR),
+            ("postgres", &config.postgres, DEFAULT_POSTGRES_ADDR),
+        ] {
+            let server = server.clone().unwrap_or_default();
+            if server.enable != Some(false) {
+                servers.push((name, server.addr.unwrap_or_else(|| default_addr.to_string())));
             }
         }
-
-        CheckResult::from_details(details)
+        for (name, protocol) in [("opentsdb", &config.opentsdb), ("influxdb", &config.influxdb)] {
+            if let Some(protocol) = protocol.as_ref().filter(|protocol| protocol.enable != Some(false)) {
+                servers.extend(protocol.addr.clone().map(|addr| (name, addr)));
+            }
+        }
+        servers
     }
 
-    /// Parse address string into host and port
-    fn parse_address(&self, addr: &str) -> error::Result<(String, u16)> {
-        // Handle different address formats
-        if addr.starts_with("http://") {
-            let addr = addr.strip_prefix("http://").unwrap();
-            self.parse_host_port(addr)
-        } else if addr.starts_with("https://") {
-            let addr = addr.strip_prefix("https://").unwrap();
-            self.parse_host_port(addr)
-        } else {
-            self.parse_host_port(addr)
+    /// Checks of the frontend's own servers in `mode`: before startup their ports must be free,
+    /// and once it runs its gRPC and HTTP servers must be healthy
+    pub async fn check_own_servers(&self, mode: CheckMode) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+        details.extend(grpc_health::check_running_server("gRPC Health", self.config.grpc.as_ref(), grpc_health::FRONTEND_SERVICES, mode).await);
+        let http = self.config.http.as_ref().and_then(|http| http.addr.as_deref()).unwrap_or(DEFAULT_HTTP_ADDR);
+        details.extend(network::check_running_http("HTTP Health".to_string(), http, mode).await);
+        if mode.checks_binds() {
+            details.extend(self.check_protocol_ports(mode));
         }
+        details
     }
 
-    /// Parse host:port format
-    fn parse_host_port(&self, addr: &str) -> error::Result<(String, u16)> {
-        if let Some(colon_pos) = addr.rfind(':') {
-            let host = addr[..colon_pos].to_string();
-            let port_str = &addr[colon_pos + 1..];
-
-            // Remove any path component
-            let port_str = if let Some(slash_pos) = port_str.find('/') {
-                &port_str[..slash_pos]
-            } else {
-                port_str
-            };
-
-            port_str.parse::<u16>()
-                .map(|port| (host, port))
-                .context(error::InvalidPortSnafu {
-                    address: addr.to_string(),
-                    port_str: port_str.to_string(),
-                })
-        } else {
-            error::MissingPortSnafu {
-                address: addr.to_string(),
-            }.fail()
-        }
+    /// Check that each protocol server has a valid port that is free on this host and not
+    /// shared with another server
+    fn check_protocol_ports(&self, mode: CheckMode) -> Vec<CheckDetail> {
+        network::check_server_ports(
+            &self.protocol_servers(),
+            "Give each protocol server its own port; unset addresses use the defaults 4000-4003",
+            mode,
+        )
     }
 
     /// Check server configuration if present
@@ -158,7 +126,7 @@ impl FrontendChecker {
         // Check HTTP server configuration
         if let Some(http_config) = &self.config.http {
             if let Some(addr) = &http_config.addr {
-                match self.parse_address(addr) {
+                match network::parse_address(addr) {
                     Ok(_) => {
                         details.push(CheckDetail::pass(
                             "HTTP Server Address Configuration".to_string(),
@@ -181,7 +149,7 @@ impl FrontendChecker {
         // Check gRPC server configuration
         if let Some(grpc_config) = &self.config.grpc {
             if let Some(addr) = &grpc_config.addr {
-                match self.parse_address(addr) {
+                match network::parse_address(addr) {
                     Ok(_) => {
                         details.push(CheckDetail::pass(
                             "gRPC Server Address Configuration".to_string(),
@@ -199,6 +167,16 @@ impl FrontendChecker {
                     }
                 }
             }
+
+            if let Some(detail) = network::check_grpc_advertise_address(grpc_config) {
+                details.push(detail);
+            }
+
+            if self.probe_advertise {
+                if let Some(server_addr) = grpc_config.server_addr.as_deref().or(grpc_config.addr.as_deref()) {
+                    details.push(network::probe_advertise_address(server_addr).await);
+                }
+            }
         }
 
         if details.is_empty() {
@@ -217,7 +195,16 @@ impl FrontendChecker {
 #[async_trait]
 impl ComponentChecker for FrontendChecker {
     async fn check(&self) -> CheckResult {
-        let mut all_details = Vec::new();
+        // Validate relationships between config fields before touching the network
+        let mut all_details = lint::lint_frontend(&self.config);
+        all_details.extend(tls::check_frontend_tls(&self.config));
+        all_details.extend(logging::check_log_dir(self.config.logging.as_ref()));
+        all_details.extend(query::check_query(self.config.query.as_ref(), None, self.host));
+        all_details.extend(export_metrics::check_remote_write(self.config.export_metrics.as_ref()).await);
+        all_details.extend(otlp::check_otlp_tracing(self.config.logging.as_ref()).await);
+        if let Some(provider) = &self.config.user_provider {
+            all_details.extend(auth::check_user_provider(provider));
+        }
 
         // Check metasrv connectivity
         let metasrv_result = self.check_metasrv_connectivity().await;
@@ -226,6 +213,7 @@ impl ComponentChecker for FrontendChecker {
         // Check server configuration
         let server_result = self.check_server_config().await;
         all_details.extend(server_result.details);
+        all_details.extend(self.check_own_servers(mode::current()).await);
 
         CheckResult::from_details(all_details)
     }
@@ -233,4 +221,15 @@ impl ComponentChecker for FrontendChecker {
     fn component_name(&self) -> &'static str {
         "Frontend"
     }
+
+    fn dependencies(&self) -> &'static [Dependency] {
+        &[Dependency::Metasrv]
+    }
+
+    async fn check_dependency(&self, dependency: Dependency) -> Option<CheckResult> {
+        match dependency {
+            Dependency::Metasrv => Some(self.check_metasrv_connectivity().await),
+            Dependency::Storage | Dependency::Backend => None,
+        }
+    }
 }
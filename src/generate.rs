@@ -0,0 +1,475 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Starter configs built from the same structs the checkers parse, so a generated file is
+//! valid by construction.
+
+use crate::config::{
+    DatanodeConfig, DatanodeStorageConfig, FrontendConfig, GrpcConfig, HeartbeatConfig, HttpConfig, LoggingConfig,
+    MetaClientConfig, MetasrvConfig, MitoConfig, RegionEngineConfig, SqlServerConfig, WalConfig,
+};
+use crate::lint::{DEFAULT_HEARTBEAT_INTERVAL, FRONTEND_HEARTBEAT_FACTOR};
+use serde::Serialize;
+use snafu::ResultExt;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Comments placed above a table header or key, by dotted path
+const COMMENTS: &[(&str, &str)] = &[
+    ("data_home", "Directory for metadata and local data."),
+    ("store_addrs", "Endpoints of the metadata backend."),
+    ("backend", "Metadata backend: etcd_store, postgres_store or mysql_store."),
+    ("meta_table_name", "Table holding the metadata key-values in the SQL backend."),
+    ("selector", "How datanodes are chosen for new regions: round_robin, lease_based or load_based."),
+    ("enable_region_failover", "Region failover requires a remote WAL (wal.provider = \"kafka\")."),
+    ("node_id", "Unique identifier of this datanode in the cluster."),
+    ("require_lease_before_startup", "Wait for region leases from metasrv before serving."),
+    ("grpc", "gRPC server options."),
+    ("grpc.addr", "Address the gRPC server binds to."),
+    ("grpc.server_addr", "Address advertised to other nodes; must be reachable from them."),
+    ("http", "HTTP server options."),
+    ("mysql", "MySQL protocol server."),
+    ("postgres", "PostgreSQL protocol server."),
+    ("meta_client", "Connection to metasrv."),
+    ("meta_client.metasrv_addrs", "Every metasrv the node may connect to."),
+    ("meta_client.heartbeat_timeout", "Must be shorter than heartbeat.interval."),
+    ("heartbeat", "Heartbeats to metasrv."),
+    ("wal", "Write-ahead log."),
+    ("wal.provider", "raft_engine keeps the WAL on local disk, kafka in a Kafka cluster."),
+    ("wal.purge_threshold", "Keep several times wal.file_size."),
+    ("wal.broker_endpoints", "Kafka brokers shared by metasrv and every datanode."),
+    ("wal.num_topics", "Number of topics regions are spread across; cannot be lowered later."),
+    ("storage", "Where region data is stored."),
+    ("storage.credential_path", "Service account key file."),
+    ("region_engine", "Region engine options."),
+    ("region_engine.mito.write_cache_size", "Local disk cache for object storage uploads."),
+    ("logging", "Logging options."),
+];
+
+/// Secrets a starter config leaves out, by the setting and value that need them. GreptimeDB does
+/// not expand placeholders in its config, but reads any key from `GREPTIMEDB_<COMPONENT>__<KEY>`.
+const SECRETS: &[(&str, &str, &[&str])] = &[
+    ("storage.type", "\"S3\"", &["storage.access_key_id", "storage.secret_access_key"]),
+    ("storage.type", "\"Oss\"", &["storage.access_key_id", "storage.access_key_secret"]),
+    ("storage.type", "\"Azblob\"", &["storage.account_key"]),
+    ("backend", "\"postgres_store\"", &["store_addrs"]),
+    ("backend", "\"mysql_store\"", &["store_addrs"]),
+];
+
+/// Generate a commented starter config for a component.
+///
+/// `storage` (file, s3, oss, azblob, gcs) only applies to datanode, `backend` (etcd, postgres,
+/// mysql) only to metasrv, and `wal` (raft_engine, kafka) to both.
+pub fn generate_config(component: &str, storage: &str, wal: &str, backend: &str) -> crate::error::Result<String> {
+    let wal_config = wal_config(wal)?;
+    let (body, setup) = match component {
+        "metasrv" => (
+            to_toml(&metasrv_config(backend, wal_config)?)?,
+            format!("backend: {}, WAL: {}", backend, wal),
+        ),
+        "frontend" => (to_toml(&frontend_config())?, "MySQL and PostgreSQL protocols enabled".to_string()),
+        "datanode" => (
+            to_toml(&datanode_config(storage, wal_config)?)?,
+            format!("storage: {}, WAL: {}", storage, wal),
+        ),
+        other => {
+            return crate::error::InvalidConfigSnafu {
+                message: format!("Unknown component '{}', expected metasrv, frontend or datanode", other),
+            }
+            .fail();
+        }
+    };
+
+    Ok(with_header(component, &setup, &body))
+}
+
+/// Render a config built by this module or edited from one, with comments
+pub fn render_config<T: Serialize>(component: &str, setup: &str, config: &T) -> crate::error::Result<String> {
+    Ok(with_header(component, setup, &to_toml(config)?))
+}
+
+fn with_header(component: &str, setup: &str, body: &str) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "## GreptimeDB {} starter config ({})", component, setup);
+    let _ = writeln!(output, "## Check it before deploying with: stepstone {} -c {}.toml", component, component);
+    let _ = writeln!(output);
+    output.push_str(&annotate(component, body));
+    output
+}
+
+fn to_toml<T: Serialize>(config: &T) -> crate::error::Result<String> {
+    toml::to_string(config).context(crate::error::TomlSerializationSnafu {
+        message: "Failed to render config".to_string(),
+    })
+}
+
+/// Starter metasrv config for a backend (etcd, postgres, mysql) and WAL
+pub fn metasrv_config(backend: &str, wal: WalConfig) -> crate::error::Result<MetasrvConfig> {
+    let (backend, store_addrs, meta_table_name) = match backend {
+        "etcd" => ("etcd_store", "127.0.0.1:2379", None),
+        "postgres" => (
+            "postgres_store",
+            "postgresql://greptime@127.0.0.1:5432/greptime_meta",
+            Some("greptime_metakv".to_string()),
+        ),
+        "mysql" => (
+            "mysql_store",
+            "mysql://greptime@127.0.0.1:3306/greptime_meta",
+            Some("greptime_metakv".to_string()),
+        ),
+        other => {
+            return crate::error::InvalidConfigSnafu {
+                message: format!("Unknown backend '{}', expected etcd, postgres or mysql", other),
+            }
+            .fail();
+        }
+    };
+    // Metasrv only needs the provider of a local WAL, and manages the topics of a Kafka one
+    let remote_wal = wal.provider.as_deref() == Some("kafka");
+    let wal = if remote_wal {
+        WalConfig {
+            auto_create_topics: Some(true),
+            num_topics: Some(64),
+            topic_name_prefix: Some("greptimedb_wal_topic".to_string()),
+            replication_factor: Some(1),
+            ..wal
+        }
+    } else {
+        WalConfig {
+            provider: wal.provider,
+            ..Default::default()
+        }
+    };
+
+    Ok(MetasrvConfig {
+        data_home: Some("./greptimedb_data".to_string()),
+        store_addrs: vec![store_addrs.to_string()],
+        store_key_prefix: Some(String::new()),
+        backend: backend.to_string(),
+        meta_table_name,
+        selector: Some("round_robin".to_string()),
+        enable_region_failover: Some(remote_wal),
+        grpc: Some(GrpcConfig {
+            addr: Some("127.0.0.1:3002".to_string()),
+            server_addr: Some("127.0.0.1:3002".to_string()),
+            ..Default::default()
+        }),
+        http: Some(HttpConfig {
+            addr: Some("127.0.0.1:4000".to_string()),
+            ..Default::default()
+        }),
+        wal: Some(wal),
+        logging: Some(logging_config()),
+        ..Default::default()
+    })
+}
+
+/// Starter frontend config with the MySQL and PostgreSQL protocols enabled
+pub fn frontend_config() -> FrontendConfig {
+    FrontendConfig {
+        http: Some(HttpConfig {
+            addr: Some("127.0.0.1:4000".to_string()),
+            timeout: Some("30s".to_string()),
+            body_limit: Some("64MB".to_string()),
+            ..Default::default()
+        }),
+        grpc: Some(GrpcConfig {
+            addr: Some("127.0.0.1:4001".to_string()),
+            server_addr: Some("127.0.0.1:4001".to_string()),
+            ..Default::default()
+        }),
+        mysql: Some(SqlServerConfig {
+            enable: Some(true),
+            addr: Some("127.0.0.1:4002".to_string()),
+            ..Default::default()
+        }),
+        postgres: Some(SqlServerConfig {
+            enable: Some(true),
+            addr: Some("127.0.0.1:4003".to_string()),
+            ..Default::default()
+        }),
+        meta_client: Some(meta_client_config()),
+        heartbeat: Some(heartbeat_config(DEFAULT_HEARTBEAT_INTERVAL * FRONTEND_HEARTBEAT_FACTOR)),
+        logging: Some(logging_config()),
+        ..Default::default()
+    }
+}
+
+/// Starter datanode config for a storage (file, s3, oss, azblob, gcs) and WAL
+pub fn datanode_config(storage: &str, wal: WalConfig) -> crate::error::Result<DatanodeConfig> {
+    let storage = storage_config(storage)?;
+    let object_storage = storage.storage_type.as_deref() != Some("File");
+
+    Ok(DatanodeConfig {
+        node_id: Some(0),
+        require_lease_before_startup: Some(false),
+        grpc: Some(GrpcConfig {
+            addr: Some("127.0.0.1:3001".to_string()),
+            server_addr: Some("127.0.0.1:3001".to_string()),
+            ..Default::default()
+        }),
+        http: Some(HttpConfig {
+            addr: Some("127.0.0.1:4000".to_string()),
+            ..Default::default()
+        }),
+        meta_client: Some(meta_client_config()),
+        heartbeat: Some(heartbeat_config(DEFAULT_HEARTBEAT_INTERVAL)),
+        wal: Some(wal),
+        storage: Some(storage),
+        region_engine: Some(vec![RegionEngineConfig {
+            mito: Some(MitoConfig {
+                num_workers: Some(8),
+                global_write_buffer_size: Some("1GB".to_string()),
+                enable_write_cache: object_storage.then_some(true),
+                write_cache_size: object_storage.then(|| "5GiB".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]),
+        logging: Some(logging_config()),
+        ..Default::default()
+    })
+}
+
+/// Starter `[storage]` section for file, s3, oss, azblob or gcs
+fn storage_config(storage: &str) -> crate::error::Result<DatanodeStorageConfig> {
+    let base = DatanodeStorageConfig {
+        data_home: Some("./greptimedb_data".to_string()),
+        ..Default::default()
+    };
+    let storage = match storage {
+        "file" => DatanodeStorageConfig {
+            storage_type: Some("File".to_string()),
+            ..base
+        },
+        "s3" => DatanodeStorageConfig {
+            storage_type: Some("S3".to_string()),
+            bucket: Some("greptimedb".to_string()),
+            root: Some("data".to_string()),
+            endpoint: Some("https://s3.us-east-1.amazonaws.com".to_string()),
+            region: Some("us-east-1".to_string()),
+            ..base
+        },
+        "oss" => DatanodeStorageConfig {
+            storage_type: Some("Oss".to_string()),
+            bucket: Some("greptimedb".to_string()),
+            root: Some("data".to_string()),
+            endpoint: Some("https://oss-cn-hangzhou.aliyuncs.com".to_string()),
+            ..base
+        },
+        "azblob" => DatanodeStorageConfig {
+            storage_type: Some("Azblob".to_string()),
+            container: Some("greptimedb".to_string()),
+            root: Some("data".to_string()),
+            account_name: Some("greptimedb".to_string()),
+            endpoint: Some("https://greptimedb.blob.core.windows.net".to_string()),
+            ..base
+        },
+        "gcs" => DatanodeStorageConfig {
+            storage_type: Some("Gcs".to_string()),
+            bucket: Some("greptimedb".to_string()),
+            root: Some("data".to_string()),
+            scope: Some("https://www.googleapis.com/auth/devstorage.read_write".to_string()),
+            credential_path: Some("/etc/greptimedb/gcs-credential.json".to_string()),
+            endpoint: Some("https://storage.googleapis.com".to_string()),
+            ..base
+        },
+        other => {
+            return crate::error::InvalidConfigSnafu {
+                message: format!("Unknown storage '{}', expected file, s3, oss, azblob or gcs", other),
+            }
+            .fail();
+        }
+    };
+    Ok(storage)
+}
+
+/// Starter datanode `[wal]` section for raft_engine or kafka
+pub fn wal_config(wal: &str) -> crate::error::Result<WalConfig> {
+    let wal = match wal {
+        "raft_engine" => WalConfig {
+            provider: Some("raft_engine".to_string()),
+            dir: Some("./greptimedb_data/wal".to_string()),
+            file_size: Some("128MB".to_string()),
+            purge_threshold: Some("1GB".to_string()),
+            purge_interval: Some("1m".to_string()),
+            ..Default::default()
+        },
+        "kafka" => WalConfig {
+            provider: Some("kafka".to_string()),
+            broker_endpoints: Some(vec!["127.0.0.1:9092".to_string()]),
+            ..Default::default()
+        },
+        other => {
+            return crate::error::InvalidConfigSnafu {
+                message: format!("Unknown WAL '{}', expected raft_engine or kafka", other),
+            }
+            .fail();
+        }
+    };
+    Ok(wal)
+}
+
+fn meta_client_config() -> MetaClientConfig {
+    MetaClientConfig {
+        metasrv_addrs: vec!["127.0.0.1:3002".to_string()],
+        timeout: Some("3s".to_string()),
+        heartbeat_timeout: Some("500ms".to_string()),
+        ddl_timeout: Some("10s".to_string()),
+        connect_timeout: Some("1s".to_string()),
+        tcp_nodelay: Some(true),
+        ..Default::default()
+    }
+}
+
+/// GreptimeDB's heartbeat defaults; failed heartbeats are retried after a datanode's interval
+fn heartbeat_config(interval: Duration) -> HeartbeatConfig {
+    HeartbeatConfig {
+        interval: Some(format!("{}s", interval.as_secs())),
+        retry_interval: Some(format!("{}s", DEFAULT_HEARTBEAT_INTERVAL.as_secs())),
+    }
+}
+
+fn logging_config() -> LoggingConfig {
+    LoggingConfig {
+        dir: Some("./greptimedb_data/logs".to_string()),
+        level: Some("info".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Insert the comment of each table header and key above its line
+fn annotate(component: &str, body: &str) -> String {
+    let mut output = String::new();
+    let mut table = String::new();
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        let header = trimmed
+            .strip_prefix("[[")
+            .and_then(|header| header.strip_suffix("]]"))
+            .or_else(|| trimmed.strip_prefix('[').and_then(|header| header.strip_suffix(']')));
+        let (path, value) = match header {
+            Some(header) => {
+                table = header.to_string();
+                (Some(table.clone()), None)
+            }
+            None => match line.split_once(" = ") {
+                Some((key, value)) if table.is_empty() => (Some(key.trim().to_string()), Some(value.trim())),
+                Some((key, value)) => (Some(format!("{}.{}", table, key.trim())), Some(value.trim())),
+                None => (None, None),
+            },
+        };
+
+        if let Some((_, comment)) = path.as_ref().and_then(|path| COMMENTS.iter().find(|(key, _)| *key == path.as_str())) {
+            let _ = writeln!(output, "## {}", comment);
+        }
+        let secrets = SECRETS
+            .iter()
+            .find(|(key, expected, _)| path.as_deref() == Some(*key) && value == Some(*expected));
+        if let Some((_, _, keys)) = secrets {
+            let vars = keys.iter().map(|key| env_var(component, key)).collect::<Vec<_>>().join(" and ");
+            let _ = writeln!(output, "## Secrets are left out of this file; GreptimeDB reads them from {} in its environment.", vars);
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// The environment variable GreptimeDB reads a config key of `component` from
+fn env_var(component: &str, key: &str) -> String {
+    format!("GREPTIMEDB_{}__{}", component, key.replace('.', "__")).to_uppercase()
+}
+
+/// Drop the secrets of a storage section, so a config written after checking them keeps them
+/// in the environment as [`SECRETS`] describes
+pub fn strip_storage_secrets(storage: &mut DatanodeStorageConfig) {
+    storage.access_key_id = None;
+    storage.secret_access_key = None;
+    storage.access_key_secret = None;
+    storage.account_key = None;
+}
+
+/// A backend address without the password of its URL, if it has one
+pub fn strip_password(addr: &str) -> String {
+    match reqwest::Url::parse(addr) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(None);
+            url.to_string()
+        }
+        _ => addr.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use crate::lint::lint_config;
+
+    #[test]
+    fn test_generated_configs_lint_clean() {
+        let mut combinations = vec![("frontend", "file", "raft_engine", "etcd")];
+        for storage in ["file", "s3", "oss", "azblob", "gcs"] {
+            for wal in ["raft_engine", "kafka"] {
+                combinations.push(("datanode", storage, wal, "etcd"));
+            }
+        }
+        for backend in ["etcd", "postgres", "mysql"] {
+            for wal in ["raft_engine", "kafka"] {
+                combinations.push(("metasrv", "file", wal, backend));
+            }
+        }
+
+        for (component, storage, wal, backend) in combinations {
+            let generated = generate_config(component, storage, wal, backend).unwrap();
+            let raw: toml::Value = toml::from_str(&generated).unwrap();
+            let failures = lint_config(&raw, component)
+                .unwrap()
+                .into_iter()
+                .filter(|detail| detail.status == CheckStatus::Fail)
+                .map(|detail| detail.item)
+                .collect::<Vec<_>>();
+            assert!(failures.is_empty(), "{} {} {} {}: {:?}", component, storage, wal, backend, failures);
+        }
+    }
+
+    #[test]
+    fn test_generate_config() {
+        let generated = generate_config("datanode", "s3", "kafka", "etcd").unwrap();
+        assert!(generated.contains("## Where region data is stored.\n[storage]"));
+        assert!(generated.contains("type = \"S3\""));
+        assert!(generated.contains("provider = \"kafka\""));
+        assert!(generated.contains("write_cache_size = \"5GiB\""));
+
+        // GreptimeDB does not expand placeholders, so secrets are only named, once
+        assert!(!generated.contains("${"));
+        assert!(!generated.contains("access_key_id ="));
+        assert_eq!(
+            generated.matches("GREPTIMEDB_DATANODE__STORAGE__ACCESS_KEY_ID and GREPTIMEDB_DATANODE__STORAGE__SECRET_ACCESS_KEY").count(),
+            1
+        );
+        let generated = generate_config("metasrv", "file", "raft_engine", "postgres").unwrap();
+        assert!(generated.contains("GREPTIMEDB_METASRV__STORE_ADDRS"));
+        assert!(generated.contains("\"postgresql://greptime@127.0.0.1:5432/greptime_meta\""));
+
+        assert_eq!(
+            strip_password("postgresql://greptime:secret@db:5432/meta"),
+            "postgresql://greptime@db:5432/meta"
+        );
+        assert_eq!(strip_password("127.0.0.1:2379"), "127.0.0.1:2379");
+
+        assert!(generate_config("datanode", "hdfs", "raft_engine", "etcd").is_err());
+        assert!(generate_config("flownode", "file", "raft_engine", "etcd").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! gRPC health checks through the standard `grpc.health.v1.Health/Check` call.
+//!
+//! An open port only shows that something listens; a server that is still starting up or
+//! shutting down accepts connections too. The health call asks the server itself whether it is
+//! serving, as a whole and for each `greptime.v1.*` service the component registers.
+
+use crate::common::{CheckDetail, CheckStatus, PhaseTimer};
+use crate::config::GrpcConfig;
+use crate::export_metrics::put_bytes;
+use crate::mode::CheckMode;
+use crate::network;
+use std::time::Duration;
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+const HEALTH_CHECK_PATH: &str = "/grpc.health.v1.Health/Check";
+
+/// `grpc-status` a server answers with when it does not implement the health service
+const GRPC_UNIMPLEMENTED: &str = "12";
+
+/// `grpc-status` of a health service that has no status registered for the service name
+const GRPC_NOT_FOUND: &str = "5";
+
+/// gRPC services of the frontend, see greptime-proto
+pub const FRONTEND_SERVICES: &[&str] = &["greptime.v1.GreptimeDatabase"];
+
+/// gRPC services of the datanode
+pub const DATANODE_SERVICES: &[&str] = &["greptime.v1.region.Region"];
+
+/// gRPC services of metasrv
+pub const METASRV_SERVICES: &[&str] = &[
+    "greptime.v1.meta.Heartbeat",
+    "greptime.v1.meta.Store",
+    "greptime.v1.meta.Cluster",
+    "greptime.v1.meta.ProcedureService",
+];
+
+/// Call the health service of a gRPC endpoint for a service name, the empty name standing for
+/// the server as a whole
+pub async fn check_grpc_health(item: String, addr: &str, service: &str, ca: Option<&str>) -> CheckDetail {
+    timed_health_call(item, addr, service, ca).await.0
+}
+
+/// Ask for the health of each of `services` as `<item> (<service>)`; nothing is reported when the
+/// server does not implement the health service, since it could not answer for any of them
+pub async fn check_grpc_services(item: &str, addr: &str, services: &[&str], ca: Option<&str>) -> Vec<CheckDetail> {
+    let mut details = Vec::new();
+    for service in services {
+        let (detail, implemented) = timed_health_call(format!("{} ({})", item, service), addr, service, ca).await;
+        if !implemented {
+            break;
+        }
+        details.push(detail);
+    }
+    details
+}
+
+/// [`health_call`] with its phases attached to the detail
+async fn timed_health_call(item: String, addr: &str, service: &str, ca: Option<&str>) -> (CheckDetail, bool) {
+    let mut timer = PhaseTimer::start();
+    let (detail, implemented) = health_call(item, addr, service, ca, &mut timer).await;
+    (detail.with_timer(&timer), implemented)
+}
+
+/// The health call of [`check_grpc_health`], timing client setup, the response headers and
+/// the response body as phases of `timer`; also returns whether the server implements the
+/// health service
+async fn health_call(item: String, addr: &str, service: &str, ca: Option<&str>, timer: &mut PhaseTimer) -> (CheckDetail, bool) {
+    let tls = addr.starts_with("https://");
+    let authority = addr.split_once("://").map_or(addr, |(_, rest)| rest).trim_end_matches('/');
+    let url = format!("{}://{}{}", if tls { "https" } else { "http" }, authority, HEALTH_CHECK_PATH);
+    let target = if service.is_empty() { "the server".to_string() } else { format!("service '{}'", service) };
+
+    let mut builder = reqwest::Client::builder().http2_prior_knowledge().timeout(HEALTH_TIMEOUT);
+    if let Some(ca) = ca.filter(|_| tls) {
+        match std::fs::read(ca).map_err(|e| e.to_string()).and_then(|pem| reqwest::Certificate::from_pem(&pem).map_err(|e| e.to_string())) {
+            Ok(certificate) => builder = builder.add_root_certificate(certificate),
+            Err(e) => return (CheckDetail::fail(item, format!("Cannot load CA {}: {}", ca, e), None, None), false),
+        }
+    }
+    let client = match builder.build() {
+        Ok(client) => client,
+        Err(e) => return (CheckDetail::fail(item, format!("Failed to build HTTP client: {}", e), None, None), false),
+    };
+    timer.phase("client_build");
+
+    let response = client
+        .post(&url)
+        .header("Content-Type", "application/grpc")
+        .header("TE", "trailers")
+        .body(encode_health_request(service))
+        .send()
+        .await;
+    timer.phase("first_byte");
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            let detail = CheckDetail::fail(
+                item,
+                format!("gRPC health call to {} failed: {}", addr, e),
+                Some(timer.elapsed()),
+                Some("Check that the endpoint is a gRPC server, and that it is addressed with https:// if it serves TLS".to_string()),
+            );
+            return (detail, false);
+        }
+    };
+
+    let grpc_status = response.headers().get("grpc-status").and_then(|value| value.to_str().ok()).map(str::to_string);
+    match grpc_status.as_deref() {
+        // Answering with a gRPC status still proves a live gRPC server
+        Some(GRPC_UNIMPLEMENTED) => {
+            let detail = CheckDetail::pass(
+                item,
+                format!("{} answers gRPC but does not implement grpc.health.v1", addr),
+                Some(timer.elapsed()),
+            );
+            return (detail, false);
+        }
+        Some(GRPC_NOT_FOUND) => {
+            let detail = CheckDetail::info(
+                item,
+                format!("{} does not report health for {}", addr, target),
+                Some(timer.elapsed()),
+            );
+            return (detail, true);
+        }
+        Some(code) if code != "0" => {
+            let detail = CheckDetail::fail(
+                item,
+                format!("gRPC health call to {} returned grpc-status {}", addr, code),
+                Some(timer.elapsed()),
+                Some("Check the server's logs".to_string()),
+            );
+            return (detail, true);
+        }
+        _ => {}
+    }
+
+    let body = response.bytes().await.unwrap_or_default();
+    timer.phase("body");
+    let detail = match decode_health_status(&body) {
+        Some(1) => CheckDetail::pass(item, format!("{} reports SERVING for {}", addr, target), Some(timer.elapsed())),
+        Some(2) => CheckDetail::fail(
+            item,
+            format!("{} reports NOT_SERVING for {}", addr, target),
+            Some(timer.elapsed()),
+            Some("The process is up but not ready; it may still be starting, or be shutting down".to_string()),
+        ),
+        Some(3) => CheckDetail::warning(
+            item,
+            format!("{} does not know {}", addr, target),
+            Some(timer.elapsed()),
+            None,
+        ),
+        _ => CheckDetail::warning(
+            item,
+            format!("{} reports UNKNOWN health for {}", addr, target),
+            Some(timer.elapsed()),
+            None,
+        ),
+    };
+    (detail, true)
+}
+
+/// Health of this node's own gRPC server and of its `services`, checked only when something
+/// already listens on its address, i.e. the node is running; in live `mode` it is always
+/// checked, and never in preflight. The services are asked only when the server is serving.
+pub async fn check_running_server(item: &str, grpc: Option<&GrpcConfig>, services: &[&str], mode: CheckMode) -> Vec<CheckDetail> {
+    if !mode.checks_running() {
+        return Vec::new();
+    }
+    let Some(addr) = grpc.and_then(|grpc| grpc.server_addr.as_deref().or(grpc.addr.as_deref())) else {
+        return Vec::new();
+    };
+    let Ok((host, port)) = network::parse_address(addr) else {
+        return Vec::new();
+    };
+    if mode != CheckMode::Live && !network::is_listening(&host, port).await {
+        return Vec::new();
+    }
+    let ca = grpc.and_then(|grpc| grpc.tls.as_ref()).and_then(|tls| tls.ca.as_deref());
+    let (server, implemented) = timed_health_call(item.to_string(), addr, "", ca).await;
+    let serving = implemented && server.status == CheckStatus::Pass;
+    let mut details = vec![server];
+    if serving {
+        details.extend(check_grpc_services(item, addr, services, ca).await);
+    }
+    details
+}
+
+/// A gRPC-framed `HealthCheckRequest`: no compression, big-endian length, then the message
+fn encode_health_request(service: &str) -> Vec<u8> {
+    let mut message = Vec::new();
+    if !service.is_empty() {
+        put_bytes(&mut message, 1, service.as_bytes());
+    }
+    let mut frame = vec![0];
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&message);
+    frame
+}
+
+/// The `status` of a gRPC-framed `HealthCheckResponse`; 0 (UNKNOWN) when the field is absent
+fn decode_health_status(body: &[u8]) -> Option<u64> {
+    let length = u32::from_be_bytes(body.get(1..5)?.try_into().ok()?) as usize;
+    let message = body.get(5..5 + length)?;
+    match message {
+        [] => Some(0),
+        [0x08, status, ..] if *status < 0x80 => Some(u64::from(*status)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_messages() {
+        assert_eq!(encode_health_request(""), vec![0, 0, 0, 0, 0]);
+        assert_eq!(encode_health_request("x"), vec![0, 0, 0, 0, 3, 0x0a, 0x01, b'x']);
+
+        assert_eq!(decode_health_status(&[0, 0, 0, 0, 2, 0x08, 0x01]), Some(1));
+        assert_eq!(decode_health_status(&[0, 0, 0, 0, 0]), Some(0));
+        assert_eq!(decode_health_status(&[0, 0, 0, 0, 2, 0x08]), None);
+    }
+
+    #[tokio::test]
+    async fn test_health_of_stopped_server_is_skipped() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let grpc = GrpcConfig {
+            addr: Some(addr.to_string()),
+            ..Default::default()
+        };
+        assert!(check_running_server("gRPC Health", Some(&grpc), FRONTEND_SERVICES, CheckMode::Auto).await.is_empty());
+        assert!(check_running_server("gRPC Health", Some(&grpc), FRONTEND_SERVICES, CheckMode::Preflight).await.is_empty());
+        // The services of a server that does not answer are not asked
+        let live = check_running_server("gRPC Health", Some(&grpc), FRONTEND_SERVICES, CheckMode::Live).await;
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].status, CheckStatus::Fail);
+
+        let detail = check_grpc_health("gRPC Health".to_string(), &addr.to_string(), "", None).await;
+        assert_eq!(detail.status, CheckStatus::Fail);
+    }
+}
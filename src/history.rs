@@ -0,0 +1,244 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Local history of runs.
+//!
+//! With `--history-dir`, each run's results are saved as one JSON report named after the time
+//! of the run, e.g. `20261017T093000.125Z.json`, so `stepstone history diff` can show what
+//! changed since a run that worked. Older runs are pruned on save by [`Retention`].
+
+use crate::common::Report;
+use crate::error;
+use snafu::ResultExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// History directory used by `stepstone history` when `--history-dir` is not given
+pub const DEFAULT_HISTORY_DIR: &str = "~/.stepstone/history";
+
+/// Runs kept unless `--history-keep` says otherwise
+pub const DEFAULT_KEEP: usize = 500;
+
+/// Format of a run's ID, the name of its file
+const RUN_ID_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+static HISTORY: Mutex<Option<History>> = Mutex::new(None);
+
+struct History {
+    dir: PathBuf,
+    retention: Retention,
+    report: Report,
+}
+
+/// Which saved runs survive a save
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// The most recent runs to keep
+    pub keep: usize,
+    /// Runs older than this are removed, however few are left
+    pub max_age: Option<Duration>,
+}
+
+/// Save each run to `dir` for the rest of the process, pruning it by `retention`
+pub fn configure(dir: &str, retention: Retention) {
+    *HISTORY.lock().unwrap_or_else(|e| e.into_inner()) = Some(History {
+        dir: expand_dir(dir),
+        retention,
+        report: Report::default(),
+    });
+}
+
+/// Parse `--history-max-age`, e.g. `30d` or `12h`
+pub fn parse_max_age(value: &str) -> Result<Duration, String> {
+    crate::lint::parse_duration(value).ok_or_else(|| format!("invalid age '{}', expected e.g. 30d or 12h", value))
+}
+
+/// Record a result for the next [`flush`]; a no-op without `--history-dir`
+pub fn record(component: &str, config_file: Option<&str>, result: &crate::common::CheckResult) {
+    if let Some(history) = HISTORY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        crate::sink::replace_section(&mut history.report, component, config_file, result);
+    }
+}
+
+/// Save the recorded results as one run, then prune the runs past the retention
+pub fn flush() {
+    let pending = HISTORY.lock().unwrap_or_else(|e| e.into_inner()).as_mut().and_then(|history| {
+        let report = std::mem::take(&mut history.report);
+        (!report.sections.is_empty()).then(|| (history.dir.clone(), history.retention, report))
+    });
+    let Some((dir, retention, report)) = pending else {
+        return;
+    };
+    if let Err(e) = save_run(&dir, &report).and_then(|_| prune(&dir, retention, chrono::Utc::now())) {
+        eprintln!("Failed to save run history: {}", e);
+    }
+}
+
+fn save_run(dir: &Path, report: &Report) -> error::Result<PathBuf> {
+    std::fs::create_dir_all(dir).context(error::FileSystemSnafu {
+        message: format!("Failed to create history directory: {}", dir.display()),
+    })?;
+    let json = report.to_json().context(error::JsonSerializationSnafu {
+        message: "Failed to serialize run history".to_string(),
+    })?;
+    let path = dir.join(format!("{}.json", chrono::Utc::now().format(RUN_ID_FORMAT)));
+    std::fs::write(&path, json).context(error::FileSystemSnafu {
+        message: format!("Failed to write {}", path.display()),
+    })?;
+    Ok(path)
+}
+
+/// Remove the runs `retention` does not keep as of `now`, returning how many were removed.
+/// Other JSON files in the directory, whose names are not run IDs, are left alone.
+fn prune(dir: &Path, retention: Retention, now: chrono::DateTime<chrono::Utc>) -> error::Result<usize> {
+    let runs = list_runs(dir)?
+        .into_iter()
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?;
+            let saved = chrono::NaiveDateTime::parse_from_str(id, RUN_ID_FORMAT).ok()?.and_utc();
+            Some((path, saved))
+        })
+        .collect::<Vec<_>>();
+    let surplus = runs.len().saturating_sub(retention.keep);
+    let mut removed = 0;
+    for (index, (path, saved)) in runs.iter().enumerate() {
+        let expired = retention
+            .max_age
+            .is_some_and(|max_age| now.signed_duration_since(*saved).to_std().is_ok_and(|age| age > max_age));
+        if index < surplus || expired {
+            std::fs::remove_file(path).context(error::FileSystemSnafu {
+                message: format!("Failed to remove {}", path.display()),
+            })?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_dir(dir: &str) -> PathBuf {
+    let rest = if dir == "~" { Some("") } else { dir.strip_prefix("~/") };
+    match (rest, std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(dir),
+    }
+}
+
+/// Saved runs, oldest first
+pub fn list_runs(dir: &Path) -> error::Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).context(error::FileSystemSnafu {
+        message: format!("Failed to read history directory: {}", dir.display()),
+    })?;
+    let mut runs = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect::<Vec<_>>();
+    runs.sort();
+    Ok(runs)
+}
+
+/// Find a run by its ID (the file name without `.json`), a path, `latest`, or `latest~N` for
+/// the run N before the latest
+pub fn resolve_run(dir: &Path, selector: &str) -> error::Result<PathBuf> {
+    let path = Path::new(selector);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    let back = match selector.strip_prefix("latest") {
+        Some("") => Some(0),
+        Some(rest) => rest.strip_prefix('~').and_then(|n| n.parse::<usize>().ok()),
+        None => None,
+    };
+    let found = match back {
+        Some(back) => {
+            let runs = list_runs(dir)?;
+            runs.len().checked_sub(back + 1).map(|index| runs[index].clone())
+        }
+        None => Some(dir.join(format!("{}.json", selector.trim_end_matches(".json")))).filter(|path| path.is_file()),
+    };
+    found.ok_or_else(|| {
+        error::InvalidConfigSnafu {
+            message: format!("No run '{}' in {}; see `stepstone history list`", selector, dir.display()),
+        }
+        .build()
+    })
+}
+
+/// Read a saved run, or any report saved with `--output json`
+pub fn load_run(path: &Path) -> error::Result<serde_json::Value> {
+    let content = std::fs::read_to_string(path).context(error::FileSystemSnafu {
+        message: format!("Failed to read {}", path.display()),
+    })?;
+    serde_json::from_str(&content).context(error::JsonSerializationSnafu {
+        message: format!("{} is not a JSON report", path.display()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{CheckDetail, CheckResult};
+
+    #[test]
+    fn test_save_and_resolve_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut report = Report::default();
+        report.push(
+            "Datanode",
+            "datanode.toml",
+            CheckResult::from_details(vec![CheckDetail::pass("Etcd".to_string(), "ok".to_string(), None)]),
+        );
+        let first = save_run(dir.path(), &report).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = save_run(dir.path(), &report).unwrap();
+
+        assert_eq!(list_runs(dir.path()).unwrap(), vec![first.clone(), second.clone()]);
+        assert_eq!(resolve_run(dir.path(), "latest").unwrap(), second);
+        assert_eq!(resolve_run(dir.path(), "latest~1").unwrap(), first);
+        assert!(resolve_run(dir.path(), "latest~2").is_err());
+
+        let id = first.file_stem().unwrap().to_str().unwrap();
+        assert_eq!(resolve_run(dir.path(), id).unwrap(), first);
+        assert_eq!(load_run(&first).unwrap()["files"][0]["component"], "Datanode");
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        for id in ["20261001T000000.000Z", "20261010T000000.000Z", "20261015T000000.000Z", "20261016T000000.000Z"] {
+            std::fs::write(dir.path().join(format!("{}.json", id)), "{}").unwrap();
+        }
+        std::fs::write(dir.path().join("baseline.json"), "{}").unwrap();
+        let now = chrono::NaiveDateTime::parse_from_str("20261017T000000.000Z", RUN_ID_FORMAT).unwrap().and_utc();
+
+        // The oldest runs go first once there are more than `keep`
+        let keep_three = Retention { keep: 3, max_age: None };
+        assert_eq!(prune(dir.path(), keep_three, now).unwrap(), 1);
+        assert!(!dir.path().join("20261001T000000.000Z.json").exists());
+
+        // Runs past the age limit go even when fewer than `keep` are left
+        let five_days = Retention { keep: DEFAULT_KEEP, max_age: Some(Duration::from_secs(5 * 86_400)) };
+        assert_eq!(prune(dir.path(), five_days, now).unwrap(), 1);
+        let left = list_runs(dir.path()).unwrap();
+        let names = left.iter().map(|path| path.file_name().unwrap().to_str().unwrap()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["20261015T000000.000Z.json", "20261016T000000.000Z.json", "baseline.json"]);
+    }
+
+    #[test]
+    fn test_expand_dir() {
+        assert_eq!(expand_dir("/var/lib/stepstone"), PathBuf::from("/var/lib/stepstone"));
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(expand_dir("~/.stepstone/history"), Path::new(&home).join(".stepstone/history"));
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metadata of the host a report was produced on, so reports collected from many nodes are
+//! self-describing.
+//!
+//! Everything is read from local files (`/proc`, `/sys/class/dmi/id`, `/etc/os-release`);
+//! cloud metadata services are not queried, as they may be firewalled and stall every run.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+static HOST: OnceLock<HostInfo> = OnceLock::new();
+
+/// Where a report was produced
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HostInfo {
+    pub hostname: Option<String>,
+    /// Distribution, e.g. `Ubuntu 22.04.4 LTS`, or the OS family when unknown
+    pub os: String,
+    pub kernel: Option<String>,
+    pub stepstone_version: String,
+    /// Cloud provider detected from the DMI vendor, e.g. `aws`
+    pub cloud_provider: Option<String>,
+    /// Instance type, where the provider exposes it through DMI (AWS Nitro)
+    pub instance_type: Option<String>,
+    /// Container runtime the process runs in, e.g. `kubernetes` or `docker`
+    pub container_runtime: Option<String>,
+    /// `--tag key=value` pairs
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Collect the host's metadata once, with the user's tags, for every report of the process
+pub fn init(tags: &[(String, String)]) {
+    let mut info = detect();
+    info.tags = tags.iter().cloned().collect();
+    let _ = HOST.set(info);
+}
+
+/// The metadata collected by [`init`], if it ran
+pub fn current() -> Option<&'static HostInfo> {
+    HOST.get()
+}
+
+/// Parse a `--tag key=value` argument
+pub fn parse_tag(tag: &str) -> Result<(String, String), String> {
+    match tag.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+        _ => Err(format!("tag '{}' must have the form key=value", tag)),
+    }
+}
+
+impl HostInfo {
+    /// One line for report headers, e.g. `db-1 (Ubuntu 22.04, kernel 6.1.0, aws m5.large, kubernetes)`
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.os.clone()];
+        parts.extend(self.kernel.as_ref().map(|kernel| format!("kernel {}", kernel)));
+        match (&self.cloud_provider, &self.instance_type) {
+            (Some(provider), Some(instance_type)) => parts.push(format!("{} {}", provider, instance_type)),
+            (Some(provider), None) => parts.push(provider.clone()),
+            _ => {}
+        }
+        parts.extend(self.container_runtime.clone());
+        format!("{} ({})", self.hostname.as_deref().unwrap_or("unknown"), parts.join(", "))
+    }
+}
+
+fn detect() -> HostInfo {
+    let read = |path: &str| std::fs::read_to_string(path).ok().map(|content| content.trim().to_string()).filter(|content| !content.is_empty());
+    let (cloud_provider, instance_type) = match (read("/sys/class/dmi/id/sys_vendor"), read("/sys/class/dmi/id/product_name")) {
+        (Some(vendor), product) => detect_cloud(&vendor, product.as_deref().unwrap_or("")),
+        (None, _) => (None, None),
+    };
+    HostInfo {
+        hostname: read("/proc/sys/kernel/hostname").or_else(|| std::env::var("HOSTNAME").ok()),
+        os: read("/etc/os-release")
+            .and_then(|content| pretty_name(&content))
+            .unwrap_or_else(|| std::env::consts::OS.to_string()),
+        kernel: read("/proc/sys/kernel/osrelease"),
+        stepstone_version: env!("CARGO_PKG_VERSION").to_string(),
+        cloud_provider,
+        instance_type,
+        container_runtime: detect_container(
+            &read("/proc/1/cgroup").unwrap_or_default(),
+            Path::new("/.dockerenv").exists(),
+            Path::new("/run/.containerenv").exists(),
+            std::env::var_os("KUBERNETES_SERVICE_HOST").is_some(),
+        ),
+        tags: BTreeMap::new(),
+    }
+}
+
+/// `PRETTY_NAME` of an os-release file
+fn pretty_name(os_release: &str) -> Option<String> {
+    os_release
+        .lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|value| value.trim_matches('"').to_string())
+}
+
+/// Provider and instance type from the DMI system vendor and product name
+fn detect_cloud(vendor: &str, product: &str) -> (Option<String>, Option<String>) {
+    let provider = match vendor {
+        "Amazon EC2" => "aws",
+        "Google" => "gcp",
+        "Microsoft Corporation" if product == "Virtual Machine" => "azure",
+        "Alibaba Cloud" => "alibaba",
+        "Tencent Cloud" => "tencent",
+        "DigitalOcean" => "digitalocean",
+        "Hetzner" => "hetzner",
+        _ => return (None, None),
+    };
+    // Nitro instances report their type, e.g. m5.large, as the product name
+    let instance_type = (provider == "aws" && product.contains('.')).then(|| product.to_string());
+    (Some(provider.to_string()), instance_type)
+}
+
+fn detect_container(cgroup: &str, dockerenv: bool, containerenv: bool, kubernetes_env: bool) -> Option<String> {
+    let runtime = if kubernetes_env || cgroup.contains("kubepods") {
+        "kubernetes"
+    } else if containerenv || cgroup.contains("libpod") {
+        "podman"
+    } else if dockerenv || cgroup.contains("/docker") {
+        "docker"
+    } else if cgroup.contains("containerd") {
+        "containerd"
+    } else if cgroup.contains("/lxc") {
+        "lxc"
+    } else {
+        return None;
+    };
+    Some(runtime.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection() {
+        assert_eq!(detect_cloud("Amazon EC2", "m5.large"), (Some("aws".to_string()), Some("m5.large".to_string())));
+        assert_eq!(detect_cloud("Google", "Google Compute Engine"), (Some("gcp".to_string()), None));
+        assert_eq!(detect_cloud("Microsoft Corporation", "Surface Laptop"), (None, None));
+        assert_eq!(detect_cloud("Dell Inc.", "PowerEdge R640"), (None, None));
+
+        assert_eq!(detect_container("0::/kubepods/burstable/pod1/abc", false, false, false).as_deref(), Some("kubernetes"));
+        assert_eq!(detect_container("0::/", true, false, false).as_deref(), Some("docker"));
+        assert_eq!(detect_container("0::/init.scope", false, false, false), None);
+
+        assert_eq!(pretty_name("NAME=\"Ubuntu\"\nPRETTY_NAME=\"Ubuntu 22.04.4 LTS\"\n").as_deref(), Some("Ubuntu 22.04.4 LTS"));
+    }
+
+    #[test]
+    fn test_tags_and_summary() {
+        assert_eq!(parse_tag("rack = r12"), Ok(("rack".to_string(), "r12".to_string())));
+        assert!(parse_tag("=r12").is_err());
+        assert!(parse_tag("rack").is_err());
+
+        let info = HostInfo {
+            hostname: Some("db-1".to_string()),
+            os: "Ubuntu 22.04.4 LTS".to_string(),
+            kernel: Some("6.1.0".to_string()),
+            cloud_provider: Some("aws".to_string()),
+            instance_type: Some("m5.large".to_string()),
+            container_runtime: Some("kubernetes".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(info.summary(), "db-1 (Ubuntu 22.04.4 LTS, kernel 6.1.0, aws m5.large, kubernetes)");
+    }
+}
@@ -0,0 +1,306 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Localization of the human-readable report, selected with `--lang`.
+//!
+//! Catalogs under `locales/` map English report labels, check items, messages and suggestions to
+//! their translation. Only what people read is translated: JSON reports, stored results and
+//! notifications keep the English items, and error codes and check IDs never change, so
+//! automation works the same in every language.
+
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Language of the human-readable report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Zh,
+}
+
+impl std::str::FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" => Ok(Self::En),
+            "zh" | "zh-cn" | "zh_cn" => Ok(Self::Zh),
+            other => Err(format!("unknown language '{}', expected en or zh", other)),
+        }
+    }
+}
+
+/// Translations of one language
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Catalog {
+    /// Report labels by key, see [`label`]
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    /// Check items by English name; a key ending in `*` translates a prefix
+    #[serde(default)]
+    items: BTreeMap<String, String>,
+    /// Messages and suggestions by English text, or by the `format!` string that builds them
+    #[serde(default)]
+    messages: BTreeMap<String, String>,
+    /// The message keys with `{}` placeholders, split into their literal text
+    #[serde(skip)]
+    patterns: Vec<(Vec<String>, String)>,
+}
+
+impl Catalog {
+    fn parse(content: &str) -> Catalog {
+        let mut catalog: Catalog = toml::from_str(content).expect("built-in locale catalogs are valid");
+        catalog.patterns = catalog
+            .messages
+            .iter()
+            .filter_map(|(key, translation)| Some((literals(key)?, translation.clone())))
+            .filter(|(literals, _)| literals.len() > 1)
+            .collect();
+        catalog
+    }
+
+    fn item<'a>(&self, item: &'a str) -> Cow<'a, str> {
+        if let Some(translation) = self.items.get(item) {
+            return Cow::Owned(translation.clone());
+        }
+        // The longest matching prefix, so "Port Conflict *" wins over "Port *"
+        let prefix = self
+            .items
+            .iter()
+            .filter_map(|(key, translation)| Some((key.strip_suffix('*')?, translation)))
+            .filter(|(prefix, _)| item.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len());
+        match prefix {
+            Some((prefix, translation)) => Cow::Owned(format!("{}{}", translation, &item[prefix.len()..])),
+            None => Cow::Borrowed(item),
+        }
+    }
+
+    fn text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if let Some(translation) = self.messages.get(text) {
+            return Cow::Owned(translation.clone());
+        }
+        // The pattern with the most literal text, so "Failed to connect to etcd: {}" wins over
+        // "Failed to connect to {}: {}"
+        let pattern = self
+            .patterns
+            .iter()
+            .filter_map(|(literals, translation)| Some((literals, translation, captures(literals, text)?)))
+            .max_by_key(|(literals, _, _)| literals.iter().map(String::len).sum::<usize>());
+        match pattern {
+            Some((_, translation, captures)) => Cow::Owned(fill(translation, &captures)),
+            None => Cow::Borrowed(text),
+        }
+    }
+}
+
+/// The literal text of a `format!` string between its placeholders, or None if it is malformed
+fn literals(pattern: &str) -> Option<Vec<String>> {
+    let mut literals = vec![String::new()];
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                literals.last_mut()?.push(c);
+            }
+            '{' => {
+                chars.find(|&c| c == '}')?;
+                literals.push(String::new());
+            }
+            c => literals.last_mut()?.push(c),
+        }
+    }
+    Some(literals)
+}
+
+/// What `text` has in place of each placeholder, or None if it was not built from `literals`
+fn captures<'a>(literals: &[String], text: &'a str) -> Option<Vec<&'a str>> {
+    let (first, rest) = literals.split_first()?;
+    let mut remaining = text.strip_prefix(first.as_str())?;
+    let mut captures = Vec::with_capacity(rest.len());
+    for (index, literal) in rest.iter().enumerate() {
+        let end = if index + 1 == rest.len() {
+            remaining.strip_suffix(literal.as_str())?.len()
+        } else if literal.is_empty() {
+            // Adjacent placeholders have no boundary to split at
+            return None;
+        } else {
+            remaining.find(literal.as_str())?
+        };
+        if end == 0 {
+            return None;
+        }
+        captures.push(&remaining[..end]);
+        remaining = &remaining[end + literal.len()..];
+    }
+    Some(captures)
+}
+
+/// A translation with its `{}` placeholders filled in order, or `{0}`, `{1}`… by position
+fn fill(translation: &str, captures: &[&str]) -> String {
+    let mut filled = String::with_capacity(translation.len());
+    let mut next = 0;
+    let mut chars = translation.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                filled.push(c);
+            }
+            '{' => {
+                let spec: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let index = match spec.split(':').next().unwrap_or_default().parse() {
+                    Ok(index) => index,
+                    Err(_) => {
+                        next += 1;
+                        next - 1
+                    }
+                };
+                filled.push_str(captures.get(index).copied().unwrap_or_default());
+            }
+            c => filled.push(c),
+        }
+    }
+    filled
+}
+
+/// Translate the human-readable report into `lang` for the rest of the process
+pub fn init(lang: Lang) {
+    let catalog = match lang {
+        Lang::En => Catalog::default(),
+        Lang::Zh => Catalog::parse(include_str!("../locales/zh.toml")),
+    };
+    let _ = CATALOG.set(catalog);
+}
+
+/// A report label by key, e.g. `suggestion`, or `english` without a translation
+pub fn label(key: &str, english: &'static str) -> Cow<'static, str> {
+    match CATALOG.get().and_then(|catalog| catalog.labels.get(key)) {
+        Some(translation) => Cow::Owned(translation.clone()),
+        None => Cow::Borrowed(english),
+    }
+}
+
+/// The name of a check item
+pub fn item(item: &str) -> Cow<'_, str> {
+    match CATALOG.get() {
+        Some(catalog) => catalog.item(item),
+        None => Cow::Borrowed(item),
+    }
+}
+
+/// A message or suggestion
+pub fn text(text: &str) -> Cow<'_, str> {
+    match CATALOG.get() {
+        Some(catalog) => catalog.text(text),
+        None => Cow::Borrowed(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zh_catalog() {
+        let catalog = Catalog::parse(include_str!("../locales/zh.toml"));
+        assert_eq!(catalog.item("S3 PUT Operation"), "S3 PUT 操作");
+        assert_eq!(catalog.item("Metasrv Connectivity 2"), "Metasrv 连通性 2");
+        assert_eq!(catalog.item("Port Conflict (http)"), "端口冲突 (http)");
+        assert_eq!(catalog.item("Port (http)"), "端口 (http)");
+        assert_eq!(catalog.item("Etcd Member Count"), "Etcd 成员数量");
+        assert_eq!(catalog.item("VPC Endpoint"), "VPC Endpoint");
+        assert_eq!(catalog.messages["Renew the certificate"], "请续期证书");
+
+        // Every item the check registry documents has a translation
+        for check in crate::checks::CHECKS {
+            for pattern in check.items {
+                let english = pattern.trim_end_matches('*');
+                assert_ne!(catalog.item(english), english, "{} is not translated", pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zh_messages() {
+        let catalog = Catalog::parse(include_str!("../locales/zh.toml"));
+        assert_eq!(catalog.text("Some checks failed"), "部分检查失败");
+        assert_eq!(
+            catalog.text("Failed to connect to etcd: connection refused"),
+            "连接 etcd 失败：connection refused"
+        );
+        assert_eq!(
+            catalog.text("Successfully connected to metasrv at 10.0.0.1:3002"),
+            "已成功连接 metasrv 10.0.0.1:3002"
+        );
+        assert_eq!(catalog.text("Something new"), "Something new");
+    }
+
+    #[test]
+    fn test_format_patterns() {
+        let literals = literals("Cannot bind {}:{}: {:?} {{x}}").unwrap();
+        assert_eq!(literals, ["Cannot bind ", ":", ": ", " {x}"]);
+        assert_eq!(
+            captures(&literals, "Cannot bind ::1:4000: denied {x}").unwrap(),
+            ["::1", "4000", "denied"]
+        );
+        assert!(captures(&literals, "Cannot bind ::1:4000: denied").is_none());
+        assert!(literals("Unterminated {").is_none());
+
+        assert_eq!(fill("无法绑定 {}:{}：{}", &["h", "1", "e"]), "无法绑定 h:1：e");
+        assert_eq!(fill("{1} 中的 {0} {{}}", &["a", "b"]), "b 中的 a {}");
+    }
+
+    // A catalog entry whose English text is no longer in the source is never shown
+    #[test]
+    fn test_catalog_keys_in_source() {
+        fn read_sources(dir: &std::path::Path, sources: &mut String) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    read_sources(&path, sources);
+                } else if path.extension().is_some_and(|extension| extension == "rs") {
+                    sources.push_str(&std::fs::read_to_string(&path).unwrap());
+                }
+            }
+        }
+        let mut sources = String::new();
+        read_sources(&std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut sources);
+
+        let catalog = Catalog::parse(include_str!("../locales/zh.toml"));
+        for key in catalog.labels.keys() {
+            assert!(sources.contains(&format!("\"{}\"", key)), "label {} is not used", key);
+        }
+        for key in catalog.items.keys() {
+            let english = key.trim_end_matches('*');
+            assert!(sources.contains(english), "item {} is not in the source", key);
+        }
+        for key in catalog.messages.keys() {
+            let english = key.replace('"', "\\\"");
+            assert!(sources.contains(&english), "message {} is not in the source", key);
+        }
+    }
+
+    #[test]
+    fn test_parse_lang() {
+        assert_eq!("zh".parse::<Lang>().unwrap(), Lang::Zh);
+        assert_eq!("EN".parse::<Lang>().unwrap(), Lang::En);
+        assert!("fr".parse::<Lang>().is_err());
+    }
+}
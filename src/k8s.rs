@@ -0,0 +1,241 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extraction of component TOML embedded in Kubernetes manifests.
+//!
+//! Three layouts are recognised: Helm values of the `greptimedb-cluster` chart
+//! (`meta.configData`, `frontend.configData`, `datanode.configData`), `GreptimeDBCluster`
+//! resources of the operator (`spec.meta.config`, ...), and ConfigMaps holding a `*.toml` key.
+
+use crate::config::ConfigParser;
+use serde::Deserialize;
+use snafu::ResultExt;
+
+/// Chart and operator section name for each component
+const COMPONENT_SECTIONS: &[(&str, &str)] = &[("meta", "metasrv"), ("frontend", "frontend"), ("datanode", "datanode")];
+
+/// A component config found inside a manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedConfig {
+    /// Component the config belongs to: metasrv, frontend or datanode
+    pub component: &'static str,
+    /// Where the config was found, e.g. `meta.configData`
+    pub source: String,
+    /// The embedded TOML
+    pub content: String,
+}
+
+/// Extract every embedded component config from a Helm values file or a multi-document
+/// manifest of ConfigMaps and `GreptimeDBCluster` resources
+pub fn embedded_configs(content: &str) -> crate::error::Result<Vec<EmbeddedConfig>> {
+    let mut configs = Vec::new();
+
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = serde_yaml::Value::deserialize(document).context(crate::error::YamlParsingSnafu {
+            message: "Failed to parse Kubernetes YAML".to_string(),
+        })?;
+
+        match value.get("kind").and_then(|kind| kind.as_str()) {
+            Some("ConfigMap") => configs.extend(configmap_configs(&value)),
+            Some("GreptimeDBCluster") => {
+                if let Some(spec) = value.get("spec") {
+                    configs.extend(section_configs(spec, "config", "spec."));
+                }
+            }
+            Some(_) => {}
+            None => {
+                let mut values = section_configs(&value, "configData", "");
+                fill_chart_defaults(&value, &mut values);
+                configs.extend(values);
+            }
+        }
+    }
+
+    Ok(configs)
+}
+
+/// `<section>.<key>` strings of the chart or operator layout
+fn section_configs(root: &serde_yaml::Value, key: &str, prefix: &str) -> Vec<EmbeddedConfig> {
+    COMPONENT_SECTIONS
+        .iter()
+        .filter_map(|(section, component)| {
+            let content = root.get(*section)?.get(key)?.as_str()?;
+            Some(EmbeddedConfig {
+                component: *component,
+                source: format!("{}{}.{}", prefix, section, key),
+                content: content.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `*.toml` entries of a ConfigMap, attributed by a chart section in the ConfigMap name, e.g.
+/// `mycluster-meta`, or failing that by content
+fn configmap_configs(configmap: &serde_yaml::Value) -> Vec<EmbeddedConfig> {
+    let name = configmap
+        .get("metadata")
+        .and_then(|metadata| metadata.get("name"))
+        .and_then(|name| name.as_str())
+        .unwrap_or("configmap");
+    let Some(data) = configmap.get("data").and_then(|data| data.as_mapping()) else {
+        return Vec::new();
+    };
+
+    data.iter()
+        .filter_map(|(key, value)| {
+            let key = key.as_str()?;
+            let content = value.as_str()?;
+            if !key.ends_with(".toml") {
+                return None;
+            }
+            let component = COMPONENT_SECTIONS
+                .iter()
+                .find(|(section, _)| name.split(['-', '.']).any(|part| part == *section))
+                .map(|(_, component)| *component)
+                .or_else(|| sniff_component(content))?;
+            Some(EmbeddedConfig {
+                component,
+                source: format!("ConfigMap/{}[{}]", name, key),
+                content: content.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Attribute a config by its content when the ConfigMap name does not tell
+fn sniff_component(content: &str) -> Option<&'static str> {
+    let value: toml::Value = toml::from_str(content).ok()?;
+    ConfigParser::detect_component(&value)
+}
+
+/// The chart passes the etcd endpoints to metasrv on the command line rather than in
+/// `configData`; carry them over so the metasrv check has a backend to test
+fn fill_chart_defaults(values: &serde_yaml::Value, configs: &mut [EmbeddedConfig]) {
+    let endpoints = values
+        .get("meta")
+        .and_then(|meta| {
+            meta.get("backendStorage")
+                .and_then(|storage| storage.get("etcd"))
+                .and_then(|etcd| etcd.get("endpoints"))
+                .or_else(|| meta.get("etcdEndpoints"))
+        })
+        .and_then(|endpoints| endpoints.as_str());
+    let Some(endpoints) = endpoints else {
+        return;
+    };
+
+    for config in configs.iter_mut().filter(|config| config.component == "metasrv") {
+        let Ok(mut value) = toml::from_str::<toml::Table>(&config.content) else {
+            continue;
+        };
+        if value.contains_key("store_addrs") {
+            continue;
+        }
+        let addrs = endpoints
+            .split(',')
+            .map(|addr| toml::Value::String(addr.trim().to_string()))
+            .collect();
+        value.insert("store_addrs".to_string(), toml::Value::Array(addrs));
+        value
+            .entry("backend")
+            .or_insert_with(|| toml::Value::String("etcd_store".to_string()));
+        config.content = toml::to_string(&value).unwrap_or_else(|_| config.content.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helm_values() {
+        let values = r#"
+meta:
+  backendStorage:
+    etcd:
+      endpoints: "etcd-0.etcd:2379,etcd-1.etcd:2379"
+  configData: |
+    selector = "round_robin"
+datanode:
+  configData: |
+    [storage]
+    type = "S3"
+    bucket = "greptimedb"
+frontend:
+  replicas: 2
+"#;
+        let configs = embedded_configs(values).unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].component, "metasrv");
+        assert_eq!(configs[0].source, "meta.configData");
+        let meta: toml::Value = toml::from_str(&configs[0].content).unwrap();
+        assert_eq!(meta["store_addrs"].as_array().unwrap().len(), 2);
+        assert_eq!(meta["backend"].as_str(), Some("etcd_store"));
+        assert_eq!(configs[1].component, "datanode");
+    }
+
+    #[test]
+    fn test_manifests() {
+        let manifests = r#"
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: mycluster-frontend
+data:
+  config.toml: |
+    [meta_client]
+    metasrv_addrs = ["mycluster-meta:3002"]
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings
+data:
+  node.toml: |
+    node_id = 1
+  README: not a config
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: cache-metadata
+data:
+  config.toml: |
+    node_id = 2
+---
+apiVersion: greptime.io/v1alpha1
+kind: GreptimeDBCluster
+metadata:
+  name: mycluster
+spec:
+  meta:
+    config: |
+      store_addrs = ["etcd:2379"]
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: ignored
+"#;
+        let configs = embedded_configs(manifests).unwrap();
+        let found = configs.iter().map(|c| (c.component, c.source.as_str())).collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("frontend", "ConfigMap/mycluster-frontend[config.toml]"),
+                ("datanode", "ConfigMap/settings[node.toml]"),
+                ("datanode", "ConfigMap/cache-metadata[config.toml]"),
+                ("metasrv", "spec.meta.config"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `check --k8s-init`: run as a Kubernetes init container that holds the GreptimeDB container
+//! back until its dependencies verify.
+//!
+//! Dependencies such as etcd or object storage gateways are often still starting when the pod
+//! is scheduled, so failing runs are retried with backoff until a deadline instead of failing
+//! on the first attempt. The outcome is written to the termination log, where
+//! `kubectl describe pod` shows it, and mapped to an exit code.
+
+use crate::common::{CheckDetail, CheckResult, CheckStatus};
+use crate::error;
+use crate::redact;
+use snafu::ResultExt;
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Where Kubernetes reads a terminated container's message from
+pub const TERMINATION_LOG: &str = "/dev/termination-log";
+
+/// Directory a Secret volume is expected at; each file is named after the config key it sets
+pub const DEFAULT_SECRETS_DIR: &str = "/etc/stepstone/secrets";
+
+/// Kubernetes keeps at most this much of the termination message
+const MAX_TERMINATION_MESSAGE: usize = 4096;
+
+/// Limit of a single run, shorter than the checks' own timeouts add up to, so a dependency
+/// that hangs costs one attempt rather than the whole deadline
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(15);
+
+/// Every check passed; the GreptimeDB container may start
+pub const EXIT_READY: i32 = 0;
+/// Checks still failed at the deadline; Kubernetes restarts the init container
+pub const EXIT_NOT_READY: i32 = 1;
+/// The config cannot be read, which no retry fixes
+pub const EXIT_CONFIG_ERROR: i32 = 2;
+
+/// `key.path=value` overrides from a mounted Secret volume, where each file is named after the
+/// key it sets, e.g. `storage.secret_access_key`. A missing directory yields no overrides.
+pub fn secret_overrides(dir: &Path) -> error::Result<Vec<String>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(dir).context(error::FileSystemSnafu {
+        message: format!("Failed to read secrets directory: {}", dir.display()),
+    })?;
+
+    let mut overrides = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let Some(key) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+            continue;
+        };
+        // Secret volumes hold `..data` and timestamped directories next to the key files
+        if key.starts_with('.') || !path.is_file() {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path).context(error::FileSystemSnafu {
+            message: format!("Failed to read secret file: {}", path.display()),
+        })?;
+        let secret = content.trim_end_matches(['\r', '\n']);
+        redact::register_secret(secret);
+        overrides.push(format!("{}={}", key, toml::Value::String(secret.to_string())));
+    }
+    overrides.sort();
+    Ok(overrides)
+}
+
+/// Run `attempt` until it passes or `deadline` has passed, returning the last result and the
+/// number of attempts made
+pub async fn run_until_ready<F, Fut>(deadline: Duration, mut attempt: F) -> (CheckResult, u32)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = CheckResult>,
+{
+    let start = Instant::now();
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        // No attempt runs past the deadline, however long the checks would take
+        let limit = ATTEMPT_TIMEOUT.min(deadline.saturating_sub(start.elapsed()));
+        let result = tokio::time::timeout(limit, attempt()).await.unwrap_or_else(|_| {
+            CheckResult::from_details(vec![CheckDetail::fail(
+                "Attempt Timeout".to_string(),
+                format!("Checks did not finish within {:.1}s", limit.as_secs_f64()),
+                Some(limit),
+                Some("A dependency accepts connections but does not answer; check its logs".to_string()),
+            )])
+        });
+        if result.success || start.elapsed() + backoff > deadline {
+            return (result, attempts);
+        }
+        eprintln!(
+            "Attempt {}: {}; retrying in {}s",
+            attempts,
+            redact::redact(&result.message),
+            backoff.as_secs()
+        );
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// A compact summary of a run naming every failed check, cut to the size Kubernetes keeps
+pub fn termination_message(component: &str, result: &CheckResult, attempts: u32) -> String {
+    let mut message = format!(
+        "{} {} after {} attempt(s): {}",
+        component,
+        if result.success { "PASS" } else { "FAIL" },
+        attempts,
+        result.message
+    );
+    for detail in result.details.iter().filter(|detail| detail.status == CheckStatus::Fail) {
+        message.push_str(&format!("\n{}: {}", detail.item, detail.message));
+    }
+    let mut message = redact::redact(&message);
+    if message.len() > MAX_TERMINATION_MESSAGE {
+        let mut end = MAX_TERMINATION_MESSAGE - 3;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+        message.push_str("...");
+    }
+    message
+}
+
+/// Write the termination message when running in a pod; elsewhere the file does not exist and
+/// nothing is written
+pub fn write_termination_log(path: &Path, message: &str) {
+    if !path.exists() {
+        return;
+    }
+    if let Err(e) = std::fs::write(path, message) {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(secret_overrides(&dir.path().join("missing")).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("storage.secret_access_key"), "wJalr\"XUtnFEMI\n").unwrap();
+        std::fs::write(dir.path().join("backend_password"), "pa55word").unwrap();
+        std::fs::create_dir(dir.path().join("..data")).unwrap();
+        let overrides = secret_overrides(dir.path()).unwrap();
+        assert_eq!(overrides.len(), 2);
+
+        let mut value = toml::Value::Table(toml::Table::new());
+        for assignment in &overrides {
+            crate::config::apply_override(&mut value, assignment).unwrap();
+        }
+        assert_eq!(value["storage"]["secret_access_key"].as_str(), Some("wJalr\"XUtnFEMI"));
+        assert_eq!(value["backend_password"].as_str(), Some("pa55word"));
+    }
+
+    #[tokio::test]
+    async fn test_run_until_ready() {
+        let (result, attempts) = run_until_ready(Duration::from_secs(60), || async {
+            CheckResult::from_details(vec![CheckDetail::pass("Etcd".to_string(), "ok".to_string(), None)])
+        })
+        .await;
+        assert!(result.success);
+        assert_eq!(attempts, 1);
+
+        // A deadline shorter than the first backoff gives up after one attempt
+        let (result, attempts) = run_until_ready(Duration::ZERO, || async {
+            CheckResult::from_details(vec![CheckDetail::fail("Etcd".to_string(), "refused".to_string(), None, None)])
+        })
+        .await;
+        assert!(!result.success);
+        assert_eq!(attempts, 1);
+
+        // A hanging attempt is cut off at the deadline rather than after ATTEMPT_TIMEOUT
+        let start = Instant::now();
+        let (result, _) = run_until_ready(Duration::from_millis(200), || std::future::pending::<CheckResult>()).await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(result.details[0].item, "Attempt Timeout");
+    }
+
+    #[test]
+    fn test_termination_message() {
+        let result = CheckResult::from_details(vec![
+            CheckDetail::pass("Etcd".to_string(), "ok".to_string(), None),
+            CheckDetail::fail("S3 Write".to_string(), "x".repeat(5000), None, None),
+        ]);
+        let message = termination_message("Datanode", &result, 4);
+        assert!(message.starts_with("Datanode FAIL after 4 attempt(s)"));
+        assert!(message.contains("\nS3 Write: xxx"));
+        assert!(!message.contains("Etcd"));
+        assert_eq!(message.len(), MAX_TERMINATION_MESSAGE);
+    }
+}
@@ -0,0 +1,998 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Config lint: offline validation of relationships between config fields.
+//!
+//! Lints never touch the network or disk. Only violations are reported, each as its own
+//! [`CheckDetail`], so a clean config adds nothing to a checker's report.
+
+use crate::common::CheckDetail;
+use crate::config::{DatanodeConfig, FrontendConfig, HeartbeatConfig, LoggingConfig, MetaClientConfig, MetasrvConfig, WalConfig};
+use crate::credentials;
+use common_meta::distributed_time_constants::{HEARTBEAT_INTERVAL_MILLIS, REGION_LEASE_SECS};
+use itertools::Itertools;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Keys GreptimeDB accepts that stepstone's config structs store under another name
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("bind_addr", "addr"),
+    ("cert_path", "cert"),
+    ("key_path", "key"),
+    ("ca_cert_path", "ca"),
+    ("prom_store", "prometheus"),
+];
+
+/// Heartbeat interval of a datanode whose `heartbeat.interval` is unset, GreptimeDB's own constant
+pub(crate) const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_millis(HEARTBEAT_INTERVAL_MILLIS);
+
+/// Frontends heartbeat this many times less often than datanodes by default, every 18s
+pub(crate) const FRONTEND_HEARTBEAT_FACTOR: u32 = 6;
+
+/// Lint a whole config file for a component: schema, unknown keys and cross-field rules.
+///
+/// This is what `stepstone lint` runs; it only looks at the already-parsed file.
+pub fn lint_config(raw: &toml::Value, component: &str) -> crate::error::Result<Vec<CheckDetail>> {
+    match component {
+        "metasrv" => Ok(lint_typed(raw, lint_metasrv)),
+        "frontend" => Ok(lint_typed(raw, lint_frontend)),
+        "datanode" => Ok(lint_typed(raw, lint_datanode)),
+        other => crate::error::InvalidConfigSnafu {
+            message: format!("Unknown component '{}', expected metasrv, frontend or datanode", other),
+        }
+        .fail(),
+    }
+}
+
+fn lint_typed<T>(raw: &toml::Value, lint: fn(&T) -> Vec<CheckDetail>) -> Vec<CheckDetail>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let config: T = match raw.clone().try_into() {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![CheckDetail::fail(
+                "Config Schema".to_string(),
+                format!("Config does not match the expected schema: {}", e.to_string().trim()),
+                None,
+                Some("Fix the field type or section named in the error".to_string()),
+            )];
+        }
+    };
+
+    let mut details = lint_unknown_keys(raw, &config);
+    details.extend(lint(&config));
+    details
+}
+
+/// Report keys in the raw file that the parsed config dropped, usually typos that GreptimeDB
+/// would silently ignore
+pub fn lint_unknown_keys<T: Serialize>(raw: &toml::Value, config: &T) -> Vec<CheckDetail> {
+    let mut details = Vec::new();
+    if let Ok(known) = toml::Value::try_from(config) {
+        walk_unknown_keys("", raw, &known, &mut details);
+    }
+    details
+}
+
+fn walk_unknown_keys(path: &str, raw: &toml::Value, known: &toml::Value, details: &mut Vec<CheckDetail>) {
+    match (raw, known) {
+        (toml::Value::Table(raw), toml::Value::Table(known)) => {
+            for (key, value) in raw {
+                let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                let canonical = KEY_ALIASES
+                    .iter()
+                    .find(|(alias, _)| alias == key)
+                    .map_or(key.as_str(), |(_, name)| name);
+                match known.get(key).or_else(|| known.get(canonical)) {
+                    Some(known) => walk_unknown_keys(&field, value, known, details),
+                    // Read by stepstone in place of the credential, which the structs only hold resolved
+                    None if credentials::is_secret_ref(key) => {}
+                    None => details.push(CheckDetail::warning(
+                        format!("Unknown Key ({})", field),
+                        format!("{} is not a known GreptimeDB option and will be ignored", field),
+                        None,
+                        Some("Check the key for typos and that it is in the right section".to_string()),
+                    )),
+                }
+            }
+        }
+        (toml::Value::Array(raw), toml::Value::Array(known)) => {
+            for (index, (raw, known)) in raw.iter().zip(known).enumerate() {
+                walk_unknown_keys(&format!("{}[{}]", path, index), raw, known, details);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Lint a metasrv config
+pub fn lint_metasrv(config: &MetasrvConfig) -> Vec<CheckDetail> {
+    let mut details = lint_units(config);
+    if let Some(logging) = &config.logging {
+        lint_logging(logging, &mut details);
+    }
+
+    if config.backend != "memory_store" && config.use_memory_store != Some(true) && config.store_addrs.is_empty() {
+        details.push(CheckDetail::fail(
+            "Backend Addresses".to_string(),
+            format!("backend = \"{}\" but store_addrs is empty", config.backend),
+            None,
+            Some("Add the backend endpoints to store_addrs".to_string()),
+        ));
+    }
+
+    if let Some(detail) = config.selector.as_deref().and_then(lint_selector) {
+        details.push(detail);
+    }
+
+    let pause = config
+        .failure_detector
+        .as_ref()
+        .and_then(|detector| duration_of(detector.acceptable_heartbeat_pause.as_deref()));
+    if let Some(pause) = pause.filter(|pause| *pause <= DEFAULT_HEARTBEAT_INTERVAL) {
+        details.push(CheckDetail::warning(
+            "Failure Detector Heartbeat Pause".to_string(),
+            format!(
+                "failure_detector.acceptable_heartbeat_pause ({:?}) is not longer than the default datanode heartbeat interval ({:?})",
+                pause, DEFAULT_HEARTBEAT_INTERVAL
+            ),
+            None,
+            Some("Every regular heartbeat gap would count towards a failure; keep the pause several heartbeat intervals long, e.g. the default 10s".to_string()),
+        ));
+    }
+
+    if config.enable_region_failover == Some(true) {
+        let provider = config.wal.as_ref().and_then(|wal| wal.provider.as_deref()).unwrap_or("raft_engine");
+        if provider != "kafka" && config.allow_region_failover_on_local_wal == Some(true) {
+            details.push(CheckDetail::info(
+                "Region Failover Prerequisites".to_string(),
+                format!("allow_region_failover_on_local_wal = true permits region failover on the \"{}\" WAL; writes not yet flushed are lost when a region fails over", provider),
+                None,
+            ));
+        } else if provider != "kafka" {
+            details.push(CheckDetail::fail(
+                "Region Failover Prerequisites".to_string(),
+                format!("enable_region_failover = true requires a remote WAL, but wal.provider is \"{}\"", provider),
+                None,
+                Some("Set wal.provider = \"kafka\" on metasrv and every datanode, set allow_region_failover_on_local_wal = true to accept losing unflushed writes, or disable region failover".to_string()),
+            ));
+        }
+    }
+
+    details
+}
+
+/// Lint a frontend config
+pub fn lint_frontend(config: &FrontendConfig) -> Vec<CheckDetail> {
+    let mut details = lint_units(config);
+    lint_meta_client(config.meta_client.as_ref(), config.heartbeat.as_ref(), &mut details);
+    if let Some(logging) = &config.logging {
+        lint_logging(logging, &mut details);
+    }
+    if let Some(timezone) = &config.default_timezone {
+        details.extend(lint_timezone(timezone));
+    }
+    details
+}
+
+/// Lint a datanode config
+pub fn lint_datanode(config: &DatanodeConfig) -> Vec<CheckDetail> {
+    let mut details = lint_units(config);
+    lint_meta_client(config.meta_client.as_ref(), config.heartbeat.as_ref(), &mut details);
+    lint_region_lease(config.heartbeat.as_ref(), &mut details);
+    if let Some(logging) = &config.logging {
+        lint_logging(logging, &mut details);
+    }
+    if let Some(wal) = &config.wal {
+        lint_wal(wal, &mut details);
+    }
+
+    if let Some(storage) = &config.storage {
+        if let Some(class) = &storage.storage_class {
+            details.extend(lint_storage_class(class, storage.bucket.as_deref().unwrap_or("")));
+        }
+    }
+
+    let storage_type = config.storage.as_ref().and_then(|storage| storage.storage_type.as_deref());
+    let provider = config.wal.as_ref().and_then(|wal| wal.provider.as_deref());
+    if matches!(storage_type, None | Some("File")) && provider == Some("kafka") {
+        details.push(CheckDetail::warning(
+            "Storage and WAL Placement".to_string(),
+            "WAL is remote (kafka) but region data is on local disk; a failed-over region cannot read its data on another datanode".to_string(),
+            None,
+            Some("Use object storage (S3, Oss, Azblob, Gcs) together with a remote WAL".to_string()),
+        ));
+    }
+
+    details
+}
+
+/// Timeouts between a node and metasrv must leave room for heartbeats.
+///
+/// `meta_client.heartbeat_timeout` bounds a single heartbeat RPC, so it has to be shorter
+/// than `heartbeat.interval` or heartbeats overlap.
+fn lint_meta_client(
+    meta_client: Option<&MetaClientConfig>,
+    heartbeat: Option<&HeartbeatConfig>,
+    details: &mut Vec<CheckDetail>,
+) {
+    let Some(meta_client) = meta_client else {
+        return;
+    };
+
+    let interval = duration_of(heartbeat.and_then(|h| h.interval.as_deref()));
+    if let (Some(interval), Some(timeout)) = (interval, duration_of(meta_client.heartbeat_timeout.as_deref())) {
+        if timeout >= interval {
+            details.push(CheckDetail::fail(
+                "Heartbeat Interval vs Timeout".to_string(),
+                format!(
+                    "meta_client.heartbeat_timeout ({:?}) must be shorter than heartbeat.interval ({:?})",
+                    timeout, interval
+                ),
+                None,
+                Some("A slow heartbeat would still be in flight when the next one is due; lower heartbeat_timeout or raise the interval".to_string()),
+            ));
+        }
+    }
+
+    if let (Some(interval), Some(retry)) = (interval, duration_of(heartbeat.and_then(|h| h.retry_interval.as_deref()))) {
+        if retry > interval {
+            details.push(CheckDetail::warning(
+                "Heartbeat Retry vs Interval".to_string(),
+                format!(
+                    "heartbeat.retry_interval ({:?}) is longer than heartbeat.interval ({:?})",
+                    retry, interval
+                ),
+                None,
+                Some("A failed heartbeat is retried later than the next regular one would be sent; lower retry_interval".to_string()),
+            ));
+        }
+    }
+
+    if let (Some(connect), Some(timeout)) = (
+        duration_of(meta_client.connect_timeout.as_deref()),
+        duration_of(meta_client.timeout.as_deref()),
+    ) {
+        if connect > timeout {
+            details.push(CheckDetail::warning(
+                "Connect Timeout vs Timeout".to_string(),
+                format!(
+                    "meta_client.connect_timeout ({:?}) exceeds meta_client.timeout ({:?})",
+                    connect, timeout
+                ),
+                None,
+                Some("Requests time out before a slow connection can be established; keep connect_timeout below timeout".to_string()),
+            ));
+        }
+    }
+}
+
+/// `logging.level` is a tracing filter: comma-separated directives, each a level or
+/// `target=level`, where the level must be one tracing knows
+fn lint_logging(logging: &LoggingConfig, details: &mut Vec<CheckDetail>) {
+    const LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error", "off"];
+
+    if let Some(level) = &logging.level {
+        let invalid = level
+            .split(',')
+            .map(str::trim)
+            .filter(|directive| !directive.is_empty())
+            .map(|directive| directive.rsplit_once('=').map_or(directive, |(_, level)| level))
+            .filter(|level| !LEVELS.contains(&level.to_ascii_lowercase().as_str()))
+            .collect::<Vec<_>>();
+        if !invalid.is_empty() {
+            details.push(CheckDetail::fail(
+                "Log Level".to_string(),
+                format!("logging.level \"{}\" has unknown levels: {}", level, invalid.join(", ")),
+                None,
+                Some(format!("Use one of {}, optionally per target as \"info,greptime=debug\"", LEVELS.join(", "))),
+            ));
+        }
+    }
+
+    if let Some(format) = logging.log_format.as_deref().filter(|format| !matches!(*format, "text" | "json")) {
+        details.push(CheckDetail::fail(
+            "Log Format".to_string(),
+            format!("logging.log_format \"{}\" is not supported", format),
+            None,
+            Some("Use \"text\" or \"json\"".to_string()),
+        ));
+    }
+
+    if let Some(sample_ratio) = &logging.tracing_sample_ratio {
+        let ratios = std::iter::once(("default_ratio".to_string(), sample_ratio.default_ratio)).chain(
+            sample_ratio.rules.iter().flatten().enumerate().map(|(index, rule)| (format!("rules[{}].ratio", index), rule.ratio)),
+        );
+        for (field, ratio) in ratios {
+            if let Some(ratio) = ratio.filter(|ratio| !(0.0..=1.0).contains(ratio)) {
+                details.push(CheckDetail::fail(
+                    "Tracing Sample Ratio".to_string(),
+                    format!("logging.tracing_sample_ratio.{} is {}, outside 0.0-1.0", field, ratio),
+                    None,
+                    Some("Use a fraction of traces to keep, e.g. 0.1 for one in ten".to_string()),
+                ));
+            }
+        }
+    }
+}
+
+/// A datanode keeps its regions only while heartbeats renew their lease on metasrv, so a
+/// heartbeat, or a heartbeat and its retry, must arrive within one lease
+fn lint_region_lease(heartbeat: Option<&HeartbeatConfig>, details: &mut Vec<CheckDetail>) {
+    let lease = Duration::from_secs(REGION_LEASE_SECS);
+    let Some(interval) = duration_of(heartbeat.and_then(|h| h.interval.as_deref())) else {
+        return;
+    };
+
+    if interval >= lease {
+        details.push(CheckDetail::fail(
+            "Heartbeat Interval vs Region Lease".to_string(),
+            format!(
+                "heartbeat.interval ({:?}) is not shorter than the region lease ({:?}) granted by metasrv",
+                interval, lease
+            ),
+            None,
+            Some("Regions would lose their lease between heartbeats and become unwritable; keep heartbeat.interval at a third of the lease or less, e.g. the default 3s".to_string()),
+        ));
+        return;
+    }
+
+    let retry = duration_of(heartbeat.and_then(|h| h.retry_interval.as_deref())).unwrap_or(interval);
+    if interval + retry >= lease {
+        details.push(CheckDetail::warning(
+            "Heartbeat Retry vs Region Lease".to_string(),
+            format!(
+                "heartbeat.interval ({:?}) plus heartbeat.retry_interval ({:?}) reaches the region lease ({:?})",
+                interval, retry, lease
+            ),
+            None,
+            Some("A single failed heartbeat would let region leases expire before the retry; shorten the interval or retry_interval".to_string()),
+        ));
+    }
+}
+
+/// Local WAL segment and purge settings must be consistent
+fn lint_wal(wal: &WalConfig, details: &mut Vec<CheckDetail>) {
+    if let (Some(file_size), Some(threshold)) = (
+        size_of(wal.file_size.as_deref()),
+        size_of(wal.purge_threshold.as_deref()),
+    ) {
+        if file_size >= threshold {
+            details.push(CheckDetail::warning(
+                "WAL File Size vs Purge Threshold".to_string(),
+                format!(
+                    "wal.file_size ({} bytes) is not smaller than wal.purge_threshold ({} bytes)",
+                    file_size, threshold
+                ),
+                None,
+                Some("Purging works on whole segment files; keep purge_threshold several times file_size".to_string()),
+            ));
+        }
+    }
+
+    if duration_of(wal.purge_interval.as_deref()) == Some(Duration::ZERO) {
+        details.push(CheckDetail::fail(
+            "WAL Purge Interval".to_string(),
+            "wal.purge_interval is zero".to_string(),
+            None,
+            Some("Set a positive purge_interval such as \"1m\"".to_string()),
+        ));
+    }
+}
+
+/// S3 storage classes GreptimeDB can read from directly
+const READABLE_STORAGE_CLASSES: &[&str] = &["STANDARD", "INTELLIGENT_TIERING", "EXPRESS_ONEZONE", "STANDARD_IA", "ONEZONE_IA", "GLACIER_IR", "REDUCED_REDUNDANCY"];
+
+/// S3 storage classes whose objects must be restored before they can be read
+const ARCHIVE_STORAGE_CLASSES: &[&str] = &["GLACIER", "DEEP_ARCHIVE"];
+
+/// `storage.storage_class` must be a class GreptimeDB can read from, and S3 Express One Zone
+/// only works with directory buckets, named `<name>--<zone>--x-s3`
+fn lint_storage_class(class: &str, bucket: &str) -> Option<CheckDetail> {
+    let upper = class.to_ascii_uppercase();
+    let directory_bucket = bucket.ends_with("--x-s3");
+    if ARCHIVE_STORAGE_CLASSES.contains(&upper.as_str()) {
+        return Some(CheckDetail::fail(
+            "S3 Storage Class".to_string(),
+            format!("storage.storage_class is {}; its objects cannot be read until restored, so every SST read would fail", class),
+            None,
+            Some("Use STANDARD or INTELLIGENT_TIERING; archive classes are only suitable for backups".to_string()),
+        ));
+    }
+    if !READABLE_STORAGE_CLASSES.contains(&upper.as_str()) {
+        return Some(CheckDetail::fail(
+            "S3 Storage Class".to_string(),
+            format!("storage.storage_class '{}' is not an S3 storage class", class),
+            None,
+            Some(format!("Use one of {}", READABLE_STORAGE_CLASSES.join(", "))),
+        ));
+    }
+    if upper == "EXPRESS_ONEZONE" && !directory_bucket {
+        return Some(CheckDetail::fail(
+            "S3 Storage Class".to_string(),
+            format!("EXPRESS_ONEZONE needs a directory bucket, but '{}' is a general purpose bucket name", bucket),
+            None,
+            Some("Create a directory bucket named <name>--<zone-id>--x-s3 in the datanodes' availability zone, or use STANDARD".to_string()),
+        ));
+    }
+    if upper != "EXPRESS_ONEZONE" && directory_bucket {
+        return Some(CheckDetail::fail(
+            "S3 Storage Class".to_string(),
+            format!("'{}' is a directory bucket, which only stores EXPRESS_ONEZONE objects, not {}", bucket, class),
+            None,
+            Some("Set storage_class = \"EXPRESS_ONEZONE\" or remove it".to_string()),
+        ));
+    }
+    if matches!(upper.as_str(), "STANDARD_IA" | "ONEZONE_IA" | "GLACIER_IR") {
+        return Some(CheckDetail::warning(
+            "S3 Storage Class".to_string(),
+            format!("storage.storage_class is {}, which charges per read and for at least 30 days of storage", class),
+            None,
+            Some("Compaction rewrites and deletes SST files within days and queries read them often; STANDARD or INTELLIGENT_TIERING is usually cheaper and faster".to_string()),
+        ));
+    }
+    None
+}
+
+/// `default_timezone` must be an IANA zone name or a `+HH:MM` offset; anything else stops the
+/// frontend at startup
+fn lint_timezone(timezone: &str) -> Option<CheckDetail> {
+    if timezone.parse::<chrono_tz::Tz>().is_ok() || is_utc_offset(timezone) {
+        return None;
+    }
+
+    let suggestions = similar_timezones(timezone);
+    let suggestion = if suggestions.is_empty() {
+        "Use an IANA zone name such as \"Asia/Shanghai\" or \"UTC\", or an offset such as \"+08:00\"".to_string()
+    } else {
+        format!("Did you mean {}?", suggestions.iter().map(|name| format!("\"{}\"", name)).join(" or "))
+    };
+    Some(CheckDetail::fail(
+        "Default Timezone".to_string(),
+        format!("default_timezone \"{}\" is not a known time zone", timezone),
+        None,
+        Some(suggestion),
+    ))
+}
+
+/// `selector` must name one of the datanode selectors metasrv ships with
+fn lint_selector(selector: &str) -> Option<CheckDetail> {
+    const SELECTORS: &[&str] = &["round_robin", "lease_based", "load_based"];
+    if SELECTORS.contains(&selector) {
+        return None;
+    }
+
+    let closest = SELECTORS
+        .iter()
+        .map(|name| (edit_distance(&selector.to_lowercase().replace('-', "_"), name), *name))
+        .min()
+        .filter(|(distance, _)| *distance <= 3);
+    let suggestion = match closest {
+        Some((_, name)) => format!("Did you mean \"{}\"? Valid selectors are {}", name, SELECTORS.join(", ")),
+        None => format!("Valid selectors are {}", SELECTORS.join(", ")),
+    };
+    Some(CheckDetail::fail(
+        "Selector".to_string(),
+        format!("selector \"{}\" is not a known datanode selector", selector),
+        None,
+        Some(suggestion),
+    ))
+}
+
+/// A fixed offset from UTC such as `+08:00` or `-5:30`
+fn is_utc_offset(value: &str) -> bool {
+    let Some(offset) = value.strip_prefix(['+', '-']) else {
+        return false;
+    };
+    let Some((hours, minutes)) = offset.split_once(':') else {
+        return false;
+    };
+    let digits = |part: &str, max_len: usize| !part.is_empty() && part.len() <= max_len && part.bytes().all(|b| b.is_ascii_digit());
+    digits(hours, 2) && minutes.len() == 2 && digits(minutes, 2) && hours.parse::<u32>().is_ok_and(|h| h <= 14) && minutes.parse::<u32>().is_ok_and(|m| m < 60)
+}
+
+/// Up to three zone names closest to a misspelled one, matching the city alone too, so
+/// `"Shanghai"` suggests `"Asia/Shanghai"`
+fn similar_timezones(timezone: &str) -> Vec<&'static str> {
+    let wanted = timezone.to_lowercase();
+    let max_distance = (wanted.len() / 4).max(2);
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter_map(|name| {
+            let lower = name.to_lowercase();
+            let city = lower.rsplit('/').next().unwrap_or(&lower);
+            let distance = edit_distance(&wanted, &lower).min(edit_distance(&wanted, city));
+            (distance <= max_distance).then_some((distance, name))
+        })
+        .sorted()
+        .take(3)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// Levenshtein distance between two strings, by characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn duration_of(value: Option<&str>) -> Option<Duration> {
+    value.and_then(parse_duration)
+}
+
+fn size_of(value: Option<&str>) -> Option<u64> {
+    value.and_then(parse_size)
+}
+
+/// Parse a duration with the humantime grammar GreptimeDB accepts, e.g. `3s`, `1h 30m`
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let number: u64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+
+        let unit_len = rest.find(|c: char| c.is_ascii_digit() || c.is_whitespace()).unwrap_or(rest.len());
+        let nanos_per_unit: u128 = match &rest[..unit_len] {
+            "nanos" | "nsec" | "ns" => 1,
+            "usec" | "us" | "µs" => 1_000,
+            "millis" | "msec" | "ms" => 1_000_000,
+            "seconds" | "second" | "secs" | "sec" | "s" => 1_000_000_000,
+            "minutes" | "minute" | "min" | "mins" | "m" => 60 * 1_000_000_000,
+            "hours" | "hour" | "hr" | "hrs" | "h" => 3_600 * 1_000_000_000,
+            "days" | "day" | "d" => 86_400 * 1_000_000_000,
+            "weeks" | "week" | "w" => 604_800 * 1_000_000_000,
+            "months" | "month" | "M" => 2_630_016 * 1_000_000_000,
+            "years" | "year" | "y" => 31_557_600 * 1_000_000_000,
+            _ => return None,
+        };
+        let nanos = (number as u128).checked_mul(nanos_per_unit)?;
+        total = total.checked_add(Duration::from_nanos(u64::try_from(nanos).ok()?))?;
+        rest = rest[unit_len..].trim_start();
+    }
+
+    Some(total)
+}
+
+/// Parse a size with GreptimeDB's `ReadableSize` grammar: a number followed by an optional,
+/// case-sensitive binary unit, e.g. `1024`, `512MB`, `1G`, `1.5GiB`
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if value.is_empty() || !value.is_ascii() {
+        return None;
+    }
+
+    let number_len = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+')))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(number_len);
+    let multiplier: u64 = match unit.trim() {
+        "B" | "" => 1,
+        "K" | "KB" | "KiB" => 1 << 10,
+        "M" | "MB" | "MiB" => 1 << 20,
+        "G" | "GB" | "GiB" => 1 << 30,
+        "T" | "TB" | "TiB" => 1 << 40,
+        "P" | "PB" | "PiB" => 1 << 50,
+        _ => return None,
+    };
+
+    let number: f64 = number.parse().ok()?;
+    if !number.is_finite() || number < 0.0 {
+        return None;
+    }
+    Some((number * multiplier as f64) as u64)
+}
+
+/// Fields parsed as durations by GreptimeDB, matched by key name anywhere in the config
+const DURATION_FIELDS: &[&str] = &[
+    "timeout",
+    "heartbeat_timeout",
+    "ddl_timeout",
+    "connect_timeout",
+    "interval",
+    "retry_interval",
+    "purge_interval",
+    "metadata_cache_ttl",
+    "metadata_cache_tti",
+    "keep_alive",
+    "write_interval",
+    "threshold",
+    "ttl",
+    "sync_period",
+    "consumer_wait_timeout",
+    "dump_index_interval",
+    "auto_prune_interval",
+    "create_topic_timeout",
+    "pool_idle_timeout",
+    "retry_delay",
+    "min_std_deviation",
+    "acceptable_heartbeat_pause",
+    "first_heartbeat_estimate",
+    "region_failure_detector_initialization_delay",
+    "node_max_idle_time",
+    "auto_flush_interval",
+    "write_cache_ttl",
+    "min_compaction_interval",
+    "staging_ttl",
+    "experimental_manifest_keep_removed_file_ttl",
+];
+
+/// Fields parsed as sizes by GreptimeDB, matched by key name anywhere in the config
+const SIZE_FIELDS: &[&str] = &[
+    "body_limit",
+    "file_size",
+    "purge_threshold",
+    "cache_capacity",
+    "max_recv_message_size",
+    "max_send_message_size",
+    "max_in_flight_write_bytes",
+    "max_batch_bytes",
+    "flush_trigger_size",
+    "checkpoint_trigger_size",
+    "max_metadata_value_size",
+    "global_write_buffer_size",
+    "global_write_buffer_reject_size",
+    "sst_meta_cache_size",
+    "vector_cache_size",
+    "page_cache_size",
+    "selector_result_cache_size",
+    "write_cache_size",
+    "sst_write_buffer_size",
+    "staging_size",
+    "metadata_cache_size",
+    "content_cache_size",
+    "content_cache_page_size",
+    "result_cache_size",
+    "fork_dictionary_bytes",
+];
+
+/// Check that every duration and size field holds a value GreptimeDB will accept at startup,
+/// reporting the full field path of each one that does not
+pub fn lint_units<T: Serialize>(config: &T) -> Vec<CheckDetail> {
+    let mut details = Vec::new();
+    if let Ok(value) = toml::Value::try_from(config) {
+        walk_units("", &value, &mut details);
+    }
+    details
+}
+
+fn walk_units(path: &str, value: &toml::Value, details: &mut Vec<CheckDetail>) {
+    let Some(table) = value.as_table() else {
+        return;
+    };
+
+    for (key, value) in table {
+        let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+        match value {
+            toml::Value::Table(_) => walk_units(&field, value, details),
+            toml::Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    walk_units(&format!("{}[{}]", field, index), item, details);
+                }
+            }
+            toml::Value::String(text) if DURATION_FIELDS.contains(&key.as_str()) => {
+                if parse_duration(text).is_none() {
+                    details.push(CheckDetail::fail(
+                        format!("Duration Format ({})", field),
+                        format!("{} = \"{}\" is not a valid duration", field, text),
+                        None,
+                        Some("Use whole numbers with a unit, e.g. \"30s\", \"500ms\" or \"1h 30m\"".to_string()),
+                    ));
+                }
+            }
+            toml::Value::String(text) if SIZE_FIELDS.contains(&key.as_str()) => {
+                if parse_size(text).is_none() {
+                    details.push(CheckDetail::fail(
+                        format!("Size Format ({})", field),
+                        format!("{} = \"{}\" is not a valid size", field, text),
+                        None,
+                        Some("Use a number with a case-sensitive unit, e.g. \"512MB\", \"1GB\" or \"1GiB\"".to_string()),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use crate::config::{
+        ConfigParser, DatanodeStorageConfig, MitoConfig, RegionEngineConfig, TracingSampleRatio, TracingSampleRule,
+    };
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("3s"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1h 30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("10 secs"), Some(Duration::from_secs(10)));
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_duration("1.5s"), None);
+        assert_eq!(parse_duration("3 sekunden"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn test_lint_logging() {
+        let mut details = Vec::new();
+        let logging = LoggingConfig {
+            level: Some("info,greptime=debug,mito2=verbose".to_string()),
+            log_format: Some("json".to_string()),
+            ..Default::default()
+        };
+        lint_logging(&logging, &mut details);
+        assert_eq!(details.len(), 1);
+        assert!(details[0].message.ends_with("unknown levels: verbose"));
+
+        let mut details = Vec::new();
+        lint_logging(&LoggingConfig { level: Some("WARN".to_string()), ..Default::default() }, &mut details);
+        assert!(details.is_empty());
+
+        let mut details = Vec::new();
+        let logging = LoggingConfig {
+            tracing_sample_ratio: Some(TracingSampleRatio {
+                default_ratio: Some(1.0),
+                rules: Some(vec![TracingSampleRule { ratio: Some(10.0), ..Default::default() }]),
+            }),
+            ..Default::default()
+        };
+        lint_logging(&logging, &mut details);
+        assert_eq!(details.len(), 1);
+        assert!(details[0].message.contains("rules[0].ratio is 10"));
+    }
+
+    #[test]
+    fn test_lint_timezone() {
+        assert!(lint_timezone("Asia/Shanghai").is_none());
+        assert!(lint_timezone("UTC").is_none());
+        assert!(lint_timezone("+08:00").is_none());
+        assert!(lint_timezone("-5:30").is_none());
+
+        let detail = lint_timezone("Asia/Shangai").unwrap();
+        assert_eq!(detail.status, CheckStatus::Fail);
+        assert_eq!(detail.suggestion.as_deref(), Some("Did you mean \"Asia/Shanghai\"?"));
+
+        let detail = lint_timezone("shanghai").unwrap();
+        assert!(detail.suggestion.unwrap().contains("Asia/Shanghai"));
+        assert!(lint_timezone("+8").is_some());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("512MB"), Some(512 << 20));
+        assert_eq!(parse_size("1G"), Some(1 << 30));
+        assert_eq!(parse_size("1.5KB"), Some(1536));
+        assert_eq!(parse_size("4GB"), Some(4 << 30));
+        assert_eq!(parse_size("1Gb"), None);
+        assert_eq!(parse_size("1GiB"), Some(1 << 30));
+        assert_eq!(parse_size("1gb"), None);
+        assert_eq!(parse_size("MB"), None);
+        assert_eq!(parse_size("64 MB"), Some(64 << 20));
+    }
+
+    #[test]
+    fn test_lint_units() {
+        let mut config = ConfigParser::default_datanode_config();
+        assert!(lint_units(&config).is_empty());
+
+        config.meta_client.as_mut().unwrap().timeout = Some("30 sekunden".to_string());
+        config.storage.as_mut().unwrap().cache_capacity = Some("1Gb".to_string());
+        let details = lint_units(&config);
+        let items = details.iter().map(|d| d.item.as_str()).collect::<Vec<_>>();
+        assert_eq!(details.len(), 2);
+        assert!(items.contains(&"Duration Format (meta_client.timeout)"));
+        assert!(items.contains(&"Size Format (storage.cache_capacity)"));
+
+        config.region_engine = Some(vec![RegionEngineConfig {
+            mito: Some(MitoConfig {
+                write_cache_size: Some("5 gigs".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]);
+        let details = lint_units(&config);
+        assert!(details.iter().any(|d| d.item == "Size Format (region_engine[0].mito.write_cache_size)"));
+
+        assert!(lint_units(&ConfigParser::parse_datanode_config("datanode.example.toml", &Default::default()).unwrap()).is_empty());
+        assert!(lint_units(&ConfigParser::parse_frontend_config("frontend.example.toml", &Default::default()).unwrap()).is_empty());
+        assert!(lint_units(&ConfigParser::parse_metasrv_config("metasrv.example.toml", &Default::default()).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_lint_meta_client() {
+        let mut config = ConfigParser::default_datanode_config();
+        assert!(lint_datanode(&config).is_empty());
+
+        config.heartbeat = Some(HeartbeatConfig {
+            interval: Some("3s".to_string()),
+            retry_interval: Some("5s".to_string()),
+        });
+        config.meta_client.as_mut().unwrap().heartbeat_timeout = Some("3s".to_string());
+        config.meta_client.as_mut().unwrap().connect_timeout = Some("10s".to_string());
+        config.meta_client.as_mut().unwrap().timeout = Some("3s".to_string());
+
+        let details = lint_datanode(&config);
+        let items = details.iter().map(|d| d.item.as_str()).collect::<Vec<_>>();
+        assert!(items.contains(&"Heartbeat Interval vs Timeout"));
+        assert!(items.contains(&"Heartbeat Retry vs Interval"));
+        assert!(items.contains(&"Connect Timeout vs Timeout"));
+    }
+
+    #[test]
+    fn test_lint_region_lease() {
+        let mut details = Vec::new();
+        let heartbeat = HeartbeatConfig {
+            interval: Some(format!("{}s", REGION_LEASE_SECS)),
+            retry_interval: None,
+        };
+        lint_region_lease(Some(&heartbeat), &mut details);
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].status, CheckStatus::Fail);
+
+        let mut details = Vec::new();
+        let heartbeat = HeartbeatConfig {
+            interval: Some(format!("{}s", REGION_LEASE_SECS - 1)),
+            retry_interval: Some("1s".to_string()),
+        };
+        lint_region_lease(Some(&heartbeat), &mut details);
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].item, "Heartbeat Retry vs Region Lease");
+    }
+
+    #[test]
+    fn test_lint_wal_and_storage() {
+        let mut config = ConfigParser::default_datanode_config();
+        config.wal = Some(WalConfig {
+            provider: Some("kafka".to_string()),
+            dir: None,
+            file_size: Some("1GB".to_string()),
+            purge_threshold: Some("512MB".to_string()),
+            purge_interval: Some("0s".to_string()),
+            ..Default::default()
+        });
+        config.storage = Some(DatanodeStorageConfig {
+            storage_type: Some("File".to_string()),
+            ..Default::default()
+        });
+
+        let details = lint_datanode(&config);
+        let items = details.iter().map(|d| d.item.as_str()).collect::<Vec<_>>();
+        assert!(items.contains(&"WAL File Size vs Purge Threshold"));
+        assert!(items.contains(&"WAL Purge Interval"));
+        assert!(items.contains(&"Storage and WAL Placement"));
+    }
+
+    #[test]
+    fn test_lint_storage_class() {
+        assert!(lint_storage_class("STANDARD", "greptime-data").is_none());
+        assert!(lint_storage_class("intelligent_tiering", "greptime-data").is_none());
+        assert!(lint_storage_class("EXPRESS_ONEZONE", "greptime--use1-az4--x-s3").is_none());
+
+        let fail = |class, bucket| lint_storage_class(class, bucket).unwrap().status == CheckStatus::Fail;
+        assert!(fail("GLACIER", "greptime-data"));
+        assert!(fail("DEEP_ARCHIVE", "greptime-data"));
+        assert!(fail("STANDART", "greptime-data"));
+        assert!(fail("EXPRESS_ONEZONE", "greptime-data"));
+        assert!(fail("STANDARD", "greptime--use1-az4--x-s3"));
+        assert_eq!(lint_storage_class("STANDARD_IA", "greptime-data").unwrap().status, CheckStatus::Warning);
+    }
+
+    #[test]
+    fn test_lint_metasrv() {
+        let config = MetasrvConfig {
+            backend: "etcd_store".to_string(),
+            enable_region_failover: Some(true),
+            ..Default::default()
+        };
+        let details = lint_metasrv(&config);
+        assert_eq!(details.len(), 2);
+        assert!(details.iter().all(|d| d.status == CheckStatus::Fail));
+
+        let allowed = MetasrvConfig {
+            allow_region_failover_on_local_wal: Some(true),
+            ..config
+        };
+        let details = lint_metasrv(&allowed);
+        let failover = details.iter().find(|d| d.item == "Region Failover Prerequisites").unwrap();
+        assert_eq!(failover.status, CheckStatus::Info);
+
+        assert!(lint_metasrv(&ConfigParser::default_metasrv_config()).is_empty());
+    }
+
+    #[test]
+    fn test_lint_selector() {
+        assert!(lint_selector("load_based").is_none());
+        let detail = lint_selector("round-robin").unwrap();
+        assert_eq!(detail.status, CheckStatus::Fail);
+        assert!(detail.suggestion.unwrap().starts_with("Did you mean \"round_robin\"?"));
+        assert!(lint_selector("random").unwrap().suggestion.unwrap().starts_with("Valid selectors"));
+    }
+
+    #[test]
+    fn test_lint_config() {
+        let raw: toml::Value = toml::from_str(
+            r#"
+node_id = 1
+nodeid = 2
+
+[grpc]
+bind_addr = "127.0.0.1:3001"
+
+[grpc.tls]
+mode = "disable"
+cert_path = ""
+
+[meta_client]
+metasrv_addrs = ["127.0.0.1:3002"]
+heartbeat_timout = "500ms"
+
+[storage]
+type = "S3"
+bucket = "greptimedb"
+access_key_id_env = "S3_ACCESS_KEY_ID"
+secret_access_key_file = "/run/secrets/s3-secret"
+account_key_vault = "account_key"
+endpoint_file = "/run/secrets/s3-endpoint"
+
+[[region_engine]]
+[region_engine.mito]
+num_worker = 8
+"#,
+        )
+        .unwrap();
+        let details = lint_config(&raw, "datanode").unwrap();
+        let mut items = details.iter().map(|d| d.item.as_str()).collect::<Vec<_>>();
+        items.sort();
+        assert_eq!(
+            items,
+            vec![
+                "Unknown Key (meta_client.heartbeat_timout)",
+                "Unknown Key (nodeid)",
+                "Unknown Key (region_engine[0].mito.num_worker)",
+                "Unknown Key (storage.endpoint_file)",
+            ]
+        );
+
+        let raw: toml::Value = toml::from_str("node_id = \"one\"").unwrap();
+        let details = lint_config(&raw, "datanode").unwrap();
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].item, "Config Schema");
+
+        let raw = toml::Value::Table(toml::Table::new());
+        assert!(lint_config(&raw, "standalone").is_err());
+    }
+}
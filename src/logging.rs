@@ -0,0 +1,254 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks on the log directory: that it is writable and has room for the rotated log files.
+//!
+//! A component that cannot write its logs keeps running until the disk fills, then dies hours
+//! after a clean startup, so the space needed for a full rotation is estimated up front from
+//! the logs already written.
+
+use crate::common::{CheckDetail, CheckStatus};
+use crate::config::LoggingConfig;
+use std::path::Path;
+
+/// Files GreptimeDB keeps when `max_log_files` is unset; logs rotate hourly
+const DEFAULT_MAX_LOG_FILES: u64 = 720;
+
+/// Check the configured log directory. Nothing is checked when `dir` is unset, or empty,
+/// which logs to stdout only.
+pub fn check_log_dir(logging: Option<&LoggingConfig>) -> Vec<CheckDetail> {
+    let Some(logging) = logging else {
+        return Vec::new();
+    };
+    let Some(dir) = logging.dir.as_deref().filter(|dir| !dir.is_empty()) else {
+        return Vec::new();
+    };
+
+    let path = Path::new(dir);
+    let writable = if path.exists() {
+        writable_detail(path)
+    } else {
+        creatable_detail(path)
+    };
+    let mut details = vec![writable];
+    if details[0].status != CheckStatus::Fail {
+        details.extend(space_detail(logging, path));
+    }
+    details
+}
+
+fn writable_detail(path: &Path) -> CheckDetail {
+    let item = "Log Directory".to_string();
+    if !path.is_dir() {
+        return CheckDetail::fail(
+            item,
+            format!("{} exists but is not a directory", path.display()),
+            None,
+            Some("Point logging.dir at a directory".to_string()),
+        );
+    }
+
+    let probe = path.join(format!(".stepstone-{}", uuid::Uuid::new_v4()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            CheckDetail::pass(item, format!("{} is writable", path.display()), None)
+        }
+        Err(e) => CheckDetail::fail(
+            item,
+            format!("Cannot write to {}: {}", path.display(), e),
+            None,
+            Some("Give the user running the component write access to logging.dir".to_string()),
+        ),
+    }
+}
+
+/// A missing directory is created at startup, which needs its closest existing ancestor to
+/// be writable
+fn creatable_detail(path: &Path) -> CheckDetail {
+    let item = "Log Directory".to_string();
+    let Some(ancestor) = existing_ancestor(path) else {
+        return CheckDetail::fail(
+            item,
+            format!("No parent of {} exists", path.display()),
+            None,
+            Some("Create logging.dir before starting the component".to_string()),
+        );
+    };
+
+    match writable_detail(ancestor) {
+        detail if detail.status == CheckStatus::Pass => CheckDetail::pass(
+            item,
+            format!("{} does not exist yet and can be created under {}", path.display(), ancestor.display()),
+            None,
+        ),
+        detail => CheckDetail::fail(
+            item,
+            format!("{} does not exist and cannot be created: {}", path.display(), detail.message),
+            None,
+            Some(format!("Create {} with write access for the component's user", path.display())),
+        ),
+    }
+}
+
+/// Compare the free space under the log directory with a full rotation, sized by the hourly
+/// files already written there
+fn space_detail(logging: &LoggingConfig, path: &Path) -> Option<CheckDetail> {
+    let existing = existing_ancestor(path)?;
+    let available = fs2::available_space(existing).ok()?;
+    let files = logging.max_log_files.map_or(DEFAULT_MAX_LOG_FILES, u64::from);
+
+    let item = "Log Disk Space".to_string();
+    let Some(hourly) = hourly_log_bytes(path).filter(|hourly| *hourly > 0) else {
+        return Some(CheckDetail::info(
+            item,
+            format!(
+                "{} free for {} rotated hourly files; no rotated logs in {} yet to estimate their size from",
+                format_bytes(available),
+                files,
+                path.display()
+            ),
+            None,
+        ));
+    };
+    let needed = hourly * files;
+    let hours = available / hourly;
+
+    Some(if available >= needed {
+        CheckDetail::pass(
+            item,
+            format!(
+                "{} free, about {} needed for {} rotated hourly files of {} each",
+                format_bytes(available),
+                format_bytes(needed),
+                files,
+                format_bytes(hourly)
+            ),
+            None,
+        )
+    } else {
+        CheckDetail::warning(
+            item,
+            format!(
+                "{} free, but {} rotated hourly files of {} each need about {}; the disk may fill after roughly {} hours",
+                format_bytes(available),
+                files,
+                format_bytes(hourly),
+                format_bytes(needed),
+                hours
+            ),
+            None,
+            Some("Lower logging.level, reduce logging.max_log_files or move logging.dir to a larger volume".to_string()),
+        )
+    })
+}
+
+/// Average bytes logged per hour, from the rotated files in `dir` named `<prefix>.YYYY-MM-DD-HH`.
+/// Files of every prefix (e.g. `greptimedb` and `greptimedb-err`) that share an hour add up.
+fn hourly_log_bytes(dir: &Path) -> Option<u64> {
+    let mut hours = std::collections::BTreeSet::new();
+    let mut total = 0;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let name = entry.file_name();
+        let Some((_, hour)) = name.to_str().and_then(|name| name.rsplit_once('.')) else {
+            continue;
+        };
+        if !is_rotation_hour(hour) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_file() {
+            total += metadata.len();
+            hours.insert(hour.to_string());
+        }
+    }
+    (!hours.is_empty()).then(|| total / hours.len() as u64)
+}
+
+/// `YYYY-MM-DD-HH`, the suffix of hourly rotated files
+fn is_rotation_hour(suffix: &str) -> bool {
+    suffix.len() == 13
+        && suffix
+            .char_indices()
+            .all(|(i, c)| if matches!(i, 4 | 7 | 10) { c == '-' } else { c.is_ascii_digit() })
+}
+
+/// The path itself or its closest existing ancestor, with `.` for relative paths
+pub(crate) fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .map(|ancestor| if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor })
+        .find(|ancestor| ancestor.exists())
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const GIB: u64 = 1 << 30;
+    const MIB: u64 = 1 << 20;
+    if bytes >= GIB {
+        format!("{:.1} GiB", bytes as f64 / GIB as f64)
+    } else {
+        format!("{} MiB", bytes / MIB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_check_log_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let logging = LoggingConfig {
+            dir: Some(dir.path().join("logs/greptimedb").display().to_string()),
+            max_log_files: Some(1),
+            ..Default::default()
+        };
+        let details = check_log_dir(Some(&logging));
+        assert_eq!(details[0].status, CheckStatus::Pass);
+        assert!(details[0].message.contains("can be created"));
+        assert_eq!(details[1].item, "Log Disk Space");
+        assert_eq!(details[1].status, CheckStatus::Info);
+
+        let logs = dir.path().join("existing");
+        std::fs::create_dir(&logs).unwrap();
+        std::fs::write(logs.join("greptimedb.2024-05-01-10"), vec![0; 3000]).unwrap();
+        std::fs::write(logs.join("greptimedb-err.2024-05-01-10"), vec![0; 1000]).unwrap();
+        std::fs::write(logs.join("greptimedb.2024-05-01-11"), vec![0; 4000]).unwrap();
+        std::fs::write(logs.join("notes.txt"), vec![0; 10000]).unwrap();
+        assert_eq!(hourly_log_bytes(&logs), Some(4000));
+        let logging = LoggingConfig {
+            dir: Some(logs.display().to_string()),
+            max_log_files: Some(1),
+            ..Default::default()
+        };
+        let details = check_log_dir(Some(&logging));
+        assert_eq!(details[1].status, CheckStatus::Pass);
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        let logging = LoggingConfig {
+            dir: Some(file.display().to_string()),
+            ..Default::default()
+        };
+        let details = check_log_dir(Some(&logging));
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].status, CheckStatus::Fail);
+
+        let stdout_only = LoggingConfig {
+            dir: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(check_log_dir(Some(&stdout_only)).is_empty());
+    }
+}
@@ -10,29 +10,143 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod auth;
+mod checks;
+mod cluster;
 mod common;
 mod config;
+mod credentials;
 mod datanode;
+mod diff;
 mod error;
+mod export_metrics;
+mod fix;
 mod frontend;
+mod generate;
+mod grpc_health;
+mod history;
+mod host;
+mod i18n;
+mod k8s;
+mod k8s_init;
+mod lint;
+mod logging;
 #[allow(dead_code)]
 mod metasrv;
+mod mito;
+mod mode;
+mod net;
+mod network;
+mod notify;
+mod otlp;
+mod plugins;
+mod profile;
+mod query;
+mod redact;
+mod report_db;
+mod report_diff;
+mod request_rate;
+mod run_trace;
+mod schema;
+mod serve;
+mod settings;
+mod sink;
+mod storage;
+mod suppress;
+mod tls;
+mod vault;
+mod wait;
+mod wal;
+mod wasm_plugin;
+mod wizard;
 
 #[cfg(test)]
 mod tests;
 
-use clap::{Parser, Subcommand};
-use common::{ComponentChecker, CheckResult};
-use config::ConfigParser;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use cluster::ClusterConfigs;
+use common::{CheckDetail, CheckResult, ComponentChecker, Report, ReportSection};
+use config::{ConfigFormat, ConfigParser, DatanodeStorageConfig, MetasrvConfig, ParseOptions, TlsConfig};
+use datanode::provider::S3Provider;
 use datanode::DatanodeChecker;
+use diff::ConfigDiffChecker;
 use frontend::FrontendChecker;
 use metasrv::MetasrvChecker;
+use net::{NetBenchChecker, NetChecker};
+use profile::Profile;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use storage::StorageChecker;
+use tokio::sync::Semaphore;
 
 #[derive(Parser)]
 #[command(author, version, about = "GreptimeDB Self-Test Tool", long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Vault address for `<key>_vault` credential references (defaults to VAULT_ADDR)
+    #[arg(long, global = true)]
+    vault_addr: Option<String>,
+    /// Vault secret whose fields `<key>_vault` references name, e.g. secret/data/greptimedb
+    #[arg(long, global = true)]
+    vault_path: Option<String>,
+    /// Webhook to POST the results to whenever a run fails
+    #[arg(long, global = true)]
+    notify_webhook: Option<String>,
+    /// Webhook payload: report (full JSON), summary (failed checks only) or slack
+    #[arg(long, global = true, default_value = "report")]
+    notify_format: notify::NotifyFormat,
+    /// HTTP address of a GreptimeDB frontend to write each run's results to, e.g. http://frontend:4000
+    #[arg(long, global = true, value_name = "URL")]
+    report_to_greptimedb: Option<String>,
+    /// Table the results are written to, created on first write
+    #[arg(long, global = true, default_value = "stepstone_results")]
+    report_table: String,
+    /// Database the results table lives in
+    #[arg(long, global = true, default_value = "public")]
+    report_db: String,
+    /// Save each run's results as JSON in this directory, e.g. ~/.stepstone/history
+    #[arg(long, global = true)]
+    history_dir: Option<String>,
+    /// Most recent runs kept in --history-dir; older ones are removed after each save
+    #[arg(long, global = true, default_value_t = history::DEFAULT_KEEP)]
+    history_keep: usize,
+    /// Remove runs older than this from --history-dir after each save, e.g. 30d
+    #[arg(long, global = true, value_parser = history::parse_max_age)]
+    history_max_age: Option<Duration>,
+    /// OTLP collector to export a trace of each run to, e.g. http://collector:4317
+    #[arg(long, global = true)]
+    otlp_endpoint: Option<String>,
+    /// OTLP protocol of --otlp-endpoint: grpc or http
+    #[arg(long, global = true, default_value = "grpc")]
+    otlp_protocol: String,
+    /// TOML file with a [plugins] table of commands or WebAssembly modules to run as extra checks
+    #[arg(long, global = true)]
+    plugins: Option<String>,
+    /// Label the report with a key=value pair, e.g. `--tag rack=r12` (repeatable)
+    #[arg(long = "tag", global = true, value_name = "KEY=VALUE", value_parser = host::parse_tag)]
+    tags: Vec<(String, String)>,
+    /// Report only checks with one of these tags or categories, e.g. `--tag-filter performance`
+    /// (comma-separated); see `stepstone list-checks --output json` for each check's tags
+    #[arg(long, global = true, value_delimiter = ',')]
+    tag_filter: Vec<String>,
+    /// Stepstone settings file with team defaults (~/.config/stepstone/config.toml when it exists)
+    #[arg(long, global = true)]
+    settings: Option<String>,
+    /// Checks to run: quick, standard (default), deep or a profile of the settings file
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Which side of startup to check: preflight (ports must be free), live (running servers
+    /// must answer) or auto (default: whichever is found)
+    #[arg(long, global = true, default_value = "auto")]
+    mode: mode::CheckMode,
+    /// TOML file of accepted findings to report as INFO or hide, see `[[suppress]]` in the README
+    #[arg(long, global = true)]
+    suppressions: Option<String>,
+    /// Language of the human-readable report: en or zh; JSON reports stay in English
+    #[arg(long, global = true, default_value = "en")]
+    lang: i18n::Lang,
 }
 
 #[derive(Subcommand)]
@@ -42,9 +156,18 @@ enum Commands {
         /// Path to configuration file
         #[arg(short = 'c', long)]
         config: String,
+        /// Override a config value before checking, e.g. `--set storage.bucket=staging` (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
+        /// Probe that the advertised gRPC address resolves to a local, non-loopback interface
+        #[arg(long)]
+        probe_advertise: bool,
         /// Output format: human (default) or json
         #[arg(long, default_value = "human")]
         output: String,
@@ -54,12 +177,30 @@ enum Commands {
         /// Path to configuration file
         #[arg(short = 'c', long)]
         config: String,
+        /// Override a config value before checking, e.g. `--set storage.bucket=staging` (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
-        /// Include performance tests
+        /// Include performance tests, with the 1GB write, on top of the profile
         #[arg(long)]
         include_performance: bool,
+        /// Estimate network bandwidth to object storage on top of the profile
+        #[arg(long)]
+        include_bandwidth: bool,
+        /// Probe that the advertised gRPC address resolves to a local, non-loopback interface
+        #[arg(long)]
+        probe_advertise: bool,
+        /// Remediate simple failures, such as missing directories, then re-run the checks
+        #[arg(long)]
+        fix: bool,
+        /// Apply fixes without asking for confirmation
+        #[arg(short = 'y', long, requires = "fix")]
+        yes: bool,
         /// Output format: human (default) or json
         #[arg(long, default_value = "human")]
         output: String,
@@ -69,9 +210,365 @@ enum Commands {
         /// Path to configuration file
         #[arg(short = 'c', long)]
         config: String,
+        /// Override a config value before checking, e.g. `--set storage.bucket=staging` (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
+        /// Include metadata backend performance benchmark on top of the profile
+        #[arg(long)]
+        include_performance: bool,
+        /// Concurrent connections to open against a PostgreSQL/MySQL backend
+        #[arg(long, default_value_t = 20)]
+        sql_connections: usize,
+        /// Probe that the advertised gRPC address resolves to a local, non-loopback interface
+        #[arg(long)]
+        probe_advertise: bool,
+        /// Same as --mode live: check an already-running metasrv, whose gRPC and HTTP servers
+        /// must answer instead of their ports being free
+        #[arg(long)]
+        live: bool,
+        /// Remediate simple failures, such as missing directories, then re-run the checks
+        #[arg(long)]
+        fix: bool,
+        /// Apply fixes without asking for confirmation
+        #[arg(short = 'y', long, requires = "fix")]
+        yes: bool,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Network diagnostics between cluster hosts
+    Net {
+        #[command(subcommand)]
+        command: NetCommands,
+    },
+    /// Check an etcd cluster directly, without a metasrv config
+    Etcd {
+        /// Comma-separated etcd endpoints (host:port)
+        #[arg(long, value_delimiter = ',', required = true)]
+        endpoints: Vec<String>,
+        /// Key prefix used for test keys
+        #[arg(long)]
+        store_key_prefix: Option<String>,
+        /// CA certificate file for TLS
+        #[arg(long)]
+        tls_ca: Option<String>,
+        /// Client certificate file for TLS
+        #[arg(long)]
+        tls_cert: Option<String>,
+        /// Client private key file for TLS
+        #[arg(long)]
+        tls_key: Option<String>,
+        /// Server name used to verify the etcd certificate
+        #[arg(long)]
+        tls_server_name: Option<String>,
+        /// Username for etcd authentication
+        #[arg(long)]
+        username: Option<String>,
+        /// Password for etcd authentication
+        #[arg(long)]
+        password: Option<String>,
+        /// Operations per transaction to validate against etcd's --max-txn-ops
+        #[arg(long)]
+        max_txn_ops: Option<usize>,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Object storage diagnostics without a datanode config
+    Storage {
+        #[command(subcommand)]
+        command: StorageCommands,
+    },
+    /// Check a config file, detecting whether it configures metasrv, frontend or datanode
+    Check {
+        /// Path to configuration file
+        #[arg(short = 'c', long)]
+        config: String,
+        /// Override a config value before checking, e.g. `--set storage.bucket=staging` (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
+        /// Run as a Kubernetes init container: retry until the checks pass or the deadline,
+        /// write the outcome to /dev/termination-log and exit 2 on config errors
+        #[arg(long)]
+        k8s_init: bool,
+        /// Seconds to keep retrying in --k8s-init mode
+        #[arg(long, default_value_t = 300, requires = "k8s_init")]
+        init_deadline: u64,
+        /// Mounted Secret volume in --k8s-init mode; each file sets the config key it is named after
+        #[arg(long, default_value = k8s_init::DEFAULT_SECRETS_DIR, requires = "k8s_init")]
+        secrets_dir: String,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Wait until the services a node depends on pass their checks, for startup scripts
+    Wait {
+        /// Path to configuration file; the component is detected from its contents
+        #[arg(short = 'c', long)]
+        config: String,
+        /// Override a config value before checking, e.g. `--set storage.bucket=staging` (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
+        /// Comma-separated dependencies to wait for: metasrv and storage for a datanode, metasrv
+        /// for a frontend, backend for a metasrv
+        #[arg(long = "for", value_delimiter = ',', required = true)]
+        dependencies: Vec<common::Dependency>,
+        /// How long to keep retrying, e.g. 90s or 5m
+        #[arg(long, default_value = "5m", value_parser = wait::parse_timeout)]
+        timeout: Duration,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Validate a config file offline: schema, unknown keys and cross-field rules
+    Lint {
+        /// Path to configuration file
+        #[arg(short = 'c', long)]
+        config: String,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
+        /// Component the file configures: metasrv, frontend or datanode
+        #[arg(long)]
+        component: String,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Interactively build a config, checking each answer live
+    Wizard,
+    /// Print a commented starter config for a component
+    GenerateConfig {
+        /// Component to generate: metasrv, frontend or datanode
+        component: String,
+        /// Datanode storage: file, s3, oss, azblob or gcs
+        #[arg(long, default_value = "file")]
+        storage: String,
+        /// WAL provider for datanode and metasrv: raft_engine or kafka
+        #[arg(long, default_value = "raft_engine")]
+        wal: String,
+        /// Metasrv backend: etcd, postgres or mysql
+        #[arg(long, default_value = "etcd")]
+        backend: String,
+    },
+    /// Check every config file in a directory, detecting each file's component from its content
+    CheckDir {
+        /// Directory holding .toml, .yaml or .yml config files
+        dir: String,
+        /// Also connect to each datanode's advertised gRPC address; run this on the metasrv host
+        #[arg(long)]
+        probe_datanodes: bool,
+        /// Files checked at the same time
+        #[arg(short = 'j', long, default_value_t = 4)]
+        jobs: usize,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Check the component configs embedded in Helm values, ConfigMaps or GreptimeDBCluster resources
+    #[command(name = "k8s-values")]
+    K8sValues {
+        /// Path to a Helm values file or a Kubernetes manifest
+        #[arg(short = 'f', long)]
+        file: String,
+        /// Component configs checked at the same time
+        #[arg(short = 'j', long, default_value_t = 4)]
+        jobs: usize,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Compare a local config file with the effective config of a running node
+    DiffConfig {
+        /// Path to the local configuration file
+        #[arg(short = 'c', long)]
+        config: String,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
+        /// URL of the node's config endpoint, e.g. http://node:4000/config
+        #[arg(long)]
+        from_url: String,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Run a config's checks periodically and serve the results over HTTP
+    Serve {
+        /// Path to configuration file
+        #[arg(short = 'c', long)]
+        config: String,
+        /// Override a config value before checking, e.g. `--set storage.bucket=staging` (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Config file format: toml or yaml (detected from the extension by default)
+        #[arg(long)]
+        format: Option<ConfigFormat>,
+        /// Address to serve /healthz, /results, /metrics and /run on
+        #[arg(long, default_value = "0.0.0.0:9090")]
+        listen: String,
+        /// Seconds between check runs; 0 runs only on `POST /run`
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        /// Bearer token `POST /run` must carry; without it only clients on this host may trigger runs
+        #[arg(long)]
+        run_token: Option<String>,
+    },
+    /// Compare two reports saved with --output json; exits non-zero on new failures or slowdowns
+    Compare {
+        /// Baseline report
+        old: String,
+        /// Report to check against the baseline
+        new: String,
+        /// A check regresses when its duration grows by this factor
+        #[arg(long, default_value_t = 1.5)]
+        latency_ratio: f64,
+        /// Ignore slowdowns smaller than this many milliseconds
+        #[arg(long, default_value_t = 50)]
+        min_latency_delta_ms: u64,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Inspect the runs saved with --history-dir
+    History {
+        #[command(subcommand)]
+        command: HistoryCommands,
+    },
+    /// Print the JSON Schema of the reports written with --output json
+    Schema {
+        /// Schema format; only json is supported
+        #[arg(long, default_value = "json")]
+        output_format: String,
+    },
+    /// List the documented checks with their IDs
+    ListChecks {
+        /// Only checks of this component: metasrv, frontend, datanode, cluster or network
+        #[arg(long)]
+        component: Option<String>,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Explain what a check does, why it matters and how to fix it
+    Explain {
+        /// Check ID from `list-checks`, e.g. datanode.storage.s3.put, or a report item name
+        id: String,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// List saved runs, oldest first
+    List,
+    /// Show which checks changed status or became slower between two runs
+    Diff {
+        /// Earlier run: an ID from `history list`, a path, `latest` or `latest~N`
+        #[arg(default_value = "latest~1")]
+        run_a: String,
+        /// Later run, in the same forms
+        #[arg(default_value = "latest")]
+        run_b: String,
+        /// A check is slower when its duration grows by this factor
+        #[arg(long, default_value_t = 1.5)]
+        latency_ratio: f64,
+        /// Ignore slowdowns smaller than this many milliseconds
+        #[arg(long, default_value_t = 50)]
+        min_latency_delta_ms: u64,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageCommands {
+    /// Run the object storage permission and performance suite
+    Bench {
+        /// Path to a TOML file with a [storage] section or top-level storage keys
+        #[arg(short = 'c', long)]
+        config: Option<String>,
+        /// Storage type: S3, Oss, Azblob, Gcs, Webhdfs, File
+        #[arg(long = "type")]
+        storage_type: Option<String>,
+        /// Bucket name
+        #[arg(long)]
+        bucket: Option<String>,
+        /// Root path in the bucket
+        #[arg(long)]
+        root: Option<String>,
+        /// Access key ID
+        #[arg(long)]
+        access_key: Option<String>,
+        /// Secret access key
+        #[arg(long)]
+        secret_key: Option<String>,
+        /// Endpoint URL
+        #[arg(long)]
+        endpoint: Option<String>,
+        /// Region
+        #[arg(long)]
+        region: Option<String>,
+        /// Endpoint and region preset of an S3-compatible provider: r2 or b2
+        #[arg(long)]
+        preset: Option<S3Provider>,
+        /// Cloudflare account ID, for --preset r2
+        #[arg(long)]
+        account_id: Option<String>,
+        /// Data directory for File storage
+        #[arg(long)]
+        data_home: Option<String>,
+        /// Estimate network bandwidth to object storage
+        #[arg(long)]
+        include_bandwidth: bool,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum NetCommands {
+    /// Run DNS, TCP, optional TLS, and RTT checks against a single endpoint
+    Check {
+        /// Endpoint to check (host:port)
+        target: String,
+        /// Also perform a TLS handshake
+        #[arg(long)]
+        tls: bool,
+        /// Output format: human (default) or json
+        #[arg(long, default_value = "human")]
+        output: String,
+    },
+    /// Run a benchmark server for `net bench` to connect to
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "0.0.0.0:7007")]
+        listen: String,
+    },
+    /// Measure TCP latency and throughput against a `net serve` instance
+    Bench {
+        /// Address of the benchmark server (host:port)
+        #[arg(long)]
+        target: String,
+        /// Seconds to spend on each throughput direction
+        #[arg(long, default_value_t = 5)]
+        duration: u64,
         /// Output format: human (default) or json
         #[arg(long, default_value = "human")]
         output: String,
@@ -80,20 +577,250 @@ enum Commands {
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    common::mark_run_start();
+    // The settings file provides flag defaults, so it is located before the real parse
+    let settings_path = Cli::command()
+        .ignore_errors(true)
+        .try_get_matches()
+        .ok()
+        .and_then(|matches| matches.get_one::<String>("settings").cloned());
+    let settings = match settings::load(settings_path.as_deref()) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let matches = settings::apply_defaults(Cli::command(), &settings.flag_defaults()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    host::init(&cli.tags);
+    checks::set_tag_filter(cli.tag_filter.clone());
+    i18n::init(cli.lang);
+    let live = matches!(cli.command, Commands::Metasrv { live: true, .. });
+    let explicit = matches.value_source("mode") == Some(clap::parser::ValueSource::CommandLine);
+    match mode::resolve(cli.mode, explicit, live) {
+        Ok(mode) => mode::init(mode),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let name = cli.profile.as_deref().or(settings.profile.as_deref()).unwrap_or("standard");
+    let profile = match profile::resolve(name, &settings.profiles) {
+        Ok(profile) => profile.select(checks::selected),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    settings::init(settings);
+    if let Some(path) = &cli.plugins {
+        if let Err(e) = plugins::load(path) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = &cli.suppressions {
+        if let Err(e) = suppress::load(path) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Vault references are resolved while configs are parsed, so the secret is read first
+    let vault = match &cli.vault_path {
+        Some(path) => vault::load(cli.vault_addr.as_deref(), path).await,
+        None => Ok(()),
+    };
+    if let Err(e) = vault {
+        eprintln!("Error: {}", redact::redact(&e.to_string()));
+        std::process::exit(1);
+    }
+    if let Some(url) = &cli.notify_webhook {
+        notify::configure(url, cli.notify_format);
+    }
+    if let Some(url) = &cli.report_to_greptimedb {
+        report_db::configure(url, &cli.report_table, &cli.report_db);
+    }
+    if let Some(endpoint) = &cli.otlp_endpoint {
+        let command = matches.subcommand_name().unwrap_or_default();
+        if let Err(e) = run_trace::configure(endpoint, &cli.otlp_protocol, command) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+    // Comparisons of saved runs are not runs worth saving themselves
+    let compares = matches!(cli.command, Commands::History { .. } | Commands::Compare { .. });
+    if let Some(dir) = cli.history_dir.as_deref().filter(|_| !compares) {
+        history::configure(dir, history::Retention { keep: cli.history_keep, max_age: cli.history_max_age });
+    }
 
     let result = match &cli.command {
-        Commands::Frontend { config, verbose, output } => {
-            run_frontend_check(config, *verbose, output).await
+        Commands::Frontend { config, set, format, verbose, probe_advertise, output } => {
+            let options = ParseOptions { format: *format, overrides: set.clone() };
+            run_frontend_check(config, &options, *verbose, *probe_advertise || profile.probe_advertise, output).await
+        }
+        Commands::Datanode { config, set, format, verbose: _, include_performance, include_bandwidth, probe_advertise, fix, yes, output } => {
+            let options = ParseOptions { format: *format, overrides: set.clone() };
+            let fix_mode = fix.then_some(*yes);
+            // As before profiles existed, --include-performance also writes the 1GB object
+            let profile = Profile {
+                storage_operations: profile.storage_operations || *include_performance || *include_bandwidth,
+                performance: profile.performance || *include_performance,
+                large_objects: profile.large_objects || *include_performance,
+                bandwidth: profile.bandwidth || *include_bandwidth,
+                probe_advertise: profile.probe_advertise || *probe_advertise,
+                ..profile
+            }
+            .select(checks::selected);
+            run_datanode_check(config, &options, profile, fix_mode, output).await
+        }
+        Commands::Metasrv { config, set, format, verbose: _, include_performance, sql_connections, probe_advertise, live: _, fix, yes, output } => {
+            let options = ParseOptions { format: *format, overrides: set.clone() };
+            let fix_mode = fix.then_some(*yes);
+            let profile = Profile {
+                performance: profile.performance || *include_performance,
+                probe_advertise: profile.probe_advertise || *probe_advertise,
+                ..profile
+            }
+            .select(checks::selected);
+            run_metasrv_check(config, &options, profile, *sql_connections, fix_mode, output).await
+        }
+        Commands::Etcd { endpoints, store_key_prefix, tls_ca, tls_cert, tls_key, tls_server_name, username, password, max_txn_ops, output } => {
+            if let Some(password) = password {
+                redact::register_secret(password);
+            }
+            let tls = TlsConfig {
+                cert: tls_cert.clone(),
+                key: tls_key.clone(),
+                ca: tls_ca.clone(),
+                server_name: tls_server_name.clone(),
+                ..Default::default()
+            };
+            let has_tls = tls.cert.is_some() || tls.key.is_some() || tls.ca.is_some() || tls.server_name.is_some();
+            let config = MetasrvConfig {
+                store_addrs: endpoints.clone(),
+                store_key_prefix: store_key_prefix.clone(),
+                backend: "etcd_store".to_string(),
+                backend_tls: has_tls.then_some(tls),
+                backend_username: username.clone(),
+                backend_password: password.clone(),
+                max_txn_ops: *max_txn_ops,
+                ..Default::default()
+            };
+            run_etcd_check(config, output).await
+        }
+        Commands::Storage { command: StorageCommands::Bench {
+            config, storage_type, bucket, root, access_key, secret_key, endpoint, region, preset, account_id, data_home, include_bandwidth, output,
+        } } => {
+            for secret in access_key.iter().chain(secret_key.iter()) {
+                redact::register_secret(secret);
+            }
+            let overrides = DatanodeStorageConfig {
+                data_home: data_home.clone(),
+                storage_type: storage_type.clone(),
+                bucket: bucket.clone(),
+                root: root.clone(),
+                access_key_id: access_key.clone(),
+                secret_access_key: secret_key.clone(),
+                endpoint: endpoint.clone(),
+                region: region.clone(),
+                ..Default::default()
+            };
+            let preset = preset.map(|preset| (preset, account_id.as_deref()));
+            run_storage_bench(config.as_deref(), overrides, preset, *include_bandwidth, output).await
+        }
+        Commands::Check { config, set, format, k8s_init, init_deadline, secrets_dir, output } => {
+            let options = ParseOptions { format: *format, overrides: set.clone() };
+            if *k8s_init {
+                let code = run_k8s_init(config, options, profile, secrets_dir, *init_deadline, output).await;
+                sink::flush().await;
+                std::process::exit(code);
+            }
+            run_auto_check(config, &options, profile, output).await
+        }
+        Commands::Wait { config, set, format, dependencies, timeout, output } => {
+            let options = ParseOptions { format: *format, overrides: set.clone() };
+            run_wait(config, &options, profile, dependencies, *timeout, output).await
+        }
+        Commands::Wizard => {
+            wizard::Wizard::new(std::io::stdin().lock(), std::io::stdout()).run().await.map(|_| true)
+        }
+        Commands::GenerateConfig { component, storage, wal, backend } => {
+            generate::generate_config(component, storage, wal, backend).map(|config| {
+                print!("{}", config);
+                true
+            })
+        }
+        Commands::Lint { config, format, component, output } => {
+            run_lint(config, *format, component, output)
+        }
+        Commands::CheckDir { dir, probe_datanodes, jobs, output } => {
+            run_check_dir(dir, profile, *probe_datanodes, *jobs, output).await
+        }
+        Commands::K8sValues { file, jobs, output } => {
+            run_k8s_values(file, profile, *jobs, output).await
+        }
+        Commands::DiffConfig { config, format, from_url, output } => {
+            run_diff_config(config, *format, from_url, output).await
+        }
+        Commands::Serve { config, set, format, listen, interval, run_token } => {
+            let options = ParseOptions { format: *format, overrides: set.clone() };
+            run_serve(config, options, profile, listen, *interval, run_token.as_deref()).await
         }
-        Commands::Datanode { config, verbose, include_performance, output } => {
-            run_datanode_check(config, *verbose, *include_performance, output).await
+        Commands::Compare { old, new, latency_ratio, min_latency_delta_ms, output } => {
+            // Unlike `history diff`, a slowdown fails the comparison, for gating deployments
+            let latency = report_diff::LatencyThreshold {
+                ratio: *latency_ratio,
+                min_delta_ms: *min_latency_delta_ms,
+                status: common::CheckStatus::Fail,
+            };
+            run_report_diff(Path::new(old), Path::new(new), latency, "Report Comparison", output)
         }
-        Commands::Metasrv { config, verbose, output } => {
-            run_metasrv_check(config, *verbose, output).await
+        Commands::History { command: HistoryCommands::List } => {
+            let dir = history::expand_dir(cli.history_dir.as_deref().unwrap_or(history::DEFAULT_HISTORY_DIR));
+            run_history_list(&dir)
+        }
+        Commands::History { command: HistoryCommands::Diff { run_a, run_b, latency_ratio, min_latency_delta_ms, output } } => {
+            let dir = history::expand_dir(cli.history_dir.as_deref().unwrap_or(history::DEFAULT_HISTORY_DIR));
+            let latency = report_diff::LatencyThreshold {
+                ratio: *latency_ratio,
+                min_delta_ms: *min_latency_delta_ms,
+                status: common::CheckStatus::Warning,
+            };
+            run_history_diff(&dir, run_a, run_b, latency, output)
+        }
+        Commands::Schema { output_format } => {
+            run_schema(output_format)
+        }
+        Commands::ListChecks { component, output } => {
+            print!("{}", checks::render_list(component.as_deref(), output));
+            Ok(true)
+        }
+        Commands::Explain { id, output } => {
+            checks::render_explain(id, output).map(|text| {
+                print!("{}", text);
+                true
+            })
+        }
+        Commands::Net { command: NetCommands::Check { target, tls, output } } => {
+            run_net_check(target, *tls, output).await
+        }
+        Commands::Net { command: NetCommands::Serve { listen } } => {
+            net::serve(listen).await.map(|_| true)
+        }
+        Commands::Net { command: NetCommands::Bench { target, duration, output } } => {
+            run_net_bench(target, *duration, output).await
         }
     };
 
+    if let Err(e) = &result {
+        let detail = CheckDetail::fail("Error".to_string(), e.to_string(), None, None);
+        sink::record("stepstone", None, &CheckResult::from_details(vec![detail]));
+    }
+    sink::flush().await;
+
     match result {
         Ok(success) => {
             if !success {
@@ -101,42 +828,604 @@ async fn main() {
             }
         }
         Err(e) => {
-            eprintln!("Error: {}", e);
+            eprintln!("Error: {}", redact::redact(&e.to_string()));
             std::process::exit(1);
         }
     }
 }
 
-async fn run_frontend_check(config_path: &str, _verbose: bool, output_format: &str) -> error::Result<bool> {
-    let config = ConfigParser::parse_frontend_config(config_path)?;
-    let checker = FrontendChecker::new(config);
-    let result = checker.check().await;
+async fn run_frontend_check(config_path: &str, options: &ParseOptions, _verbose: bool, probe_advertise: bool, output_format: &str) -> error::Result<bool> {
+    let config = ConfigParser::parse_frontend_config(config_path, options)?;
+    let value = ConfigParser::parse_value(config_path, options)?;
+    let checker = FrontendChecker::new(config, probe_advertise);
+    let result = with_config_file_checks(checker.run().await, checker.component_name(), Path::new(config_path), &value).await;
 
     output_result(&result, checker.component_name(), Some(config_path), output_format)?;
     Ok(result.success)
 }
 
-async fn run_datanode_check(config_path: &str, _verbose: bool, include_performance: bool, output_format: &str) -> error::Result<bool> {
-    let config = ConfigParser::parse_datanode_config(config_path)?;
-    let checker = DatanodeChecker::new(config, include_performance);
-    let result = checker.check().await;
+/// `fix_mode` is `Some(assume_yes)` when `--fix` is given
+async fn run_datanode_check(config_path: &str, options: &ParseOptions, profile: Profile, fix_mode: Option<bool>, output_format: &str) -> error::Result<bool> {
+    let config = ConfigParser::parse_datanode_config(config_path, options)?;
+    let value = ConfigParser::parse_value(config_path, options)?;
+    let checker = DatanodeChecker::new(config.clone(), profile);
+    let mut result = with_config_file_checks(checker.run().await, checker.component_name(), Path::new(config_path), &value).await;
+    output_result(&result, checker.component_name(), Some(config_path), output_format)?;
+
+    if let Some(assume_yes) = fix_mode {
+        let fixes = fix::datanode_fixes(&config, &result);
+        if run_fixes(&fixes, assume_yes).await? {
+            result = with_config_file_checks(checker.run().await, checker.component_name(), Path::new(config_path), &value).await;
+            output_result(&result, checker.component_name(), Some(config_path), output_format)?;
+        }
+    }
+    Ok(result.success)
+}
+
+/// `fix_mode` is `Some(assume_yes)` when `--fix` is given
+async fn run_metasrv_check(config_path: &str, options: &ParseOptions, profile: Profile, sql_connections: usize, fix_mode: Option<bool>, output_format: &str) -> error::Result<bool> {
+    let config = ConfigParser::parse_metasrv_config(config_path, options)?;
+    let value = ConfigParser::parse_value(config_path, options)?;
+    let checker = MetasrvChecker::new(config.clone(), profile, sql_connections);
+    let mut result = with_config_file_checks(checker.run().await, checker.component_name(), Path::new(config_path), &value).await;
+    output_result(&result, checker.component_name(), Some(config_path), output_format)?;
+
+    if let Some(assume_yes) = fix_mode {
+        let fixes = fix::metasrv_fixes(&config, &result);
+        if run_fixes(&fixes, assume_yes).await? {
+            result = with_config_file_checks(checker.run().await, checker.component_name(), Path::new(config_path), &value).await;
+            output_result(&result, checker.component_name(), Some(config_path), output_format)?;
+        }
+    }
+    Ok(result.success)
+}
+
+/// Append the checks on the config file itself: credentials given as file or environment
+/// references, that a file holding credentials is not world-readable, and the checks of the
+/// plugins configured for the component, then finish the result
+async fn with_config_file_checks(result: CheckResult, component: &str, path: &Path, value: &toml::Value) -> CheckResult {
+    with_embedded_config_checks(result, component, path, path, value).await
+}
+
+/// [`with_config_file_checks`] for a config embedded in `file`, which plugins see as `source`
+async fn with_embedded_config_checks(result: CheckResult, component: &str, file: &Path, source: &Path, value: &toml::Value) -> CheckResult {
+    let start = Instant::now();
+    let mut file_details = credentials::check_secret_refs(value);
+    file_details.extend(credentials::check_file_permissions(file, value));
+    file_details.extend(plugins::run_plugins(component, source, value).await);
+    finish_result(component, with_extra_details(result, file_details, start))
+}
+
+/// Append `extra` details, found since `start`, to a checker's result
+fn with_extra_details(mut result: CheckResult, extra: Vec<CheckDetail>, start: Instant) -> CheckResult {
+    if !extra.is_empty() {
+        let mut details = std::mem::take(&mut result.details);
+        details.extend(extra);
+        result = result.with_details(details);
+    }
+    result.wall_duration = result.wall_duration.map(|wall| wall + start.elapsed());
+    result
+}
+
+/// Apply the suppressions to a result, give its findings their error codes, categories and tags,
+/// then drop those `--tag-filter` does not select from the report; the result stays that of
+/// all checks
+fn finish_result(component: &str, result: CheckResult) -> CheckResult {
+    checks::filter_by_tag(checks::annotate(component, suppress::apply(component, result)))
+}
+
+/// Prompt on stderr so JSON output on stdout stays parseable; returns whether anything changed
+async fn run_fixes(fixes: &[fix::Fix], assume_yes: bool) -> error::Result<bool> {
+    if fixes.is_empty() {
+        eprintln!("No automatic fixes available");
+        return Ok(false);
+    }
+    let applied = fix::apply_fixes(fixes, assume_yes, std::io::stdin().lock(), std::io::stderr()).await?;
+    if applied {
+        eprintln!("Re-running checks after fixes");
+    }
+    Ok(applied)
+}
+
+async fn run_etcd_check(config: MetasrvConfig, output_format: &str) -> error::Result<bool> {
+    let checker = MetasrvChecker::new(config, Profile::STANDARD, 0);
+    let result = finish_result("Etcd", checker.run().await);
+
+    output_result(&result, "Etcd", None, output_format)?;
+    Ok(result.success)
+}
+
+async fn run_storage_bench(
+    config_path: Option<&str>,
+    mut overrides: DatanodeStorageConfig,
+    preset: Option<(S3Provider, Option<&str>)>,
+    include_bandwidth: bool,
+    output_format: &str,
+) -> error::Result<bool> {
+    // A preset fills the endpoint and region of its provider; an explicit --endpoint still wins
+    if let Some((provider, account_id)) = preset {
+        let (endpoint, region) = provider
+            .preset(account_id, overrides.region.as_deref())
+            .map_err(|message| error::InvalidConfigSnafu { message }.build())?;
+        overrides.endpoint = overrides.endpoint.or(Some(endpoint));
+        overrides.region = Some(region);
+        overrides.storage_type = overrides.storage_type.or(Some("S3".to_string()));
+    }
+
+    // Flags take precedence over values from the optional config file
+    let base = match config_path {
+        Some(path) => ConfigParser::parse_storage_config(path)?,
+        None => DatanodeStorageConfig::default(),
+    };
+    let storage = DatanodeStorageConfig {
+        data_home: overrides.data_home.or(base.data_home),
+        storage_type: overrides.storage_type.or(base.storage_type),
+        bucket: overrides.bucket.or(base.bucket),
+        root: overrides.root.or(base.root),
+        access_key_id: overrides.access_key_id.or(base.access_key_id),
+        secret_access_key: overrides.secret_access_key.or(base.secret_access_key),
+        endpoint: overrides.endpoint.or(base.endpoint),
+        region: overrides.region.or(base.region),
+        ..base
+    };
+
+    let checker = StorageChecker::new(storage, include_bandwidth);
+    let result = finish_result(checker.component_name(), checker.run().await);
+
+    output_result(&result, checker.component_name(), config_path, output_format)?;
+    Ok(result.success)
+}
+
+fn run_lint(config_path: &str, format: Option<ConfigFormat>, component: &str, output_format: &str) -> error::Result<bool> {
+    let raw = ConfigParser::parse_config_flexible(config_path, format)?;
+    let mut details = lint::lint_config(&raw, component)?;
+    if details.is_empty() {
+        details.push(CheckDetail::pass(
+            "Config Lint".to_string(),
+            format!("No issues found in {} config", component),
+            None,
+        ));
+    }
+    let result = finish_result(component, CheckResult::from_details(details));
+
+    output_result(&result, "Config Lint", Some(config_path), output_format)?;
+    Ok(result.success)
+}
+
+async fn run_check_dir(dir: &str, profile: Profile, probe_datanodes: bool, jobs: usize, output_format: &str) -> error::Result<bool> {
+    use snafu::ResultExt;
+
+    let mut paths = std::fs::read_dir(dir)
+        .context(error::FileSystemSnafu {
+            message: format!("Failed to read directory: {}", dir),
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && ConfigFormat::from_path(path).is_some())
+        .collect::<Vec<_>>();
+    paths.sort();
+    if paths.is_empty() {
+        return error::InvalidConfigSnafu {
+            message: format!("No .toml, .yaml or .yml files found in {}", dir),
+        }
+        .fail();
+    }
+
+    let mut report = Report::default();
+    let mut cluster = ClusterConfigs::new(probe_datanodes, false);
+    let limit = Arc::new(Semaphore::new(jobs.max(1)));
+    let handles = paths
+        .iter()
+        .map(|path| {
+            let (path, limit) = (path.clone(), limit.clone());
+            tokio::spawn(async move {
+                let _permit = limit.acquire_owned().await;
+                check_file(&path, profile).await
+            })
+        })
+        .collect::<Vec<_>>();
+    // Results are collected in file order, whichever finishes first
+    for (path, handle) in paths.into_iter().zip(handles) {
+        let (component, result, config) = handle.await.unwrap_or_else(|e| ("Unknown", task_failure(e), None));
+        if let Some((detected, value)) = config {
+            cluster.add(&path.display().to_string(), detected, &value);
+        }
+        report.push(component, &path.display().to_string(), result);
+    }
+    if cluster.is_cluster() {
+        let start = Instant::now();
+        let result = cluster.check().await.with_wall_duration(start.elapsed());
+        report.push("Cluster", dir, finish_result("Cluster", result));
+    }
+
+    output_report(&report, output_format)?;
+    Ok(report.success())
+}
+
+/// Check one file of a directory; a file that cannot be parsed becomes a failed section
+/// rather than aborting the whole run. The detected component and config are returned too, for
+/// the cross-component checks.
+async fn check_file(path: &Path, profile: Profile) -> (&'static str, CheckResult, Option<(&'static str, toml::Value)>) {
+    let checker = ConfigParser::parse_config_flexible(path, None).and_then(|value| match detect_component(path, &value) {
+        // The files describe other machines, so nothing is sized against this one
+        Some(component) => build_checker(component, value.clone(), profile, None).map(|checker| Some((checker, component, value))),
+        None => Ok(None),
+    });
+
+    match checker {
+        Ok(Some((checker, component, value))) => (
+            checker.component_name(),
+            with_config_file_checks(checker.run().await, checker.component_name(), path, &value).await,
+            Some((component, value)),
+        ),
+        Ok(None) => (
+            "Unknown",
+            CheckResult::from_details(vec![CheckDetail::warning(
+                "Component Detection".to_string(),
+                "Could not tell which component this file configures, skipped".to_string(),
+                None,
+                Some("Name the file after its component, e.g. datanode.toml, or check it with the component subcommand".to_string()),
+            )]),
+            None,
+        ),
+        Err(e) => (
+            "Unknown",
+            CheckResult::from_details(vec![CheckDetail::fail(
+                "Config Parse".to_string(),
+                e.to_string(),
+                None,
+                Some("Fix the syntax error, or move files that are not GreptimeDB configs out of the directory".to_string()),
+            )]),
+            None,
+        ),
+    }
+}
+
+/// A failed result for a check task that panicked, so one file cannot abort a whole run
+fn task_failure(e: tokio::task::JoinError) -> CheckResult {
+    CheckResult::from_details(vec![CheckDetail::fail(
+        "Check Task".to_string(),
+        format!("The checks stopped unexpectedly: {}", e),
+        None,
+        None,
+    )])
+}
+
+/// Detect the component from the config's keys, falling back to the file name, e.g.
+/// `datanode-0.toml`, for configs too sparse to tell
+fn detect_component(path: &Path, value: &toml::Value) -> Option<&'static str> {
+    ConfigParser::detect_component(value).or_else(|| {
+        let name = path.file_stem()?.to_str()?.to_lowercase();
+        ["datanode", "frontend", "metasrv"]
+            .into_iter()
+            .find(|component| name.contains(component))
+            .or_else(|| name.contains("meta").then_some("metasrv"))
+    })
+}
+
+async fn run_auto_check(config_path: &str, options: &ParseOptions, profile: Profile, output_format: &str) -> error::Result<bool> {
+    let (component, result) = check_config(config_path, options, profile).await?;
+
+    output_result(&result, component, Some(config_path), output_format)?;
+    Ok(result.success)
+}
+
+/// Run the checks of `profile` on a config file, detecting its component
+async fn check_config(config_path: &str, options: &ParseOptions, profile: Profile) -> error::Result<(&'static str, CheckResult)> {
+    let (checker, value) = prepare_checker(config_path, options, profile)?;
+    let result = with_config_file_checks(checker.run().await, checker.component_name(), Path::new(config_path), &value).await;
+    Ok((checker.component_name(), result))
+}
+
+/// Parse a config file and build the checker of the component it configures
+fn prepare_checker(config_path: &str, options: &ParseOptions, profile: Profile) -> error::Result<(Box<dyn ComponentChecker + Send + Sync>, toml::Value)> {
+    let value = ConfigParser::parse_value(config_path, options)?;
+    let Some(component) = detect_component(Path::new(config_path), &value) else {
+        return error::InvalidConfigSnafu {
+            message: format!(
+                "Could not tell which component {} configures; run `stepstone metasrv|frontend|datanode -c {}` instead",
+                config_path, config_path
+            ),
+        }
+        .fail();
+    };
+    Ok((build_checker(component, value.clone(), profile, query::Host::current())?, value))
+}
+
+/// Returns the process exit code, see `k8s_init::EXIT_*`
+async fn run_k8s_init(config_path: &str, options: ParseOptions, profile: Profile, secrets_dir: &str, deadline_secs: u64, output_format: &str) -> i32 {
+    let termination_log = Path::new(k8s_init::TERMINATION_LOG);
+    // Explicit --set values win over mounted secrets
+    let prepared = k8s_init::secret_overrides(Path::new(secrets_dir)).and_then(|mut overrides| {
+        overrides.extend(options.overrides.iter().cloned());
+        prepare_checker(config_path, &ParseOptions { overrides, ..options }, profile)
+    });
+    let (checker, value) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            let message = redact::redact(&e.to_string());
+            eprintln!("Error: {}", message);
+            k8s_init::write_termination_log(termination_log, &format!("Config error: {}", message));
+            return k8s_init::EXIT_CONFIG_ERROR;
+        }
+    };
+
+    let (checker, value) = (&checker, &value);
+    let (result, attempts) = k8s_init::run_until_ready(Duration::from_secs(deadline_secs), move || async move {
+        with_config_file_checks(checker.run().await, checker.component_name(), Path::new(config_path), value).await
+    })
+    .await;
+
+    if let Err(e) = output_result(&result, checker.component_name(), Some(config_path), output_format) {
+        eprintln!("Error: {}", redact::redact(&e.to_string()));
+    }
+    k8s_init::write_termination_log(termination_log, &k8s_init::termination_message(checker.component_name(), &result, attempts));
+    if result.success { k8s_init::EXIT_READY } else { k8s_init::EXIT_NOT_READY }
+}
+
+async fn run_wait(config_path: &str, options: &ParseOptions, profile: Profile, dependencies: &[common::Dependency], timeout: Duration, output_format: &str) -> error::Result<bool> {
+    let (checker, _) = prepare_checker(config_path, options, profile)?;
+    if let Some(dependency) = wait::unsupported(&*checker, dependencies) {
+        let supported = checker.dependencies().iter().map(|dependency| dependency.as_str()).collect::<Vec<_>>();
+        return error::InvalidConfigSnafu {
+            message: format!(
+                "A {} does not depend on {}; it can wait for: {}",
+                checker.component_name().to_lowercase(),
+                dependency.as_str(),
+                if supported.is_empty() { "nothing".to_string() } else { supported.join(", ") }
+            ),
+        }
+        .fail();
+    }
 
+    let (result, attempts) = wait::wait_for(&*checker, dependencies, timeout).await;
+    let result = finish_result(checker.component_name(), result);
+    if !result.success {
+        eprintln!("Dependencies still failing after {} attempt(s) in {}s", attempts, timeout.as_secs());
+    }
     output_result(&result, checker.component_name(), Some(config_path), output_format)?;
     Ok(result.success)
 }
 
-async fn run_metasrv_check(config_path: &str, _verbose: bool, output_format: &str) -> error::Result<bool> {
-    let config = ConfigParser::parse_metasrv_config(config_path)?;
-    let checker = MetasrvChecker::new(config);
-    let result = checker.check().await;
+/// The config is re-read on every run, so edits are picked up without a restart
+async fn run_serve(config_path: &str, options: ParseOptions, profile: Profile, listen: &str, interval_secs: u64, run_token: Option<&str>) -> error::Result<bool> {
+    let path = config_path.to_string();
+    serve::serve(listen, config_path, Duration::from_secs(interval_secs), run_token, move || {
+        let path = path.clone();
+        let options = options.clone();
+        async move {
+            match check_config(&path, &options, profile).await {
+                Ok((component, result)) => (component.to_string(), result),
+                // A config broken by an edit fails the run rather than stopping the server
+                Err(e) => (
+                    "Unknown".to_string(),
+                    CheckResult::from_details(vec![CheckDetail::fail(
+                        "Config Parse".to_string(),
+                        e.to_string(),
+                        None,
+                        Some("Fix the config file; it is re-read on the next run".to_string()),
+                    )]),
+                ),
+            }
+        }
+    })
+    .await
+    .map(|_| true)
+}
+
+async fn run_k8s_values(path: &str, profile: Profile, jobs: usize, output_format: &str) -> error::Result<bool> {
+    use snafu::ResultExt;
+
+    let content = std::fs::read_to_string(path).context(error::FileSystemSnafu {
+        message: format!("Failed to read manifest: {}", path),
+    })?;
+    let configs = k8s::embedded_configs(&content)?;
+    if configs.is_empty() {
+        return error::InvalidConfigSnafu {
+            message: format!("No GreptimeDB component config found in {}", path),
+        }
+        .fail();
+    }
+
+    let mut report = Report::default();
+    let mut cluster = ClusterConfigs::new(false, true);
+    let limit = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut handles = Vec::new();
+    for embedded in configs {
+        let source = format!("{}#{}", path, embedded.source);
+        let parsed = toml::from_str::<toml::Value>(&embedded.content)
+            .context(error::TomlParsingSnafu {
+                message: format!("Failed to parse {}", embedded.source),
+            })
+            .and_then(|value| {
+                redact::register_config_secrets(&value);
+                // The pods' resources are not this host's
+                build_checker(embedded.component, value.clone(), profile, None).map(|checker| (checker, value))
+            });
+        // A config that cannot be parsed becomes a failed section, as in check-dir
+        let (checker, value) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let result = CheckResult::from_details(vec![CheckDetail::fail(
+                    "Config Parse".to_string(),
+                    e.to_string(),
+                    None,
+                    Some(format!("Fix the config embedded at {} in the manifest", embedded.source)),
+                )]);
+                handles.push((source, "Unknown", Err(result)));
+                continue;
+            }
+        };
+        cluster.add(&source, embedded.component, &value);
+        let name = checker.component_name();
+        let (manifest, plugin_source, limit) = (path.to_string(), source.clone(), limit.clone());
+        let handle = tokio::spawn(async move {
+            let _permit = limit.acquire_owned().await;
+            let result = checker.run().await;
+            // Plugins see the embedded config as they would the file it becomes
+            with_embedded_config_checks(result, name, Path::new(&manifest), Path::new(&plugin_source), &value).await
+        });
+        handles.push((source, name, Ok(handle)));
+    }
+    // Results are collected in manifest order, whichever finishes first
+    for (source, name, handle) in handles {
+        let result = match handle {
+            Ok(handle) => handle.await.unwrap_or_else(task_failure),
+            Err(result) => result,
+        };
+        report.push(name, &source, result);
+    }
+    if cluster.is_cluster() {
+        let start = Instant::now();
+        let result = cluster.check().await.with_wall_duration(start.elapsed());
+        report.push("Cluster", path, finish_result("Cluster", result));
+    }
+
+    output_report(&report, output_format)?;
+    Ok(report.success())
+}
+
+/// Build the checker running the checks of `profile` for a component from an already-parsed config,
+/// sized against `host`, or without the sizing checks when the config belongs to another machine
+fn build_checker(component: &str, value: toml::Value, profile: Profile, host: Option<query::Host>) -> error::Result<Box<dyn ComponentChecker + Send + Sync>> {
+    use snafu::ResultExt;
+
+    let message = format!("Failed to parse {} config", component);
+    Ok(match component {
+        "metasrv" => {
+            let config = value.try_into().context(error::TomlParsingSnafu { message })?;
+            Box::new(MetasrvChecker::new(config, profile, 0))
+        }
+        "frontend" => {
+            let config = value.try_into().context(error::TomlParsingSnafu { message })?;
+            Box::new(FrontendChecker::new(config, profile.probe_advertise).with_host(host))
+        }
+        "datanode" => {
+            let config = value.try_into().context(error::TomlParsingSnafu { message })?;
+            Box::new(DatanodeChecker::new(config, profile).with_host(host))
+        }
+        other => {
+            return error::InvalidConfigSnafu {
+                message: format!("Unknown component '{}', expected metasrv, frontend or datanode", other),
+            }
+            .fail();
+        }
+    })
+}
+
+async fn run_diff_config(config_path: &str, format: Option<ConfigFormat>, url: &str, output_format: &str) -> error::Result<bool> {
+    let local = ConfigParser::parse_config_flexible(config_path, format)?;
+    let checker = ConfigDiffChecker::new(local, url.to_string());
+    let result = finish_result(checker.component_name(), checker.run().await);
 
     output_result(&result, checker.component_name(), Some(config_path), output_format)?;
     Ok(result.success)
 }
 
+fn run_schema(output_format: &str) -> error::Result<bool> {
+    use snafu::ResultExt;
+
+    if output_format != "json" {
+        return error::InvalidConfigSnafu {
+            message: format!("Unknown schema format '{}', expected json", output_format),
+        }
+        .fail();
+    }
+    let schema = serde_json::to_string_pretty(&schema::report_schema()).context(error::JsonSerializationSnafu {
+        message: "Failed to serialize the report schema".to_string(),
+    })?;
+    println!("{}", schema);
+    Ok(true)
+}
+
+fn run_history_list(dir: &Path) -> error::Result<bool> {
+    for path in history::list_runs(dir)? {
+        let id = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let summary = match history::load_run(&path) {
+            Ok(run) => format!(
+                "{:<4} {} file(s)",
+                run["overall_result"].as_str().unwrap_or("?"),
+                run["total_files"].as_u64().unwrap_or(0)
+            ),
+            Err(e) => e.to_string(),
+        };
+        println!("{}  {}", id, summary);
+    }
+    Ok(true)
+}
+
+fn run_history_diff(dir: &Path, run_a: &str, run_b: &str, latency: report_diff::LatencyThreshold, output_format: &str) -> error::Result<bool> {
+    let path_a = history::resolve_run(dir, run_a)?;
+    let path_b = history::resolve_run(dir, run_b)?;
+    run_report_diff(&path_a, &path_b, latency, "History Diff", output_format)
+}
+
+/// Print the differences between two saved JSON reports as a result
+fn run_report_diff(old_path: &Path, new_path: &Path, latency: report_diff::LatencyThreshold, name: &str, output_format: &str) -> error::Result<bool> {
+    let old = report_diff::load_checks(&history::load_run(old_path)?);
+    let new = report_diff::load_checks(&history::load_run(new_path)?);
+
+    let mut details = report_diff::diff_checks(&old, &new, latency);
+    if details.is_empty() {
+        details.push(CheckDetail::pass(name.to_string(), "No check changed between the reports".to_string(), None));
+    }
+    let result = CheckResult::from_details(details);
+    let reports = format!("{} -> {}", old_path.display(), new_path.display());
+
+    output_result(&result, name, Some(&reports), output_format)?;
+    Ok(result.success)
+}
+
+async fn run_net_check(target: &str, tls: bool, output_format: &str) -> error::Result<bool> {
+    let checker = NetChecker::new(target.to_string(), tls);
+    let result = finish_result(checker.component_name(), checker.run().await);
+
+    output_result(&result, checker.component_name(), None, output_format)?;
+    Ok(result.success)
+}
+
+async fn run_net_bench(target: &str, duration_secs: u64, output_format: &str) -> error::Result<bool> {
+    let checker = NetBenchChecker::new(target.to_string(), Duration::from_secs(duration_secs));
+    let result = finish_result(checker.component_name(), checker.run().await);
+
+    output_result(&result, checker.component_name(), None, output_format)?;
+    Ok(result.success)
+}
+
+fn output_report(report: &Report, output_format: &str) -> error::Result<()> {
+    use snafu::ResultExt;
+
+    let report = Report {
+        sections: report
+            .sections
+            .iter()
+            .map(|section| ReportSection {
+                result: redact::redact_result(&section.result),
+                ..section.clone()
+            })
+            .collect(),
+    };
+    for section in &report.sections {
+        sink::record(&section.component, Some(&section.config_file), &section.result);
+    }
+
+    match output_format {
+        "json" => {
+            let json_output = report.to_json().context(error::JsonSerializationSnafu {
+                message: "Failed to serialize report to JSON".to_string(),
+            })?;
+            println!("{}", json_output);
+        }
+        _ => report.print_human_readable(),
+    }
+    Ok(())
+}
+
 fn output_result(result: &CheckResult, component_name: &str, config_file: Option<&str>, output_format: &str) -> error::Result<()> {
     use snafu::ResultExt;
 
+    let result = redact::redact_result(result);
+    sink::record(component_name, config_file, &result);
+
     match output_format {
         "json" => {
             let json_output = result.to_json(component_name, config_file)
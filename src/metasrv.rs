@@ -10,9 +10,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::{CheckDetail, CheckResult, ComponentChecker};
+mod etcd;
+mod rds;
+mod servers;
+
+use crate::common::{CheckDetail, CheckResult, ComponentChecker, Dependency};
 use crate::config::MetasrvConfig;
+use crate::credentials;
 use crate::error;
+use crate::export_metrics;
+use crate::lint;
+use crate::logging;
+use crate::mode;
+use crate::network::{self, RttStats};
+use crate::otlp;
+use crate::profile::Profile;
+use crate::run_trace;
+use crate::tls;
 use async_trait::async_trait;
 use common_meta::kv_backend::etcd::EtcdStore;
 use common_meta::kv_backend::KvBackendRef;
@@ -21,13 +35,18 @@ use itertools::Itertools;
 use snafu::{ensure, OptionExt, ResultExt};
 use sqlx::{MySqlPool, PgPool};
 use std::fmt::{Debug, Formatter};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 const TEST_KEY_VALUE: &str = "/__stepstone_test";
 
+/// p95 latency above which a metadata backend benchmark phase is reported as a warning
+const BENCH_P95_WARN: Duration = Duration::from_millis(50);
+
 /// Metasrv component checker
 pub struct MetasrvChecker {
     config: MetasrvConfig,
+    profile: Profile,
+    sql_connections: usize,
 }
 
 impl Debug for MetasrvChecker {
@@ -49,21 +68,110 @@ impl Debug for EtcdChecker {
 }
 
 impl MetasrvChecker {
-    /// Create a new MetasrvChecker with the given configuration
-    pub fn new(config: MetasrvConfig) -> Self {
-        Self { config }
+    /// Create a new MetasrvChecker running the checks of `profile`
+    pub fn new(config: MetasrvConfig, profile: Profile, sql_connections: usize) -> Self {
+        Self { config, profile, sql_connections }
+    }
+
+    /// Check the metadata backend the config selects
+    async fn check_backend(&self) -> CheckResult {
+        // The legacy `use_memory_store` flag overrides `backend`
+        let backend = if self.config.use_memory_store == Some(true) {
+            "memory_store"
+        } else {
+            self.config.backend.as_str()
+        };
+
+        match backend {
+            "etcd_store" => self.check_etcd_new().await,
+            "postgres_store" => self.check_postgres_new().await,
+            "mysql_store" => self.check_mysql_new().await,
+            "memory_store" => self.check_memory_store(),
+            unknown => CheckResult::failure(
+                format!("Unknown store type: {}", unknown),
+                vec![CheckDetail::fail(
+                    "Store Type".to_string(),
+                    format!("Unsupported store type: {}", unknown),
+                    None,
+                    Some("Use one of: etcd_store, postgres_store, mysql_store, memory_store".to_string()),
+                )],
+            ),
+        }
     }
 
     /// Check etcd store using new config format
     async fn check_etcd_new(&self) -> CheckResult {
         let mut details = Vec::new();
+
+        // Check each etcd member once, however often it is listed
+        let (duplicates, store_addrs) = network::dedup_addresses("store_addrs", &self.config.store_addrs).await;
+        details.extend(duplicates);
+        let config = MetasrvConfig { store_addrs, ..self.config.clone() };
+
+        let options = match etcd::connect_options(&config) {
+            Ok(options) => options,
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "Etcd Client Configuration".to_string(),
+                    e.to_string(),
+                    None,
+                    Some("Check backend_tls file paths and backend_username/backend_password".to_string()),
+                ));
+                return CheckResult::from_details(details);
+            }
+        };
+
+        // Resolve every endpoint first so DNS failures are reported separately
+        for (index, addr) in config.store_addrs.iter().enumerate() {
+            if let Ok((host, port)) = network::parse_address(addr) {
+                let (dns_detail, _) = network::check_dns_resolution(
+                    format!("Etcd DNS Resolution {}", index + 1),
+                    &host,
+                    port,
+                ).await;
+                details.push(dns_detail);
+            }
+        }
+
+        // Query each member on its own before exercising the cluster as a whole
+        let statuses = etcd::check_members(&config, &options, &mut details).await;
+        etcd::check_cluster(&config, &options, &statuses, &mut details).await;
+        etcd::check_key_prefix(&config, &options, &mut details).await;
+        // Leases and transactions write keys, which the quick profile leaves alone
+        if self.profile.storage_operations {
+            etcd::check_lease(&config, &options, &mut details).await;
+            etcd::check_txn(&config, &options, &mut details).await;
+        }
+
+        // Performance test if requested
+        if self.profile.performance {
+            etcd::check_performance(&config, &options, &mut details).await;
+        }
+
         let start = Instant::now();
 
         // Connect to etcd and test basic operations
-        match EtcdStore::with_endpoints(&self.config.store_addrs, 128).await {
+        match etcd::connect_store(&config, options).await {
+            // Without writes, reading the test key is what proves connectivity
+            Ok(store) if !self.profile.storage_operations => {
+                let test_key = format!("{}__stepstone_test", config.store_key_prefix.as_deref().unwrap_or(""));
+                match store.get(test_key.as_bytes()).await {
+                    Ok(_) => details.push(CheckDetail::pass(
+                        "Etcd Connection".to_string(),
+                        format!("Successfully connected to etcd endpoints: {:?}", config.store_addrs),
+                        Some(start.elapsed()),
+                    )),
+                    Err(e) => details.push(CheckDetail::fail(
+                        "Etcd Connection".to_string(),
+                        format!("Failed to connect to etcd: {}", e),
+                        Some(start.elapsed()),
+                        Some("Check etcd service status and network connectivity".to_string()),
+                    )),
+                }
+            }
             Ok(store) => {
                 // Test basic operations immediately to verify real connectivity
-                let test_key = format!("{}__stepstone_test", self.config.store_key_prefix.as_deref().unwrap_or(""));
+                let test_key = format!("{}__stepstone_test", config.store_key_prefix.as_deref().unwrap_or(""));
                 let test_value = b"stepstone_test_value";
 
                 // PUT operation (this will test real connectivity)
@@ -75,7 +183,7 @@ impl MetasrvChecker {
                     Ok(_) => {
                         details.push(CheckDetail::pass(
                             "Etcd Connection".to_string(),
-                            format!("Successfully connected to etcd endpoints: {:?}", self.config.store_addrs),
+                            format!("Successfully connected to etcd endpoints: {:?}", config.store_addrs),
                             Some(start.elapsed()),
                         ));
                         details.push(CheckDetail::pass(
@@ -162,48 +270,124 @@ impl MetasrvChecker {
         CheckResult::from_details(details)
     }
 
+    /// Check memory store, which needs no external dependency but loses metadata on restart
+    fn check_memory_store(&self) -> CheckResult {
+        let mut details = vec![
+            CheckDetail::pass(
+                "Memory Store".to_string(),
+                "Memory store is always available".to_string(),
+                None,
+            ),
+            CheckDetail::warning(
+                "Memory Store Durability".to_string(),
+                "Metadata is lost when metasrv restarts, including table routes for every datanode".to_string(),
+                None,
+                Some("Use memory_store only for single-datanode test deployments; use etcd_store, postgres_store or mysql_store otherwise".to_string()),
+            ),
+        ];
+
+        if self.config.enable_region_failover == Some(true) {
+            details.push(CheckDetail::warning(
+                "Memory Store Region Failover".to_string(),
+                "enable_region_failover is set, but failover state kept in memory does not survive a metasrv restart".to_string(),
+                None,
+                Some("Use a persistent backend before enabling region failover".to_string()),
+            ));
+        }
+
+        if self.config.use_memory_store == Some(true) && self.config.backend != "memory_store" {
+            details.push(CheckDetail::warning(
+                "Memory Store Override".to_string(),
+                format!("use_memory_store = true overrides backend = \"{}\"", self.config.backend),
+                None,
+                Some("Remove use_memory_store, or set backend = \"memory_store\" explicitly".to_string()),
+            ));
+        }
+
+        CheckResult::from_details(details)
+    }
+
     /// Check PostgreSQL store using new config format
     async fn check_postgres_new(&self) -> CheckResult {
         let mut details = Vec::new();
-        let start = Instant::now();
 
         if let Some(addr) = self.config.store_addrs.first() {
-            match PgPool::connect(addr).await {
+            // Resolve the database host so DNS failures are reported separately
+            if let Some(path) = rds::socket_path(addr) {
+                details.push(rds::socket_detail("PostgreSQL Socket", path));
+            } else if let Ok((host, port)) = network::parse_database_url(addr, 5432) {
+                let (dns_detail, _) = network::check_dns_resolution(
+                    "PostgreSQL DNS Resolution".to_string(),
+                    &host,
+                    port,
+                ).await;
+                details.push(dns_detail);
+            }
+
+            let start = Instant::now();
+            match rds::connect_postgres(&self.config, addr).await {
                 Ok(pool) => {
                     details.push(CheckDetail::pass(
                         "PostgreSQL Connection".to_string(),
                         format!("Successfully connected to PostgreSQL: {}", addr),
                         Some(start.elapsed()),
                     ));
+                    if self.config.backend_tls.is_some() {
+                        details.push(rds::postgres_tls_detail(&pool).await);
+                    }
+
+                    // Check the schema holding the metadata table
+                    let schema_name = self.config.meta_schema_name.as_deref();
+                    if let Some(schema_name) = schema_name {
+                        if !self.check_postgres_schema(&pool, schema_name, &mut details).await {
+                            return CheckResult::from_details(details);
+                        }
+                    }
 
                     // Check metadata table
                     let table_name = self.config.meta_table_name.as_deref().unwrap_or("greptime_metasrv");
-                    let query = format!(
-                        "SELECT EXISTS (SELECT FROM information_schema.tables WHERE table_name = '{}')",
-                        table_name
-                    );
+                    let qualified_name = self.postgres_table_name();
+                    let sql = "SELECT EXISTS (SELECT FROM information_schema.tables \
+                               WHERE table_schema = COALESCE($1::text, current_schema()) AND table_name = $2)";
+                    let query = sqlx::query_scalar::<_, bool>(sql).bind(schema_name).bind(table_name);
 
-                    match sqlx::query_scalar::<_, bool>(&query).fetch_one(&pool).await {
+                    match run_trace::traced("sqlx.query", sql, query.fetch_one(&pool)).await {
                         Ok(exists) => {
                             if exists {
                                 details.push(CheckDetail::pass(
                                     "Metadata Table Existence".to_string(),
-                                    format!("Table '{}' exists", table_name),
+                                    format!("Table {} exists", qualified_name),
                                     None,
                                 ));
 
                                 // Test read/write permissions on existing table
-                                self.test_postgres_permissions(&pool, table_name, &mut details).await;
+                                if self.profile.storage_operations {
+                                    self.test_postgres_permissions(&pool, &qualified_name, &mut details).await;
+                                }
                             } else {
                                 details.push(CheckDetail::warning(
                                     "Metadata Table Existence".to_string(),
-                                    format!("Table '{}' does not exist, will be created automatically", table_name),
+                                    format!("Table {} does not exist, will be created automatically", qualified_name),
                                     None,
                                     Some("This is normal for first-time setup".to_string()),
                                 ));
 
                                 // Test table creation permissions
-                                self.test_postgres_create_permissions(&pool, table_name, &mut details).await;
+                                if self.profile.storage_operations {
+                                    self.test_postgres_create_permissions(&pool, &qualified_name, &mut details).await;
+                                }
+                            }
+
+                            // Check the server can take metasrv's connections plus headroom
+                            let available = rds::postgres_connection_limit(&pool, self.sql_connections, &mut details).await;
+                            if let Ok(options) = rds::postgres_options(&self.config, addr) {
+                                let item = "PostgreSQL Concurrent Connections";
+                                rds::connection_test(item, &options, addr, self.sql_connections, available, &mut details).await;
+                            }
+
+                            // Performance test if requested
+                            if self.profile.performance {
+                                rds::postgres_benchmark(&pool, &mut details).await;
                             }
                         }
                         Err(e) => {
@@ -221,7 +405,7 @@ impl MetasrvChecker {
                         "PostgreSQL Connection".to_string(),
                         format!("Failed to connect to PostgreSQL: {}", e),
                         Some(start.elapsed()),
-                        Some("Check connection string, network connectivity, and database availability".to_string()),
+                        Some("Check connection string, network connectivity, database availability, and backend_tls certificate paths".to_string()),
                     ));
                 }
             }
@@ -240,25 +424,39 @@ impl MetasrvChecker {
     /// Check MySQL store using new config format
     async fn check_mysql_new(&self) -> CheckResult {
         let mut details = Vec::new();
-        let start = Instant::now();
 
         if let Some(addr) = self.config.store_addrs.first() {
-            match MySqlPool::connect(addr).await {
+            // Resolve the database host so DNS failures are reported separately
+            if let Some(path) = rds::socket_path(addr) {
+                details.push(rds::socket_detail("MySQL Socket", path));
+            } else if let Ok((host, port)) = network::parse_database_url(addr, 3306) {
+                let (dns_detail, _) = network::check_dns_resolution(
+                    "MySQL DNS Resolution".to_string(),
+                    &host,
+                    port,
+                ).await;
+                details.push(dns_detail);
+            }
+
+            let start = Instant::now();
+            match rds::connect_mysql(&self.config, addr).await {
                 Ok(pool) => {
                     details.push(CheckDetail::pass(
                         "MySQL Connection".to_string(),
                         format!("Successfully connected to MySQL: {}", addr),
                         Some(start.elapsed()),
                     ));
+                    if self.config.backend_tls.is_some() {
+                        details.push(rds::mysql_tls_detail(&pool).await);
+                    }
 
-                    // Check metadata table
+                    // Check metadata table in the connection's database
                     let table_name = self.config.meta_table_name.as_deref().unwrap_or("greptime_metasrv");
-                    let query = format!(
-                        "SELECT EXISTS (SELECT * FROM information_schema.tables WHERE table_name = '{}')",
-                        table_name
-                    );
+                    let sql = "SELECT EXISTS (SELECT * FROM information_schema.tables \
+                               WHERE table_schema = DATABASE() AND table_name = ?)";
+                    let query = sqlx::query_scalar::<_, bool>(sql).bind(table_name);
 
-                    match sqlx::query_scalar::<_, bool>(&query).fetch_one(&pool).await {
+                    match run_trace::traced("sqlx.query", sql, query.fetch_one(&pool)).await {
                         Ok(exists) => {
                             if exists {
                                 details.push(CheckDetail::pass(
@@ -266,6 +464,12 @@ impl MetasrvChecker {
                                     format!("Table '{}' exists", table_name),
                                     None,
                                 ));
+
+                                // Test read/write permissions on existing table
+                                if self.profile.storage_operations {
+                                    let quoted_name = rds::quote_mysql_identifier(table_name);
+                                    self.test_mysql_permissions(&pool, &quoted_name, &mut details).await;
+                                }
                             } else {
                                 details.push(CheckDetail::warning(
                                     "Metadata Table Existence".to_string(),
@@ -273,6 +477,26 @@ impl MetasrvChecker {
                                     None,
                                     Some("This is normal for first-time setup".to_string()),
                                 ));
+
+                                // Test table creation permissions
+                                if self.profile.storage_operations {
+                                    self.test_mysql_create_permissions(&pool, &mut details).await;
+                                }
+                            }
+
+                            // Test the named lock metasrv uses for leader election
+                            self.test_mysql_election_lock(&pool, &mut details).await;
+
+                            // Check the server can take metasrv's connections plus headroom
+                            let available = rds::mysql_connection_limit(&pool, self.sql_connections, &mut details).await;
+                            if let Ok(options) = rds::mysql_options(&self.config, addr) {
+                                let item = "MySQL Concurrent Connections";
+                                rds::connection_test(item, &options, addr, self.sql_connections, available, &mut details).await;
+                            }
+
+                            // Performance test if requested
+                            if self.profile.performance {
+                                rds::mysql_benchmark(&pool, &mut details).await;
                             }
                         }
                         Err(e) => {
@@ -290,7 +514,7 @@ impl MetasrvChecker {
                         "MySQL Connection".to_string(),
                         format!("Failed to connect to MySQL: {}", e),
                         Some(start.elapsed()),
-                        Some("Check connection string, network connectivity, and database availability".to_string()),
+                        Some("Check connection string, network connectivity, database availability, and backend_tls certificate paths".to_string()),
                     ));
                 }
             }
@@ -306,22 +530,297 @@ impl MetasrvChecker {
         CheckResult::from_details(details)
     }
 
-    /// Test PostgreSQL read/write permissions on existing table
+    /// Test MySQL read/write permissions on the existing metadata table, given as a quoted identifier
+    async fn test_mysql_permissions(&self, pool: &MySqlPool, table_name: &str, details: &mut Vec<CheckDetail>) {
+        // Test SELECT permission
+        let select_query = format!("SELECT COUNT(*) FROM {}", table_name);
+        match run_trace::traced("sqlx.query", &select_query, sqlx::query_scalar::<_, i64>(&select_query).fetch_one(pool)).await {
+            Ok(_) => {
+                details.push(CheckDetail::pass(
+                    "MySQL Read Permission".to_string(),
+                    format!("Successfully read from table {}", table_name),
+                    None,
+                ));
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "MySQL Read Permission".to_string(),
+                    format!("Failed to read from table {}: {}", table_name, e),
+                    None,
+                    Some("Grant SELECT permission on the metadata table".to_string()),
+                ));
+                return; // If we can't read, we probably can't write either
+            }
+        }
+
+        // Test INSERT permission with a test record, using metasrv's k/v columns
+        let test_key = "stepstone_test_key";
+        let test_value = "stepstone_test_value";
+        let insert_query = format!(
+            "INSERT INTO {} (k, v) VALUES (?, ?) ON DUPLICATE KEY UPDATE v = VALUES(v)",
+            table_name
+        );
+
+        match run_trace::traced("sqlx.query", &insert_query, sqlx::query(&insert_query).bind(test_key).bind(test_value).execute(pool)).await {
+            Ok(_) => {
+                details.push(CheckDetail::pass(
+                    "MySQL Write Permission".to_string(),
+                    format!("Successfully wrote to table {}", table_name),
+                    None,
+                ));
+
+                // Clean up test record
+                let delete_query = format!("DELETE FROM {} WHERE k = ?", table_name);
+                let _ = run_trace::traced("sqlx.query", &delete_query, sqlx::query(&delete_query).bind(test_key).execute(pool)).await;
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "MySQL Write Permission".to_string(),
+                    format!("Failed to write to table {}: {}", table_name, e),
+                    None,
+                    Some("Grant INSERT/UPDATE/DELETE permission on the metadata table".to_string()),
+                ));
+            }
+        }
+    }
+
+    /// Test MySQL table creation permissions with a scratch table
+    async fn test_mysql_create_permissions(&self, pool: &MySqlPool, details: &mut Vec<CheckDetail>) {
+        let scratch_table = "stepstone_create_test";
+        let create_query = format!(
+            "CREATE TABLE IF NOT EXISTS {} (k VARBINARY(3072) PRIMARY KEY, v BLOB)",
+            scratch_table
+        );
+
+        match run_trace::traced("sqlx.query", &create_query, sqlx::query(&create_query).execute(pool)).await {
+            Ok(_) => {
+                details.push(CheckDetail::pass(
+                    "MySQL Create Permission".to_string(),
+                    "Successfully created a test table".to_string(),
+                    None,
+                ));
+
+                let drop_query = format!("DROP TABLE IF EXISTS {}", scratch_table);
+                let _ = run_trace::traced("sqlx.query", &drop_query, sqlx::query(&drop_query).execute(pool)).await;
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "MySQL Create Permission".to_string(),
+                    format!("Failed to create a test table: {}", e),
+                    None,
+                    Some("Grant CREATE and DROP permission on the database".to_string()),
+                ));
+            }
+        }
+    }
+
+    /// Test GET_LOCK/RELEASE_LOCK, the election primitive metasrv uses on MySQL
+    async fn test_mysql_election_lock(&self, pool: &MySqlPool, details: &mut Vec<CheckDetail>) {
+        let lock_name = "stepstone_election_test";
+        let start = Instant::now();
+
+        // Named locks belong to a session, so acquire and release on the same connection
+        let mut conn = match pool.acquire().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "MySQL Election Lock".to_string(),
+                    format!("Failed to acquire a connection: {}", e),
+                    Some(start.elapsed()),
+                    None,
+                ));
+                return;
+            }
+        };
+
+        let sql = "SELECT GET_LOCK(?, 5)";
+        let acquired = run_trace::traced("sqlx.query", sql, sqlx::query_scalar::<_, Option<i64>>(sql).bind(lock_name).fetch_one(&mut *conn)).await;
+
+        match acquired {
+            Ok(Some(1)) => {
+                let sql = "SELECT RELEASE_LOCK(?)";
+                let released = run_trace::traced("sqlx.query", sql, sqlx::query_scalar::<_, Option<i64>>(sql).bind(lock_name).fetch_one(&mut *conn)).await;
+                match released {
+                    Ok(Some(1)) => {
+                        details.push(CheckDetail::pass(
+                            "MySQL Election Lock".to_string(),
+                            "GET_LOCK and RELEASE_LOCK succeeded".to_string(),
+                            Some(start.elapsed()),
+                        ));
+                    }
+                    Ok(_) => {
+                        details.push(CheckDetail::fail(
+                            "MySQL Election Lock".to_string(),
+                            "RELEASE_LOCK did not release the lock held by this session".to_string(),
+                            Some(start.elapsed()),
+                            Some("Check for proxies that do not pin sessions to one connection".to_string()),
+                        ));
+                    }
+                    Err(e) => {
+                        details.push(CheckDetail::fail(
+                            "MySQL Election Lock".to_string(),
+                            format!("RELEASE_LOCK failed: {}", e),
+                            Some(start.elapsed()),
+                            None,
+                        ));
+                    }
+                }
+            }
+            Ok(_) => {
+                details.push(CheckDetail::fail(
+                    "MySQL Election Lock".to_string(),
+                    "GET_LOCK did not acquire a free lock within 5s".to_string(),
+                    Some(start.elapsed()),
+                    Some("Named locks may be unsupported by this server or proxy; metasrv cannot elect a leader".to_string()),
+                ));
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "MySQL Election Lock".to_string(),
+                    format!("GET_LOCK failed: {}", e),
+                    Some(start.elapsed()),
+                    Some("Metasrv uses GET_LOCK for leader election; check that named locks are allowed".to_string()),
+                ));
+            }
+        }
+    }
+
+    /// The metadata table name, qualified with `meta_schema_name` when set
+    fn postgres_table_name(&self) -> String {
+        let table_name = self.config.meta_table_name.as_deref().unwrap_or("greptime_metasrv");
+        match self.config.meta_schema_name.as_deref() {
+            Some(schema_name) => format!(
+                "{}.{}",
+                rds::quote_postgres_identifier(schema_name),
+                rds::quote_postgres_identifier(table_name)
+            ),
+            None => rds::quote_postgres_identifier(table_name),
+        }
+    }
+
+    /// Create `meta_schema_name` on the PostgreSQL backend, for `--fix`
+    pub(crate) async fn create_postgres_schema(&self) -> Result<(), String> {
+        let schema_name = self.config.meta_schema_name.as_deref().ok_or("meta_schema_name is not set")?;
+        self.execute_postgres(&format!("CREATE SCHEMA IF NOT EXISTS {}", rds::quote_postgres_identifier(schema_name)))
+            .await
+    }
+
+    /// Create the metadata table with the layout metasrv itself uses, for `--fix`
+    pub(crate) async fn create_postgres_table(&self) -> Result<(), String> {
+        self.execute_postgres(&format!(
+            "CREATE TABLE IF NOT EXISTS {}(k bytea PRIMARY KEY, v bytea)",
+            self.postgres_table_name()
+        ))
+        .await
+    }
+
+    async fn execute_postgres(&self, statement: &str) -> Result<(), String> {
+        let addr = self.config.store_addrs.first().ok_or("No PostgreSQL address configured")?;
+        let pool = rds::connect_postgres(&self.config, addr).await.map_err(|e| e.to_string())?;
+        run_trace::traced("sqlx.query", statement, sqlx::query(statement).execute(&pool)).await.map_err(|e| e.to_string())?;
+        pool.close().await;
+        Ok(())
+    }
+
+    /// Check that the configured schema exists and the user holds USAGE and CREATE on it.
+    /// Returns false when the metadata table cannot be used at all.
+    async fn check_postgres_schema(&self, pool: &PgPool, schema_name: &str, details: &mut Vec<CheckDetail>) -> bool {
+        let sql = "SELECT EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = $1)";
+        let exists = run_trace::traced("sqlx.query", sql, sqlx::query_scalar::<_, bool>(sql).bind(schema_name).fetch_one(pool)).await;
+
+        match exists {
+            Ok(true) => {
+                details.push(CheckDetail::pass(
+                    "Metadata Schema Existence".to_string(),
+                    format!("Schema '{}' exists", schema_name),
+                    None,
+                ));
+            }
+            Ok(false) => {
+                details.push(CheckDetail::fail(
+                    "Metadata Schema Existence".to_string(),
+                    format!("Schema '{}' does not exist", schema_name),
+                    None,
+                    Some(format!("Create it with: CREATE SCHEMA {};", rds::quote_postgres_identifier(schema_name))),
+                ));
+                return false;
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "Metadata Schema Check".to_string(),
+                    format!("Failed to check schema existence: {}", e),
+                    None,
+                    Some("Check database permissions".to_string()),
+                ));
+                return false;
+            }
+        }
+
+        let sql = "SELECT has_schema_privilege($1, 'USAGE'), has_schema_privilege($1, 'CREATE')";
+        let query = sqlx::query_as::<_, (bool, bool)>(sql).bind(schema_name);
+        let privileges = run_trace::traced("sqlx.query", sql, query.fetch_one(pool)).await;
+
+        match privileges {
+            Ok((true, true)) => {
+                details.push(CheckDetail::pass(
+                    "Metadata Schema Privileges".to_string(),
+                    format!("USAGE and CREATE granted on schema '{}'", schema_name),
+                    None,
+                ));
+                true
+            }
+            Ok((true, false)) => {
+                details.push(CheckDetail::warning(
+                    "Metadata Schema Privileges".to_string(),
+                    format!("USAGE granted on schema '{}', but CREATE is missing", schema_name),
+                    None,
+                    Some(format!(
+                        "Metasrv cannot create its metadata table; grant it with: GRANT CREATE ON SCHEMA {} TO CURRENT_USER;",
+                        rds::quote_postgres_identifier(schema_name)
+                    )),
+                ));
+                true
+            }
+            Ok((false, _)) => {
+                details.push(CheckDetail::fail(
+                    "Metadata Schema Privileges".to_string(),
+                    format!("USAGE is not granted on schema '{}'", schema_name),
+                    None,
+                    Some(format!(
+                        "Grant it with: GRANT USAGE, CREATE ON SCHEMA {} TO CURRENT_USER;",
+                        rds::quote_postgres_identifier(schema_name)
+                    )),
+                ));
+                false
+            }
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "Metadata Schema Privileges".to_string(),
+                    format!("Failed to check schema privileges: {}", e),
+                    None,
+                    Some("Check database permissions".to_string()),
+                ));
+                false
+            }
+        }
+    }
+
+    /// Test PostgreSQL read/write permissions on existing table, given as a quoted identifier
     async fn test_postgres_permissions(&self, pool: &PgPool, table_name: &str, details: &mut Vec<CheckDetail>) {
         // Test SELECT permission
         let select_query = format!("SELECT COUNT(*) FROM {}", table_name);
-        match sqlx::query_scalar::<_, i64>(&select_query).fetch_one(pool).await {
+        match run_trace::traced("sqlx.query", &select_query, sqlx::query_scalar::<_, i64>(&select_query).fetch_one(pool)).await {
             Ok(_) => {
                 details.push(CheckDetail::pass(
                     "PostgreSQL Read Permission".to_string(),
-                    format!("Successfully read from table '{}'", table_name),
+                    format!("Successfully read from table {}", table_name),
                     None,
                 ));
             }
             Err(e) => {
                 details.push(CheckDetail::fail(
                     "PostgreSQL Read Permission".to_string(),
-                    format!("Failed to read from table '{}': {}", table_name, e),
+                    format!("Failed to read from table {}: {}", table_name, e),
                     None,
                     Some("Grant SELECT permission on the metadata table".to_string()),
                 ));
@@ -337,27 +836,22 @@ impl MetasrvChecker {
             table_name
         );
 
-        match sqlx::query(&insert_query)
-            .bind(test_key)
-            .bind(test_value)
-            .execute(pool)
-            .await
-        {
+        match run_trace::traced("sqlx.query", &insert_query, sqlx::query(&insert_query).bind(test_key).bind(test_value).execute(pool)).await {
             Ok(_) => {
                 details.push(CheckDetail::pass(
                     "PostgreSQL Write Permission".to_string(),
-                    format!("Successfully wrote to table '{}'", table_name),
+                    format!("Successfully wrote to table {}", table_name),
                     None,
                 ));
 
                 // Clean up test record
                 let delete_query = format!("DELETE FROM {} WHERE key = $1", table_name);
-                let _ = sqlx::query(&delete_query).bind(test_key).execute(pool).await;
+                let _ = run_trace::traced("sqlx.query", &delete_query, sqlx::query(&delete_query).bind(test_key).execute(pool)).await;
             }
             Err(e) => {
                 details.push(CheckDetail::fail(
                     "PostgreSQL Write Permission".to_string(),
-                    format!("Failed to write to table '{}': {}", table_name, e),
+                    format!("Failed to write to table {}: {}", table_name, e),
                     None,
                     Some("Grant INSERT/UPDATE permission on the metadata table".to_string()),
                 ));
@@ -365,7 +859,7 @@ impl MetasrvChecker {
         }
     }
 
-    /// Test PostgreSQL table creation permissions
+    /// Test PostgreSQL table creation permissions, given the table as a quoted identifier
     async fn test_postgres_create_permissions(&self, pool: &PgPool, table_name: &str, details: &mut Vec<CheckDetail>) {
         let create_query = format!(
             "CREATE TABLE IF NOT EXISTS {} (
@@ -376,11 +870,11 @@ impl MetasrvChecker {
             table_name
         );
 
-        match sqlx::query(&create_query).execute(pool).await {
+        match run_trace::traced("sqlx.query", &create_query, sqlx::query(&create_query).execute(pool)).await {
             Ok(_) => {
                 details.push(CheckDetail::pass(
                     "PostgreSQL Create Permission".to_string(),
-                    format!("Successfully created/verified table '{}'", table_name),
+                    format!("Successfully created/verified table {}", table_name),
                     None,
                 ));
 
@@ -390,7 +884,7 @@ impl MetasrvChecker {
             Err(e) => {
                 details.push(CheckDetail::fail(
                     "PostgreSQL Create Permission".to_string(),
-                    format!("Failed to create table '{}': {}", table_name, e),
+                    format!("Failed to create table {}: {}", table_name, e),
                     None,
                     Some("Grant CREATE permission on the database/schema".to_string()),
                 ));
@@ -402,33 +896,57 @@ impl MetasrvChecker {
 #[async_trait]
 impl ComponentChecker for MetasrvChecker {
     async fn check(&self) -> CheckResult {
-        match self.config.backend.as_str() {
-            "etcd_store" => self.check_etcd_new().await,
-            "postgres_store" => self.check_postgres_new().await,
-            "mysql_store" => self.check_mysql_new().await,
-            "memory_store" => CheckResult::success(
-                "Memory store requires no external dependencies".to_string(),
-                vec![CheckDetail::pass(
-                    "Memory Store".to_string(),
-                    "Memory store is always available".to_string(),
-                    None,
-                )],
-            ),
-            unknown => CheckResult::failure(
-                format!("Unknown store type: {}", unknown),
-                vec![CheckDetail::fail(
-                    "Store Type".to_string(),
-                    format!("Unsupported store type: {}", unknown),
-                    None,
-                    Some("Use one of: etcd_store, postgres_store, mysql_store, memory_store".to_string()),
-                )],
-            ),
+        let store_result = self.check_backend().await;
+
+        // Validate relationships between config fields
+        let mut extra_details = lint::lint_metasrv(&self.config);
+        extra_details.extend(credentials::check_backend_credentials(&self.config));
+        extra_details.extend(tls::check_metasrv_tls(&self.config));
+        extra_details.extend(logging::check_log_dir(self.config.logging.as_ref()));
+        extra_details.extend(export_metrics::check_remote_write(self.config.export_metrics.as_ref()).await);
+        extra_details.extend(otlp::check_otlp_tracing(self.config.logging.as_ref()).await);
+        // Configs without server sections, such as the one `stepstone etcd` builds, have no
+        // servers of their own to check
+        if self.config.grpc.is_some() || self.config.http.is_some() {
+            extra_details.extend(self.check_servers(mode::current()).await);
+        }
+
+        // Check gRPC advertise address
+        if let Some(grpc_config) = &self.config.grpc {
+            if let Some(detail) = network::check_grpc_advertise_address(grpc_config) {
+                extra_details.push(detail);
+            }
+
+            if self.profile.probe_advertise {
+                if let Some(server_addr) = grpc_config.server_addr.as_deref().or(grpc_config.addr.as_deref()) {
+                    extra_details.push(network::probe_advertise_address(server_addr).await);
+                }
+            }
+        }
+
+        if extra_details.is_empty() {
+            store_result
+        } else {
+            let mut details = store_result.details;
+            details.extend(extra_details);
+            CheckResult::from_details(details)
         }
     }
 
     fn component_name(&self) -> &'static str {
         "Metasrv"
     }
+
+    fn dependencies(&self) -> &'static [Dependency] {
+        &[Dependency::Backend]
+    }
+
+    async fn check_dependency(&self, dependency: Dependency) -> Option<CheckResult> {
+        match dependency {
+            Dependency::Backend => Some(self.check_backend().await),
+            Dependency::Metasrv | Dependency::Storage => None,
+        }
+    }
 }
 
 impl EtcdChecker {
@@ -491,9 +1009,73 @@ impl EtcdChecker {
     }
 }
 
+/// Summarize one metadata backend benchmark phase as throughput plus latency percentiles
+fn bench_detail(item: &str, samples: &[Duration], elapsed: Duration, slow_suggestion: &str) -> CheckDetail {
+    let Some(stats) = RttStats::from_samples(samples) else {
+        return CheckDetail::fail(
+            item.to_string(),
+            "No operation succeeded".to_string(),
+            Some(elapsed),
+            Some("Check write permissions on the metadata backend".to_string()),
+        );
+    };
+
+    let ops_per_second = stats.samples as f64 / elapsed.as_secs_f64();
+    let message = format!(
+        "{} ops, {:.1} ops/s, latency min/avg/p95/max {:.2}/{:.2}/{:.2}/{:.2} ms",
+        stats.samples,
+        ops_per_second,
+        stats.min.as_secs_f64() * 1000.0,
+        stats.avg.as_secs_f64() * 1000.0,
+        stats.p95.as_secs_f64() * 1000.0,
+        stats.max.as_secs_f64() * 1000.0,
+    );
+    if stats.p95 > BENCH_P95_WARN {
+        CheckDetail::warning(item.to_string(), message, Some(elapsed), Some(slow_suggestion.to_string()))
+    } else {
+        CheckDetail::pass(item.to_string(), message, Some(elapsed))
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{bench_detail, MetasrvChecker};
+    use crate::common::{CheckStatus, ComponentChecker};
+    use crate::config::MetasrvConfig;
     use crate::metasrv::EtcdChecker;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bench_detail() {
+        let elapsed = Duration::from_secs(1);
+        assert_eq!(bench_detail("Bench", &[], elapsed, "slow").status, CheckStatus::Fail);
+
+        let fast = vec![Duration::from_millis(2); 10];
+        let detail = bench_detail("Bench", &fast, elapsed, "slow");
+        assert_eq!(detail.status, CheckStatus::Pass);
+        assert!(detail.message.contains("10 ops"));
+
+        let slow = vec![Duration::from_millis(200); 10];
+        let detail = bench_detail("Bench", &slow, elapsed, "slow");
+        assert_eq!(detail.status, CheckStatus::Warning);
+        assert_eq!(detail.suggestion.as_deref(), Some("slow"));
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_warnings() {
+        let config = MetasrvConfig {
+            backend: "etcd_store".to_string(),
+            use_memory_store: Some(true),
+            enable_region_failover: Some(true),
+            ..Default::default()
+        };
+        let result = MetasrvChecker::new(config, Profile::STANDARD, 0).check().await;
+        let items = result.details.iter().map(|d| d.item.as_str()).collect::<Vec<_>>();
+        assert!(items.contains(&"Memory Store Durability"));
+        assert!(items.contains(&"Memory Store Region Failover"));
+        assert!(items.contains(&"Memory Store Override"));
+        assert!(items.contains(&"Region Failover Prerequisites"));
+    }
 
     #[tokio::test]
     async fn test_connect_to_etcd_failed() {
@@ -0,0 +1,935 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! etcd cluster diagnostics that talk to etcd directly rather than through the KV backend
+
+use crate::common::CheckDetail;
+use crate::config::MetasrvConfig;
+use crate::error;
+use super::bench_detail;
+use common_meta::kv_backend::etcd::EtcdStore;
+use common_meta::kv_backend::KvBackendRef;
+use etcd_client::{
+    AlarmAction, AlarmType, Certificate, Client, ConnectOptions, DeleteOptions, GetOptions,
+    Identity, PutOptions, StatusResponse, TlsOptions, Txn, TxnOp,
+};
+use itertools::Itertools;
+use snafu::{OptionExt, ResultExt};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+/// Timeout applied to each per-member RPC
+const MEMBER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// etcd's default `--quota-backend-bytes`, used when the actual quota cannot be queried
+const DEFAULT_QUOTA_BYTES: i64 = 2 * 1024 * 1024 * 1024;
+
+/// Fraction of the quota above which DB size is reported as a warning
+const DB_SIZE_WARN_RATIO: f64 = 0.8;
+
+/// Metasrv's default `max_txn_ops`
+pub(crate) const DEFAULT_MAX_TXN_OPS: usize = 128;
+
+/// Key prefixes written by GreptimeDB metasrv, relative to `store_key_prefix`
+const GREPTIME_KEY_PREFIXES: &[&str] = &[
+    "__catalog_name/",
+    "__schema_name/",
+    "__table_name/",
+    "__table_info/",
+    "__table_route/",
+    "__dn_table/",
+    "__flow/",
+    "__metasrv_election",
+    "__meta_",
+    "__topic_name/",
+    "__procedure_",
+];
+
+/// Keys written by stepstone itself, ignored when classifying existing data
+const STEPSTONE_KEY_PREFIX: &str = "__stepstone";
+
+/// Maximum number of keys sampled when looking for existing data
+const KEY_SAMPLE_LIMIT: i64 = 1000;
+
+/// Operations issued by each sequential benchmark phase
+const BENCH_SEQUENTIAL_OPS: usize = 200;
+
+/// Suggestion attached to slow etcd benchmark phases
+const ETCD_SLOW_SUGGESTION: &str =
+    "High etcd latency slows metasrv heartbeats; place etcd on low-latency SSDs close to metasrv";
+
+/// Concurrent clients in the concurrent benchmark phase
+const BENCH_CONCURRENCY: usize = 16;
+
+/// Operations issued by each concurrent client
+const BENCH_OPS_PER_CLIENT: usize = 50;
+
+/// TTL requested for the lease test; etcd may round it up to its minimum lease TTL
+const LEASE_TTL_SECS: i64 = 2;
+
+/// Extra time allowed past the granted TTL before an unexpired key is reported
+const LEASE_EXPIRY_GRACE: Duration = Duration::from_secs(3);
+
+/// Longest wait for the leased key to expire, however long a TTL etcd grants
+const LEASE_EXPIRY_MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// Build etcd connect options from the metasrv configuration, applying `backend_tls` and
+/// the optional backend credentials
+pub(crate) fn connect_options(config: &MetasrvConfig) -> error::Result<ConnectOptions> {
+    let mut options = ConnectOptions::new()
+        .with_connect_timeout(MEMBER_TIMEOUT)
+        .with_timeout(MEMBER_TIMEOUT);
+
+    if let Some(tls_config) = &config.backend_tls {
+        let mut tls = TlsOptions::new();
+        if let Some(ca) = &tls_config.ca {
+            let pem = std::fs::read(ca).context(error::FileSystemSnafu {
+                message: format!("Failed to read etcd CA certificate {}", ca),
+            })?;
+            tls = tls.ca_certificate(Certificate::from_pem(pem));
+        }
+        match (&tls_config.cert, &tls_config.key) {
+            (Some(cert), Some(key)) => {
+                let cert_pem = std::fs::read(cert).context(error::FileSystemSnafu {
+                    message: format!("Failed to read etcd client certificate {}", cert),
+                })?;
+                let key_pem = std::fs::read(key).context(error::FileSystemSnafu {
+                    message: format!("Failed to read etcd client key {}", key),
+                })?;
+                tls = tls.identity(Identity::from_pem(cert_pem, key_pem));
+            }
+            (None, None) => {}
+            _ => {
+                return error::InvalidConfigSnafu {
+                    message: "backend_tls requires both cert and key for client authentication",
+                }
+                .fail();
+            }
+        }
+        if let Some(server_name) = &tls_config.server_name {
+            tls = tls.domain_name(server_name);
+        }
+        options = options.with_tls(tls);
+    }
+
+    if let Some(username) = &config.backend_username {
+        let password = config.backend_password.as_ref().context(error::InvalidConfigSnafu {
+            message: "backend_username is set but backend_password is missing",
+        })?;
+        options = options.with_user(username, password);
+    }
+
+    Ok(options)
+}
+
+/// Connect a KV backend to every configured endpoint with the given options
+pub(crate) async fn connect_store(
+    config: &MetasrvConfig,
+    options: ConnectOptions,
+) -> Result<KvBackendRef, etcd_client::Error> {
+    let client = Client::connect(&config.store_addrs, Some(options)).await?;
+    Ok(EtcdStore::with_etcd_client(
+        client,
+        config.max_txn_ops.unwrap_or(DEFAULT_MAX_TXN_OPS),
+    ))
+}
+
+/// Query the status of a single etcd endpoint
+async fn member_status(endpoint: &str, options: ConnectOptions) -> Result<StatusResponse, etcd_client::Error> {
+    let mut client = Client::connect([endpoint], Some(options)).await?;
+    client.status().await
+}
+
+/// Check every etcd endpoint on its own, so a single dead member is not masked by the
+/// others, then report which member the cluster agrees is leader.
+///
+/// Returns the status of each member that answered, keyed by member id.
+pub(crate) async fn check_members(
+    config: &MetasrvConfig,
+    options: &ConnectOptions,
+    details: &mut Vec<CheckDetail>,
+) -> HashMap<u64, StatusResponse> {
+    // Maps reported leader id to the endpoints that reported it
+    let mut leaders: HashMap<u64, Vec<&str>> = HashMap::new();
+    // Maps member id to the endpoint it answered on
+    let mut member_endpoints: HashMap<u64, &str> = HashMap::new();
+    let mut statuses = HashMap::new();
+
+    for (index, endpoint) in config.store_addrs.iter().enumerate() {
+        let item = format!("Etcd Member {}", index + 1);
+        let start = Instant::now();
+        match timeout(MEMBER_TIMEOUT, member_status(endpoint, options.clone())).await {
+            Ok(Ok(status)) => {
+                let member_id = status.header().map(|h| h.member_id()).unwrap_or_default();
+                member_endpoints.insert(member_id, endpoint);
+                leaders.entry(status.leader()).or_default().push(endpoint);
+
+                let role = if member_id != 0 && member_id == status.leader() { ", leader" } else { "" };
+                let message = format!(
+                    "{} is reachable (member {:x}, etcd {}{})",
+                    endpoint,
+                    member_id,
+                    status.version(),
+                    role
+                );
+                if status.errors().is_empty() {
+                    details.push(CheckDetail::pass(item, message, Some(start.elapsed())));
+                } else {
+                    details.push(CheckDetail::warning(
+                        item,
+                        format!("{}, but reports errors: {}", message, status.errors().join("; ")),
+                        Some(start.elapsed()),
+                        Some("Inspect this member's logs; it may be out of sync with the cluster".to_string()),
+                    ));
+                }
+                statuses.insert(member_id, status);
+            }
+            Ok(Err(e)) => {
+                details.push(CheckDetail::fail(
+                    item,
+                    format!("Failed to query status of {}: {}", endpoint, e),
+                    Some(start.elapsed()),
+                    Some("Check that this etcd member is running and reachable; the remaining members can mask its failure".to_string()),
+                ));
+            }
+            Err(_) => {
+                details.push(CheckDetail::fail(
+                    item,
+                    format!("Status request to {} timed out", endpoint),
+                    Some(start.elapsed()),
+                    Some("Check network connectivity to this etcd member".to_string()),
+                ));
+            }
+        }
+    }
+
+    if let Some(detail) = leader_detail(&leaders, &member_endpoints) {
+        details.push(detail);
+    }
+
+    statuses
+}
+
+/// Check cluster-wide health through the maintenance and cluster APIs: member count parity,
+/// quorum, active alarms and DB size against the backend quota.
+pub(crate) async fn check_cluster(
+    config: &MetasrvConfig,
+    options: &ConnectOptions,
+    statuses: &HashMap<u64, StatusResponse>,
+    details: &mut Vec<CheckDetail>,
+) {
+    let start = Instant::now();
+    let mut client = match timeout(
+        MEMBER_TIMEOUT,
+        Client::connect(&config.store_addrs, Some(options.clone())),
+    )
+    .await
+    {
+        Ok(Ok(client)) => client,
+        // Per-member checks already explain why the endpoints are unreachable
+        _ => return,
+    };
+
+    match timeout(MEMBER_TIMEOUT, client.member_list(None)).await {
+        Ok(Ok(response)) => {
+            let total = response.members().len();
+            let healthy = response
+                .members()
+                .iter()
+                .filter(|member| statuses.contains_key(&member.id()))
+                .count();
+            details.push(member_count_detail(total));
+            details.push(quorum_detail(total, healthy, start.elapsed()));
+        }
+        Ok(Err(e)) => details.push(CheckDetail::fail(
+            "Etcd Quorum".to_string(),
+            format!("Failed to list cluster members: {}", e),
+            Some(start.elapsed()),
+            Some("Check that the etcd user is allowed to call the cluster API".to_string()),
+        )),
+        Err(_) => details.push(CheckDetail::fail(
+            "Etcd Quorum".to_string(),
+            "Member list request timed out".to_string(),
+            Some(start.elapsed()),
+            Some("The cluster may have lost quorum".to_string()),
+        )),
+    }
+
+    let start = Instant::now();
+    match timeout(
+        MEMBER_TIMEOUT,
+        client.alarm(AlarmAction::Get, AlarmType::None, None),
+    )
+    .await
+    {
+        Ok(Ok(response)) => {
+            if response.alarms().is_empty() {
+                details.push(CheckDetail::pass(
+                    "Etcd Alarms".to_string(),
+                    "No active alarms".to_string(),
+                    Some(start.elapsed()),
+                ));
+            } else {
+                let alarms = response
+                    .alarms()
+                    .iter()
+                    .map(|alarm| format!("{:?} on member {:x}", alarm.alarm(), alarm.member_id()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                details.push(CheckDetail::fail(
+                    "Etcd Alarms".to_string(),
+                    format!("Active alarms: {}", alarms),
+                    Some(start.elapsed()),
+                    Some("For NOSPACE, compact and defragment etcd, then run `etcdctl alarm disarm`".to_string()),
+                ));
+            }
+        }
+        Ok(Err(e)) => details.push(CheckDetail::warning(
+            "Etcd Alarms".to_string(),
+            format!("Failed to query alarms: {}", e),
+            Some(start.elapsed()),
+            Some("Alarm queries require the root role when etcd auth is enabled".to_string()),
+        )),
+        Err(_) => details.push(CheckDetail::warning(
+            "Etcd Alarms".to_string(),
+            "Alarm request timed out".to_string(),
+            Some(start.elapsed()),
+            None,
+        )),
+    }
+
+    if let Some(db_size) = statuses.values().map(|status| status.db_size()).max() {
+        details.push(db_size_detail(db_size, DEFAULT_QUOTA_BYTES));
+    }
+}
+
+/// Exercise the lease lifecycle metasrv elections rely on: grant a short lease, attach a key,
+/// keep it alive once, then let it expire and verify the key is removed.
+pub(crate) async fn check_lease(
+    config: &MetasrvConfig,
+    options: &ConnectOptions,
+    details: &mut Vec<CheckDetail>,
+) {
+    let mut client = match timeout(
+        MEMBER_TIMEOUT,
+        Client::connect(&config.store_addrs, Some(options.clone())),
+    )
+    .await
+    {
+        Ok(Ok(client)) => client,
+        _ => return,
+    };
+
+    let start = Instant::now();
+    let lease = match client.lease_grant(LEASE_TTL_SECS, None).await {
+        Ok(lease) => lease,
+        Err(e) => {
+            details.push(CheckDetail::fail(
+                "Etcd Lease Grant".to_string(),
+                format!("Failed to grant lease: {}", e),
+                Some(start.elapsed()),
+                Some("Metasrv leader election requires leases; check etcd user permissions".to_string()),
+            ));
+            return;
+        }
+    };
+    details.push(CheckDetail::pass(
+        "Etcd Lease Grant".to_string(),
+        format!("Granted lease {:x} with TTL {}s", lease.id(), lease.ttl()),
+        Some(start.elapsed()),
+    ));
+
+    let key = format!(
+        "{}__stepstone_lease_test",
+        config.store_key_prefix.as_deref().unwrap_or("")
+    );
+    if let Err(e) = client
+        .put(key.as_str(), "stepstone_lease_value", Some(PutOptions::new().with_lease(lease.id())))
+        .await
+    {
+        details.push(CheckDetail::fail(
+            "Etcd Lease KeepAlive".to_string(),
+            format!("Failed to attach key to lease: {}", e),
+            None,
+            Some("Check etcd write permissions for the key prefix".to_string()),
+        ));
+        let _ = client.lease_revoke(lease.id()).await;
+        return;
+    }
+
+    let start = Instant::now();
+    let keep_alive = async {
+        let (mut keeper, mut stream) = client.lease_keep_alive(lease.id()).await?;
+        keeper.keep_alive().await?;
+        stream.message().await
+    };
+    match timeout(MEMBER_TIMEOUT, keep_alive).await {
+        Ok(Ok(Some(response))) if response.ttl() > 0 => {
+            details.push(CheckDetail::pass(
+                "Etcd Lease KeepAlive".to_string(),
+                format!("Lease refreshed to TTL {}s", response.ttl()),
+                Some(start.elapsed()),
+            ));
+        }
+        Ok(Ok(_)) => {
+            details.push(CheckDetail::fail(
+                "Etcd Lease KeepAlive".to_string(),
+                "Lease expired before keepalive was acknowledged".to_string(),
+                Some(start.elapsed()),
+                Some("Keepalive latency exceeds the lease TTL; check etcd disk and network latency".to_string()),
+            ));
+        }
+        Ok(Err(e)) => {
+            details.push(CheckDetail::fail(
+                "Etcd Lease KeepAlive".to_string(),
+                format!("Keepalive failed: {}", e),
+                Some(start.elapsed()),
+                Some("Metasrv loses leadership when keepalives fail".to_string()),
+            ));
+        }
+        Err(_) => {
+            details.push(CheckDetail::fail(
+                "Etcd Lease KeepAlive".to_string(),
+                "Keepalive timed out".to_string(),
+                Some(start.elapsed()),
+                Some("Metasrv loses leadership when keepalives fail".to_string()),
+            ));
+        }
+    }
+
+    // Stop refreshing and wait for etcd to expire the lease on its own
+    let start = Instant::now();
+    let expected = Duration::from_secs(lease.ttl().max(0) as u64) + LEASE_EXPIRY_GRACE;
+    let deadline = expected.min(LEASE_EXPIRY_MAX_WAIT);
+    let mut expired = false;
+    while start.elapsed() < deadline {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        match timeout(MEMBER_TIMEOUT, client.get(key.as_str(), None)).await {
+            Ok(Ok(response)) if response.kvs().is_empty() => {
+                expired = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if expired {
+        details.push(CheckDetail::pass(
+            "Etcd Lease Expiry".to_string(),
+            format!("Leased key removed {:.1}s after keepalives stopped", start.elapsed().as_secs_f64()),
+            Some(start.elapsed()),
+        ));
+    } else if expected > deadline {
+        details.push(CheckDetail::warning(
+            "Etcd Lease Expiry".to_string(),
+            format!(
+                "etcd granted a {}s TTL for the requested {}s, longer than the {}s the check waits for expiry",
+                lease.ttl(),
+                LEASE_TTL_SECS,
+                LEASE_EXPIRY_MAX_WAIT.as_secs()
+            ),
+            Some(start.elapsed()),
+            Some("A long minimum lease TTL delays metasrv failover by as much; etcd derives its minimum TTL from --election-timeout".to_string()),
+        ));
+        let _ = timeout(MEMBER_TIMEOUT, client.lease_revoke(lease.id())).await;
+        let _ = timeout(MEMBER_TIMEOUT, client.delete(key.as_str(), None)).await;
+    } else {
+        details.push(CheckDetail::fail(
+            "Etcd Lease Expiry".to_string(),
+            format!("Leased key still present {:.1}s after keepalives stopped", start.elapsed().as_secs_f64()),
+            Some(start.elapsed()),
+            Some("Expired leases are not being revoked; a failed metasrv leader may never step down".to_string()),
+        ));
+        let _ = timeout(MEMBER_TIMEOUT, client.lease_revoke(lease.id())).await;
+        let _ = timeout(MEMBER_TIMEOUT, client.delete(key.as_str(), None)).await;
+    }
+}
+
+/// Run a transaction as large as metasrv's `max_txn_ops`, which fails if etcd's own
+/// `--max-txn-ops` is lower than what metasrv assumes.
+pub(crate) async fn check_txn(
+    config: &MetasrvConfig,
+    options: &ConnectOptions,
+    details: &mut Vec<CheckDetail>,
+) {
+    let mut client = match timeout(
+        MEMBER_TIMEOUT,
+        Client::connect(&config.store_addrs, Some(options.clone())),
+    )
+    .await
+    {
+        Ok(Ok(client)) => client,
+        _ => return,
+    };
+
+    let max_txn_ops = config.max_txn_ops.unwrap_or(DEFAULT_MAX_TXN_OPS);
+    let prefix = format!(
+        "{}__stepstone_txn_test/",
+        config.store_key_prefix.as_deref().unwrap_or("")
+    );
+    // etcd rejects a transaction that touches the same key twice, so each op gets its own key
+    let ops = (0..max_txn_ops)
+        .map(|i| TxnOp::put(format!("{}{:05}", prefix, i), "stepstone_txn_value", None))
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let result = client.txn(Txn::new().and_then(ops)).await;
+    let elapsed = start.elapsed();
+    let _ = client
+        .delete(prefix.as_str(), Some(DeleteOptions::new().with_prefix()))
+        .await;
+
+    details.push(txn_detail(max_txn_ops, result.map(|r| r.succeeded()), elapsed));
+}
+
+fn txn_detail(
+    max_txn_ops: usize,
+    result: Result<bool, etcd_client::Error>,
+    elapsed: Duration,
+) -> CheckDetail {
+    let item = "Etcd Transaction".to_string();
+    match result {
+        Ok(true) => CheckDetail::pass(
+            item,
+            format!("Committed a transaction with {} operations (max_txn_ops)", max_txn_ops),
+            Some(elapsed),
+        ),
+        Ok(false) => CheckDetail::fail(
+            item,
+            format!("Transaction with {} operations was not applied", max_txn_ops),
+            Some(elapsed),
+            None,
+        ),
+        Err(e) if e.to_string().contains("too many operations") => CheckDetail::fail(
+            item,
+            format!("etcd rejected a transaction with {} operations: {}", max_txn_ops, e),
+            Some(elapsed),
+            Some(format!(
+                "Start etcd with --max-txn-ops={} or higher, or lower max_txn_ops in the metasrv config",
+                max_txn_ops
+            )),
+        ),
+        Err(e) => CheckDetail::fail(
+            item,
+            format!("Transaction failed: {}", e),
+            Some(elapsed),
+            Some("Check etcd write permissions for the key prefix".to_string()),
+        ),
+    }
+}
+
+/// Benchmark small sequential and concurrent puts/gets, the access pattern of metasrv
+/// heartbeats and metadata updates.
+pub(crate) async fn check_performance(
+    config: &MetasrvConfig,
+    options: &ConnectOptions,
+    details: &mut Vec<CheckDetail>,
+) {
+    let client = match timeout(
+        MEMBER_TIMEOUT,
+        Client::connect(&config.store_addrs, Some(options.clone())),
+    )
+    .await
+    {
+        Ok(Ok(client)) => client,
+        _ => return,
+    };
+
+    let prefix = format!(
+        "{}__stepstone_bench/",
+        config.store_key_prefix.as_deref().unwrap_or("")
+    );
+    let value = vec![0u8; 256];
+
+    let start = Instant::now();
+    let puts = sequential_puts(client.clone(), &prefix, &value, BENCH_SEQUENTIAL_OPS).await;
+    details.push(bench_detail("Etcd Sequential PUT", &puts, start.elapsed(), ETCD_SLOW_SUGGESTION));
+
+    let start = Instant::now();
+    let gets = sequential_gets(client.clone(), &prefix).await;
+    details.push(bench_detail("Etcd Sequential GET", &gets, start.elapsed(), ETCD_SLOW_SUGGESTION));
+
+    let start = Instant::now();
+    let mut handles = Vec::new();
+    for worker in 0..BENCH_CONCURRENCY {
+        let client = client.clone();
+        let prefix = format!("{}c{:02}/", prefix, worker);
+        let value = value.clone();
+        handles.push(tokio::spawn(async move {
+            sequential_puts(client, &prefix, &value, BENCH_OPS_PER_CLIENT).await
+        }));
+    }
+    let mut samples = Vec::new();
+    for handle in handles {
+        if let Ok(worker_samples) = handle.await {
+            samples.extend(worker_samples);
+        }
+    }
+    details.push(bench_detail("Etcd Concurrent PUT", &samples, start.elapsed(), ETCD_SLOW_SUGGESTION));
+
+    let mut client = client;
+    let _ = client
+        .delete(prefix.as_str(), Some(DeleteOptions::new().with_prefix()))
+        .await;
+}
+
+/// Issue `count` puts one after another, returning the latency of each successful one
+async fn sequential_puts(mut client: Client, prefix: &str, value: &[u8], count: usize) -> Vec<Duration> {
+    let mut samples = Vec::with_capacity(count);
+    for i in 0..count {
+        let start = Instant::now();
+        if client.put(format!("{}{:05}", prefix, i), value, None).await.is_ok() {
+            samples.push(start.elapsed());
+        }
+    }
+    samples
+}
+
+/// Read back the keys written by [`sequential_puts`]
+async fn sequential_gets(mut client: Client, prefix: &str) -> Vec<Duration> {
+    let mut samples = Vec::with_capacity(BENCH_SEQUENTIAL_OPS);
+    for i in 0..BENCH_SEQUENTIAL_OPS {
+        let start = Instant::now();
+        if client.get(format!("{}{:05}", prefix, i), None).await.is_ok() {
+            samples.push(start.elapsed());
+        }
+    }
+    samples
+}
+
+/// Report the key prefix metasrv will use and whether keys already live under it, so a new
+/// cluster is not pointed at another cluster's or application's data by accident.
+pub(crate) async fn check_key_prefix(
+    config: &MetasrvConfig,
+    options: &ConnectOptions,
+    details: &mut Vec<CheckDetail>,
+) {
+    let mut client = match timeout(
+        MEMBER_TIMEOUT,
+        Client::connect(&config.store_addrs, Some(options.clone())),
+    )
+    .await
+    {
+        Ok(Ok(client)) => client,
+        _ => return,
+    };
+
+    let prefix = config.store_key_prefix.clone().unwrap_or_default();
+    let get_options = GetOptions::new().with_keys_only().with_limit(KEY_SAMPLE_LIMIT);
+    // Without a prefix metasrv writes at the root, so the whole keyspace is relevant
+    let get_options = if prefix.is_empty() {
+        get_options.with_all_keys()
+    } else {
+        get_options.with_prefix()
+    };
+
+    let start = Instant::now();
+    match client.get(prefix.as_str(), Some(get_options)).await {
+        Ok(response) => {
+            let keys = response
+                .kvs()
+                .iter()
+                .map(|kv| String::from_utf8_lossy(kv.key()).into_owned())
+                .collect::<Vec<_>>();
+            details.push(key_prefix_detail(&prefix, &keys, start.elapsed()));
+        }
+        Err(e) => details.push(CheckDetail::warning(
+            "Etcd Key Prefix".to_string(),
+            format!("Failed to list keys under {:?}: {}", prefix, e),
+            Some(start.elapsed()),
+            Some("Check etcd read permissions for the key prefix".to_string()),
+        )),
+    }
+}
+
+/// Classify the keys found under `prefix` as GreptimeDB metadata or foreign data
+fn key_prefix_detail(prefix: &str, keys: &[String], duration: Duration) -> CheckDetail {
+    let item = "Etcd Key Prefix".to_string();
+    let display_prefix = if prefix.is_empty() { "<none>" } else { prefix };
+
+    let relative = keys
+        .iter()
+        .filter_map(|key| key.strip_prefix(prefix))
+        .filter(|key| !key.starts_with(STEPSTONE_KEY_PREFIX))
+        .collect::<Vec<_>>();
+    let (greptime, foreign): (Vec<&str>, Vec<&str>) = relative
+        .iter()
+        .partition(|key| GREPTIME_KEY_PREFIXES.iter().any(|p| key.starts_with(p)));
+
+    if !foreign.is_empty() {
+        let examples = foreign.iter().take(3).join(", ");
+        return CheckDetail::warning(
+            item,
+            format!(
+                "Prefix {} holds {} key(s) that do not belong to GreptimeDB, e.g. {}",
+                display_prefix,
+                foreign.len(),
+                examples
+            ),
+            Some(duration),
+            Some("Set store_key_prefix to a prefix no other application or cluster uses".to_string()),
+        );
+    }
+
+    if greptime.is_empty() {
+        CheckDetail::pass(
+            item,
+            format!("Prefix {} is empty; metasrv will start a fresh metadata tree", display_prefix),
+            Some(duration),
+        )
+    } else {
+        CheckDetail::pass(
+            item,
+            format!(
+                "Prefix {} already holds GreptimeDB metadata ({} key(s)); metasrv will reuse it",
+                display_prefix,
+                greptime.len()
+            ),
+            Some(duration),
+        )
+    }
+}
+
+/// Warn on even-sized clusters, which tolerate no more failures than one member fewer
+fn member_count_detail(total: usize) -> CheckDetail {
+    let item = "Etcd Member Count".to_string();
+    if total % 2 == 0 {
+        CheckDetail::warning(
+            item,
+            format!(
+                "Cluster has {} members; an even count tolerates {} failure(s), the same as {}",
+                total,
+                total.saturating_sub(1) / 2,
+                total.saturating_sub(1)
+            ),
+            None,
+            Some("Run an odd number of etcd members, such as 3 or 5".to_string()),
+        )
+    } else {
+        CheckDetail::pass(
+            item,
+            format!("Cluster has {} members, tolerating {} failure(s)", total, total / 2),
+            None,
+        )
+    }
+}
+
+/// Compare the number of members that answered a status request against the quorum size
+fn quorum_detail(total: usize, healthy: usize, duration: Duration) -> CheckDetail {
+    let item = "Etcd Quorum".to_string();
+    let quorum = total / 2 + 1;
+    if healthy >= quorum {
+        CheckDetail::pass(
+            item,
+            format!("{} of {} members healthy (quorum is {})", healthy, total, quorum),
+            Some(duration),
+        )
+    } else {
+        CheckDetail::fail(
+            item,
+            format!(
+                "Only {} of {} members answered through the configured endpoints (quorum is {})",
+                healthy, total, quorum
+            ),
+            Some(duration),
+            Some("Add every etcd member to store_addrs, or restore the unreachable members; metasrv elections stall without quorum".to_string()),
+        )
+    }
+}
+
+/// Compare the largest member DB size against the backend quota
+fn db_size_detail(db_size: i64, quota: i64) -> CheckDetail {
+    let item = "Etcd DB Size".to_string();
+    let ratio = db_size as f64 / quota as f64;
+    let message = format!(
+        "DB size {:.1} MB is {:.0}% of the {:.1} MB quota",
+        db_size as f64 / (1024.0 * 1024.0),
+        ratio * 100.0,
+        quota as f64 / (1024.0 * 1024.0)
+    );
+    if ratio >= DB_SIZE_WARN_RATIO {
+        CheckDetail::warning(
+            item,
+            message,
+            None,
+            Some("Compact and defragment etcd, or raise --quota-backend-bytes, before the NOSPACE alarm fires".to_string()),
+        )
+    } else {
+        CheckDetail::pass(item, message, None)
+    }
+}
+
+/// Summarize the leader reported by each reachable member
+fn leader_detail(leaders: &HashMap<u64, Vec<&str>>, member_endpoints: &HashMap<u64, &str>) -> Option<CheckDetail> {
+    let item = "Etcd Leader".to_string();
+    match leaders.len() {
+        0 => None,
+        1 => {
+            let (&leader, _) = leaders.iter().next()?;
+            if leader == 0 {
+                Some(CheckDetail::fail(
+                    item,
+                    "Reachable members report no leader".to_string(),
+                    None,
+                    Some("The cluster has likely lost quorum; restore enough members for a majority".to_string()),
+                ))
+            } else {
+                let location = member_endpoints
+                    .get(&leader)
+                    .map(|endpoint| format!(" at {}", endpoint))
+                    .unwrap_or_else(|| " (not among the configured endpoints)".to_string());
+                Some(CheckDetail::pass(
+                    item,
+                    format!("Leader is member {:x}{}", leader, location),
+                    None,
+                ))
+            }
+        }
+        _ => Some(CheckDetail::warning(
+            item,
+            format!(
+                "Members disagree on the leader: {}",
+                leaders
+                    .iter()
+                    .map(|(leader, endpoints)| format!("{:x} per {}", leader, endpoints.join(", ")))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            None,
+            Some("A leader election may be in progress, or the configured endpoints belong to different clusters".to_string()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use crate::config::TlsConfig;
+
+    #[test]
+    fn test_leader_detail() {
+        let mut member_endpoints = HashMap::new();
+        member_endpoints.insert(1, "a:2379");
+        member_endpoints.insert(2, "b:2379");
+
+        let mut leaders = HashMap::new();
+        leaders.insert(1, vec!["a:2379", "b:2379"]);
+        let detail = leader_detail(&leaders, &member_endpoints).unwrap();
+        assert_eq!(detail.status, CheckStatus::Pass);
+        assert!(detail.message.contains("a:2379"));
+
+        leaders.insert(2, vec!["c:2379"]);
+        let detail = leader_detail(&leaders, &member_endpoints).unwrap();
+        assert_eq!(detail.status, CheckStatus::Warning);
+
+        let mut no_leader = HashMap::new();
+        no_leader.insert(0, vec!["a:2379"]);
+        let detail = leader_detail(&no_leader, &member_endpoints).unwrap();
+        assert_eq!(detail.status, CheckStatus::Fail);
+
+        assert!(leader_detail(&HashMap::new(), &member_endpoints).is_none());
+    }
+
+    #[test]
+    fn test_cluster_size_details() {
+        assert_eq!(member_count_detail(3).status, CheckStatus::Pass);
+        assert_eq!(member_count_detail(4).status, CheckStatus::Warning);
+
+        assert_eq!(quorum_detail(3, 2, Duration::ZERO).status, CheckStatus::Pass);
+        assert_eq!(quorum_detail(3, 1, Duration::ZERO).status, CheckStatus::Fail);
+        assert_eq!(quorum_detail(4, 2, Duration::ZERO).status, CheckStatus::Fail);
+
+        assert_eq!(db_size_detail(100, 1000).status, CheckStatus::Pass);
+        assert_eq!(db_size_detail(900, 1000).status, CheckStatus::Warning);
+    }
+
+    #[test]
+    fn test_connect_options_validation() {
+        let mut config = MetasrvConfig {
+            store_addrs: vec!["127.0.0.1:2379".to_string()],
+            backend: "etcd_store".to_string(),
+            ..Default::default()
+        };
+        assert!(connect_options(&config).is_ok());
+
+        config.backend_username = Some("root".to_string());
+        assert!(connect_options(&config).is_err());
+        config.backend_password = Some("secret".to_string());
+        assert!(connect_options(&config).is_ok());
+
+        config.backend_tls = Some(TlsConfig {
+            cert: Some("/nonexistent/client.crt".to_string()),
+            ..Default::default()
+        });
+        assert!(connect_options(&config).is_err());
+
+        config.backend_tls = Some(TlsConfig {
+            ca: Some("/nonexistent/ca.crt".to_string()),
+            ..Default::default()
+        });
+        assert!(connect_options(&config).is_err());
+    }
+
+    #[test]
+    fn test_txn_detail() {
+        assert_eq!(txn_detail(128, Ok(true), Duration::ZERO).status, CheckStatus::Pass);
+        assert_eq!(txn_detail(128, Ok(false), Duration::ZERO).status, CheckStatus::Fail);
+
+        let error = etcd_client::Error::InvalidArgs(
+            "etcdserver: too many operations in txn request".to_string(),
+        );
+        let detail = txn_detail(256, Err(error), Duration::ZERO);
+        assert_eq!(detail.status, CheckStatus::Fail);
+        assert!(detail.suggestion.unwrap().contains("--max-txn-ops=256"));
+    }
+
+    #[test]
+    fn test_key_prefix_detail() {
+        let duration = Duration::ZERO;
+
+        let detail = key_prefix_detail("/greptime", &[], duration);
+        assert_eq!(detail.status, CheckStatus::Pass);
+        assert!(detail.message.contains("empty"));
+
+        let keys = vec![
+            "/greptime__catalog_name/greptime".to_string(),
+            "/greptime__stepstone_test".to_string(),
+        ];
+        let detail = key_prefix_detail("/greptime", &keys, duration);
+        assert_eq!(detail.status, CheckStatus::Pass);
+        assert!(detail.message.contains("1 key(s)"));
+
+        let keys = vec![
+            "__table_info/1024".to_string(),
+            "/registry/pods/default/nginx".to_string(),
+        ];
+        let detail = key_prefix_detail("", &keys, duration);
+        assert_eq!(detail.status, CheckStatus::Warning);
+        assert!(detail.message.contains("/registry/pods/default/nginx"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_member_is_reported() {
+        let config = MetasrvConfig {
+            store_addrs: vec!["127.0.0.1:1".to_string()],
+            backend: "etcd_store".to_string(),
+            ..Default::default()
+        };
+        let options = connect_options(&config).unwrap();
+        let mut details = Vec::new();
+        check_members(&config, &options, &mut details).await;
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].status, CheckStatus::Fail);
+    }
+}
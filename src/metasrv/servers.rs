@@ -0,0 +1,149 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of metasrv's own gRPC and HTTP servers.
+//!
+//! Before startup the addresses must parse and their ports be free on this host; against a
+//! running metasrv the same ports must instead answer, gRPC with the health service for the
+//! server and each of its services, and HTTP on `/health`. In auto mode both are checked, the
+//! servers only when something listens on their port.
+
+use super::MetasrvChecker;
+use crate::common::{CheckDetail, CheckStatus};
+use crate::grpc_health;
+use crate::mode::CheckMode;
+use crate::network;
+
+/// GreptimeDB's defaults for metasrv's servers
+const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:3002";
+const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:4000";
+
+impl MetasrvChecker {
+    /// The gRPC and HTTP bind addresses, with defaults filled in
+    fn servers(&self) -> [(&'static str, String); 2] {
+        let grpc = self.config.grpc.as_ref().and_then(|grpc| grpc.addr.clone());
+        let http = self.config.http.as_ref().and_then(|http| http.addr.clone());
+        [
+            ("grpc", grpc.unwrap_or_else(|| DEFAULT_GRPC_ADDR.to_string())),
+            ("http", http.unwrap_or_else(|| DEFAULT_HTTP_ADDR.to_string())),
+        ]
+    }
+
+    /// Checks of metasrv's own servers in `mode`: before startup both addresses must be valid with
+    /// ports free on this host, and once it runs it must answer on them
+    pub(super) async fn check_servers(&self, mode: CheckMode) -> Vec<CheckDetail> {
+        let mut details = Vec::new();
+        if mode.checks_running() {
+            for (name, addr) in self.servers() {
+                let (host, port) = match network::parse_address(&addr) {
+                    Ok(parsed) => parsed,
+                    // The port checks report the address when they run
+                    Err(_) if mode.checks_binds() => continue,
+                    Err(e) => {
+                        details.push(CheckDetail::fail(
+                            format!("Port ({})", name),
+                            format!("Invalid {} address '{}': {}", name, addr, e),
+                            None,
+                            Some(format!("Set {}.addr as host:port", name)),
+                        ));
+                        continue;
+                    }
+                };
+                if mode != CheckMode::Live && !network::is_listening(&host, port).await {
+                    continue;
+                }
+                if name == "grpc" {
+                    details.extend(self.grpc_reachability(&host, port).await);
+                } else {
+                    details.push(http_reachability(&host, port).await);
+                }
+            }
+        }
+        if mode.checks_binds() {
+            details.extend(network::check_server_ports(
+                &self.servers(),
+                "Give grpc.addr and http.addr their own ports; the defaults are 3002 and 4000",
+                mode,
+            ));
+        }
+        details
+    }
+
+    /// The health of the gRPC server, then of each metasrv service once the server is serving
+    async fn grpc_reachability(&self, host: &str, port: u16) -> Vec<CheckDetail> {
+        let addr = network::connect_addr(host, port);
+        let tls = self.config.grpc.as_ref().and_then(|grpc| grpc.tls.as_ref());
+        let enabled = tls.is_some_and(|tls| !matches!(tls.mode.as_deref(), None | Some("disable")));
+        let target = if enabled { network::with_tls_scheme(&addr) } else { addr };
+        let ca = tls.and_then(|tls| tls.ca.as_deref());
+        let server = grpc_health::check_grpc_health("Metasrv Reachability (grpc)".to_string(), &target, "", ca).await;
+        let serving = server.status == CheckStatus::Pass;
+        let mut details = vec![server];
+        if serving {
+            details.extend(grpc_health::check_grpc_services("gRPC Health", &target, grpc_health::METASRV_SERVICES, ca).await);
+        }
+        details
+    }
+}
+
+async fn http_reachability(host: &str, port: u16) -> CheckDetail {
+    network::check_http_health(
+        "Metasrv Reachability (http)".to_string(),
+        host,
+        port,
+        "Check that metasrv is running with this config; use --mode preflight to check a metasrv that is not started yet",
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use crate::config::{ConfigParser, GrpcConfig, HttpConfig};
+    use crate::profile::Profile;
+
+    #[tokio::test]
+    async fn test_check_servers() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let taken = listener.local_addr().unwrap().port();
+
+        let mut config = ConfigParser::default_metasrv_config();
+        config.grpc = Some(GrpcConfig {
+            addr: Some("127.0.0.1:0".to_string()),
+            ..Default::default()
+        });
+        config.http = Some(HttpConfig {
+            addr: Some(format!("127.0.0.1:{}", taken)),
+            ..Default::default()
+        });
+        let checker = MetasrvChecker::new(config.clone(), Profile::STANDARD, 0);
+        let details = checker.check_servers(CheckMode::Preflight).await;
+        assert_eq!(details[0].item, "Port (grpc)");
+        assert_eq!(details[0].status, CheckStatus::Fail);
+        assert_eq!(details[1].status, CheckStatus::Fail);
+
+        // In auto mode the taken HTTP port is asked for its health as well, and only warned about
+        let details = checker.check_servers(CheckMode::Auto).await;
+        assert_eq!(details[0].item, "Metasrv Reachability (http)");
+        assert_eq!(details[2].item, "Port (http)");
+        assert_eq!(details[2].status, CheckStatus::Warning);
+
+        let details = checker.check_servers(CheckMode::Live).await;
+        assert!(details.iter().all(|detail| !detail.item.starts_with("Port")));
+
+        config.grpc.as_mut().unwrap().addr = Some(format!("0.0.0.0:{}", taken));
+        let checker = MetasrvChecker::new(config, Profile::STANDARD, 0);
+        let details = checker.check_servers(CheckMode::Preflight).await;
+        assert_eq!(details[1].item, "Port Conflict (http)");
+    }
+}
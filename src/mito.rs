@@ -0,0 +1,201 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of `[region_engine.mito]` sizing against the host it runs on.
+//!
+//! Mito's defaults scale with the host's memory and cores, but explicit sizes copied from a
+//! larger machine do not: the write buffer and caches together can exceed the memory limit and
+//! get the datanode OOM-killed under load, and far more workers than cores only add contention.
+
+use crate::common::CheckDetail;
+use crate::config::MitoConfig;
+use crate::lint::parse_size;
+use crate::logging::format_bytes;
+use crate::query::Host;
+
+/// Share of memory the write buffer and caches may take; queries need the rest
+const MEMORY_BUDGET_PERCENT: u64 = 70;
+
+/// Workers per core beyond which a setting is reported
+const MAX_WORKERS_PER_CORE: u32 = 2;
+
+/// Check the configured memtable and cache sizes and worker counts against the host the
+/// datanode runs on
+pub fn check_mito(mito: Option<&MitoConfig>, host: Host) -> Vec<CheckDetail> {
+    let Some(mito) = mito else {
+        return Vec::new();
+    };
+    let mut details = Vec::new();
+    if let Some(memory) = host.memory {
+        details.extend(memory_detail(mito, memory));
+    }
+    details.extend(workers_detail(mito, host.cores));
+    details
+}
+
+/// Explicitly configured sizes that stay in memory, by setting name
+fn memory_settings(mito: &MitoConfig) -> Vec<(&'static str, u64)> {
+    let index = mito.index.as_ref();
+    // Writes are only rejected at the reject size, so that is what memtables can reach
+    let write_buffer = match mito.global_write_buffer_reject_size.as_deref() {
+        Some(reject) => ("global_write_buffer_reject_size", Some(reject)),
+        None => ("global_write_buffer_size", mito.global_write_buffer_size.as_deref()),
+    };
+    [
+        write_buffer,
+        ("sst_meta_cache_size", mito.sst_meta_cache_size.as_deref()),
+        ("vector_cache_size", mito.vector_cache_size.as_deref()),
+        ("page_cache_size", mito.page_cache_size.as_deref()),
+        ("selector_result_cache_size", mito.selector_result_cache_size.as_deref()),
+        ("index.metadata_cache_size", index.and_then(|index| index.metadata_cache_size.as_deref())),
+        ("index.content_cache_size", index.and_then(|index| index.content_cache_size.as_deref())),
+        ("index.result_cache_size", index.and_then(|index| index.result_cache_size.as_deref())),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some((name, value.and_then(parse_size)?)))
+    .collect()
+}
+
+fn memory_detail(mito: &MitoConfig, memory: u64) -> Option<CheckDetail> {
+    let settings = memory_settings(mito);
+    if settings.is_empty() {
+        return None;
+    }
+    let total: u64 = settings.iter().map(|(_, size)| size).sum();
+    let breakdown = settings
+        .iter()
+        .map(|(name, size)| format!("{} {}", name, format_bytes(*size)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let budget = memory / 100 * MEMORY_BUDGET_PERCENT;
+
+    let item = "Mito Memory Budget".to_string();
+    Some(if total > memory {
+        CheckDetail::fail(
+            item,
+            format!("Memtables and caches may take {} ({}), more than the {} of memory", format_bytes(total), breakdown, format_bytes(memory)),
+            None,
+            Some("Lower the sizes or remove them so mito derives them from the host's memory; the datanode is OOM-killed once they fill".to_string()),
+        )
+    } else if total > budget {
+        CheckDetail::warning(
+            item,
+            format!(
+                "Memtables and caches may take {} of {} memory ({}), leaving little for queries",
+                format_bytes(total),
+                format_bytes(memory),
+                breakdown
+            ),
+            None,
+            Some(format!("Keep the configured sizes below {}% of memory", MEMORY_BUDGET_PERCENT)),
+        )
+    } else {
+        CheckDetail::pass(
+            item,
+            format!("Memtables and caches take up to {} of {} memory", format_bytes(total), format_bytes(memory)),
+            None,
+        )
+    })
+}
+
+fn workers_detail(mito: &MitoConfig, cores: u32) -> Option<CheckDetail> {
+    let settings = [
+        ("num_workers", mito.num_workers),
+        ("max_background_flushes", mito.max_background_flushes),
+        ("max_background_compactions", mito.max_background_compactions),
+        ("max_background_purges", mito.max_background_purges),
+    ];
+    let configured = settings.iter().filter(|(_, value)| value.is_some()).count();
+    if configured == 0 {
+        return None;
+    }
+    let excessive = settings
+        .iter()
+        .filter_map(|(name, value)| value.filter(|value| *value > cores * MAX_WORKERS_PER_CORE).map(|value| format!("{} = {}", name, value)))
+        .collect::<Vec<_>>();
+
+    let item = "Mito Worker Count".to_string();
+    Some(if excessive.is_empty() {
+        CheckDetail::pass(item, format!("{} worker settings fit {} cores", configured, cores), None)
+    } else {
+        CheckDetail::warning(
+            item,
+            format!("{} on {} cores", excessive.join(", "), cores),
+            None,
+            Some("Remove the setting so mito derives it from the core count, or keep it within twice the cores".to_string()),
+        )
+    })
+}
+
+/// Memory available to this process: the cgroup limit in a container, otherwise `MemTotal`
+pub(crate) fn memory_limit() -> Option<u64> {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+    let cgroup = read("/sys/fs/cgroup/memory.max")
+        .or_else(|| read("/sys/fs/cgroup/memory/memory.limit_in_bytes"))
+        .and_then(|limit| limit.trim().parse::<u64>().ok());
+    let total = read("/proc/meminfo").and_then(|meminfo| mem_total(&meminfo));
+    match (cgroup, total) {
+        // Without a limit cgroup v1 reports a huge number
+        (Some(cgroup), Some(total)) => Some(cgroup.min(total)),
+        (cgroup, total) => cgroup.or(total),
+    }
+}
+
+/// `MemTotal` of `/proc/meminfo`, in bytes
+fn mem_total(meminfo: &str) -> Option<u64> {
+    let line = meminfo.lines().find_map(|line| line.strip_prefix("MemTotal:"))?;
+    let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use crate::config::MitoIndexConfig;
+
+    #[test]
+    fn test_check_mito() {
+        assert_eq!(mem_total("MemTotal:       16303580 kB\nMemFree: 1 kB\n"), Some(16303580 * 1024));
+
+        let mito = MitoConfig {
+            global_write_buffer_size: Some("4GB".to_string()),
+            page_cache_size: Some("8GB".to_string()),
+            index: Some(MitoIndexConfig {
+                content_cache_size: Some("2GB".to_string()),
+                ..Default::default()
+            }),
+            num_workers: Some(64),
+            max_background_flushes: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(memory_detail(&mito, 64 << 30).unwrap().status, CheckStatus::Pass);
+        assert_eq!(memory_detail(&mito, 16 << 30).unwrap().status, CheckStatus::Warning);
+        let detail = memory_detail(&mito, 8 << 30).unwrap();
+        assert_eq!(detail.status, CheckStatus::Fail);
+        assert!(detail.message.contains("page_cache_size 8.0 GiB"), "{}", detail.message);
+        assert!(memory_detail(&MitoConfig::default(), 8 << 30).is_none());
+
+        let rejecting = MitoConfig {
+            global_write_buffer_size: Some("1GB".to_string()),
+            global_write_buffer_reject_size: Some("2GB".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(memory_settings(&rejecting), vec![("global_write_buffer_reject_size", 2 << 30)]);
+
+        assert_eq!(workers_detail(&mito, 32).unwrap().status, CheckStatus::Pass);
+        let detail = workers_detail(&mito, 8).unwrap();
+        assert_eq!(detail.status, CheckStatus::Warning);
+        assert_eq!(detail.message, "num_workers = 64 on 8 cores");
+        assert!(workers_detail(&MitoConfig::default(), 8).is_none());
+    }
+}
@@ -0,0 +1,111 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Whether a run checks a node about to start, one already running, or whichever it finds.
+//!
+//! Some checks only make sense on one side of startup: a port must be free before the node binds
+//! it, and a health endpoint only answers once it runs. The mode of the run picks which of them
+//! apply, and is reported so a passing report says what it proved.
+
+use std::sync::OnceLock;
+
+static MODE: OnceLock<CheckMode> = OnceLock::new();
+
+/// Which side of startup the checks target, set with `--mode`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CheckMode {
+    /// Before startup: ports must be free, running servers are not asked
+    Preflight,
+    /// Against running nodes: their servers must answer, ports are expected to be taken
+    Live,
+    /// Ports in use are warnings, and servers are asked when something listens on their port
+    #[default]
+    Auto,
+}
+
+impl std::str::FromStr for CheckMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "preflight" => Ok(Self::Preflight),
+            "live" => Ok(Self::Live),
+            "auto" => Ok(Self::Auto),
+            other => Err(format!("unknown mode '{}', expected preflight, live or auto", other)),
+        }
+    }
+}
+
+impl CheckMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Preflight => "preflight",
+            Self::Live => "live",
+            Self::Auto => "auto",
+        }
+    }
+
+    /// Whether ports are checked to be free to bind
+    pub fn checks_binds(self) -> bool {
+        self != Self::Live
+    }
+
+    /// Whether running servers are asked for their health
+    pub fn checks_running(self) -> bool {
+        self != Self::Preflight
+    }
+}
+
+/// The mode of a run from `--mode` and a command's `--live`, which means `--mode live`;
+/// `explicit` is whether `--mode` was given rather than defaulted
+pub fn resolve(mode: CheckMode, explicit: bool, live: bool) -> Result<CheckMode, String> {
+    match (live, explicit) {
+        (true, true) if mode != CheckMode::Live => {
+            Err(format!("--live conflicts with --mode {}; use one of them", mode.as_str()))
+        }
+        (true, _) => Ok(CheckMode::Live),
+        (false, _) => Ok(mode),
+    }
+}
+
+/// Use `mode` for the rest of the process
+pub fn init(mode: CheckMode) {
+    let _ = MODE.set(mode);
+}
+
+/// The mode passed to [`init`], or [`CheckMode::Auto`]
+pub fn current() -> CheckMode {
+    MODE.get().copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_mode() {
+        assert_eq!("Live".parse::<CheckMode>(), Ok(CheckMode::Live));
+        assert!("running".parse::<CheckMode>().is_err());
+        assert!(CheckMode::Preflight.checks_binds() && !CheckMode::Preflight.checks_running());
+        assert!(!CheckMode::Live.checks_binds() && CheckMode::Live.checks_running());
+        assert!(CheckMode::Auto.checks_binds() && CheckMode::Auto.checks_running());
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve(CheckMode::Auto, false, true), Ok(CheckMode::Live));
+        assert_eq!(resolve(CheckMode::Live, true, true), Ok(CheckMode::Live));
+        assert_eq!(resolve(CheckMode::Preflight, true, false), Ok(CheckMode::Preflight));
+        assert!(resolve(CheckMode::Preflight, true, true).unwrap_err().contains("--mode preflight"));
+        assert!(resolve(CheckMode::Auto, true, true).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::{CheckDetail, CheckResult, CheckStatus, ComponentChecker};
+use crate::error;
+use crate::network::{self, RttStats};
+use async_trait::async_trait;
+use snafu::ResultExt;
+use std::fmt::{Debug, Formatter};
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+
+/// Echo a small payload back to the client
+const MODE_PING: u8 = b'P';
+/// Discard everything the client sends, then report the byte count
+const MODE_UPLOAD: u8 = b'U';
+/// Stream data to the client until it disconnects
+const MODE_DOWNLOAD: u8 = b'D';
+
+/// Size of the payload echoed in ping mode
+const PING_PAYLOAD_SIZE: usize = 8;
+/// Number of ping round trips used for latency measurement
+const PING_COUNT: usize = 20;
+/// Buffer size used for throughput streams
+const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Run the peer benchmark server until the process is stopped
+pub async fn serve(listen: &str) -> error::Result<()> {
+    let listener = TcpListener::bind(listen).await.context(error::TcpConnectionSnafu {
+        address: listen.to_string(),
+        message: "Failed to bind benchmark listener".to_string(),
+    })?;
+    println!("Listening for stepstone net bench on {}", listen);
+
+    loop {
+        let (stream, peer) = listener.accept().await.context(error::TcpConnectionSnafu {
+            address: listen.to_string(),
+            message: "Failed to accept connection".to_string(),
+        })?;
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream).await {
+                eprintln!("Connection from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Serve a single benchmark connection according to its mode byte
+async fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    match stream.read_u8().await? {
+        MODE_PING => {
+            let mut buf = [0u8; PING_PAYLOAD_SIZE];
+            loop {
+                match stream.read_exact(&mut buf).await {
+                    Ok(_) => stream.write_all(&buf).await?,
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        MODE_UPLOAD => {
+            let mut buf = vec![0u8; STREAM_BUFFER_SIZE];
+            let mut received: u64 = 0;
+            loop {
+                let n = stream.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                received += n as u64;
+            }
+            stream.write_u64(received).await
+        }
+        MODE_DOWNLOAD => {
+            let buf = vec![0u8; STREAM_BUFFER_SIZE];
+            loop {
+                if let Err(e) = stream.write_all(&buf).await {
+                    // The client closes the connection once it has measured enough
+                    return match e.kind() {
+                        io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset => Ok(()),
+                        _ => Err(e),
+                    };
+                }
+            }
+        }
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown benchmark mode {:?}", other as char),
+        )),
+    }
+}
+
+/// Client side of the peer benchmark, run against `stepstone net serve`
+pub struct NetBenchChecker {
+    target: String,
+    duration: Duration,
+}
+
+impl Debug for NetBenchChecker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NetBenchChecker")
+    }
+}
+
+impl NetBenchChecker {
+    /// Create a new NetBenchChecker for the given target and per-direction duration
+    pub fn new(target: String, duration: Duration) -> Self {
+        Self { target, duration }
+    }
+
+    /// Measure application-level round trips over a single connection
+    async fn bench_latency(&self) -> CheckDetail {
+        let start = Instant::now();
+        let result = self.ping_round_trips().await;
+
+        match result {
+            Ok(stats) => CheckDetail::pass(
+                "Peer Latency".to_string(),
+                format!(
+                    "{} round trips: avg {:?}, p95 {:?}, min {:?}, max {:?}, jitter {:?}",
+                    stats.samples, stats.avg, stats.p95, stats.min, stats.max, stats.jitter
+                ),
+                Some(start.elapsed()),
+            ),
+            Err(e) => CheckDetail::fail(
+                "Peer Latency".to_string(),
+                format!("Latency benchmark against {} failed: {}", self.target, network::describe_connect_error(&e)),
+                Some(start.elapsed()),
+                Some(network::connect_error_suggestion(&e, "stepstone net serve")),
+            ),
+        }
+    }
+
+    /// Stream data to the server and use its byte count to compute throughput
+    async fn bench_upload(&self) -> CheckDetail {
+        let start = Instant::now();
+        let result = self.stream_upload(start).await;
+        let elapsed = start.elapsed();
+
+        match result {
+            Ok(bytes) => CheckDetail::pass(
+                "Peer Upload Throughput".to_string(),
+                format!("{:.2} MB/s ({} bytes in {:?})", mb_per_sec(bytes, elapsed), bytes, elapsed),
+                Some(elapsed),
+            ),
+            Err(e) => CheckDetail::fail(
+                "Peer Upload Throughput".to_string(),
+                format!("Upload benchmark against {} failed: {}", self.target, e),
+                Some(elapsed),
+                Some("Check that stepstone net serve is still running on the target".to_string()),
+            ),
+        }
+    }
+
+    /// Read data streamed by the server for the configured duration
+    async fn bench_download(&self) -> CheckDetail {
+        let start = Instant::now();
+        let result = self.stream_download(start).await;
+        let elapsed = start.elapsed();
+
+        match result {
+            Ok(bytes) => CheckDetail::pass(
+                "Peer Download Throughput".to_string(),
+                format!("{:.2} MB/s ({} bytes in {:?})", mb_per_sec(bytes, elapsed), bytes, elapsed),
+                Some(elapsed),
+            ),
+            Err(e) => CheckDetail::fail(
+                "Peer Download Throughput".to_string(),
+                format!("Download benchmark against {} failed: {}", self.target, e),
+                Some(elapsed),
+                Some("Check that stepstone net serve is still running on the target".to_string()),
+            ),
+        }
+    }
+
+    /// Time small echo round trips over one connection
+    async fn ping_round_trips(&self) -> io::Result<RttStats> {
+        let mut stream = self.connect(MODE_PING).await?;
+        let payload = [0u8; PING_PAYLOAD_SIZE];
+        let mut buf = [0u8; PING_PAYLOAD_SIZE];
+        let mut samples = Vec::with_capacity(PING_COUNT);
+        for _ in 0..PING_COUNT {
+            let sent = Instant::now();
+            stream.write_all(&payload).await?;
+            stream.read_exact(&mut buf).await?;
+            samples.push(sent.elapsed());
+        }
+        RttStats::from_samples(&samples)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no samples"))
+    }
+
+    /// Send data until the duration has elapsed and return the server's byte count
+    async fn stream_upload(&self, start: Instant) -> io::Result<u64> {
+        let mut stream = self.connect(MODE_UPLOAD).await?;
+        let buf = vec![0u8; STREAM_BUFFER_SIZE];
+        while start.elapsed() < self.duration {
+            stream.write_all(&buf).await?;
+        }
+        stream.shutdown().await?;
+        stream.read_u64().await
+    }
+
+    /// Receive data until the duration has elapsed and return the byte count
+    async fn stream_download(&self, start: Instant) -> io::Result<u64> {
+        let mut stream = self.connect(MODE_DOWNLOAD).await?;
+        let mut buf = vec![0u8; STREAM_BUFFER_SIZE];
+        let mut received: u64 = 0;
+        while start.elapsed() < self.duration {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            received += n as u64;
+        }
+        Ok(received)
+    }
+
+    /// Open a connection to the benchmark server in the given mode
+    async fn connect(&self, mode: u8) -> io::Result<TcpStream> {
+        let mut stream = match timeout(Duration::from_secs(10), TcpStream::connect(self.target.as_str())).await {
+            Ok(result) => result?,
+            Err(_) => return Err(io::Error::new(io::ErrorKind::TimedOut, "connect timed out")),
+        };
+        stream.set_nodelay(true)?;
+        stream.write_u8(mode).await?;
+        Ok(stream)
+    }
+}
+
+#[async_trait]
+impl ComponentChecker for NetBenchChecker {
+    async fn check(&self) -> CheckResult {
+        let latency = self.bench_latency().await;
+        if latency.status == CheckStatus::Fail {
+            return CheckResult::from_details(vec![latency]);
+        }
+
+        let upload = self.bench_upload().await;
+        let download = self.bench_download().await;
+        CheckResult::from_details(vec![latency, upload, download])
+    }
+
+    fn component_name(&self) -> &'static str {
+        "Network"
+    }
+}
+
+/// Diagnose a single endpoint: DNS, TCP, optional TLS handshake, and RTT
+pub struct NetChecker {
+    target: String,
+    tls: bool,
+}
+
+impl Debug for NetChecker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NetChecker")
+    }
+}
+
+impl NetChecker {
+    /// Create a new NetChecker for the given host:port
+    pub fn new(target: String, tls: bool) -> Self {
+        Self { target, tls }
+    }
+}
+
+#[async_trait]
+impl ComponentChecker for NetChecker {
+    async fn check(&self) -> CheckResult {
+        let mut details = Vec::new();
+
+        let (host, port) = match network::parse_address(&self.target) {
+            Ok(host_port) => host_port,
+            Err(e) => {
+                details.push(CheckDetail::fail(
+                    "Address Parsing".to_string(),
+                    format!("Failed to parse address '{}': {}", self.target, e),
+                    None,
+                    Some("Check address format (should be host:port, with IPv6 hosts in brackets such as [::1]:3002)".to_string()),
+                ));
+                return CheckResult::from_details(details);
+            }
+        };
+
+        let (dns_detail, resolved) = network::check_dns_resolution("DNS Resolution".to_string(), &host, port).await;
+        details.push(dns_detail);
+        if resolved.is_empty() {
+            return CheckResult::from_details(details);
+        }
+
+        let start = Instant::now();
+        match timeout(Duration::from_secs(10), TcpStream::connect(resolved.as_slice())).await {
+            Ok(Ok(_stream)) => {
+                details.push(CheckDetail::pass(
+                    "TCP Connectivity".to_string(),
+                    format!("Successfully connected to {}", self.target),
+                    Some(start.elapsed()),
+                ));
+            }
+            Ok(Err(e)) => {
+                details.push(CheckDetail::fail(
+                    "TCP Connectivity".to_string(),
+                    format!("Failed to connect to {}: {}", self.target, network::describe_connect_error(&e)),
+                    Some(start.elapsed()),
+                    Some(network::connect_error_suggestion(&e, "the service")),
+                ));
+                return CheckResult::from_details(details);
+            }
+            Err(_) => {
+                details.push(CheckDetail::fail(
+                    "TCP Connectivity".to_string(),
+                    format!("Connection to {} timed out", self.target),
+                    Some(start.elapsed()),
+                    Some("Check firewalls and security groups between this host and the endpoint".to_string()),
+                ));
+                return CheckResult::from_details(details);
+            }
+        }
+
+        if self.tls {
+            details.push(network::check_tls_handshake("TLS Handshake".to_string(), &host, &resolved).await);
+        }
+
+        details.push(network::check_tcp_latency("TCP Latency".to_string(), &self.target, &resolved).await);
+
+        CheckResult::from_details(details)
+    }
+
+    fn component_name(&self) -> &'static str {
+        "Network"
+    }
+}
+
+/// Convert a byte count over a duration into MB/s
+fn mb_per_sec(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 / elapsed.as_secs_f64() / (1024.0 * 1024.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bench_against_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(stream));
+            }
+        });
+
+        let checker = NetBenchChecker::new(addr.to_string(), Duration::from_millis(200));
+        let result = checker.check().await;
+        assert!(result.success, "{:?}", result.details);
+        assert_eq!(result.details.len(), 3);
+        assert!(result.details.iter().all(|d| d.status == CheckStatus::Pass));
+    }
+
+    #[tokio::test]
+    async fn test_net_check_local_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while listener.accept().await.is_ok() {}
+        });
+
+        let result = NetChecker::new(addr.to_string(), false).check().await;
+        assert!(result.success, "{:?}", result.details);
+        let items: Vec<_> = result.details.iter().map(|d| d.item.as_str()).collect();
+        assert_eq!(items, vec!["DNS Resolution", "TCP Connectivity", "TCP Latency"]);
+    }
+
+    #[tokio::test]
+    async fn test_net_check_invalid_address() {
+        let result = NetChecker::new("localhost".to_string(), false).check().await;
+        assert!(!result.success);
+        assert_eq!(result.details[0].item, "Address Parsing");
+    }
+
+    #[tokio::test]
+    async fn test_bench_against_missing_server() {
+        let checker = NetBenchChecker::new("127.0.0.1:1".to_string(), Duration::from_millis(100));
+        let result = checker.check().await;
+        assert!(!result.success);
+        assert_eq!(result.details.len(), 1);
+    }
+}
@@ -0,0 +1,222 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Webhook notification when a run fails.
+//!
+//! With `--notify-webhook`, every result that is printed is also recorded here, and [`flush`]
+//! posts the recorded results once at the end of a run when any of them failed. `stepstone
+//! serve` flushes after each of its runs.
+
+use crate::common::{CheckResult, CheckStatus, Report};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Failed checks listed in a summary or Slack message before the rest is elided
+const MAX_LISTED_FAILURES: usize = 20;
+
+static NOTIFIER: Mutex<Option<Notifier>> = Mutex::new(None);
+
+/// Payload posted to the webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyFormat {
+    /// The full JSON report, as printed by `check-dir --output json`
+    Report,
+    /// Only the failed checks, as compact JSON
+    Summary,
+    /// A Slack incoming-webhook message
+    Slack,
+}
+
+impl std::str::FromStr for NotifyFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "report" => Ok(Self::Report),
+            "summary" => Ok(Self::Summary),
+            "slack" => Ok(Self::Slack),
+            other => Err(format!("unknown notify format '{}', expected report, summary or slack", other)),
+        }
+    }
+}
+
+struct Notifier {
+    url: String,
+    format: NotifyFormat,
+    report: Report,
+}
+
+impl Notifier {
+    /// Record a result, replacing an earlier result of the same file, e.g. before `--fix`
+    fn record(&mut self, component: &str, config_file: Option<&str>, result: &CheckResult) {
+        crate::sink::replace_section(&mut self.report, component, config_file, result);
+    }
+
+    /// The payload for the recorded results when any failed, clearing them either way
+    fn take_payload(&mut self) -> Option<String> {
+        let report = std::mem::take(&mut self.report);
+        if report.sections.is_empty() || report.success() {
+            return None;
+        }
+        Some(payload(self.format, &report))
+    }
+}
+
+/// Send notifications to `url` for the rest of the process
+pub fn configure(url: &str, format: NotifyFormat) {
+    // Slack and similar webhooks carry their token in the URL
+    crate::redact::register_secret(url);
+    *NOTIFIER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Notifier {
+        url: url.to_string(),
+        format,
+        report: Report::default(),
+    });
+}
+
+/// Record a result for the next [`flush`]; a no-op without `--notify-webhook`
+pub fn record(component: &str, config_file: Option<&str>, result: &CheckResult) {
+    if let Some(notifier) = NOTIFIER.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        notifier.record(component, config_file, result);
+    }
+}
+
+/// Post the recorded results when any failed; a run whose checks all passed sends nothing
+pub async fn flush() {
+    let pending = NOTIFIER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .and_then(|notifier| Some((notifier.url.clone(), notifier.take_payload()?)));
+    let Some((url, body)) = pending else {
+        return;
+    };
+
+    let client = match crate::settings::http_client(POST_TIMEOUT) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to build HTTP client for the webhook: {}", e);
+            return;
+        }
+    };
+    let response = client.post(&url).header("Content-Type", "application/json").body(body).send().await;
+    match response {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => eprintln!("Webhook notification was rejected: {}", response.status()),
+        Err(e) => eprintln!("Webhook notification failed: {}", crate::redact::redact(&e.to_string())),
+    }
+}
+
+fn payload(format: NotifyFormat, report: &Report) -> String {
+    let failures = report
+        .sections
+        .iter()
+        .flat_map(|section| {
+            section
+                .result
+                .details
+                .iter()
+                .filter(|detail| detail.status == CheckStatus::Fail)
+                .map(move |detail| (section, detail))
+        })
+        .collect::<Vec<_>>();
+
+    let value = match format {
+        NotifyFormat::Report => return report.to_json().unwrap_or_default(),
+        NotifyFormat::Summary => serde_json::json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "overall_result": "FAIL",
+            "failed_checks": failures.len(),
+            "failures": failures.iter().take(MAX_LISTED_FAILURES).map(|(section, detail)| serde_json::json!({
+                "component": section.component,
+                "config_file": section.config_file,
+                "item": detail.item,
+                "message": detail.message,
+                "suggestion": detail.suggestion,
+            })).collect::<Vec<_>>(),
+        }),
+        NotifyFormat::Slack => {
+            let mut text = format!("*stepstone: FAIL* ({} failed checks)", failures.len());
+            for (section, detail) in failures.iter().take(MAX_LISTED_FAILURES) {
+                text.push_str(&format!(
+                    "\n• `{}` {}: *{}*: {}",
+                    section.config_file, section.component, detail.item, detail.message
+                ));
+            }
+            if failures.len() > MAX_LISTED_FAILURES {
+                text.push_str(&format!("\n… and {} more", failures.len() - MAX_LISTED_FAILURES));
+            }
+            serde_json::json!({ "text": text })
+        }
+    };
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckDetail;
+
+    fn notifier(format: NotifyFormat) -> Notifier {
+        Notifier {
+            url: "http://127.0.0.1:1/hook".to_string(),
+            format,
+            report: Report::default(),
+        }
+    }
+
+    fn failed() -> CheckResult {
+        CheckResult::from_details(vec![
+            CheckDetail::pass("Etcd".to_string(), "ok".to_string(), None),
+            CheckDetail::fail("S3 Write".to_string(), "denied".to_string(), None, Some("check the policy".to_string())),
+        ])
+    }
+
+    #[test]
+    fn test_only_failed_runs_notify() {
+        let mut notifier = notifier(NotifyFormat::Summary);
+        assert!(notifier.take_payload().is_none());
+
+        notifier.record("Datanode", Some("datanode.toml"), &failed());
+        // The run after --fix replaces the failed one
+        notifier.record("Datanode", Some("datanode.toml"), &CheckResult::from_details(Vec::new()));
+        assert!(notifier.take_payload().is_none());
+
+        notifier.record("Datanode", Some("datanode.toml"), &failed());
+        let payload: serde_json::Value = serde_json::from_str(&notifier.take_payload().unwrap()).unwrap();
+        assert_eq!(payload["failed_checks"], 1);
+        assert_eq!(payload["failures"][0]["item"], "S3 Write");
+        assert_eq!(payload["failures"][0]["suggestion"], "check the policy");
+        // Recorded results are cleared after each flush
+        assert!(notifier.take_payload().is_none());
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let mut report = Report::default();
+        report.push("Datanode", "datanode.toml", failed());
+
+        let slack: serde_json::Value = serde_json::from_str(&payload(NotifyFormat::Slack, &report)).unwrap();
+        assert_eq!(
+            slack["text"],
+            "*stepstone: FAIL* (1 failed checks)\n• `datanode.toml` Datanode: *S3 Write*: denied"
+        );
+
+        let full: serde_json::Value = serde_json::from_str(&payload(NotifyFormat::Report, &report)).unwrap();
+        assert_eq!(full["overall_result"], "FAIL");
+        assert_eq!(full["files"][0]["component"], "Datanode");
+
+        assert_eq!("SLACK".parse::<NotifyFormat>(), Ok(NotifyFormat::Slack));
+        assert!("teams".parse::<NotifyFormat>().is_err());
+    }
+}
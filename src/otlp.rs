@@ -0,0 +1,324 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Check of the OTLP collector that receives traces when `logging.enable_otlp_tracing` is set.
+//!
+//! Spans are exported in the background and dropped when the collector is unreachable, so a
+//! wrong endpoint or protocol only shows up as missing traces. The check exports one test span
+//! over the configured protocol, with the configured `otlp_headers`.
+
+use crate::common::CheckDetail;
+use crate::config::LoggingConfig;
+use crate::export_metrics::{put_bytes, put_varint};
+use std::time::{Duration, Instant};
+
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Collector endpoints GreptimeDB exports to when `otlp_endpoint` is unset
+const DEFAULT_GRPC_ENDPOINT: &str = "http://localhost:4317";
+const DEFAULT_HTTP_ENDPOINT: &str = "http://localhost:4318";
+
+const GRPC_EXPORT_PATH: &str = "/opentelemetry.proto.collector.trace.v1.TraceService/Export";
+const HTTP_EXPORT_PATH: &str = "/v1/traces";
+
+/// Name of the span exported by the check
+const CHECK_SPAN: &str = "stepstone.otlp_check";
+
+/// Check the OTLP collector when tracing export is enabled
+pub async fn check_otlp_tracing(logging: Option<&LoggingConfig>) -> Vec<CheckDetail> {
+    let Some(logging) = logging.filter(|logging| logging.enable_otlp_tracing == Some(true)) else {
+        return Vec::new();
+    };
+    let item = "OTLP Tracing".to_string();
+    let protocol = logging.otlp_export_protocol.as_deref().unwrap_or("grpc");
+    let url = match export_url(protocol, logging.otlp_endpoint.as_deref()) {
+        Some(url) => url,
+        None => {
+            return vec![CheckDetail::fail(
+                item,
+                format!("logging.otlp_export_protocol \"{}\" is not supported", protocol),
+                None,
+                Some("Use \"grpc\" (port 4317) or \"http\" (port 4318)".to_string()),
+            )];
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+    let message = encode_export_request(CHECK_SPAN, now, now);
+    let start = Instant::now();
+    let headers = logging.otlp_headers.iter().flatten().map(|(name, value)| (name.clone(), value.clone())).collect::<Vec<_>>();
+
+    let detail = match export(protocol, &url, &headers, message).await {
+        Ok(()) => CheckDetail::pass(
+            item,
+            format!("{} accepted a test span ({}) over {}", url, CHECK_SPAN, protocol),
+            Some(start.elapsed()),
+        ),
+        Err(ExportError::Transport(e)) => CheckDetail::fail(
+            item,
+            format!("Cannot export to {} over {}: {}", url, protocol, e),
+            Some(start.elapsed()),
+            Some(format!(
+                "Check logging.otlp_endpoint and that the collector accepts OTLP/{} on that port (4317 for grpc, 4318 for http)",
+                protocol
+            )),
+        ),
+        Err(ExportError::Rejected { status, reason }) => {
+            let suggestion = if matches!(status, 401 | 403) || reason.starts_with("grpc-status 16") {
+                "Authentication was rejected; check logging.otlp_headers"
+            } else if matches!(status, 404 | 405 | 415) || reason.starts_with("grpc-status 12") {
+                "The endpoint does not speak this OTLP protocol; check logging.otlp_export_protocol and the port"
+            } else {
+                "Check the collector's logs for why the export was rejected"
+            };
+            CheckDetail::fail(
+                item,
+                format!("{} rejected the test span: {}", url, reason),
+                Some(start.elapsed()),
+                Some(suggestion.to_string()),
+            )
+        }
+    };
+    vec![detail]
+}
+
+/// Why an export did not go through
+pub(crate) enum ExportError {
+    /// The collector could not be reached
+    Transport(String),
+    /// The collector answered with an error, as an HTTP status or a `grpc-status`
+    Rejected { status: u16, reason: String },
+}
+
+/// Send an encoded `ExportTraceServiceRequest` to `url` over the grpc or http protocol
+pub(crate) async fn export(protocol: &str, url: &str, headers: &[(String, String)], message: Vec<u8>) -> Result<(), ExportError> {
+    let mut builder = reqwest::Client::builder().timeout(EXPORT_TIMEOUT);
+    if protocol == "grpc" {
+        builder = builder.http2_prior_knowledge();
+    }
+    let client = builder.build().map_err(|e| ExportError::Transport(format!("Failed to build HTTP client: {}", e)))?;
+    let mut request = if protocol == "grpc" {
+        // gRPC frames the message with a compression flag and a big-endian length
+        let mut body = vec![0];
+        body.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        body.extend_from_slice(&message);
+        client.post(url).header("Content-Type", "application/grpc").header("TE", "trailers").body(body)
+    } else {
+        client.post(url).header("Content-Type", "application/x-protobuf").body(message)
+    };
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+
+    let response = request.send().await.map_err(|e| ExportError::Transport(e.to_string()))?;
+    let status = response.status();
+    // A rejected gRPC call still answers 200, with the error in grpc-status
+    let grpc_error = response
+        .headers()
+        .get("grpc-status")
+        .and_then(|value| value.to_str().ok())
+        .filter(|code| *code != "0")
+        .map(|code| {
+            let message = response.headers().get("grpc-message").and_then(|value| value.to_str().ok()).unwrap_or("");
+            format!("grpc-status {} {}", code, message).trim_end().to_string()
+        });
+    match grpc_error {
+        None if status.is_success() => Ok(()),
+        error => Err(ExportError::Rejected {
+            status: status.as_u16(),
+            reason: error.unwrap_or_else(|| status.to_string()),
+        }),
+    }
+}
+
+/// URL of the trace export call, or `None` for an unknown protocol. Endpoints without a
+/// scheme are plain http; the http protocol appends `/v1/traces` unless already present.
+pub(crate) fn export_url(protocol: &str, endpoint: Option<&str>) -> Option<String> {
+    let (default_endpoint, path) = match protocol {
+        "grpc" => (DEFAULT_GRPC_ENDPOINT, GRPC_EXPORT_PATH),
+        "http" => (DEFAULT_HTTP_ENDPOINT, HTTP_EXPORT_PATH),
+        _ => return None,
+    };
+    let endpoint = endpoint.filter(|endpoint| !endpoint.is_empty()).unwrap_or(default_endpoint);
+    let endpoint = if endpoint.contains("://") {
+        endpoint.trim_end_matches('/').to_string()
+    } else {
+        format!("http://{}", endpoint.trim_end_matches('/'))
+    };
+    if endpoint.ends_with(path) {
+        Some(endpoint)
+    } else {
+        Some(format!("{}{}", endpoint, path))
+    }
+}
+
+/// A span to export
+pub(crate) struct Span {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub parent_span_id: Option<[u8; 8]>,
+    pub name: String,
+    pub start_ns: u64,
+    pub end_ns: u64,
+    pub attributes: Vec<(&'static str, String)>,
+    /// Points in time within the span, such as findings that recorded no bounds of their own
+    pub events: Vec<SpanEvent>,
+    /// Status message of a failed span; other spans are exported with an unset status
+    pub error: Option<String>,
+}
+
+/// An event of a [`Span`]
+pub(crate) struct SpanEvent {
+    pub time_ns: u64,
+    pub name: String,
+    pub attributes: Vec<(&'static str, String)>,
+}
+
+impl Span {
+    /// A span with a random ID and no attributes
+    pub(crate) fn new(trace_id: [u8; 16], parent_span_id: Option<[u8; 8]>, name: String, start_ns: u64, end_ns: u64) -> Self {
+        let mut span_id = [0; 8];
+        span_id.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..8]);
+        Self {
+            trace_id,
+            span_id,
+            parent_span_id,
+            name,
+            start_ns,
+            end_ns,
+            attributes: Vec::new(),
+            events: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut span = Vec::new();
+        put_bytes(&mut span, 1, &self.trace_id);
+        put_bytes(&mut span, 2, &self.span_id);
+        if let Some(parent_span_id) = &self.parent_span_id {
+            put_bytes(&mut span, 4, parent_span_id);
+        }
+        put_bytes(&mut span, 5, self.name.as_bytes());
+        // SPAN_KIND_INTERNAL
+        put_varint(&mut span, 6 << 3);
+        put_varint(&mut span, 1);
+        for (field, nanos) in [(7, self.start_ns), (8, self.end_ns)] {
+            put_varint(&mut span, (field << 3) | 1);
+            span.extend_from_slice(&nanos.to_le_bytes());
+        }
+        for (key, value) in &self.attributes {
+            put_bytes(&mut span, 9, &encode_attribute(key, value));
+        }
+        for event in &self.events {
+            let mut encoded = Vec::new();
+            put_varint(&mut encoded, (1 << 3) | 1);
+            encoded.extend_from_slice(&event.time_ns.to_le_bytes());
+            put_bytes(&mut encoded, 2, event.name.as_bytes());
+            for (key, value) in &event.attributes {
+                put_bytes(&mut encoded, 3, &encode_attribute(key, value));
+            }
+            put_bytes(&mut span, 11, &encoded);
+        }
+        if let Some(message) = &self.error {
+            // STATUS_CODE_ERROR
+            let mut status = Vec::new();
+            put_bytes(&mut status, 2, message.as_bytes());
+            put_varint(&mut status, 3 << 3);
+            put_varint(&mut status, 2);
+            put_bytes(&mut span, 15, &status);
+        }
+        span
+    }
+}
+
+/// A `KeyValue` with a string value
+fn encode_attribute(key: &str, value: &str) -> Vec<u8> {
+    let mut any_value = Vec::new();
+    put_bytes(&mut any_value, 1, value.as_bytes());
+    let mut attribute = Vec::new();
+    put_bytes(&mut attribute, 1, key.as_bytes());
+    put_bytes(&mut attribute, 2, &any_value);
+    attribute
+}
+
+/// Encode an `ExportTraceServiceRequest` holding one internal span of the `stepstone` service
+fn encode_export_request(name: &str, start_ns: u64, end_ns: u64) -> Vec<u8> {
+    encode_spans(&[Span::new(*uuid::Uuid::new_v4().as_bytes(), None, name.to_string(), start_ns, end_ns)])
+}
+
+/// Encode an `ExportTraceServiceRequest` holding spans of the `stepstone` service
+pub(crate) fn encode_spans(spans: &[Span]) -> Vec<u8> {
+    let mut scope = Vec::new();
+    put_bytes(&mut scope, 1, b"stepstone");
+    let mut scope_spans = Vec::new();
+    put_bytes(&mut scope_spans, 1, &scope);
+    for span in spans {
+        put_bytes(&mut scope_spans, 2, &span.encode());
+    }
+
+    let mut resource = Vec::new();
+    put_bytes(&mut resource, 1, &encode_attribute("service.name", "stepstone"));
+
+    let mut resource_spans = Vec::new();
+    put_bytes(&mut resource_spans, 1, &resource);
+    put_bytes(&mut resource_spans, 2, &scope_spans);
+
+    let mut request = Vec::new();
+    put_bytes(&mut request, 1, &resource_spans);
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_url() {
+        assert_eq!(
+            export_url("grpc", None).as_deref(),
+            Some("http://localhost:4317/opentelemetry.proto.collector.trace.v1.TraceService/Export")
+        );
+        assert_eq!(export_url("http", Some("collector:4318/")).as_deref(), Some("http://collector:4318/v1/traces"));
+        assert_eq!(
+            export_url("http", Some("https://collector:4318/v1/traces")).as_deref(),
+            Some("https://collector:4318/v1/traces")
+        );
+        assert_eq!(export_url("thrift", None), None);
+    }
+
+    #[test]
+    fn test_encode_export_request() {
+        let request = encode_export_request("s", 1, 2);
+        // resource_spans, then the resource with its single attribute
+        assert_eq!(request[0], 0x0a);
+        assert_eq!(request[1] as usize, request.len() - 2);
+        let span_tail = [0x30, 0x01, 0x39, 1, 0, 0, 0, 0, 0, 0, 0, 0x41, 2, 0, 0, 0, 0, 0, 0, 0];
+        assert!(request.ends_with(&span_tail));
+        assert!(request.windows(12).any(|window| window == b"service.name"));
+    }
+
+    #[tokio::test]
+    async fn test_otlp_tracing_disabled_or_unknown_protocol() {
+        let logging = LoggingConfig {
+            enable_otlp_tracing: Some(false),
+            otlp_export_protocol: Some("thrift".to_string()),
+            ..Default::default()
+        };
+        assert!(check_otlp_tracing(Some(&logging)).await.is_empty());
+
+        let logging = LoggingConfig { enable_otlp_tracing: Some(true), ..logging };
+        let details = check_otlp_tracing(Some(&logging)).await;
+        assert_eq!(details.len(), 1);
+        assert!(details[0].message.contains("\"thrift\" is not supported"));
+    }
+}
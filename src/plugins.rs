@@ -0,0 +1,314 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Custom checks run as external commands or WebAssembly modules, for org-specific checks such
+//! as VPC endpoints or internal CA reachability without forking stepstone.
+//!
+//! A plugin receives the parsed config on stdin as JSON:
+//!
+//! ```json
+//! { "component": "datanode", "config_file": "datanode.toml", "config": { ... } }
+//! ```
+//!
+//! and prints one check detail, or an array of them, on stdout:
+//!
+//! ```json
+//! [{ "item": "VPC Endpoint", "status": "PASS", "message": "...", "suggestion": null }]
+//! ```
+
+use crate::common::CheckDetail;
+use crate::error;
+use serde::Deserialize;
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+static PLUGINS: OnceLock<BTreeMap<String, PluginConfig>> = OnceLock::new();
+
+/// The `[plugins]` table of a plugins file
+#[derive(Debug, Deserialize)]
+struct PluginsFile {
+    #[serde(default)]
+    plugins: BTreeMap<String, PluginConfig>,
+}
+
+/// One `[plugins.<name>]` entry, running either `command` or the `wasm` module
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginConfig {
+    /// Executable to run
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// WebAssembly module implementing the check ABI of [`crate::wasm_plugin`]
+    pub wasm: Option<String>,
+    /// Components the plugin checks: metasrv, frontend or datanode; all when empty
+    #[serde(default)]
+    pub components: Vec<String>,
+    /// Seconds before the plugin is killed, `timeouts.plugin_secs` of the settings or 30 by default
+    pub timeout_secs: Option<u64>,
+    /// Instructions a WebAssembly plugin may execute, 1 billion by default
+    pub fuel: Option<u64>,
+}
+
+/// A check detail as printed by a plugin
+#[derive(Debug, Deserialize)]
+struct PluginDetail {
+    item: String,
+    status: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    suggestion: Option<String>,
+    #[serde(default)]
+    duration_ms: Option<u64>,
+    #[serde(default)]
+    error_code: Option<String>,
+    #[serde(default)]
+    doc_url: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PluginOutput {
+    One(PluginDetail),
+    Many(Vec<PluginDetail>),
+}
+
+/// Read the `[plugins]` table of `path` and run those plugins for the rest of the process
+pub fn load(path: &str) -> error::Result<()> {
+    let content = std::fs::read_to_string(path).context(error::FileSystemSnafu {
+        message: format!("Failed to read plugins file: {}", path),
+    })?;
+    let file: PluginsFile = toml::from_str(&content).context(error::TomlParsingSnafu {
+        message: format!("Failed to parse plugins file: {}", path),
+    })?;
+    if let Some((name, _)) = file.plugins.iter().find(|(_, plugin)| plugin.command.is_some() == plugin.wasm.is_some()) {
+        return error::InvalidConfigSnafu {
+            message: format!("[plugins.{}] in {} must set exactly one of command and wasm", name, path),
+        }
+        .fail();
+    }
+    if let Some((name, _)) = file.plugins.iter().find(|(_, plugin)| plugin.wasm.is_some() && !plugin.args.is_empty()) {
+        return error::InvalidConfigSnafu {
+            message: format!("[plugins.{}] in {} sets args, which only apply to command", name, path),
+        }
+        .fail();
+    }
+    register(file.plugins);
+    Ok(())
+}
+
+/// Run `plugins` for the rest of the process; only the first registration takes effect
+pub fn register(plugins: BTreeMap<String, PluginConfig>) {
+    let _ = PLUGINS.set(plugins);
+}
+
+/// Run every plugin configured for `component` against a config
+pub async fn run_plugins(component: &str, config_file: &Path, config: &toml::Value) -> Vec<CheckDetail> {
+    let Some(plugins) = PLUGINS.get() else {
+        return Vec::new();
+    };
+    let component = component.to_lowercase();
+    let input = serde_json::json!({
+        "component": component,
+        "config_file": config_file.display().to_string(),
+        "config": config,
+    })
+    .to_string();
+
+    let mut details = Vec::new();
+    for (name, plugin) in plugins {
+        if plugin.components.is_empty() || plugin.components.iter().any(|c| c.eq_ignore_ascii_case(&component)) {
+            details.extend(run_plugin(name, plugin, &input).await);
+        }
+    }
+    details
+}
+
+async fn run_plugin(name: &str, plugin: &PluginConfig, input: &str) -> Vec<CheckDetail> {
+    let item = format!("Plugin {}", name);
+    let start = Instant::now();
+    let output = match (&plugin.command, &plugin.wasm) {
+        (Some(command), _) => run_command(name, command, plugin, input).await,
+        (None, Some(wasm)) => crate::wasm_plugin::run(wasm, input.to_string(), plugin.fuel, plugin_timeout(plugin)).await,
+        (None, None) => Err(format!("[plugins.{}] sets neither command nor wasm", name)),
+    };
+    match output {
+        Ok(stdout) => parse_output(&item, &stdout, start),
+        Err(reason) => vec![CheckDetail::fail(item, reason, Some(start.elapsed()), None)],
+    }
+}
+
+/// `timeout_secs` of the plugin, else `timeouts.plugin_secs` of the settings
+fn plugin_timeout(plugin: &PluginConfig) -> Duration {
+    let default_secs = crate::settings::current().and_then(|settings| settings.timeouts.plugin_secs);
+    Duration::from_secs(plugin.timeout_secs.or(default_secs).unwrap_or(DEFAULT_TIMEOUT_SECS))
+}
+
+/// Run an executable plugin and return its stdout
+async fn run_command(name: &str, command: &str, plugin: &PluginConfig, input: &str) -> Result<String, String> {
+    let timeout = plugin_timeout(plugin);
+    let child = tokio::process::Command::new(command)
+        .args(&plugin.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = child.map_err(|e| format!("Cannot run {}: {}; check the command of [plugins.{}]", command, e, name))?;
+    // Written from its own task while the output is read, so a plugin printing more than a pipe
+    // holds before it has read all of its input cannot deadlock
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_string();
+        tokio::spawn(async move {
+            // A plugin that does not read its input closes the pipe early; that is not an error
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("Plugin failed: {}", e)),
+        Err(_) => return Err(format!("Plugin did not finish within {}s and was killed", timeout.as_secs())),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    // Output that parses counts even from a plugin exiting non-zero, e.g. to signal failed checks
+    if !output.status.success() && serde_json::from_str::<PluginOutput>(stdout.trim()).is_err() {
+        return Err(format!(
+            "Plugin exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(stdout)
+}
+
+/// The details printed by a plugin
+fn parse_output(item: &str, output: &str, start: Instant) -> Vec<CheckDetail> {
+    match serde_json::from_str::<PluginOutput>(output.trim()) {
+        Ok(PluginOutput::One(detail)) => vec![plugin_detail(detail)],
+        Ok(PluginOutput::Many(details)) => details.into_iter().map(plugin_detail).collect(),
+        Err(e) => vec![CheckDetail::fail(
+            item.to_string(),
+            format!("Plugin printed invalid output: {}", e),
+            Some(start.elapsed()),
+            None,
+        )],
+    }
+}
+
+fn plugin_detail(detail: PluginDetail) -> CheckDetail {
+    let duration = detail.duration_ms.map(Duration::from_millis);
+    let check = match detail.status.to_ascii_uppercase().as_str() {
+        "PASS" => CheckDetail::pass(detail.item, detail.message, duration),
+        "WARNING" | "WARN" => CheckDetail::warning(detail.item, detail.message, duration, detail.suggestion),
+        "FAIL" => CheckDetail::fail(detail.item, detail.message, duration, detail.suggestion),
+        "INFO" => CheckDetail::info(detail.item, detail.message, duration),
+        other => {
+            return CheckDetail::fail(
+                detail.item,
+                format!("Plugin reported unknown status \"{}\": {}", other, detail.message),
+                duration,
+                Some("Plugins report PASS, WARNING, FAIL or INFO".to_string()),
+            );
+        }
+    };
+    CheckDetail {
+        error_code: detail.error_code,
+        doc_url: detail.doc_url,
+        category: detail.category,
+        tags: detail.tags,
+        ..check
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    fn shell(script: &str) -> PluginConfig {
+        PluginConfig {
+            command: Some("sh".to_string()),
+            args: vec!["-c".to_string(), script.to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_plugin() {
+        let input = r#"{"component":"datanode","config":{"storage":{"type":"S3"}}}"#;
+
+        let echo = shell(r#"grep -q '"type":"S3"' && echo '[{"item":"VPC Endpoint","status":"WARNING","message":"no endpoint"},{"item":"CA","status":"PASS"}]'"#);
+        let details = run_plugin("vpc", &echo, input).await;
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].item, "VPC Endpoint");
+        assert_eq!(details[0].status, CheckStatus::Warning);
+        assert_eq!(details[1].status, CheckStatus::Pass);
+
+        let broken = shell("echo boom >&2; exit 3");
+        let details = run_plugin("broken", &broken, input).await;
+        assert_eq!(details[0].item, "Plugin broken");
+        assert_eq!(details[0].status, CheckStatus::Fail);
+        assert!(details[0].message.contains("boom"));
+
+        let slow = PluginConfig { timeout_secs: Some(1), ..shell("sleep 5") };
+        let details = run_plugin("slow", &slow, input).await;
+        assert!(details[0].message.contains("did not finish within 1s"));
+
+        // Filling the stderr pipe before reading a large input does not deadlock
+        let large_input = format!(r#"{{"config":"{}"}}"#, "x".repeat(256 * 1024));
+        let chatty = PluginConfig {
+            timeout_secs: Some(10),
+            ..shell(r#"head -c 262144 /dev/zero >&2; cat >/dev/null; echo '{"item":"Chatty","status":"PASS"}'"#)
+        };
+        let details = run_plugin("chatty", &chatty, &large_input).await;
+        assert_eq!(details[0].status, CheckStatus::Pass, "{}", details[0].message);
+    }
+
+    #[test]
+    fn test_plugins_file() {
+        let file: PluginsFile = toml::from_str(
+            r#"
+[plugins.vpc-endpoints]
+command = "/opt/checks/vpc-endpoints"
+args = ["--region", "us-east-1"]
+components = ["datanode"]
+"#,
+        )
+        .unwrap();
+        let plugin = &file.plugins["vpc-endpoints"];
+        assert_eq!(plugin.command.as_deref(), Some("/opt/checks/vpc-endpoints"));
+        assert_eq!(plugin.components, vec!["datanode".to_string()]);
+        assert_eq!(plugin.timeout_secs, None);
+    }
+
+    #[test]
+    fn test_load_rejects_args_for_wasm() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plugins.toml");
+        std::fs::write(&path, "[plugins.policy]\nwasm = \"policy.wasm\"\nargs = [\"--strict\"]\n").unwrap();
+        let error = load(path.to_str().unwrap()).unwrap_err().to_string();
+        assert!(error.contains("only apply to command"), "{}", error);
+    }
+}
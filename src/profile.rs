@@ -0,0 +1,284 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Check profiles: named selections of which checks run and how hard they push.
+//!
+//! Config validation and connectivity checks always run. The built-in profiles add to them:
+//!
+//! - `quick`: nothing more; object storage is only listed and etcd only read, neither is written
+//! - `standard` (default): test objects are written, read back and deleted, small performance
+//!   tests run against object storage, and the metadata backend's writes, leases and
+//!   permissions are tested
+//! - `deep`: everything, including 1 GB objects, backend benchmarks, bandwidth estimates, listing
+//!   and batch delete tests, a 30 second mixed workload and probes of the advertised addresses
+//!
+//! Teams define their own under `[profiles.<name>]` in the settings file.
+
+use crate::error;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Which checks run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile {
+    /// Write, read back and delete test objects in object storage, with 64 MB and concurrent
+    /// writes, and test data in the metadata backend
+    pub storage_operations: bool,
+    /// Performance tests of object storage and metadata backend benchmarks
+    pub performance: bool,
+    /// 1 GB object writes
+    pub large_objects: bool,
+    /// Network bandwidth estimate to object storage
+    pub bandwidth: bool,
+    /// Whether the advertised gRPC addresses resolve to a local interface
+    pub probe_advertise: bool,
+    /// Paginated listing of a few hundred objects, delimiter semantics and a batch delete of a
+    /// hundred objects in object storage
+    pub listing: bool,
+    /// Seconds of concurrent small writes and large reads against object storage, 0 to skip
+    pub workload_secs: u64,
+}
+
+impl Profile {
+    pub const QUICK: Profile = Profile {
+        storage_operations: false,
+        performance: false,
+        large_objects: false,
+        bandwidth: false,
+        probe_advertise: false,
+        listing: false,
+        workload_secs: 0,
+    };
+
+    pub const STANDARD: Profile = Profile {
+        storage_operations: true,
+        ..Profile::QUICK
+    };
+
+    pub const DEEP: Profile = Profile {
+        storage_operations: true,
+        performance: true,
+        large_objects: true,
+        bandwidth: true,
+        probe_advertise: true,
+        listing: true,
+        workload_secs: 30,
+    };
+
+    /// A built-in profile by name
+    pub fn builtin(name: &str) -> Option<Profile> {
+        match name {
+            "quick" => Some(Profile::QUICK),
+            "standard" => Some(Profile::STANDARD),
+            "deep" => Some(Profile::DEEP),
+            _ => None,
+        }
+    }
+
+    /// This profile with the switches off whose checks `selected` rejects, so `--tag-filter`
+    /// skips writes and performance tests it would only drop from the report
+    pub fn select(self, selected: impl Fn(&str) -> bool) -> Profile {
+        let any = |ids: &[&str]| ids.iter().any(|id| selected(id));
+        let mut profile = Profile {
+            performance: self.performance && any(PERFORMANCE_CHECKS),
+            large_objects: self.large_objects && any(LARGE_OBJECT_CHECKS),
+            bandwidth: self.bandwidth && any(BANDWIDTH_CHECKS),
+            listing: self.listing && any(LISTING_CHECKS),
+            workload_secs: if any(WORKLOAD_CHECKS) { self.workload_secs } else { 0 },
+            ..self
+        };
+        // The other object storage tests only run after the test object was written
+        let dependents = profile.performance
+            || profile.large_objects
+            || profile.bandwidth
+            || profile.listing
+            || profile.workload_secs > 0;
+        profile.storage_operations = self.storage_operations && (any(STORAGE_OPERATION_CHECKS) || dependents);
+        profile
+    }
+}
+
+/// Registry IDs of the checks `storage_operations` runs
+const STORAGE_OPERATION_CHECKS: &[&str] = &[
+    "datanode.storage.s3.put",
+    "datanode.storage.s3.get",
+    "datanode.storage.s3.delete",
+    "datanode.storage.s3.perf_64mb",
+    "datanode.storage.s3.concurrency",
+    "datanode.storage.s3.throttling",
+    "datanode.storage.s3.stat",
+    "datanode.storage.s3.provider_api",
+    "metasrv.etcd.kv",
+    "metasrv.etcd.lease",
+    "metasrv.rds.permissions",
+];
+
+/// Registry IDs of the checks `performance` runs
+const PERFORMANCE_CHECKS: &[&str] =
+    &["datanode.storage.s3.performance", "metasrv.etcd.performance", "metasrv.rds.performance"];
+
+/// Registry IDs of the checks `large_objects` runs
+const LARGE_OBJECT_CHECKS: &[&str] = &["datanode.storage.s3.perf_1gb"];
+
+/// Registry IDs of the checks `bandwidth` runs
+const BANDWIDTH_CHECKS: &[&str] = &["datanode.storage.s3.bandwidth"];
+
+/// Registry IDs of the checks `listing` runs
+const LISTING_CHECKS: &[&str] = &["datanode.storage.s3.listing", "datanode.storage.s3.batch_delete"];
+
+/// Registry IDs of the checks `workload_secs` runs
+const WORKLOAD_CHECKS: &[&str] = &["datanode.storage.s3.workload"];
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::STANDARD
+    }
+}
+
+/// A `[profiles.<name>]` table of the settings file: a built-in profile with some checks
+/// switched on or off
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Built-in profile to start from, `standard` by default
+    pub extends: Option<String>,
+    pub storage_operations: Option<bool>,
+    pub performance: Option<bool>,
+    pub large_objects: Option<bool>,
+    pub bandwidth: Option<bool>,
+    pub probe_advertise: Option<bool>,
+    pub listing: Option<bool>,
+    pub workload_secs: Option<u64>,
+}
+
+/// The profile called `name`; profiles of the settings file take precedence over built-in
+/// ones of the same name
+pub fn resolve(name: &str, custom: &BTreeMap<String, ProfileConfig>) -> error::Result<Profile> {
+    let Some(config) = custom.get(name) else {
+        if let Some(profile) = Profile::builtin(name) {
+            return Ok(profile);
+        }
+        let mut known = vec!["quick", "standard", "deep"];
+        known.extend(custom.keys().map(String::as_str));
+        return error::InvalidConfigSnafu {
+            message: format!("Unknown profile '{}', expected one of: {}", name, known.join(", ")),
+        }
+        .fail();
+    };
+
+    let base_name = config.extends.as_deref().unwrap_or("standard");
+    let Some(base) = Profile::builtin(base_name) else {
+        return error::InvalidConfigSnafu {
+            message: format!("[profiles.{}] extends '{}', expected quick, standard or deep", name, base_name),
+        }
+        .fail();
+    };
+    Ok(Profile {
+        storage_operations: config.storage_operations.unwrap_or(base.storage_operations),
+        performance: config.performance.unwrap_or(base.performance),
+        large_objects: config.large_objects.unwrap_or(base.large_objects),
+        bandwidth: config.bandwidth.unwrap_or(base.bandwidth),
+        probe_advertise: config.probe_advertise.unwrap_or(base.probe_advertise),
+        listing: config.listing.unwrap_or(base.listing),
+        workload_secs: config.workload_secs.unwrap_or(base.workload_secs),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let custom: BTreeMap<String, ProfileConfig> = toml::from_str(
+            r#"
+[nightly]
+extends = "deep"
+large_objects = false
+workload_secs = 120
+
+[standard]
+storage_operations = false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(resolve("quick", &custom).unwrap(), Profile::QUICK);
+        assert_eq!(resolve("deep", &custom).unwrap(), Profile::DEEP);
+        assert_eq!(
+            resolve("nightly", &custom).unwrap(),
+            Profile { large_objects: false, workload_secs: 120, ..Profile::DEEP }
+        );
+        assert_eq!(resolve("standard", &custom).unwrap(), Profile::QUICK);
+
+        let error = resolve("thorough", &custom).unwrap_err().to_string();
+        assert!(error.contains("quick, standard, deep, nightly"), "{}", error);
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(Profile::DEEP.select(|_| true), Profile::DEEP);
+        assert_eq!(Profile::DEEP.select(|_| false), Profile { probe_advertise: true, ..Profile::QUICK });
+
+        // The selected tests run after the test object is written, so they keep storage_operations
+        let performance = Profile::DEEP.select(|id| PERFORMANCE_CHECKS.contains(&id));
+        assert!(performance.performance && performance.storage_operations);
+        assert!(!performance.large_objects && performance.workload_secs == 0);
+        let large = Profile::DEEP.select(|id| LARGE_OBJECT_CHECKS.contains(&id));
+        assert!(large.large_objects && large.storage_operations && !large.bandwidth);
+        assert_eq!(Profile::QUICK.select(|_| true), Profile::QUICK);
+    }
+
+    /// Whether `profile` runs the check `id`; checks no switch controls always run
+    fn runs(profile: Profile, id: &str) -> bool {
+        let switches = [
+            (STORAGE_OPERATION_CHECKS, profile.storage_operations),
+            (PERFORMANCE_CHECKS, profile.performance),
+            (LARGE_OBJECT_CHECKS, profile.large_objects),
+            (BANDWIDTH_CHECKS, profile.bandwidth),
+            (LISTING_CHECKS, profile.listing),
+            (WORKLOAD_CHECKS, profile.workload_secs > 0),
+        ];
+        switches.iter().filter(|(ids, _)| ids.contains(&id)).all(|(_, on)| *on)
+    }
+
+    #[test]
+    fn test_registry_profiles() {
+        let switched = [
+            STORAGE_OPERATION_CHECKS,
+            PERFORMANCE_CHECKS,
+            LARGE_OBJECT_CHECKS,
+            BANDWIDTH_CHECKS,
+            LISTING_CHECKS,
+            WORKLOAD_CHECKS,
+        ];
+        for id in switched.concat() {
+            assert!(crate::checks::find(id).is_some(), "{} is not in the registry", id);
+        }
+
+        // The registry's profile is the least built-in profile that runs the check
+        let order = ["quick", "standard", "deep"];
+        for check in crate::checks::CHECKS {
+            let least = order.iter().position(|name| *name == check.profile).unwrap();
+            for (rank, name) in order.iter().enumerate() {
+                assert_eq!(
+                    runs(Profile::builtin(name).unwrap(), check.id),
+                    rank >= least,
+                    "{} is listed for {} but {} disagrees",
+                    check.id,
+                    check.profile,
+                    name
+                );
+            }
+        }
+    }
+}
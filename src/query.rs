@@ -0,0 +1,173 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of the `[query]` settings of datanodes and frontends against the host.
+//!
+//! Query settings rarely fail on their own; they fail under load, when a parallelism above the
+//! core count only adds scheduling overhead, or when unlimited concurrent queries on a small
+//! host each take their share of memory until the node is OOM-killed.
+
+use crate::common::CheckDetail;
+use crate::config::QueryConfig;
+use crate::logging::format_bytes;
+
+/// Hosts below either of these are too small to run unlimited concurrent queries
+const SMALL_HOST_CORES: u32 = 4;
+const SMALL_HOST_MEMORY: u64 = 8 << 30;
+
+/// Resources of the host the query settings are compared with
+#[derive(Debug, Clone, Copy)]
+pub struct Host {
+    pub cores: u32,
+    pub memory: Option<u64>,
+}
+
+impl Host {
+    /// This host: its available cores and memory limit
+    pub fn current() -> Option<Host> {
+        let cores = std::thread::available_parallelism().ok()?.get() as u32;
+        Some(Host { cores, memory: crate::mito::memory_limit() })
+    }
+
+    fn is_small(self) -> bool {
+        self.cores < SMALL_HOST_CORES || self.memory.is_some_and(|memory| memory < SMALL_HOST_MEMORY)
+    }
+
+    fn describe(self) -> String {
+        match self.memory {
+            Some(memory) => format!("{} cores and {}", self.cores, format_bytes(memory)),
+            None => format!("{} cores", self.cores),
+        }
+    }
+}
+
+/// Check `query.parallelism` and `query.allow_query_fallback`, and on datanodes
+/// `max_concurrent_queries`, which frontends do not have and pass as None; a datanode that does
+/// not set it passes GreptimeDB's default of 0. Without the `host` the config runs on, nothing
+/// is compared with cores or memory.
+pub fn check_query(query: Option<&QueryConfig>, max_concurrent_queries: Option<u32>, host: Option<Host>) -> Vec<CheckDetail> {
+    let mut details = Vec::new();
+    let parallelism = query.and_then(|query| query.parallelism).filter(|parallelism| *parallelism > 0);
+    let oversubscribed = host.is_some_and(|host| parallelism.is_some_and(|parallelism| parallelism > host.cores));
+
+    if let (Some(parallelism), Some(host)) = (parallelism, host) {
+        details.push(if oversubscribed {
+            CheckDetail::warning(
+                "Query Parallelism".to_string(),
+                format!("query.parallelism = {} exceeds the {} cores", parallelism, host.cores),
+                None,
+                Some("Set query.parallelism to at most the core count, or 0 to use all cores".to_string()),
+            )
+        } else {
+            CheckDetail::pass(
+                "Query Parallelism".to_string(),
+                format!("query.parallelism = {} on {} cores", parallelism, host.cores),
+                None,
+            )
+        });
+    }
+
+    // 0 means unlimited
+    let unlimited = max_concurrent_queries == Some(0);
+    if query.and_then(|query| query.allow_query_fallback) == Some(true) {
+        let mut problems = Vec::new();
+        if oversubscribed {
+            problems.push("query.parallelism above the core count");
+        }
+        if unlimited {
+            problems.push("unlimited max_concurrent_queries");
+        }
+        details.push(if problems.is_empty() {
+            CheckDetail::info(
+                "Query Fallback".to_string(),
+                "allow_query_fallback lets queries the distributed planner cannot push down run on the frontend".to_string(),
+                None,
+            )
+        } else {
+            CheckDetail::warning(
+                "Query Fallback".to_string(),
+                format!(
+                    "allow_query_fallback with {}: fallback queries pull every matching row into one node, without a bound on how many run at once",
+                    problems.join(" and ")
+                ),
+                None,
+                Some("Disable allow_query_fallback, or bound parallelism and max_concurrent_queries".to_string()),
+            )
+        });
+    }
+
+    if unlimited {
+        let (message, suggestion) = match host {
+            Some(host) if host.is_small() => (
+                format!("max_concurrent_queries is 0 (unlimited) on a host with {}", host.describe()),
+                format!(
+                    "Bound it to about twice the cores, e.g. max_concurrent_queries = {}, so bursts queue instead of exhausting memory",
+                    host.cores * 2
+                ),
+            ),
+            Some(host) => (
+                format!("max_concurrent_queries is 0 (unlimited); a burst of queries can still exhaust the {}", host.describe()),
+                format!("Bound it to about twice the cores, e.g. max_concurrent_queries = {}", host.cores * 2),
+            ),
+            None => (
+                "max_concurrent_queries is 0 (unlimited); a burst of queries can exhaust the datanode's memory".to_string(),
+                "Bound it to about twice the datanode's cores, so bursts queue instead of exhausting memory".to_string(),
+            ),
+        };
+        details.push(CheckDetail::warning("Max Concurrent Queries".to_string(), message, None, Some(suggestion)));
+    }
+
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_check_query() {
+        let small = Host { cores: 2, memory: Some(4 << 30) };
+        let large = Host { cores: 32, memory: Some(128 << 30) };
+        let query = QueryConfig {
+            parallelism: Some(16),
+            allow_query_fallback: Some(true),
+        };
+
+        let details = check_query(Some(&query), Some(0), Some(small));
+        let statuses = details.iter().map(|detail| (detail.item.as_str(), detail.status)).collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Query Parallelism", CheckStatus::Warning),
+                ("Query Fallback", CheckStatus::Warning),
+                ("Max Concurrent Queries", CheckStatus::Warning),
+            ]
+        );
+        assert!(details[2].suggestion.as_deref().unwrap().contains("max_concurrent_queries = 4"));
+
+        let details = check_query(Some(&query), Some(0), Some(large));
+        assert_eq!(details[0].status, CheckStatus::Pass);
+        assert!(details[1].message.contains("unlimited max_concurrent_queries"));
+        assert_eq!(details[2].status, CheckStatus::Warning);
+        assert!(details[2].suggestion.as_deref().unwrap().contains("max_concurrent_queries = 64"));
+
+        let details = check_query(Some(&query), None, Some(large));
+        assert_eq!(details[1].status, CheckStatus::Info);
+        assert!(check_query(None, Some(64), Some(small)).is_empty());
+
+        // A config checked from another machine is not compared with this host
+        let details = check_query(Some(&query), Some(0), None);
+        let items = details.iter().map(|detail| detail.item.as_str()).collect::<Vec<_>>();
+        assert_eq!(items, ["Query Fallback", "Max Concurrent Queries"]);
+    }
+}
@@ -0,0 +1,244 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Differences between two saved JSON reports: checks that changed status, appeared or
+//! disappeared, or became slower.
+
+use crate::common::{CheckDetail, CheckStatus};
+use std::collections::HashMap;
+
+/// One check read back from a saved report
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedCheck {
+    pub component: String,
+    pub config_file: String,
+    pub item: String,
+    pub status: CheckStatus,
+    pub message: String,
+    pub duration_ms: Option<u64>,
+}
+
+impl SavedCheck {
+    fn key(&self) -> (&str, &str, &str) {
+        (&self.component, &self.config_file, &self.item)
+    }
+
+    fn name(&self) -> String {
+        format!("{}: {}", self.component, self.item)
+    }
+}
+
+/// When a check counts as slower
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyThreshold {
+    /// New duration over old duration above which a check is slower, e.g. 1.5 for 50%
+    pub ratio: f64,
+    /// Slowdowns smaller than this are noise, whatever the ratio
+    pub min_delta_ms: u64,
+    /// Status a slower check is reported with
+    pub status: CheckStatus,
+}
+
+/// The checks of a report saved with `--output json`, either of one component or of a
+/// multi-file report such as `check-dir` prints
+pub fn load_checks(report: &serde_json::Value) -> Vec<SavedCheck> {
+    let sections = match report.get("files").and_then(|files| files.as_array()) {
+        Some(files) => files.iter().collect(),
+        None => vec![report],
+    };
+
+    let mut checks = Vec::new();
+    for section in sections {
+        let component = section["component"].as_str().unwrap_or("").to_string();
+        let config_file = section["config_file"].as_str().unwrap_or("").to_string();
+        for detail in section["details"].as_array().into_iter().flatten() {
+            let status = match detail["status"].as_str() {
+                Some("PASS") => CheckStatus::Pass,
+                Some("WARNING") => CheckStatus::Warning,
+                Some("INFO") => CheckStatus::Info,
+                _ => CheckStatus::Fail,
+            };
+            checks.push(SavedCheck {
+                component: component.clone(),
+                config_file: config_file.clone(),
+                item: detail["item"].as_str().unwrap_or("").to_string(),
+                status,
+                message: detail["message"].as_str().unwrap_or("").to_string(),
+                duration_ms: detail["duration_ms"].as_u64(),
+            });
+        }
+    }
+    checks
+}
+
+/// One detail per difference from `old` to `new`: regressions as failures or warnings by
+/// their new status, fixes as passes. Checks are matched by component, file and name.
+pub fn diff_checks(old: &[SavedCheck], new: &[SavedCheck], latency: LatencyThreshold) -> Vec<CheckDetail> {
+    let mut old_by_key = HashMap::new();
+    for check in old {
+        old_by_key.entry(check.key()).or_insert(check);
+    }
+
+    let mut details = Vec::new();
+    let mut seen = Vec::new();
+    for check in new {
+        if seen.contains(&check.key()) {
+            continue;
+        }
+        seen.push(check.key());
+
+        let Some(before) = old_by_key.get(&check.key()) else {
+            if check.status != CheckStatus::Pass {
+                details.push(detail_with_status(
+                    check.status.clone(),
+                    check.name(),
+                    format!("New check, {}: {}", status_name(&check.status), check.message),
+                ));
+            }
+            continue;
+        };
+
+        if before.status != check.status {
+            let message = format!(
+                "{} -> {}: {}",
+                status_name(&before.status),
+                status_name(&check.status),
+                check.message
+            );
+            let status = if rank(&check.status) < rank(&before.status) { CheckStatus::Pass } else { check.status.clone() };
+            details.push(detail_with_status(status, check.name(), message));
+            continue;
+        }
+
+        if let (Some(old_ms), Some(new_ms)) = (before.duration_ms, check.duration_ms) {
+            let slower = new_ms as f64 > old_ms as f64 * latency.ratio && new_ms.saturating_sub(old_ms) >= latency.min_delta_ms;
+            if slower {
+                let percent = if old_ms == 0 { f64::INFINITY } else { new_ms.saturating_sub(old_ms) as f64 * 100.0 / old_ms as f64 };
+                details.push(detail_with_status(
+                    latency.status.clone(),
+                    check.name(),
+                    format!("Slower: {}ms -> {}ms (+{:.0}%)", old_ms, new_ms, percent),
+                ));
+            }
+        }
+    }
+
+    for check in old {
+        if !seen.contains(&check.key()) {
+            seen.push(check.key());
+            details.push(CheckDetail::warning(
+                check.name(),
+                format!("No longer run; was {}", status_name(&check.status)),
+                None,
+                None,
+            ));
+        }
+    }
+    details
+}
+
+fn detail_with_status(status: CheckStatus, item: String, message: String) -> CheckDetail {
+    match status {
+        CheckStatus::Pass => CheckDetail::pass(item, message, None),
+        CheckStatus::Warning => CheckDetail::warning(item, message, None, None),
+        CheckStatus::Fail => CheckDetail::fail(item, message, None, None),
+        CheckStatus::Info => CheckDetail::info(item, message, None),
+    }
+}
+
+fn status_name(status: &CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Pass => "PASS",
+        CheckStatus::Warning => "WARNING",
+        CheckStatus::Fail => "FAIL",
+        CheckStatus::Info => "INFO",
+    }
+}
+
+fn rank(status: &CheckStatus) -> u8 {
+    match status {
+        CheckStatus::Pass | CheckStatus::Info => 0,
+        CheckStatus::Warning => 1,
+        CheckStatus::Fail => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: LatencyThreshold = LatencyThreshold {
+        ratio: 1.5,
+        min_delta_ms: 50,
+        status: CheckStatus::Warning,
+    };
+
+    fn report(details: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "overall_result": "FAIL",
+            "files": [{ "component": "Datanode", "config_file": "datanode.toml", "details": details }],
+        })
+    }
+
+    #[test]
+    fn test_diff_checks() {
+        let old = load_checks(&report(serde_json::json!([
+            { "item": "Etcd", "status": "PASS", "message": "ok", "duration_ms": 10 },
+            { "item": "S3 Write", "status": "PASS", "message": "ok", "duration_ms": 100 },
+            { "item": "S3 Read", "status": "FAIL", "message": "denied", "duration_ms": null },
+            { "item": "Kafka", "status": "PASS", "message": "ok", "duration_ms": 5 },
+        ])));
+        let new = load_checks(&report(serde_json::json!([
+            { "item": "Etcd", "status": "PASS", "message": "ok", "duration_ms": 40 },
+            { "item": "S3 Write", "status": "FAIL", "message": "denied", "duration_ms": 100 },
+            { "item": "S3 Read", "status": "PASS", "message": "ok", "duration_ms": 500 },
+            { "item": "S3 List", "status": "WARNING", "message": "slow", "duration_ms": 900 },
+        ])));
+
+        let details = diff_checks(&old, &new, THRESHOLD)
+            .into_iter()
+            .map(|detail| (detail.item, detail.status, detail.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            details,
+            vec![
+                ("Datanode: S3 Write".to_string(), CheckStatus::Fail, "PASS -> FAIL: denied".to_string()),
+                ("Datanode: S3 Read".to_string(), CheckStatus::Pass, "FAIL -> PASS: ok".to_string()),
+                ("Datanode: S3 List".to_string(), CheckStatus::Warning, "New check, WARNING: slow".to_string()),
+                ("Datanode: Kafka".to_string(), CheckStatus::Warning, "No longer run; was PASS".to_string()),
+            ]
+        );
+
+        // Etcd went from 10ms to 40ms: 4x slower, but within the 50ms noise floor
+        let slower = load_checks(&report(serde_json::json!([
+            { "item": "Etcd", "status": "PASS", "message": "ok", "duration_ms": 400 },
+        ])));
+        let details = diff_checks(&old[..1], &slower, THRESHOLD);
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].message, "Slower: 10ms -> 400ms (+3900%)");
+    }
+
+    #[test]
+    fn test_load_single_result() {
+        let result = serde_json::json!({
+            "component": "Metasrv",
+            "config_file": null,
+            "details": [{ "item": "Etcd", "status": "WARNING", "message": "2 members", "duration_ms": 3 }],
+        });
+        let checks = load_checks(&result);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].component, "Metasrv");
+        assert_eq!(checks[0].config_file, "");
+        assert_eq!(checks[0].status, CheckStatus::Warning);
+        assert_eq!(checks[0].duration_ms, Some(3));
+    }
+}
@@ -0,0 +1,142 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimate of the steady-state object storage write rate of a datanode.
+//!
+//! Every flush of a region uploads an SST, its index and a manifest delta, and compaction later
+//! rewrites and deletes about as much. With thousands of regions or a small write buffer, the
+//! flushes alone can reach the request rate a provider allows on one prefix or bucket, and the
+//! datanode then spends its time retrying throttled uploads. The estimate needs the expected
+//! region count from the `[capacity]` settings, since a config cannot tell it.
+
+use crate::common::CheckDetail;
+use crate::config::DatanodeConfig;
+use crate::lint::{parse_duration, parse_size};
+use crate::settings::Capacity;
+
+/// Mito defaults the estimate falls back to
+const DEFAULT_AUTO_FLUSH_SECS: f64 = 3600.0;
+const DEFAULT_WRITE_BUFFER: u64 = 1 << 30;
+const DEFAULT_PART_SIZE: u64 = 8 << 20;
+
+/// Writes of one flush (SST, index, manifest delta), and of compacting its output later
+/// (rewritten SST, index and manifest delta, plus deleting the inputs' SST and index)
+const WRITES_PER_FLUSH: f64 = 3.0;
+const COMPACTION_WRITES_PER_FLUSH: f64 = 5.0;
+
+/// Share of a provider's limit above which throttling is likely, since flushes and compactions
+/// come in bursts rather than evenly
+const BURST_HEADROOM: f64 = 0.5;
+
+/// Write requests per second a provider allows, and what the limit applies to
+fn write_limit(storage_type: &str, endpoint: Option<&str>) -> Option<(f64, &'static str)> {
+    match storage_type.to_ascii_lowercase().as_str() {
+        // Other S3-compatible stores have limits of their own, or none
+        "s3" if endpoint.is_none_or(|endpoint| endpoint.contains("amazonaws.com")) => {
+            Some((3500.0, "per prefix on S3 until it repartitions the bucket"))
+        }
+        "gcs" => Some((1000.0, "per bucket on GCS until it scales up")),
+        "oss" => Some((10000.0, "per bucket on OSS")),
+        "azblob" => Some((20000.0, "per storage account on Azure Blob Storage")),
+        _ => None,
+    }
+}
+
+/// Estimated flushes and write requests per second of `regions` regions
+fn estimate(config: &DatanodeConfig, regions: u32, write_mib_per_sec: f64) -> (f64, f64) {
+    let mito = config.mito();
+    let flush_secs = mito
+        .and_then(|mito| mito.auto_flush_interval.as_deref())
+        .and_then(parse_duration)
+        .map(|interval| interval.as_secs_f64())
+        .filter(|secs| *secs > 0.0)
+        .unwrap_or(DEFAULT_AUTO_FLUSH_SECS);
+    let buffer = mito
+        .and_then(|mito| mito.global_write_buffer_size.as_deref())
+        .and_then(parse_size)
+        .unwrap_or(DEFAULT_WRITE_BUFFER) as f64;
+    let part_size = mito
+        .and_then(|mito| mito.sst_write_buffer_size.as_deref())
+        .and_then(parse_size)
+        .unwrap_or(DEFAULT_PART_SIZE)
+        .max(1) as f64;
+
+    let regions = f64::from(regions);
+    let write_bytes = write_mib_per_sec * (1 << 20) as f64;
+    // A full write buffer flushes regions at their share of it, at the latest each interval
+    let flushes = (regions / flush_secs).max(write_bytes * regions / buffer);
+    // Large SSTs are uploaded in parts, one request each
+    let parts = write_bytes / part_size;
+    let writes = flushes * (WRITES_PER_FLUSH + COMPACTION_WRITES_PER_FLUSH) + parts * 2.0;
+    (flushes, writes)
+}
+
+/// Compare the estimated write rate with the limit of the storage provider. Nothing is reported
+/// without a region count, or for File storage.
+pub fn check_request_rate(config: &DatanodeConfig, capacity: &Capacity) -> Option<CheckDetail> {
+    let regions = capacity.regions?;
+    let storage = config.storage.as_ref()?;
+    let storage_type = storage.storage_type.as_deref().unwrap_or("File");
+    if storage_type.eq_ignore_ascii_case("file") {
+        return None;
+    }
+
+    let (flushes, writes) = estimate(config, regions, capacity.write_mib_per_sec.unwrap_or(0.0));
+    let summary = format!(
+        "{} regions flush about {:.1} times/s, about {:.0} write requests/s with compaction",
+        regions, flushes, writes
+    );
+    let item = "Object Storage Request Rate".to_string();
+    Some(match write_limit(storage_type, storage.endpoint.as_deref()) {
+        Some((limit, scope)) if writes > limit * BURST_HEADROOM => CheckDetail::warning(
+            item,
+            format!("{}, against {:.0} allowed {}", summary, limit, scope),
+            None,
+            Some("Raise region_engine.mito.global_write_buffer_size and auto_flush_interval to flush less often, spread regions over more datanodes or buckets, or ask the provider to pre-partition the bucket".to_string()),
+        ),
+        Some((limit, scope)) => CheckDetail::pass(item, format!("{}, within {:.0} allowed {}", summary, limit, scope), None),
+        None => CheckDetail::info(item, format!("{}; the provider's request limit is unknown", summary), None),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use crate::config::{ConfigParser, DatanodeStorageConfig};
+
+    #[test]
+    fn test_check_request_rate() {
+        let mut config = ConfigParser::default_datanode_config();
+        config.storage = Some(DatanodeStorageConfig {
+            storage_type: Some("S3".to_string()),
+            bucket: Some("greptime".to_string()),
+            ..Default::default()
+        });
+        config.region_engine = None;
+
+        let (flushes, writes) = estimate(&config, 3600, 0.0);
+        assert_eq!(flushes, 1.0);
+        assert_eq!(writes, 8.0);
+        // 64 MiB/s into a 1 GiB buffer shared by 1000 regions flushes 62.5 regions/s
+        let (flushes, _) = estimate(&config, 1000, 64.0);
+        assert_eq!(flushes, 62.5);
+
+        let capacity = |regions| Capacity { regions: Some(regions), write_mib_per_sec: Some(64.0) };
+        assert_eq!(check_request_rate(&config, &capacity(1000)).unwrap().status, CheckStatus::Pass);
+        assert_eq!(check_request_rate(&config, &capacity(20000)).unwrap().status, CheckStatus::Warning);
+        assert!(check_request_rate(&config, &Capacity::default()).is_none());
+
+        config.storage.as_mut().unwrap().endpoint = Some("http://minio:9000".to_string());
+        assert_eq!(check_request_rate(&config, &capacity(20000)).unwrap().status, CheckStatus::Info);
+    }
+}
@@ -0,0 +1,284 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Traces of stepstone's own runs.
+//!
+//! With `--otlp-endpoint`, each run is exported as one trace: a root span for the run and a span
+//! per checked file. Below them are spans of the checks that recorded when they started and
+//! ended, and of every storage (opendal) and metadata backend (sqlx) operation, timed where it
+//! ran. An operation is placed under the check or file whose span contains it. Findings without
+//! bounds of their own are events of their file's span rather than spans of made-up length.
+
+use crate::common::{CheckDetail, CheckStatus, Report};
+use crate::error;
+use crate::otlp::{self, ExportError, Span, SpanEvent};
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+static TRACER: Mutex<Option<RunTracer>> = Mutex::new(None);
+
+struct RunTracer {
+    url: String,
+    protocol: String,
+    run_name: String,
+    /// Start of the first run, when stepstone started; later runs start with their first check
+    started: Option<SystemTime>,
+    report: Report,
+    operations: Vec<Operation>,
+}
+
+/// A storage or database operation
+#[derive(Debug, Clone)]
+struct Operation {
+    /// e.g. `opendal.write` or `sqlx.query`
+    name: &'static str,
+    /// The object path or SQL statement
+    target: String,
+    start: SystemTime,
+    end: SystemTime,
+    error: Option<String>,
+}
+
+/// Export a trace of each run to the OTLP collector at `endpoint` for the rest of the process
+pub fn configure(endpoint: &str, protocol: &str, command: &str) -> error::Result<()> {
+    let Some(url) = otlp::export_url(protocol, Some(endpoint)) else {
+        return error::InvalidConfigSnafu {
+            message: format!("--otlp-protocol \"{}\" is not supported, expected grpc or http", protocol),
+        }
+        .fail();
+    };
+    *TRACER.lock().unwrap_or_else(|e| e.into_inner()) = Some(RunTracer {
+        url,
+        protocol: protocol.to_string(),
+        run_name: format!("stepstone {}", command),
+        started: Some(SystemTime::now()),
+        report: Report::default(),
+        operations: Vec::new(),
+    });
+    Ok(())
+}
+
+/// Record a result for the next [`flush`]; a no-op without `--otlp-endpoint`
+pub fn record(component: &str, config_file: Option<&str>, result: &crate::common::CheckResult) {
+    if let Some(tracer) = TRACER.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        tracer.report.push(component, config_file.unwrap_or(""), crate::redact::redact_result(result));
+    }
+}
+
+/// Record an operation on `target` that started at `start` and ends now; a no-op without
+/// `--otlp-endpoint`
+pub fn record_operation(name: &'static str, target: &str, start: SystemTime, error: Option<String>) {
+    if let Some(tracer) = TRACER.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        tracer.operations.push(Operation {
+            name,
+            target: crate::redact::redact(target),
+            start,
+            end: SystemTime::now(),
+            error: error.map(|error| crate::redact::redact(&error)),
+        });
+    }
+}
+
+/// Await `operation`, recording it as the operation `name` on `target`
+pub async fn traced<T, E: Display>(name: &'static str, target: &str, operation: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    let start = SystemTime::now();
+    let result = operation.await;
+    record_operation(name, target, start, result.as_ref().err().map(ToString::to_string));
+    result
+}
+
+/// Export the recorded results and the traced operations as one trace
+pub async fn flush() {
+    let pending = TRACER.lock().unwrap_or_else(|e| e.into_inner()).as_mut().and_then(|tracer| {
+        let report = std::mem::take(&mut tracer.report);
+        let operations = std::mem::take(&mut tracer.operations);
+        if report.sections.is_empty() {
+            return None;
+        }
+        let spans = build_spans(&tracer.run_name, tracer.started.take(), SystemTime::now(), &report, &operations);
+        Some((tracer.url.clone(), tracer.protocol.clone(), otlp::encode_spans(&spans)))
+    });
+    let Some((url, protocol, message)) = pending else {
+        return;
+    };
+
+    match otlp::export(&protocol, &url, &[], message).await {
+        Ok(()) => {}
+        Err(ExportError::Transport(e)) => eprintln!("Failed to export the run trace to {}: {}", url, e),
+        Err(ExportError::Rejected { reason, .. }) => eprintln!("{} rejected the run trace: {}", url, reason),
+    }
+}
+
+/// A root span for the run, a child per section, and below them the checks with recorded
+/// bounds and the operations
+fn build_spans(run_name: &str, started: Option<SystemTime>, now: SystemTime, report: &Report, operations: &[Operation]) -> Vec<Span> {
+    let trace_id = *uuid::Uuid::new_v4().as_bytes();
+    let section_bounds = report
+        .sections
+        .iter()
+        .map(|section| {
+            let result = &section.result;
+            match (result.started_at, result.wall_duration) {
+                (Some(start), Some(wall)) => Some((start, start + wall)),
+                // Without a measured run, the file spans its checks
+                _ => {
+                    let spans = result.details.iter().filter_map(|detail| detail.span);
+                    let start = spans.clone().map(|(start, _)| start).min()?;
+                    Some((start, spans.map(|(_, end)| end).max()?))
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let first = section_bounds.iter().flatten().map(|(start, _)| *start).chain(operations.iter().map(|op| op.start)).min();
+    let run_start = started.or(first).unwrap_or(now);
+
+    let mut root = Span::new(trace_id, None, run_name.to_string(), unix_nanos(run_start), unix_nanos(now));
+    if !report.success() {
+        root.error = Some("Some checks failed".to_string());
+    }
+    let mut spans = Vec::new();
+    // Spans operations may be placed under, with their bounds; the innermost container wins
+    let mut containers = Vec::new();
+    for (section, bounds) in report.sections.iter().zip(section_bounds) {
+        let (start, end) = bounds.unwrap_or((run_start, now));
+        let name = if section.config_file.is_empty() {
+            section.component.clone()
+        } else {
+            format!("{} {}", section.component, section.config_file)
+        };
+        let mut section_span = Span::new(trace_id, Some(root.span_id), name, unix_nanos(start), unix_nanos(end));
+        section_span.attributes.push(("stepstone.component", section.component.clone()));
+        section_span.attributes.push(("stepstone.config_file", section.config_file.clone()));
+        if !section.result.success {
+            section_span.error = Some(section.result.message.clone());
+        }
+        containers.push((section_span.span_id, start, end));
+
+        for detail in &section.result.details {
+            match detail.span {
+                Some((start, end)) => {
+                    let mut span = Span::new(trace_id, Some(section_span.span_id), detail.item.clone(), unix_nanos(start), unix_nanos(end));
+                    span.attributes = detail_attributes(detail);
+                    if detail.status == CheckStatus::Fail {
+                        span.error = Some(detail.message.clone());
+                    }
+                    containers.push((span.span_id, start, end));
+                    spans.push(span);
+                }
+                None => section_span.events.push(SpanEvent {
+                    time_ns: unix_nanos(end),
+                    name: detail.item.clone(),
+                    attributes: detail_attributes(detail),
+                }),
+            }
+        }
+        spans.push(section_span);
+    }
+
+    for operation in operations {
+        let parent = containers
+            .iter()
+            .filter(|(_, start, end)| *start <= operation.start && operation.end <= *end)
+            .min_by_key(|(_, start, end)| end.duration_since(*start).unwrap_or_default())
+            .map_or(root.span_id, |(span_id, _, _)| *span_id);
+        let mut span = Span::new(trace_id, Some(parent), operation.name.to_string(), unix_nanos(operation.start), unix_nanos(operation.end));
+        span.attributes.push(("stepstone.operation.target", operation.target.clone()));
+        span.error = operation.error.clone();
+        spans.push(span);
+    }
+    spans.push(root);
+    spans
+}
+
+fn detail_attributes(detail: &CheckDetail) -> Vec<(&'static str, String)> {
+    let status = match detail.status {
+        CheckStatus::Pass => "PASS",
+        CheckStatus::Warning => "WARNING",
+        CheckStatus::Fail => "FAIL",
+        CheckStatus::Info => "INFO",
+    };
+    let mut attributes = vec![
+        ("stepstone.check.status", status.to_string()),
+        ("stepstone.check.message", detail.message.clone()),
+    ];
+    if let Some(suggestion) = &detail.suggestion {
+        attributes.push(("stepstone.check.suggestion", suggestion.clone()));
+    }
+    if let Some(duration) = detail.duration {
+        attributes.push(("stepstone.check.duration_ms", duration.as_millis().to_string()));
+    }
+    attributes
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckResult;
+
+    #[test]
+    fn test_build_spans() {
+        let now = SystemTime::now();
+        let at = |ms: u64| now - Duration::from_secs(1) + Duration::from_millis(ms);
+        let mut etcd = CheckDetail::pass("Etcd".to_string(), "ok".to_string(), Some(Duration::from_millis(20)));
+        etcd.span = Some((at(100), at(120)));
+        let mut write = CheckDetail::fail("S3 Write".to_string(), "denied".to_string(), Some(Duration::from_millis(5)), None);
+        write.span = Some((at(200), at(205)));
+        let untimed = CheckDetail::pass("Storage Type".to_string(), "S3".to_string(), None);
+        let mut result = CheckResult::from_details(vec![etcd, write, untimed]);
+        result.started_at = Some(at(50));
+        result.wall_duration = Some(Duration::from_millis(300));
+
+        let mut report = Report::default();
+        report.push("Datanode", "datanode.toml", result);
+        let operations = vec![
+            Operation { name: "opendal.write", target: "stepstone-test/a".to_string(), start: at(201), end: at(204), error: Some("denied".to_string()) },
+            Operation { name: "sqlx.query", target: "SELECT 1".to_string(), start: at(300), end: at(310), error: None },
+        ];
+        let spans = build_spans("stepstone check", None, now, &report, &operations);
+        assert_eq!(spans.len(), 6);
+
+        let (etcd, write, section, put, query, root) = (&spans[0], &spans[1], &spans[2], &spans[3], &spans[4], &spans[5]);
+        assert_eq!(root.parent_span_id, None);
+        assert_eq!(section.parent_span_id, Some(root.span_id));
+        assert_eq!(etcd.parent_span_id, Some(section.span_id));
+        assert!(spans.iter().all(|span| span.trace_id == root.trace_id));
+
+        // Bounds are the recorded ones, not reconstructed from durations
+        assert_eq!(etcd.start_ns, unix_nanos(at(100)));
+        assert_eq!(etcd.end_ns - etcd.start_ns, 20_000_000);
+        assert_eq!(section.start_ns, unix_nanos(at(50)));
+        assert_eq!(section.end_ns, unix_nanos(at(350)));
+        assert_eq!(root.start_ns, unix_nanos(at(50)));
+        assert_eq!(root.end_ns, unix_nanos(now));
+        assert_eq!(section.name, "Datanode datanode.toml");
+
+        // The untimed finding is an event, not a zero-width span
+        assert_eq!(section.events.len(), 1);
+        assert_eq!(section.events[0].name, "Storage Type");
+
+        // Operations go under the innermost span containing them
+        assert_eq!(put.parent_span_id, Some(write.span_id));
+        assert_eq!(put.error.as_deref(), Some("denied"));
+        assert_eq!(query.parent_span_id, Some(section.span_id));
+
+        assert_eq!(etcd.error, None);
+        assert_eq!(write.error.as_deref(), Some("denied"));
+        assert!(root.error.is_some());
+        assert!(write.attributes.contains(&("stepstone.check.status", "FAIL".to_string())));
+    }
+}
@@ -0,0 +1,197 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON Schema of the reports written with `--output json`, printed by `stepstone schema`.
+//!
+//! Reports carry [`SCHEMA_VERSION`] in `schema_version`. The minor version grows when fields are
+//! added, the major version when fields are removed, renamed or change meaning, so parsers can
+//! accept any report of a major version they know.
+
+use serde_json::{Value, json};
+
+/// Version of the report format
+pub const SCHEMA_VERSION: &str = "1.5";
+
+/// The JSON Schema of a report: the result of one component, or a multi-file report
+pub fn report_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("urn:stepstone:report:{}", SCHEMA_VERSION),
+        "title": "Stepstone report",
+        "description": format!("Report of stepstone --output json, schema version {}", SCHEMA_VERSION),
+        "oneOf": [
+            { "$ref": "#/$defs/result" },
+            { "$ref": "#/$defs/report" },
+        ],
+        "$defs": {
+            "schema_version": {
+                "type": "string",
+                "pattern": "^[0-9]+\\.[0-9]+$",
+                "description": "Version of this schema the document follows"
+            },
+            "overall_result": { "enum": ["PASS", "FAIL"] },
+            "mode": {
+                "enum": ["preflight", "live", "auto"],
+                "description": "Side of startup the checks targeted, see --mode"
+            },
+            "host": {
+                "description": "Where the report was produced",
+                "type": ["object", "null"],
+                "properties": {
+                    "hostname": { "type": ["string", "null"] },
+                    "os": { "type": "string" },
+                    "kernel": { "type": ["string", "null"] },
+                    "stepstone_version": { "type": "string" },
+                    "cloud_provider": { "type": ["string", "null"] },
+                    "instance_type": { "type": ["string", "null"] },
+                    "container_runtime": { "type": ["string", "null"] },
+                    "tags": { "type": "object", "additionalProperties": { "type": "string" } }
+                }
+            },
+            "detail": {
+                "description": "Result of one check item",
+                "type": "object",
+                "required": ["item", "status", "message", "duration_ms", "suggestion"],
+                "properties": {
+                    "item": { "type": "string" },
+                    "status": { "enum": ["PASS", "FAIL", "WARNING", "INFO"] },
+                    "message": { "type": "string" },
+                    "duration_ms": { "type": ["integer", "null"], "minimum": 0 },
+                    "suggestion": { "type": ["string", "null"] },
+                    "error_code": {
+                        "type": ["string", "null"],
+                        "description": "Stable code of the finding, e.g. STEP-S3-0004; see stepstone explain"
+                    },
+                    "doc_url": { "type": ["string", "null"], "format": "uri" },
+                    "category": {
+                        "type": ["string", "null"],
+                        "description": "Area the check belongs to, see stepstone list-checks; null for unknown items"
+                    },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "timings": {
+                        "type": "object",
+                        "description": "Milliseconds per phase of a check made of several operations, e.g. client_build, connect, tls, first_byte and total",
+                        "additionalProperties": { "type": "number", "minimum": 0 }
+                    }
+                }
+            },
+            "result": {
+                "description": "Result of checking one component",
+                "type": "object",
+                "required": [
+                    "schema_version",
+                    "component",
+                    "config_file",
+                    "timestamp",
+                    "overall_result",
+                    "total_checks",
+                    "passed_checks",
+                    "failed_checks",
+                    "warning_checks",
+                    "info_checks",
+                    "total_duration_ms",
+                    "message",
+                    "details"
+                ],
+                "properties": {
+                    "schema_version": { "$ref": "#/$defs/schema_version" },
+                    "component": { "type": "string" },
+                    "config_file": { "type": ["string", "null"] },
+                    "timestamp": { "type": "string", "format": "date-time" },
+                    "host": { "$ref": "#/$defs/host" },
+                    "mode": { "$ref": "#/$defs/mode" },
+                    "overall_result": { "$ref": "#/$defs/overall_result" },
+                    "total_checks": { "type": "integer", "minimum": 0 },
+                    "passed_checks": { "type": "integer", "minimum": 0 },
+                    "failed_checks": { "type": "integer", "minimum": 0 },
+                    "warning_checks": { "type": "integer", "minimum": 0 },
+                    "info_checks": { "type": "integer", "minimum": 0 },
+                    "total_duration_ms": {
+                        "type": ["integer", "null"],
+                        "minimum": 0,
+                        "description": "Durations of the timed checks added up; more than the wall-clock time when checks overlap"
+                    },
+                    "wall_duration_ms": {
+                        "type": ["integer", "null"],
+                        "minimum": 0,
+                        "description": "Wall-clock time of the run, when it was measured"
+                    },
+                    "message": { "type": "string" },
+                    "details": { "type": "array", "items": { "$ref": "#/$defs/detail" } }
+                }
+            },
+            "report": {
+                "description": "Results of several config files checked in one run",
+                "type": "object",
+                "required": ["schema_version", "timestamp", "overall_result", "total_files", "failed_files", "files"],
+                "properties": {
+                    "schema_version": { "$ref": "#/$defs/schema_version" },
+                    "timestamp": { "type": "string", "format": "date-time" },
+                    "host": { "$ref": "#/$defs/host" },
+                    "mode": { "$ref": "#/$defs/mode" },
+                    "overall_result": { "$ref": "#/$defs/overall_result" },
+                    "total_files": { "type": "integer", "minimum": 0 },
+                    "failed_files": { "type": "integer", "minimum": 0 },
+                    "total_duration_ms": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Durations of the timed checks of all files added up"
+                    },
+                    "wall_duration_ms": {
+                        "type": ["integer", "null"],
+                        "minimum": 0,
+                        "description": "Wall-clock time of the whole run"
+                    },
+                    "files": { "type": "array", "items": { "$ref": "#/$defs/result" } }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{CheckDetail, CheckResult, Report};
+
+    /// Every field of `document` is described by `definition`, and every required field is present
+    fn assert_covered(definition: &Value, document: &Value) {
+        let properties = definition["properties"].as_object().unwrap();
+        for key in document.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "{} is not in the schema", key);
+        }
+        for key in definition["required"].as_array().unwrap() {
+            assert!(document.get(key.as_str().unwrap()).is_some(), "{} is missing", key);
+        }
+    }
+
+    #[test]
+    fn test_schema_matches_reports() {
+        let schema = report_schema();
+        let definitions = &schema["$defs"];
+        let result = CheckResult::from_details(vec![
+            CheckDetail::pass("S3 PUT Operation".to_string(), "ok".to_string(), None),
+            CheckDetail::fail("Etcd Connection".to_string(), "refused".to_string(), None, Some("Start etcd".to_string())),
+        ]);
+
+        let document = result.to_json_value("Datanode", Some("datanode.toml"));
+        assert_eq!(document["schema_version"], SCHEMA_VERSION);
+        assert_covered(&definitions["result"], &document);
+        assert_covered(&definitions["detail"], &document["details"][1]);
+
+        let mut report = Report::default();
+        report.push("Datanode", "datanode.toml", result);
+        let document: Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(document["schema_version"], SCHEMA_VERSION);
+        assert_covered(&definitions["report"], &document);
+    }
+}
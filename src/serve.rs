@@ -0,0 +1,454 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `stepstone serve`: run the checks periodically and expose the latest result over HTTP, so
+//! orchestrators and dashboards can consume the self-test status.
+//!
+//! - `GET /healthz`: 200 when the latest run passed, 503 when it failed or has not finished
+//! - `GET /results`: JSON of the latest run, in the same format as `--output json`
+//! - `GET /metrics`: the latest run in the Prometheus text format
+//! - `POST /run`: run the checks now and answer with their JSON. A run writes test objects and
+//!   benchmarks the backend, so it takes the `--run-token` as a bearer token, and without one is
+//!   only accepted from this host.
+
+use crate::common::{CheckResult, CheckStatus};
+use crate::error;
+use crate::redact;
+use crate::sink;
+use chrono::{DateTime, Utc};
+use snafu::ResultExt;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{Mutex, RwLock};
+
+/// Largest request head accepted; the endpoints take no body
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Time a client has to send its request head before the connection is dropped
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept, e.g. when out of file descriptors, before accepting again
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// One finished run of the checks
+#[derive(Debug, Clone)]
+struct Run {
+    component: String,
+    result: CheckResult,
+    finished_at: DateTime<Utc>,
+}
+
+struct State<F> {
+    config_file: String,
+    checks: F,
+    /// Bearer token `POST /run` must carry; without one only loopback clients may trigger runs
+    run_token: Option<String>,
+    latest: RwLock<Option<Run>>,
+    /// Held while checks run, so a `POST /run` during a scheduled run waits for it
+    running: Mutex<()>,
+    runs: AtomicU64,
+}
+
+impl<F, Fut> State<F>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = (String, CheckResult)>,
+{
+    async fn run(&self) -> Run {
+        let _running = self.running.lock().await;
+        let (component, result) = (self.checks)().await;
+        let run = Run {
+            component,
+            result: redact::redact_result(&result),
+            finished_at: Utc::now(),
+        };
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        *self.latest.write().await = Some(run.clone());
+        sink::record(&run.component, Some(&self.config_file), &run.result);
+        sink::flush().await;
+        run
+    }
+}
+
+/// Serve check results on `listen` until the process is stopped, running `checks` every
+/// `interval`, or only on `POST /run` when the interval is zero
+pub async fn serve<F, Fut>(listen: &str, config_file: &str, interval: Duration, run_token: Option<&str>, checks: F) -> error::Result<()>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = (String, CheckResult)> + Send,
+{
+    let listener = TcpListener::bind(listen).await.context(error::TcpConnectionSnafu {
+        address: listen.to_string(),
+        message: "Failed to bind the results listener".to_string(),
+    })?;
+    println!("Serving check results of {} on http://{}", config_file, listen);
+
+    let state = Arc::new(State {
+        config_file: config_file.to_string(),
+        checks,
+        run_token: run_token.map(str::to_string),
+        latest: RwLock::new(None),
+        running: Mutex::new(()),
+        runs: AtomicU64::new(0),
+    });
+
+    if !interval.is_zero() {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // Each run is a task of its own, so a panicking check costs one run, not the schedule
+                let state = state.clone();
+                if let Err(e) = tokio::spawn(async move { state.run().await }).await {
+                    eprintln!("Scheduled check run failed: {}", e);
+                }
+            }
+        });
+    }
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                eprintln!("Failed to accept a connection on {}: {}", listen, e);
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, peer, &state).await {
+                eprintln!("Request from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection<F, Fut>(mut stream: TcpStream, peer: SocketAddr, state: &State<F>) -> io::Result<()>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = (String, CheckResult)>,
+{
+    let head = read_request_head(&mut stream).await?;
+    let response = match parse_request_line(&head) {
+        Some(("GET", "/healthz")) => match state.latest.read().await.as_ref() {
+            Some(run) if run.result.success => Response::text(200, "ok\n".to_string()),
+            Some(run) => Response::text(503, format!("{}\n", run.result.message)),
+            None => Response::text(503, "no run has finished yet\n".to_string()),
+        },
+        Some(("GET", "/results")) => match state.latest.read().await.as_ref() {
+            Some(run) => Response::json(results_json(run, &state.config_file)),
+            None => Response::text(503, "no run has finished yet\n".to_string()),
+        },
+        Some(("GET", "/metrics")) => {
+            let latest = state.latest.read().await;
+            Response::metrics(render_metrics(latest.as_ref(), state.runs.load(Ordering::Relaxed)))
+        }
+        Some(("POST", "/run")) => match refuse_run(&head, peer, state.run_token.as_deref()) {
+            Some(refusal) => refusal,
+            None => Response::json(results_json(&state.run().await, &state.config_file)),
+        },
+        Some((_, "/healthz" | "/results" | "/metrics" | "/run")) => Response::text(405, "method not allowed\n".to_string()),
+        Some(_) => Response::text(404, "not found\n".to_string()),
+        None => Response::text(400, "bad request\n".to_string()),
+    };
+    stream.write_all(&response.into_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Read up to the blank line ending the request head, giving up after [`REQUEST_HEAD_TIMEOUT`]
+async fn read_request_head(stream: &mut TcpStream) -> io::Result<String> {
+    tokio::time::timeout(REQUEST_HEAD_TIMEOUT, read_head(stream))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "no request head within 10s"))?
+}
+
+async fn read_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+        if head.len() > MAX_REQUEST_HEAD {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "request head too large"));
+        }
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// Why `POST /run` is refused, if it is: the request must carry `token` as a bearer token, or
+/// without a token come from this host
+fn refuse_run(head: &str, peer: SocketAddr, token: Option<&str>) -> Option<Response> {
+    match token {
+        Some(token) if bearer_token(head) == Some(token) => None,
+        Some(_) => Some(Response::text(401, "missing or wrong bearer token\n".to_string())),
+        None if peer.ip().is_loopback() => None,
+        None => Some(Response::text(403, "POST /run is only accepted from this host unless --run-token is set\n".to_string())),
+    }
+}
+
+/// The token of an `Authorization: Bearer` header
+fn bearer_token(head: &str) -> Option<&str> {
+    head.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if !name.trim().eq_ignore_ascii_case("authorization") {
+            return None;
+        }
+        value.trim().strip_prefix("Bearer ").map(str::trim)
+    })
+}
+
+/// Method and path of a request, without the query string
+fn parse_request_line(head: &str) -> Option<(&str, &str)> {
+    let mut parts = head.lines().next()?.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts.next()?.starts_with("HTTP/").then_some(())?;
+    Some((method, target.split('?').next().unwrap_or(target)))
+}
+
+fn results_json(run: &Run, config_file: &str) -> String {
+    let mut json = run.result.to_json_value(&run.component, Some(config_file));
+    json["timestamp"] = serde_json::Value::String(run.finished_at.to_rfc3339());
+    serde_json::to_string_pretty(&json).unwrap_or_default()
+}
+
+/// Render the latest run in the Prometheus text exposition format
+fn render_metrics(run: Option<&Run>, runs: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# HELP stepstone_runs_total Check runs finished since the server started");
+    let _ = writeln!(out, "# TYPE stepstone_runs_total counter");
+    let _ = writeln!(out, "stepstone_runs_total {}", runs);
+    let Some(run) = run else {
+        return out;
+    };
+
+    let component = escape_label(&run.component);
+    let _ = writeln!(out, "# HELP stepstone_up Whether the latest run passed");
+    let _ = writeln!(out, "# TYPE stepstone_up gauge");
+    let _ = writeln!(out, "stepstone_up{{component=\"{}\"}} {}", component, u8::from(run.result.success));
+    let _ = writeln!(out, "# HELP stepstone_last_run_timestamp_seconds When the latest run finished");
+    let _ = writeln!(out, "# TYPE stepstone_last_run_timestamp_seconds gauge");
+    let _ = writeln!(out, "stepstone_last_run_timestamp_seconds{{component=\"{}\"}} {}", component, run.finished_at.timestamp());
+
+    let items = aggregate_items(&run.result);
+    let _ = writeln!(out, "# HELP stepstone_check_status Status of each check of the latest run, 1 for the current status");
+    let _ = writeln!(out, "# TYPE stepstone_check_status gauge");
+    for (item, worst, _) in &items {
+        let item = escape_label(item);
+        for (status, name) in [
+            (CheckStatus::Pass, "pass"),
+            (CheckStatus::Warning, "warning"),
+            (CheckStatus::Fail, "fail"),
+            (CheckStatus::Info, "info"),
+        ] {
+            let _ = writeln!(
+                out,
+                "stepstone_check_status{{component=\"{}\",item=\"{}\",status=\"{}\"}} {}",
+                component,
+                item,
+                name,
+                u8::from(*worst == status)
+            );
+        }
+    }
+
+    let _ = writeln!(out, "# HELP stepstone_check_duration_seconds Duration of each timed check of the latest run");
+    let _ = writeln!(out, "# TYPE stepstone_check_duration_seconds gauge");
+    for (item, _, duration) in &items {
+        if let Some(duration) = duration {
+            let _ = writeln!(
+                out,
+                "stepstone_check_duration_seconds{{component=\"{}\",item=\"{}\"}} {}",
+                component,
+                escape_label(item),
+                duration.as_secs_f64()
+            );
+        }
+    }
+    out
+}
+
+/// One entry per item, in order of first appearance. Checks such as port conflicts report the
+/// same item more than once; a series each would collide, so they are merged into the worst
+/// status and the summed duration.
+fn aggregate_items(result: &CheckResult) -> Vec<(&str, CheckStatus, Option<Duration>)> {
+    let mut items: Vec<(&str, CheckStatus, Option<Duration>)> = Vec::new();
+    for detail in &result.details {
+        match items.iter_mut().find(|(item, _, _)| *item == detail.item) {
+            Some((_, status, duration)) => {
+                if severity(&detail.status) > severity(status) {
+                    *status = detail.status.clone();
+                }
+                if let Some(more) = detail.duration {
+                    *duration = Some(duration.unwrap_or_default() + more);
+                }
+            }
+            None => items.push((&detail.item, detail.status.clone(), detail.duration)),
+        }
+    }
+    items
+}
+
+fn severity(status: &CheckStatus) -> u8 {
+    match status {
+        CheckStatus::Pass => 0,
+        CheckStatus::Info => 1,
+        CheckStatus::Warning => 2,
+        CheckStatus::Fail => 3,
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn text(status: u16, body: String) -> Self {
+        Self { status, content_type: "text/plain; charset=utf-8", body }
+    }
+
+    fn json(body: String) -> Self {
+        Self { status: 200, content_type: "application/json", body }
+    }
+
+    fn metrics(body: String) -> Self {
+        Self { status: 200, content_type: "text/plain; version=0.0.4", body }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Service Unavailable",
+        };
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+        .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckDetail;
+
+    fn run() -> Run {
+        Run {
+            component: "Datanode".to_string(),
+            result: CheckResult::from_details(vec![
+                CheckDetail::pass("Etcd \"Ping\"".to_string(), "ok".to_string(), Some(Duration::from_millis(250))),
+                CheckDetail::fail("S3 Write".to_string(), "denied".to_string(), None, None),
+            ]),
+            finished_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_request_line() {
+        assert_eq!(parse_request_line("GET /results?pretty HTTP/1.1\r\nHost: x\r\n\r\n"), Some(("GET", "/results")));
+        assert_eq!(parse_request_line("POST /run HTTP/1.0\r\n\r\n"), Some(("POST", "/run")));
+        assert_eq!(parse_request_line("garbage\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        assert_eq!(render_metrics(None, 0).lines().last(), Some("stepstone_runs_total 0"));
+
+        let metrics = render_metrics(Some(&run()), 3);
+        assert!(metrics.contains("stepstone_runs_total 3\n"));
+        assert!(metrics.contains("stepstone_up{component=\"Datanode\"} 0\n"));
+        assert!(metrics.contains("stepstone_check_status{component=\"Datanode\",item=\"Etcd \\\"Ping\\\"\",status=\"pass\"} 1\n"));
+        assert!(metrics.contains("stepstone_check_status{component=\"Datanode\",item=\"S3 Write\",status=\"fail\"} 1\n"));
+        assert!(metrics.contains("stepstone_check_duration_seconds{component=\"Datanode\",item=\"Etcd \\\"Ping\\\"\"} 0.25\n"));
+        assert!(!metrics.contains("stepstone_check_duration_seconds{component=\"Datanode\",item=\"S3 Write\"}"));
+    }
+
+    #[test]
+    fn test_render_metrics_merges_repeated_items() {
+        let run = Run {
+            result: CheckResult::from_details(vec![
+                CheckDetail::pass("Port Conflict".to_string(), "4000 free".to_string(), Some(Duration::from_millis(100))),
+                CheckDetail::fail("Port Conflict".to_string(), "4001 taken".to_string(), Some(Duration::from_millis(150)), None),
+            ]),
+            ..run()
+        };
+        let metrics = render_metrics(Some(&run), 1);
+        assert_eq!(metrics.matches("item=\"Port Conflict\",status=\"fail\"").count(), 1);
+        assert!(metrics.contains("item=\"Port Conflict\",status=\"fail\"} 1\n"));
+        assert!(metrics.contains("item=\"Port Conflict\",status=\"pass\"} 0\n"));
+        assert!(metrics.contains("stepstone_check_duration_seconds{component=\"Datanode\",item=\"Port Conflict\"} 0.25\n"));
+    }
+
+    #[test]
+    fn test_refuse_run() {
+        let local: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+        let remote: SocketAddr = "10.0.0.7:50000".parse().unwrap();
+        let head = "POST /run HTTP/1.1\r\nHost: stepstone\r\n\r\n";
+        let authorized = "POST /run HTTP/1.1\r\nauthorization: Bearer s3cret\r\n\r\n";
+
+        assert!(refuse_run(head, local, None).is_none());
+        assert_eq!(refuse_run(head, remote, None).unwrap().status, 403);
+        assert_eq!(refuse_run(head, local, Some("s3cret")).unwrap().status, 401);
+        assert!(refuse_run(authorized, remote, Some("s3cret")).is_none());
+        assert_eq!(refuse_run(authorized, remote, Some("other")).unwrap().status, 401);
+    }
+
+    #[tokio::test]
+    async fn test_run_and_healthz() {
+        let state = State {
+            config_file: "datanode.toml".to_string(),
+            checks: || async { ("Datanode".to_string(), run().result) },
+            run_token: None,
+            latest: RwLock::new(None),
+            running: Mutex::new(()),
+            runs: AtomicU64::new(0),
+        };
+        assert!(state.latest.read().await.is_none());
+
+        let finished = state.run().await;
+        assert!(!finished.result.success);
+        assert_eq!(state.runs.load(Ordering::Relaxed), 1);
+
+        let json: serde_json::Value = serde_json::from_str(&results_json(&finished, "datanode.toml")).unwrap();
+        assert_eq!(json["overall_result"], "FAIL");
+        assert_eq!(json["config_file"], "datanode.toml");
+    }
+}
@@ -0,0 +1,253 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Stepstone's own settings file, read from `--settings` or `~/.config/stepstone/config.toml`,
+//! so teams can standardize behavior without long command lines.
+//!
+//! Settings that mirror a flag become that flag's default, so a flag given on the command line
+//! always wins.
+
+use crate::error;
+use crate::history::expand_dir;
+use crate::profile::ProfileConfig;
+use serde::Deserialize;
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+pub const DEFAULT_SETTINGS_FILE: &str = "~/.config/stepstone/config.toml";
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// Profile used without `--profile`
+    pub profile: Option<String>,
+    /// Default `--output` of every command: human or json
+    pub output: Option<String>,
+    /// Default `--lang` of the human-readable report: en or zh
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub timeouts: Timeouts,
+    #[serde(default)]
+    pub thresholds: Thresholds,
+    #[serde(default)]
+    pub proxy: Proxy,
+    #[serde(default)]
+    pub notify: Notify,
+    #[serde(default)]
+    pub capacity: Capacity,
+    /// Default `--suppressions` file of accepted findings
+    pub suppressions: Option<String>,
+    /// Documentation link of findings, with `{code}` and `{id}` replaced by the error code and
+    /// check ID, e.g. `https://wiki.example.com/stepstone/{code}`
+    pub doc_url: Option<String>,
+    /// Custom check profiles, see [`crate::profile`]
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Timeouts {
+    /// Seconds a plugin may run unless its own `timeout_secs` is set
+    pub plugin_secs: Option<u64>,
+    /// Seconds for stepstone's own HTTP requests: webhooks, result writes and Vault
+    pub http_secs: Option<u64>,
+}
+
+/// Defaults of `compare` and `history diff`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thresholds {
+    pub latency_ratio: Option<f64>,
+    pub min_latency_delta_ms: Option<u64>,
+}
+
+/// Proxy for stepstone's own HTTP requests; the checks connect as GreptimeDB would
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Proxy {
+    /// e.g. `http://proxy.internal:3128`
+    pub url: Option<String>,
+    /// Comma-separated hosts, domains or CIDRs to reach directly
+    pub no_proxy: Option<String>,
+}
+
+/// Defaults of `--notify-webhook` and `--notify-format`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Notify {
+    pub webhook: Option<String>,
+    pub format: Option<String>,
+}
+
+/// Expected workload of a datanode, for estimates that cannot be read from its config
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Capacity {
+    /// Regions each datanode is expected to hold
+    pub regions: Option<u32>,
+    /// Ingest per datanode, in MiB/s
+    pub write_mib_per_sec: Option<f64>,
+}
+
+/// Read the settings file at `path`, or the default one if it exists
+pub fn load(path: Option<&str>) -> error::Result<Settings> {
+    let file = expand_dir(path.unwrap_or(DEFAULT_SETTINGS_FILE));
+    if path.is_none() && !file.exists() {
+        return Ok(Settings::default());
+    }
+    let content = std::fs::read_to_string(&file).context(error::FileSystemSnafu {
+        message: format!("Failed to read settings file: {}", file.display()),
+    })?;
+    toml::from_str(&content).context(error::TomlParsingSnafu {
+        message: format!("Failed to parse settings file: {}", file.display()),
+    })
+}
+
+impl Settings {
+    /// Flag defaults from the settings, by argument ID
+    pub fn flag_defaults(&self) -> Vec<(&'static str, String)> {
+        let mut defaults = Vec::new();
+        defaults.extend(self.output.clone().map(|output| ("output", output)));
+        defaults.extend(self.lang.clone().map(|lang| ("lang", lang)));
+        defaults.extend(self.thresholds.latency_ratio.map(|ratio| ("latency_ratio", ratio.to_string())));
+        defaults.extend(self.thresholds.min_latency_delta_ms.map(|ms| ("min_latency_delta_ms", ms.to_string())));
+        defaults.extend(self.notify.webhook.clone().map(|url| ("notify_webhook", url)));
+        defaults.extend(self.notify.format.clone().map(|format| ("notify_format", format)));
+        defaults.extend(self.suppressions.clone().map(|path| ("suppressions", path)));
+        defaults
+    }
+}
+
+/// Make `defaults` the defaults of the matching arguments of `command` and all its subcommands
+pub fn apply_defaults(command: clap::Command, defaults: &[(&'static str, String)]) -> clap::Command {
+    let mut command = defaults.iter().fold(command, |command, (id, value)| {
+        if command.get_arguments().any(|arg| arg.get_id() == *id) {
+            command.mut_arg(*id, |arg| arg.default_value(value.clone()))
+        } else {
+            command
+        }
+    });
+    let subcommands = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect::<Vec<_>>();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |sub| apply_defaults(sub, defaults));
+    }
+    command
+}
+
+/// Use `settings` for the rest of the process
+pub fn init(settings: Settings) {
+    let _ = SETTINGS.set(settings);
+}
+
+/// The settings passed to [`init`], if it ran
+pub fn current() -> Option<&'static Settings> {
+    SETTINGS.get()
+}
+
+/// An HTTP client for stepstone's own requests, with the proxy and timeout of the settings
+pub fn http_client(default_timeout: Duration) -> reqwest::Result<reqwest::Client> {
+    let settings = current();
+    let timeout = settings
+        .and_then(|settings| settings.timeouts.http_secs)
+        .map(Duration::from_secs)
+        .unwrap_or(default_timeout);
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(proxy) = settings.map(|settings| &settings.proxy) {
+        if let Some(url) = &proxy.url {
+            let no_proxy = proxy.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string);
+            builder = builder.proxy(reqwest::Proxy::all(url)?.no_proxy(no_proxy));
+        }
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+profile = "nightly"
+
+[profiles.nightly]
+extends = "deep"
+large_objects = false
+
+[capacity]
+regions = 2000
+"#,
+        )
+        .unwrap();
+
+        let settings = load(path.to_str()).unwrap();
+        assert_eq!(settings.profile.as_deref(), Some("nightly"));
+        assert_eq!(settings.profiles["nightly"].large_objects, Some(false));
+        assert_eq!(settings.capacity.regions, Some(2000));
+
+        assert!(settings.flag_defaults().is_empty());
+
+        assert!(load(dir.path().join("missing.toml").to_str()).is_err());
+        std::fs::write(&path, "profle = \"quick\"").unwrap();
+        assert!(load(path.to_str()).is_err());
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[arg(long, global = true)]
+            notify_webhook: Option<String>,
+            #[command(subcommand)]
+            command: Command,
+        }
+        #[derive(clap::Subcommand)]
+        enum Command {
+            Compare {
+                #[arg(long, default_value_t = 1.5)]
+                latency_ratio: f64,
+                #[arg(long, default_value = "human")]
+                output: String,
+            },
+        }
+
+        let settings: Settings = toml::from_str(
+            r#"
+output = "json"
+
+[thresholds]
+latency_ratio = 2.0
+
+[notify]
+webhook = "https://hooks.example.com/stepstone"
+"#,
+        )
+        .unwrap();
+        let command = apply_defaults(<Cli as clap::CommandFactory>::command(), &settings.flag_defaults());
+
+        let matches = command.get_matches_from(["stepstone", "compare", "--output", "human"]);
+        let cli = <Cli as clap::FromArgMatches>::from_arg_matches(&matches).unwrap();
+        assert_eq!(cli.notify_webhook.as_deref(), Some("https://hooks.example.com/stepstone"));
+        let Command::Compare { latency_ratio, output } = cli.command;
+        assert_eq!(latency_ratio, 2.0);
+        assert_eq!(output, "human");
+    }
+}
@@ -0,0 +1,45 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Destinations every printed result is also sent to: the failure webhook, a GreptimeDB table,
+//! the local run history and a trace of the run. Each is a no-op unless enabled by its
+//! command-line flag. A destination that fails is reported on stderr and never changes the
+//! outcome of the run, which is decided by the checks alone.
+
+use crate::common::{CheckResult, Report};
+use crate::{history, notify, report_db, run_trace};
+
+/// Record a result for the next [`flush`]
+pub fn record(component: &str, config_file: Option<&str>, result: &CheckResult) {
+    notify::record(component, config_file, result);
+    report_db::record(component, config_file, result);
+    history::record(component, config_file, result);
+    run_trace::record(component, config_file, result);
+}
+
+/// Add the redacted `result` to a destination's pending `report`. A re-run after --fix replaces
+/// the earlier result of the same file, so only the fixed outcome is sent.
+pub(crate) fn replace_section(report: &mut Report, component: &str, config_file: Option<&str>, result: &CheckResult) {
+    let config_file = config_file.unwrap_or("");
+    report
+        .sections
+        .retain(|section| section.component != component || section.config_file != config_file);
+    report.push(component, config_file, crate::redact::redact_result(result));
+}
+
+/// Send the results recorded since the last flush, once per run
+pub async fn flush() {
+    notify::flush().await;
+    report_db::flush().await;
+    history::flush();
+    run_trace::flush().await;
+}
@@ -0,0 +1,72 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::{CheckResult, ComponentChecker};
+use crate::config::{DatanodeConfig, DatanodeStorageConfig};
+use crate::datanode::DatanodeChecker;
+use crate::profile::Profile;
+use async_trait::async_trait;
+use std::fmt::{Debug, Formatter};
+
+/// Standalone object storage checker that needs no datanode configuration
+pub struct StorageChecker {
+    datanode: DatanodeChecker,
+}
+
+impl Debug for StorageChecker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StorageChecker")
+    }
+}
+
+impl StorageChecker {
+    /// Create a new StorageChecker for the given storage section
+    pub fn new(storage: DatanodeStorageConfig, include_bandwidth: bool) -> Self {
+        let config = DatanodeConfig {
+            storage: Some(storage),
+            ..Default::default()
+        };
+        Self {
+            datanode: DatanodeChecker::new(config, Profile { bandwidth: include_bandwidth, probe_advertise: false, ..Profile::DEEP }),
+        }
+    }
+}
+
+#[async_trait]
+impl ComponentChecker for StorageChecker {
+    async fn check(&self) -> CheckResult {
+        self.datanode.check_object_storage().await
+    }
+
+    fn component_name(&self) -> &'static str {
+        "Storage"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_storage_bench() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = DatanodeStorageConfig {
+            data_home: Some(dir.path().to_string_lossy().to_string()),
+            storage_type: Some("File".to_string()),
+            ..Default::default()
+        };
+
+        let result = StorageChecker::new(storage, false).check().await;
+        assert!(result.success, "{:?}", result.details);
+        assert!(result.details.iter().all(|d| !d.item.contains("Metasrv")));
+    }
+}
@@ -0,0 +1,273 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Suppressions of known, accepted findings, so recurring warnings do not cause alert fatigue.
+//!
+//! Each `[[suppress]]` entry of a suppressions file names a check, optionally scoped to
+//! components and hosts, and either downgrades its warnings and failures to INFO or hides them.
+//! An entry past its `expires` date stops applying and is reported as a warning itself, so
+//! accepted risks get revisited.
+
+use crate::common::{CheckDetail, CheckResult, CheckStatus};
+use crate::error;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use snafu::ResultExt;
+use std::sync::OnceLock;
+
+static SUPPRESSIONS: OnceLock<Vec<Suppression>> = OnceLock::new();
+
+#[derive(Debug, Deserialize)]
+struct SuppressionsFile {
+    #[serde(default)]
+    suppress: Vec<SuppressionConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuppressionConfig {
+    check: String,
+    #[serde(default)]
+    components: Vec<String>,
+    #[serde(default)]
+    hosts: Vec<String>,
+    #[serde(default)]
+    action: Action,
+    reason: Option<String>,
+    /// A TOML date or a `YYYY-MM-DD` string
+    expires: Option<toml::Value>,
+}
+
+/// What happens to a suppressed finding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Report it as INFO
+    #[default]
+    Info,
+    /// Leave it out of the results
+    Hide,
+}
+
+/// One `[[suppress]]` entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suppression {
+    /// Check item, e.g. `S3 Bucket List Permission`; a trailing `*` matches any suffix
+    pub check: String,
+    /// Components the entry applies to, all when empty
+    pub components: Vec<String>,
+    /// Host names the entry applies to, all when empty; a trailing `*` matches any suffix
+    pub hosts: Vec<String>,
+    pub action: Action,
+    pub reason: Option<String>,
+    /// Last day the entry applies
+    pub expires: Option<NaiveDate>,
+}
+
+impl Suppression {
+    fn matches(&self, component: &str, host: Option<&str>, item: &str) -> bool {
+        matches_pattern(&self.check, item)
+            && (self.components.is_empty() || self.components.iter().any(|c| c.eq_ignore_ascii_case(component)))
+            && (self.hosts.is_empty() || host.is_some_and(|host| self.hosts.iter().any(|pattern| matches_pattern(pattern, host))))
+    }
+
+    fn expired(&self, today: NaiveDate) -> bool {
+        self.expires.is_some_and(|expires| expires < today)
+    }
+}
+
+/// Read the suppressions file at `path` and apply it to every result of the process
+pub fn load(path: &str) -> error::Result<()> {
+    let content = std::fs::read_to_string(path).context(error::FileSystemSnafu {
+        message: format!("Failed to read suppressions file: {}", path),
+    })?;
+    let file: SuppressionsFile = toml::from_str(&content).context(error::TomlParsingSnafu {
+        message: format!("Failed to parse suppressions file: {}", path),
+    })?;
+
+    let mut suppressions = Vec::new();
+    for config in file.suppress {
+        let expires = match &config.expires {
+            None => None,
+            Some(value) => Some(parse_date(value).ok_or_else(|| {
+                error::InvalidConfigSnafu {
+                    message: format!("Suppression of '{}' in {}: expires must be a date such as 2025-12-31", config.check, path),
+                }
+                .build()
+            })?),
+        };
+        suppressions.push(Suppression {
+            check: config.check,
+            components: config.components,
+            hosts: config.hosts,
+            action: config.action,
+            reason: config.reason,
+            expires,
+        });
+    }
+    let _ = SUPPRESSIONS.set(suppressions);
+    Ok(())
+}
+
+/// Apply the loaded suppressions to the result of `component`
+pub fn apply(component: &str, result: CheckResult) -> CheckResult {
+    let Some(suppressions) = SUPPRESSIONS.get().filter(|suppressions| !suppressions.is_empty()) else {
+        return result;
+    };
+    let host = crate::host::current().and_then(|host| host.hostname.as_deref());
+    let today = chrono::Local::now().date_naive();
+    apply_suppressions(suppressions, component, host, today, result)
+}
+
+fn apply_suppressions(suppressions: &[Suppression], component: &str, host: Option<&str>, today: NaiveDate, result: CheckResult) -> CheckResult {
+    let mut changed = false;
+    let mut expired: Vec<&Suppression> = Vec::new();
+    let mut details = Vec::new();
+    for detail in &result.details {
+        if matches!(detail.status, CheckStatus::Pass | CheckStatus::Info) {
+            details.push(detail.clone());
+            continue;
+        }
+        let mut matching = suppressions.iter().filter(|s| s.matches(component, host, &detail.item)).peekable();
+        let Some(first) = matching.peek().copied() else {
+            details.push(detail.clone());
+            continue;
+        };
+        match matching.find(|s| !s.expired(today)) {
+            Some(suppression) => {
+                changed = true;
+                if suppression.action == Action::Info {
+                    let reason = suppression.reason.as_deref().unwrap_or("known finding");
+                    details.push(CheckDetail {
+                        status: CheckStatus::Info,
+                        message: format!("{} (suppressed: {})", detail.message, reason),
+                        suggestion: None,
+                        ..detail.clone()
+                    });
+                }
+            }
+            None => {
+                details.push(detail.clone());
+                if !expired.contains(&first) {
+                    expired.push(first);
+                }
+            }
+        }
+    }
+
+    for suppression in expired {
+        changed = true;
+        let reason = suppression.reason.as_ref().map(|reason| format!(" ({})", reason)).unwrap_or_default();
+        details.push(CheckDetail::warning(
+            "Expired Suppression".to_string(),
+            format!(
+                "The suppression of '{}'{} expired on {}, so the finding is reported again",
+                suppression.check,
+                reason,
+                suppression.expires.map(|date| date.to_string()).unwrap_or_default()
+            ),
+            None,
+            Some("Fix the finding, or move `expires` forward in the suppressions file if it is still accepted".to_string()),
+        ));
+    }
+
+    if changed { result.with_details(details) } else { result }
+}
+
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.to_lowercase().starts_with(&prefix.to_lowercase()),
+        None => pattern.eq_ignore_ascii_case(value),
+    }
+}
+
+fn parse_date(value: &toml::Value) -> Option<NaiveDate> {
+    let text = match value {
+        toml::Value::String(text) => text.clone(),
+        toml::Value::Datetime(datetime) if datetime.time.is_none() => datetime.to_string(),
+        _ => return None,
+    };
+    NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suppressions() -> Vec<Suppression> {
+        let file: SuppressionsFile = toml::from_str(
+            r#"
+[[suppress]]
+check = "S3 Bucket List Permission"
+components = ["datanode"]
+reason = "ListBucket is denied by policy; GreptimeDB does not need it"
+
+[[suppress]]
+check = "Log Directory*"
+hosts = ["db-*"]
+action = "hide"
+
+[[suppress]]
+check = "Etcd Lease"
+expires = 2025-01-31
+"#,
+        )
+        .unwrap();
+        file.suppress
+            .into_iter()
+            .map(|config| Suppression {
+                expires: config.expires.as_ref().and_then(parse_date),
+                check: config.check,
+                components: config.components,
+                hosts: config.hosts,
+                action: config.action,
+                reason: config.reason,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_suppressions() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let result = CheckResult::from_details(vec![
+            CheckDetail::fail("S3 Bucket List Permission".to_string(), "denied".to_string(), None, None),
+            CheckDetail::warning("Log Directory Permissions".to_string(), "0777".to_string(), None, None),
+            CheckDetail::pass("S3 PUT Operation".to_string(), "ok".to_string(), None),
+        ]);
+
+        let suppressed = apply_suppressions(&suppressions(), "Datanode", Some("db-1"), today, result.clone());
+        assert!(suppressed.success);
+        assert_eq!(suppressed.details.len(), 2);
+        assert_eq!(suppressed.details[0].status, CheckStatus::Info);
+        assert!(suppressed.details[0].message.contains("suppressed: ListBucket is denied"));
+        assert_eq!(suppressed.details[1].item, "S3 PUT Operation");
+
+        // Out of scope: another component and host
+        let unchanged = apply_suppressions(&suppressions(), "Frontend", Some("web-1"), today, result);
+        assert!(!unchanged.success);
+        assert_eq!(unchanged.details.len(), 3);
+    }
+
+    #[test]
+    fn test_expired_suppression() {
+        let result = CheckResult::from_details(vec![CheckDetail::warning("Etcd Lease".to_string(), "slow".to_string(), None, None)]);
+
+        let active = apply_suppressions(&suppressions(), "Metasrv", None, NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(), result.clone());
+        assert_eq!(active.details[0].status, CheckStatus::Info);
+
+        let expired = apply_suppressions(&suppressions(), "Metasrv", None, NaiveDate::from_ymd_opt(2025, 2, 1).unwrap(), result);
+        assert_eq!(expired.details.len(), 2);
+        assert_eq!(expired.details[0].status, CheckStatus::Warning);
+        assert_eq!(expired.details[1].item, "Expired Suppression");
+        assert!(expired.details[1].message.contains("expired on 2025-01-31"));
+    }
+}
@@ -286,6 +286,7 @@ mod s3_auth_error_tests {
     use crate::common::ComponentChecker;
     use crate::config::{DatanodeConfig, DatanodeStorageConfig, MetaClientConfig};
     use crate::datanode::DatanodeChecker;
+    use crate::profile::Profile;
     use std::collections::HashMap;
 
     #[tokio::test]
@@ -315,6 +316,7 @@ mod s3_auth_error_tests {
                 ddl_timeout: Some("10s".to_string()),
                 connect_timeout: Some("1s".to_string()),
                 tcp_nodelay: Some(true),
+                ..Default::default()
             }),
             wal: None,
             storage: Some(DatanodeStorageConfig {
@@ -328,12 +330,14 @@ mod s3_auth_error_tests {
                 secret_access_key: Some("invalid-secret".to_string()),
                 endpoint: Some("https://s3.amazonaws.com".to_string()),
                 region: Some("us-east-1".to_string()),
+                ..Default::default()
             }),
             query: None,
             logging: None,
+            ..Default::default()
         };
 
-        let checker = DatanodeChecker::new(datanode_config, false);
+        let checker = DatanodeChecker::new(datanode_config, Profile::STANDARD);
         let result = checker.check().await;
 
         // 验证检查失败（因为认证无效）
@@ -375,6 +379,7 @@ mod etcd_connection_error_tests {
     use crate::common::ComponentChecker;
     use crate::config::MetasrvConfig;
     use crate::metasrv::MetasrvChecker;
+    use crate::profile::Profile;
 
     #[tokio::test]
     async fn test_etcd_connection_failed() {
@@ -393,9 +398,14 @@ mod etcd_connection_error_tests {
             grpc: None,
             http: None,
             backend_tls: None,
+            backend_username: None,
+            backend_password: None,
+            max_txn_ops: None,
+            wal: None,
+            ..Default::default()
         };
 
-        let checker = MetasrvChecker::new(metasrv_config);
+        let checker = MetasrvChecker::new(metasrv_config, Profile::STANDARD, 0);
         let result = checker.check().await;
 
         // 验证检查失败
@@ -473,13 +483,15 @@ mod frontend_address_error_tests {
                 ddl_timeout: Some("10s".to_string()),
                 connect_timeout: Some("1s".to_string()),
                 tcp_nodelay: Some(true),
+                ..Default::default()
             }),
             heartbeat: None,
             prometheus: None,
             logging: None,
+            ..Default::default()
         };
 
-        let checker = FrontendChecker::new(frontend_config);
+        let checker = FrontendChecker::new(frontend_config, false);
         let result = checker.check().await;
 
         // 验证检查失败
@@ -538,6 +550,34 @@ mod frontend_address_error_tests {
             println!("找到地址格式相关的建议");
         }
     }
+
+    #[tokio::test]
+    async fn test_frontend_protocol_port_conflict() {
+        use crate::config::SqlServerConfig;
+
+        // MySQL on the HTTP default port, Postgres disabled
+        let frontend_config = FrontendConfig {
+            mysql: Some(SqlServerConfig {
+                addr: Some("0.0.0.0:4000".to_string()),
+                ..Default::default()
+            }),
+            postgres: Some(SqlServerConfig {
+                enable: Some(false),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = FrontendChecker::new(frontend_config, false).check().await;
+        let conflict = result
+            .details
+            .iter()
+            .find(|detail| detail.item == "Port Conflict (mysql)")
+            .expect("mysql should conflict with http");
+        assert_eq!(conflict.status, CheckStatus::Fail);
+        assert!(conflict.message.contains("http"));
+        assert!(!result.details.iter().any(|detail| detail.item.contains("postgres")));
+    }
 }
 
 /// 测试磁盘性能不足场景
@@ -546,6 +586,7 @@ mod disk_performance_tests {
     use crate::common::ComponentChecker;
     use crate::config::{DatanodeConfig, DatanodeStorageConfig, MetaClientConfig};
     use crate::datanode::DatanodeChecker;
+    use crate::profile::Profile;
     use std::collections::HashMap;
 
     #[tokio::test]
@@ -571,6 +612,7 @@ mod disk_performance_tests {
                 ddl_timeout: Some("10s".to_string()),
                 connect_timeout: Some("1s".to_string()),
                 tcp_nodelay: Some(true),
+                ..Default::default()
             }),
             wal: None,
             storage: Some(DatanodeStorageConfig {
@@ -584,16 +626,18 @@ mod disk_performance_tests {
                 secret_access_key: None,
                 endpoint: None,
                 region: None,
+                ..Default::default()
             }),
             query: None,
             logging: None,
+            ..Default::default()
         };
 
         // 创建测试目录
         std::fs::create_dir_all("/tmp/greptime_perf_test").ok();
 
         // 启用性能测试
-        let checker = DatanodeChecker::new(datanode_config, true);
+        let checker = DatanodeChecker::new(datanode_config, Profile { performance: true, ..Profile::STANDARD });
         let result = checker.check().await;
 
         // 验证性能测试被执行
@@ -656,6 +700,7 @@ mod success_scenario_tests {
     use crate::common::ComponentChecker;
     use crate::config::{DatanodeConfig, DatanodeStorageConfig, MetaClientConfig};
     use crate::datanode::DatanodeChecker;
+    use crate::profile::Profile;
     use std::collections::HashMap;
 
     #[tokio::test]
@@ -681,6 +726,7 @@ mod success_scenario_tests {
                 ddl_timeout: Some("10s".to_string()),
                 connect_timeout: Some("1s".to_string()),
                 tcp_nodelay: Some(true),
+                ..Default::default()
             }),
             wal: None,
             storage: Some(DatanodeStorageConfig {
@@ -694,15 +740,17 @@ mod success_scenario_tests {
                 secret_access_key: None,
                 endpoint: None,
                 region: None,
+                ..Default::default()
             }),
             query: None,
             logging: None,
+            ..Default::default()
         };
 
         // 创建测试目录
         std::fs::create_dir_all("/tmp/greptime_success_test").ok();
 
-        let checker = DatanodeChecker::new(datanode_config, false);
+        let checker = DatanodeChecker::new(datanode_config, Profile::STANDARD);
         let result = checker.check().await;
 
         // 查找成功的检查项
@@ -767,6 +815,7 @@ mod json_output_tests {
     use crate::common::ComponentChecker;
     use crate::config::{DatanodeConfig, DatanodeStorageConfig, MetaClientConfig};
     use crate::datanode::DatanodeChecker;
+    use crate::profile::Profile;
     use std::collections::HashMap;
 
     #[tokio::test]
@@ -792,6 +841,7 @@ mod json_output_tests {
                 ddl_timeout: Some("10s".to_string()),
                 connect_timeout: Some("1s".to_string()),
                 tcp_nodelay: Some(true),
+                ..Default::default()
             }),
             wal: None,
             storage: Some(DatanodeStorageConfig {
@@ -805,15 +855,17 @@ mod json_output_tests {
                 secret_access_key: None,
                 endpoint: None,
                 region: None,
+                ..Default::default()
             }),
             query: None,
             logging: None,
+            ..Default::default()
         };
 
         // 创建测试目录
         std::fs::create_dir_all("/tmp/greptime_json_test").ok();
 
-        let checker = DatanodeChecker::new(datanode_config, false);
+        let checker = DatanodeChecker::new(datanode_config, Profile::STANDARD);
         let result = checker.check().await;
 
         // 测试 JSON 序列化
@@ -882,3 +934,61 @@ mod json_output_tests {
         std::fs::remove_dir_all("/tmp/greptime_json_test").ok();
     }
 }
+
+#[cfg(test)]
+mod check_mode_tests {
+    use super::*;
+    use crate::config::{ConfigParser, GrpcConfig, HttpConfig};
+    use crate::datanode::DatanodeChecker;
+    use crate::frontend::FrontendChecker;
+    use crate::mode::CheckMode;
+    use crate::profile::Profile;
+
+    /// 一个没有进程监听的本地地址
+    fn free_addr() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    fn items(details: &[CheckDetail]) -> Vec<&str> {
+        details.iter().map(|detail| detail.item.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_datanode_servers_per_mode() {
+        let mut config = ConfigParser::default_datanode_config();
+        config.grpc = Some(GrpcConfig { addr: Some(free_addr()), ..Default::default() });
+        config.http = Some(HttpConfig { addr: Some(free_addr()), ..Default::default() });
+        let checker = DatanodeChecker::new(config, Profile::QUICK);
+
+        // 启动前只检查端口能否绑定，不询问健康状态
+        let preflight = checker.check_own_servers(CheckMode::Preflight).await;
+        assert_eq!(items(&preflight), vec!["Port (grpc)", "Port (http)"]);
+        assert!(preflight.iter().all(|detail| detail.status == CheckStatus::Pass));
+
+        // 没有进程在运行时，auto 模式等同于 preflight
+        let auto = checker.check_own_servers(CheckMode::Auto).await;
+        assert_eq!(items(&auto), vec!["Port (grpc)", "Port (http)"]);
+
+        // live 模式要求节点应答，未启动的节点检查失败
+        let live = checker.check_own_servers(CheckMode::Live).await;
+        assert_eq!(items(&live), vec!["gRPC Health", "HTTP Health"]);
+        assert!(live.iter().all(|detail| detail.status == CheckStatus::Fail));
+    }
+
+    #[tokio::test]
+    async fn test_frontend_servers_per_mode() {
+        let mut config = ConfigParser::default_frontend_config();
+        config.grpc = Some(GrpcConfig { addr: Some(free_addr()), ..Default::default() });
+        config.http = Some(HttpConfig { addr: Some(free_addr()), ..Default::default() });
+        let checker = FrontendChecker::new(config, false);
+
+        let preflight = checker.check_own_servers(CheckMode::Preflight).await;
+        assert!(items(&preflight).contains(&"Port (http)"));
+        assert!(preflight.iter().all(|detail| detail.item.starts_with("Port")));
+
+        let live = checker.check_own_servers(CheckMode::Live).await;
+        assert_eq!(items(&live), vec!["gRPC Health", "HTTP Health"]);
+        assert!(live.iter().all(|detail| detail.status == CheckStatus::Fail));
+    }
+}
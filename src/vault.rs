@@ -0,0 +1,165 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Credentials from HashiCorp Vault.
+//!
+//! With `--vault-path`, the secret at that path is read once before any config is parsed, and
+//! `<key>_vault = "<field>"` entries resolve to its fields the same way `_file` and `_env`
+//! references do.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// File the Vault CLI stores the token in after `vault login`, relative to the home directory
+const TOKEN_FILE: &str = ".vault-token";
+
+static SECRET: Mutex<Option<VaultSecret>> = Mutex::new(None);
+
+/// The fields of the secret read at startup
+struct VaultSecret {
+    path: String,
+    fields: BTreeMap<String, String>,
+}
+
+/// Read the secret at `path` for later lookups with [`field`]. The address falls back to
+/// `VAULT_ADDR`; the token is read from `VAULT_TOKEN` or `~/.vault-token`.
+pub async fn load(addr: Option<&str>, path: &str) -> crate::error::Result<()> {
+    let addr = match addr.map(str::to_string).or_else(|| std::env::var("VAULT_ADDR").ok()) {
+        Some(addr) => addr,
+        None => {
+            return crate::error::InvalidConfigSnafu {
+                message: "--vault-path needs --vault-addr or VAULT_ADDR".to_string(),
+            }
+            .fail();
+        }
+    };
+    let Some(token) = token() else {
+        return crate::error::InvalidConfigSnafu {
+            message: "No Vault token; set VAULT_TOKEN or run `vault login`".to_string(),
+        }
+        .fail();
+    };
+    crate::redact::register_secret(&token);
+
+    let fields = fetch(&addr, path, &token).await.map_err(|message| {
+        crate::error::VaultSnafu {
+            message: format!("Failed to read {} from {}: {}", path, addr, message),
+        }
+        .build()
+    })?;
+    for value in fields.values() {
+        crate::redact::register_secret(value);
+    }
+
+    *SECRET.lock().unwrap_or_else(|e| e.into_inner()) = Some(VaultSecret {
+        path: path.to_string(),
+        fields,
+    });
+    Ok(())
+}
+
+/// A field of the secret read by [`load`]
+pub fn field(name: &str) -> Result<String, String> {
+    let secret = SECRET.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(secret) = secret.as_ref() else {
+        return Err("Vault is not configured; pass --vault-path with --vault-addr or VAULT_ADDR".to_string());
+    };
+    secret
+        .fields
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("Field {} not found in Vault secret {}", name, secret.path))
+}
+
+fn token() -> Option<String> {
+    std::env::var("VAULT_TOKEN")
+        .ok()
+        .or_else(|| {
+            let home = std::env::var_os("HOME")?;
+            std::fs::read_to_string(std::path::Path::new(&home).join(TOKEN_FILE)).ok()
+        })
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+async fn fetch(addr: &str, path: &str, token: &str) -> Result<BTreeMap<String, String>, String> {
+    let url = format!("{}/v1/{}", addr.trim_end_matches('/'), path.trim_start_matches('/'));
+    let client = crate::settings::http_client(FETCH_TIMEOUT).map_err(|e| e.to_string())?;
+    let mut request = client.get(&url).header("X-Vault-Token", token);
+    if let Ok(namespace) = std::env::var("VAULT_NAMESPACE") {
+        request = request.header("X-Vault-Namespace", namespace);
+    }
+    let body = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let body: serde_json::Value = serde_json::from_str(&body).map_err(|e| format!("response is not JSON: {}", e))?;
+    parse_secret(&body).ok_or_else(|| "response holds no secret data".to_string())
+}
+
+/// Fields of a KV version 1 or version 2 read response
+fn parse_secret(body: &serde_json::Value) -> Option<BTreeMap<String, String>> {
+    let data = body.get("data")?;
+    // KV v2 nests the fields under `data.data`, next to `data.metadata`
+    let fields = match (data.get("data"), data.get("metadata")) {
+        (Some(fields), Some(_)) => fields,
+        _ => data,
+    };
+
+    let fields = fields
+        .as_object()?
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            (name.clone(), value)
+        })
+        .collect();
+    Some(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_secret() {
+        let v1 = serde_json::json!({
+            "data": { "secret_access_key": "wJalrXUtnFEMI", "port": 5432 }
+        });
+        let fields = parse_secret(&v1).unwrap();
+        assert_eq!(fields["secret_access_key"], "wJalrXUtnFEMI");
+        assert_eq!(fields["port"], "5432");
+
+        let v2 = serde_json::json!({
+            "data": {
+                "data": { "backend_password": "s3cr3t" },
+                "metadata": { "version": 3 }
+            }
+        });
+        let fields = parse_secret(&v2).unwrap();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields["backend_password"], "s3cr3t");
+
+        assert!(parse_secret(&serde_json::json!({ "errors": [] })).is_none());
+    }
+}
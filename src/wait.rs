@@ -0,0 +1,86 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `stepstone wait`: a readiness gate for startup scripts.
+//!
+//! Only the checks of the services a node depends on run, such as metasrv or object storage for
+//! a datanode, and they are retried until all pass or the timeout expires. This replaces
+//! hand-rolled `until nc -z host port` loops with checks that also prove the service answers.
+
+use crate::common::{CheckResult, ComponentChecker, Dependency};
+use crate::k8s_init;
+use crate::lint;
+use std::time::Duration;
+
+/// Parse `--timeout`, e.g. `5m` or `90s`
+pub fn parse_timeout(value: &str) -> Result<Duration, String> {
+    lint::parse_duration(value).ok_or_else(|| format!("invalid duration '{}', expected e.g. 90s or 5m", value))
+}
+
+/// The first of `dependencies` that `checker`'s component does not have
+pub fn unsupported(checker: &dyn ComponentChecker, dependencies: &[Dependency]) -> Option<Dependency> {
+    dependencies.iter().copied().find(|dependency| !checker.dependencies().contains(dependency))
+}
+
+/// Check `dependencies` until all of them pass or `timeout` expires, returning the last result
+/// and the number of attempts made
+pub async fn wait_for(checker: &(dyn ComponentChecker + Send + Sync), dependencies: &[Dependency], timeout: Duration) -> (CheckResult, u32) {
+    k8s_init::run_until_ready(timeout, || async move {
+        let mut details = Vec::new();
+        for dependency in dependencies {
+            if let Some(result) = checker.check_dependency(*dependency).await {
+                details.extend(result.details);
+            }
+        }
+        CheckResult::from_details(details)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+    use crate::config::{ConfigParser, DatanodeStorageConfig};
+    use crate::datanode::DatanodeChecker;
+    use crate::metasrv::MetasrvChecker;
+    use crate::profile::Profile;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("Storage".parse::<Dependency>(), Ok(Dependency::Storage));
+        assert!("etcd".parse::<Dependency>().is_err());
+        assert_eq!(parse_timeout("5m"), Ok(Duration::from_secs(300)));
+        assert!(parse_timeout("soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = ConfigParser::default_datanode_config();
+        config.storage = Some(DatanodeStorageConfig {
+            storage_type: Some("File".to_string()),
+            data_home: Some(dir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        });
+        let checker = DatanodeChecker::new(config, Profile::QUICK);
+        assert_eq!(unsupported(&checker, &[Dependency::Metasrv, Dependency::Backend]), Some(Dependency::Backend));
+
+        let (result, attempts) = wait_for(&checker, &[Dependency::Storage], Duration::from_secs(1)).await;
+        assert!(result.success);
+        assert_eq!(attempts, 1);
+        assert!(result.details.iter().all(|detail| detail.status != CheckStatus::Fail));
+
+        let metasrv = MetasrvChecker::new(ConfigParser::default_metasrv_config(), Profile::STANDARD, 0);
+        assert_eq!(unsupported(&metasrv, &[Dependency::Backend]), None);
+    }
+}
@@ -0,0 +1,168 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Disk sizing of the local (raft-engine) WAL.
+//!
+//! The WAL grows until `purge_threshold` is reached and is only purged every `purge_interval`,
+//! in whole `file_size` segments, so it keeps growing by whatever is written until the next
+//! purge. A volume smaller than the threshold fills up before the first purge ever runs, so the
+//! worst-case usage is estimated from the settings and the expected ingest up front.
+
+use crate::common::CheckDetail;
+use crate::config::WalConfig;
+use crate::lint::{parse_duration, parse_size};
+use crate::logging::{existing_ancestor, format_bytes};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// GreptimeDB defaults of the raft-engine WAL
+const DEFAULT_FILE_SIZE: u64 = 128 << 20;
+const DEFAULT_PURGE_THRESHOLD: u64 = 1 << 30;
+const DEFAULT_PURGE_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_DATA_HOME: &str = "./greptimedb_data";
+
+/// Check that the volume of the local WAL directory can hold the WAL between purges, with
+/// `write_mib_per_sec` of ingest when it is known. Nothing is checked for a remote WAL.
+pub fn check_wal_disk(wal: Option<&WalConfig>, data_home: Option<&str>, write_mib_per_sec: Option<f64>) -> Option<CheckDetail> {
+    let default = WalConfig::default();
+    let wal = wal.unwrap_or(&default);
+    if wal.provider.as_deref().is_some_and(|provider| provider != "raft_engine") {
+        return None;
+    }
+
+    let dir = wal_dir(wal, data_home);
+    let existing = existing_ancestor(&dir)?;
+    let total = fs2::total_space(existing).ok()?;
+    let available = fs2::available_space(existing).ok()?;
+    Some(disk_detail(&dir, total, available, &worst_case_usage(wal, write_mib_per_sec)))
+}
+
+/// `wal.dir`, or `wal` under the data home as GreptimeDB does
+fn wal_dir(wal: &WalConfig, data_home: Option<&str>) -> PathBuf {
+    match wal.dir.as_deref().filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(data_home.unwrap_or(DEFAULT_DATA_HOME)).join("wal"),
+    }
+}
+
+/// Worst-case disk usage of the WAL between purges
+#[derive(Debug, PartialEq)]
+struct Usage {
+    threshold: u64,
+    usage: u64,
+    purge_interval: Duration,
+    /// Whether the ingest during a purge interval is part of `usage`
+    includes_ingest: bool,
+}
+
+/// The threshold, plus what is written until the next purge runs, plus the segment being
+/// written then, plus as many recycled segments again as the threshold when log recycling is on
+fn worst_case_usage(wal: &WalConfig, write_mib_per_sec: Option<f64>) -> Usage {
+    let file_size = wal.file_size.as_deref().and_then(parse_size).unwrap_or(DEFAULT_FILE_SIZE);
+    let threshold = wal.purge_threshold.as_deref().and_then(parse_size).unwrap_or(DEFAULT_PURGE_THRESHOLD);
+    let purge_interval = wal.purge_interval.as_deref().and_then(parse_duration).unwrap_or(DEFAULT_PURGE_INTERVAL);
+    let ingest = write_mib_per_sec.map(|rate| (rate * (1 << 20) as f64 * purge_interval.as_secs_f64()) as u64);
+    let mut usage = threshold.saturating_add(file_size).saturating_add(ingest.unwrap_or(0));
+    if wal.enable_log_recycle.unwrap_or(true) {
+        usage = usage.saturating_add(threshold);
+    }
+    Usage { threshold, usage, purge_interval, includes_ingest: ingest.is_some() }
+}
+
+fn disk_detail(dir: &Path, total: u64, available: u64, usage: &Usage) -> CheckDetail {
+    let Usage { threshold, usage: worst, purge_interval, includes_ingest } = *usage;
+    let ingest = if includes_ingest {
+        format!("including the ingest of a {:?} purge interval", purge_interval)
+    } else {
+        format!("plus what is written in a {:?} purge interval; set capacity.write_mib_per_sec in the settings to include it", purge_interval)
+    };
+    let item = "WAL Disk Usage".to_string();
+    if total < threshold {
+        CheckDetail::fail(
+            item,
+            format!(
+                "The volume of {} holds {}, less than wal.purge_threshold ({}); it fills up before the WAL is ever purged",
+                dir.display(),
+                format_bytes(total),
+                format_bytes(threshold)
+            ),
+            None,
+            Some("Lower wal.purge_threshold well below the volume size, or move wal.dir to a larger volume".to_string()),
+        )
+    } else if available < worst {
+        CheckDetail::warning(
+            item,
+            format!(
+                "{} free under {}, but the WAL may use up to {} between purges, {}",
+                format_bytes(available),
+                dir.display(),
+                format_bytes(worst),
+                ingest
+            ),
+            None,
+            Some("Free space on the volume, lower wal.purge_threshold or wal.purge_interval, or disable wal.enable_log_recycle".to_string()),
+        )
+    } else {
+        CheckDetail::pass(
+            item,
+            format!(
+                "{} free under {}, the WAL uses up to about {} between purges, {}",
+                format_bytes(available),
+                dir.display(),
+                format_bytes(worst),
+                ingest
+            ),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::CheckStatus;
+
+    #[test]
+    fn test_wal_disk() {
+        let wal = WalConfig {
+            file_size: Some("256MB".to_string()),
+            purge_threshold: Some("4GB".to_string()),
+            enable_log_recycle: Some(false),
+            purge_interval: Some("10m".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(worst_case_usage(&wal, None).usage, (4 << 30) + (256 << 20));
+        // 16 MiB/s for 10 minutes
+        assert_eq!(worst_case_usage(&wal, Some(16.0)).usage, (4 << 30) + (256 << 20) + (9600 << 20));
+        let default = worst_case_usage(&WalConfig::default(), Some(1.0));
+        assert_eq!((default.threshold, default.usage), (1 << 30, (2 << 30) + (128 << 20) + (60 << 20)));
+        assert_eq!(default.purge_interval, Duration::from_secs(60));
+        assert_eq!(wal_dir(&wal, Some("/var/lib/greptimedb")), PathBuf::from("/var/lib/greptimedb/wal"));
+
+        let dir = Path::new("/var/lib/greptimedb/wal");
+        let usage = worst_case_usage(&wal, None);
+        assert_eq!(disk_detail(dir, 2 << 30, 2 << 30, &usage).status, CheckStatus::Fail);
+        assert_eq!(disk_detail(dir, 100 << 30, 4 << 30, &usage).status, CheckStatus::Warning);
+        let detail = disk_detail(dir, 100 << 30, 50 << 30, &usage);
+        assert_eq!(detail.status, CheckStatus::Pass);
+        assert!(detail.message.contains("capacity.write_mib_per_sec"), "{}", detail.message);
+        // Ingest during the purge interval no longer fits
+        let usage = worst_case_usage(&wal, Some(100.0));
+        assert_eq!(disk_detail(dir, 100 << 30, 50 << 30, &usage).status, CheckStatus::Warning);
+
+        let kafka = WalConfig {
+            provider: Some("kafka".to_string()),
+            ..Default::default()
+        };
+        assert!(check_wal_disk(Some(&kafka), None, None).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Check plugins distributed as WebAssembly modules, run sandboxed in an interpreter.
+//!
+//! A module gets no imports at all, so no file system, network, clock or process access, and
+//! runs with a fuel (instruction) budget, a memory cap and the plugin's timeout. It implements a
+//! small ABI:
+//!
+//! - `memory`: the exported linear memory
+//! - `alloc(len: i32) -> i32`: a buffer of `len` bytes for the input
+//! - `check(ptr: i32, len: i32) -> i64`: takes the input JSON, the same document command
+//!   plugins read on stdin, and returns `(ptr << 32) | len` of the output JSON in memory
+
+use std::time::Duration;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Instructions a module may execute unless `fuel` is configured, a few seconds of interpreting
+const DEFAULT_FUEL: u64 = 1_000_000_000;
+
+/// Linear memory a module may grow to
+const MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Largest output accepted from a module
+const MAX_OUTPUT: usize = 1024 * 1024;
+
+/// Run the module at `path` on `input` and return its output JSON, failing after `timeout`.
+///
+/// The interpreter cannot be interrupted, so a module still running at the timeout is abandoned
+/// and its thread stops once the fuel runs out.
+pub async fn run(path: &str, input: String, fuel: Option<u64>, timeout: Duration) -> Result<String, String> {
+    let wasm = tokio::fs::read(path).await.map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let task = tokio::task::spawn_blocking(move || run_module(&wasm, &input, fuel.unwrap_or(DEFAULT_FUEL)));
+    match tokio::time::timeout(timeout, task).await {
+        Ok(result) => result.map_err(|e| format!("Plugin task failed: {}", e))?,
+        Err(_) => Err(format!("The module did not finish within {}s", timeout.as_secs())),
+    }
+}
+
+fn run_module(wasm: &[u8], input: &str, fuel: u64) -> Result<String, String> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, wasm).map_err(|e| format!("Invalid WebAssembly module: {}", e))?;
+
+    let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+    let mut store = Store::new(&engine, limits);
+    store.limiter(|limits| limits);
+    store.set_fuel(fuel).map_err(|e| e.to_string())?;
+
+    let linker = Linker::<StoreLimits>::new(&engine);
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .and_then(|instance| instance.start(&mut store))
+        .map_err(|e| format!("Cannot instantiate the module, which may not import anything: {}", e))?;
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or_else(|| "The module does not export `memory`".to_string())?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(|e| format!("The module does not export `alloc(i32) -> i32`: {}", e))?;
+    let check = instance
+        .get_typed_func::<(i32, i32), i64>(&store, "check")
+        .map_err(|e| format!("The module does not export `check(i32, i32) -> i64`: {}", e))?;
+
+    let len = i32::try_from(input.len()).map_err(|_| "The config is too large for a WebAssembly plugin".to_string())?;
+    let trapped = |store: &Store<StoreLimits>, e: wasmi::Error| {
+        if matches!(store.get_fuel(), Ok(0)) {
+            format!("The module ran out of fuel after {} instructions; raise `fuel` if it needs more", fuel)
+        } else {
+            format!("The module trapped: {}", e)
+        }
+    };
+    let ptr = alloc.call(&mut store, len).map_err(|e| trapped(&store, e))?;
+    memory
+        .write(&mut store, ptr as u32 as usize, input.as_bytes())
+        .map_err(|e| format!("`alloc` returned an invalid buffer: {}", e))?;
+    let packed = check.call(&mut store, (ptr, len)).map_err(|e| trapped(&store, e))? as u64;
+
+    let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    if out_len > MAX_OUTPUT {
+        return Err(format!("The module returned {} bytes, more than the 1 MiB allowed", out_len));
+    }
+    let mut output = vec![0; out_len];
+    memory
+        .read(&store, out_ptr, &mut output)
+        .map_err(|e| format!("`check` returned an invalid buffer: {}", e))?;
+    String::from_utf8(output).map_err(|_| "The module's output is not UTF-8".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Copies nothing and always answers with a fixed detail stored at offset 0
+    const FIXED: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (data (i32.const 0) "[{\"item\":\"Wasm\",\"status\":\"PASS\",\"message\":\"ok\"}]")
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "check") (param $ptr i32) (param $len i32) (result i64)
+    (i64.const 48)))
+"#;
+
+    /// Echoes its input back
+    const ECHO: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param $len i32) (result i32) (i32.const 16))
+  (func (export "check") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len)))))
+"#;
+
+    const SPIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param $len i32) (result i32) (i32.const 0))
+  (func (export "check") (param $ptr i32) (param $len i32) (result i64)
+    (loop $spin (br $spin))
+    (unreachable)))
+"#;
+
+    #[test]
+    fn test_run_module() {
+        let fixed = wat::parse_str(FIXED).unwrap();
+        assert_eq!(
+            run_module(&fixed, "{}", DEFAULT_FUEL).unwrap(),
+            r#"[{"item":"Wasm","status":"PASS","message":"ok"}]"#
+        );
+
+        let echo = wat::parse_str(ECHO).unwrap();
+        assert_eq!(run_module(&echo, r#"{"component":"datanode"}"#, DEFAULT_FUEL).unwrap(), r#"{"component":"datanode"}"#);
+
+        let spin = wat::parse_str(SPIN).unwrap();
+        let error = run_module(&spin, "{}", 10_000).unwrap_err();
+        assert!(error.contains("ran out of fuel"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_run_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spin.wasm");
+        std::fs::write(&path, wat::parse_str(SPIN).unwrap()).unwrap();
+        let error = run(path.to_str().unwrap(), "{}".to_string(), Some(u64::MAX), Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(error.contains("did not finish within"), "{}", error);
+    }
+
+    #[test]
+    fn test_module_with_imports_is_rejected() {
+        let wasi = wat::parse_str(r#"(module (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32))))"#).unwrap();
+        let error = run_module(&wasi, "{}", DEFAULT_FUEL).unwrap_err();
+        assert!(error.contains("may not import anything"), "{}", error);
+
+        assert!(run_module(b"not wasm", "{}", DEFAULT_FUEL).unwrap_err().starts_with("Invalid WebAssembly module"));
+    }
+}
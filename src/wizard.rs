@@ -0,0 +1,316 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interactive config setup for first-time users.
+//!
+//! Each group of answers is checked live before moving on, so a wrong endpoint or credential
+//! is caught while the user still remembers what they typed.
+
+use crate::common::{CheckResult, CheckStatus, ComponentChecker};
+use crate::config::{DatanodeConfig, DatanodeStorageConfig, MetasrvConfig, resolve_env_vars};
+use crate::datanode::DatanodeChecker;
+use crate::generate;
+use crate::metasrv::MetasrvChecker;
+use crate::net::NetChecker;
+use crate::profile::Profile;
+use crate::redact;
+use colored::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use snafu::ResultExt;
+use std::io::{BufRead, Write};
+
+/// Prompts on `output`, reads answers from `input`
+pub struct Wizard<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Wizard<R, W> {
+    /// Create a new Wizard reading answers from `input`
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Ask for every setting, then write the config to the path the user picks.
+    /// Returns the path written.
+    pub async fn run(&mut self) -> crate::error::Result<String> {
+        self.say("GreptimeDB config wizard. Press Enter to accept the [default].\n")?;
+        self.say("Secrets are only used for the checks and are not written; enter them as ${VAR} to read them from the environment.\n\n")?;
+
+        let component = self.choose("Component to configure", &["metasrv", "frontend", "datanode"], "datanode")?;
+        let content = match component.as_str() {
+            "metasrv" => self.metasrv().await?,
+            "frontend" => self.frontend().await?,
+            _ => self.datanode().await?,
+        };
+
+        let path = self.ask("Write the config to", Some(&format!("{}.toml", component)))?;
+        std::fs::write(&path, content).context(crate::error::FileSystemSnafu {
+            message: format!("Failed to write config: {}", path),
+        })?;
+        self.say(&format!("\nWrote {}. Check it again any time with: stepstone {} -c {}\n", path, component, path))?;
+        Ok(path)
+    }
+
+    async fn metasrv(&mut self) -> crate::error::Result<String> {
+        let backend = self.choose("Metadata backend", &["etcd", "postgres", "mysql"], "etcd")?;
+        let wal = self.choose("WAL provider", &["raft_engine", "kafka"], "raft_engine")?;
+        let mut config = generate::metasrv_config(&backend, generate::wal_config(&wal)?)?;
+
+        loop {
+            let addrs = self.ask("Backend endpoints, comma separated", Some(&config.store_addrs.join(",")))?;
+            config.store_addrs = split_list(&addrs);
+            let checked = resolved::<MetasrvConfig>(&config)?;
+            let result = MetasrvChecker::new(checked, Profile::STANDARD, 0).check().await;
+            if self.report(&result)? || !self.confirm("Re-enter the backend endpoints?", true)? {
+                break;
+            }
+        }
+
+        if let Some(wal) = config.wal.as_mut().filter(|wal| wal.provider.as_deref() == Some("kafka")) {
+            let brokers = self.endpoints("Kafka brokers", wal.broker_endpoints.clone().unwrap_or_default()).await?;
+            wal.broker_endpoints = Some(brokers);
+        }
+        config.grpc.get_or_insert_with(Default::default).server_addr =
+            Some(self.ask("Address other nodes reach this metasrv at", Some("127.0.0.1:3002"))?);
+        config.store_addrs = config.store_addrs.iter().map(|addr| generate::strip_password(addr)).collect();
+
+        generate::render_config("metasrv", &format!("backend: {}, WAL: {}", backend, wal), &config)
+    }
+
+    async fn frontend(&mut self) -> crate::error::Result<String> {
+        let mut config = generate::frontend_config();
+        if let Some(meta_client) = config.meta_client.as_mut() {
+            meta_client.metasrv_addrs = self.endpoints("Metasrv addresses", meta_client.metasrv_addrs.clone()).await?;
+        }
+
+        generate::render_config("frontend", "MySQL and PostgreSQL protocols enabled", &config)
+    }
+
+    async fn datanode(&mut self) -> crate::error::Result<String> {
+        let storage = self.choose("Storage", &["file", "s3", "oss", "azblob", "gcs"], "file")?;
+        let wal = self.choose("WAL provider", &["raft_engine", "kafka"], "raft_engine")?;
+        let mut config = generate::datanode_config(&storage, generate::wal_config(&wal)?)?;
+
+        let node_id = self.ask("Node ID, unique in the cluster", Some("0"))?;
+        config.node_id = node_id.parse().ok().or(config.node_id);
+        config.grpc.get_or_insert_with(Default::default).server_addr =
+            Some(self.ask("Address other nodes reach this datanode at", Some("127.0.0.1:3001"))?);
+
+        if let Some(storage) = config.storage.as_mut() {
+            loop {
+                for (name, value) in storage_fields(storage) {
+                    *value = Some(self.ask(name, value.as_deref())?);
+                }
+                let checked = resolved::<DatanodeStorageConfig>(storage)?;
+                let checker = DatanodeChecker::new(
+                    DatanodeConfig {
+                        storage: Some(checked),
+                        ..Default::default()
+                    },
+                    Profile::STANDARD,
+                );
+                let result = checker.check_object_storage().await;
+                if self.report(&result)? || !self.confirm("Re-enter the storage settings?", true)? {
+                    break;
+                }
+            }
+            generate::strip_storage_secrets(storage);
+        }
+
+        if let Some(meta_client) = config.meta_client.as_mut() {
+            meta_client.metasrv_addrs = self.endpoints("Metasrv addresses", meta_client.metasrv_addrs.clone()).await?;
+        }
+        if let Some(wal) = config.wal.as_mut().filter(|wal| wal.provider.as_deref() == Some("kafka")) {
+            let brokers = self.endpoints("Kafka brokers", wal.broker_endpoints.clone().unwrap_or_default()).await?;
+            wal.broker_endpoints = Some(brokers);
+        }
+
+        generate::render_config("datanode", &format!("storage: {}, WAL: {}", storage, wal), &config)
+    }
+
+    /// Ask for a list of `host:port` endpoints and check each is reachable
+    async fn endpoints(&mut self, question: &str, default: Vec<String>) -> crate::error::Result<Vec<String>> {
+        let mut endpoints = default;
+        loop {
+            endpoints = split_list(&self.ask(&format!("{}, comma separated", question), Some(&endpoints.join(",")))?);
+            let mut success = true;
+            for endpoint in &endpoints {
+                let result = NetChecker::new(endpoint.clone(), false).check().await;
+                success &= self.report(&result)?;
+            }
+            if success || !self.confirm(&format!("Re-enter the {}?", question.to_lowercase()), true)? {
+                return Ok(endpoints);
+            }
+        }
+    }
+
+    /// Print each check on one line and return whether all passed
+    fn report(&mut self, result: &CheckResult) -> crate::error::Result<bool> {
+        for detail in &redact::redact_result(result).details {
+            let status = match detail.status {
+                CheckStatus::Pass => "✓".green(),
+                CheckStatus::Fail => "✗".red(),
+                CheckStatus::Warning => "⚠".yellow(),
+                CheckStatus::Info => "ℹ".blue(),
+            };
+            self.say(&format!("  {} {} - {}\n", status, detail.item, detail.message))?;
+            if let Some(suggestion) = detail.suggestion.as_ref().filter(|_| detail.status != CheckStatus::Pass) {
+                self.say(&format!("    💡 {}\n", suggestion))?;
+            }
+        }
+        Ok(result.success)
+    }
+
+    fn ask(&mut self, question: &str, default: Option<&str>) -> crate::error::Result<String> {
+        match default.filter(|default| !default.is_empty()) {
+            Some(default) => self.say(&format!("{} [{}]: ", question.bold(), default))?,
+            None => self.say(&format!("{}: ", question.bold()))?,
+        }
+
+        let mut answer = String::new();
+        let read = self.input.read_line(&mut answer).context(crate::error::FileSystemSnafu {
+            message: "Failed to read answer".to_string(),
+        })?;
+        if read == 0 {
+            return crate::error::InvalidConfigSnafu {
+                message: "Input ended before the wizard finished".to_string(),
+            }
+            .fail();
+        }
+
+        let answer = answer.trim();
+        Ok(if answer.is_empty() { default.unwrap_or_default() } else { answer }.to_string())
+    }
+
+    fn choose(&mut self, question: &str, options: &[&str], default: &str) -> crate::error::Result<String> {
+        loop {
+            let answer = self.ask(&format!("{} ({})", question, options.join("/")), Some(default))?;
+            if options.contains(&answer.as_str()) {
+                return Ok(answer);
+            }
+            self.say(&format!("Please answer one of: {}\n", options.join(", ")))?;
+        }
+    }
+
+    fn confirm(&mut self, question: &str, default: bool) -> crate::error::Result<bool> {
+        let answer = self.ask(&format!("{} (y/n)", question), Some(if default { "y" } else { "n" }))?;
+        Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+    }
+
+    fn say(&mut self, text: &str) -> crate::error::Result<()> {
+        self.output
+            .write_all(text.as_bytes())
+            .and_then(|_| self.output.flush())
+            .context(crate::error::FileSystemSnafu {
+                message: "Failed to write prompt".to_string(),
+            })
+    }
+}
+
+/// The settings asked for each storage type, in prompt order
+fn storage_fields(storage: &mut DatanodeStorageConfig) -> Vec<(&'static str, &mut Option<String>)> {
+    match storage.storage_type.as_deref() {
+        Some("S3") => vec![
+            ("Bucket", &mut storage.bucket),
+            ("Root prefix", &mut storage.root),
+            ("Endpoint", &mut storage.endpoint),
+            ("Region", &mut storage.region),
+            ("Access key ID", &mut storage.access_key_id),
+            ("Secret access key", &mut storage.secret_access_key),
+        ],
+        Some("Oss") => vec![
+            ("Bucket", &mut storage.bucket),
+            ("Root prefix", &mut storage.root),
+            ("Endpoint", &mut storage.endpoint),
+            ("Access key ID", &mut storage.access_key_id),
+            ("Access key secret", &mut storage.access_key_secret),
+        ],
+        Some("Azblob") => vec![
+            ("Container", &mut storage.container),
+            ("Root prefix", &mut storage.root),
+            ("Endpoint", &mut storage.endpoint),
+            ("Account name", &mut storage.account_name),
+            ("Account key", &mut storage.account_key),
+        ],
+        Some("Gcs") => vec![
+            ("Bucket", &mut storage.bucket),
+            ("Root prefix", &mut storage.root),
+            ("Endpoint", &mut storage.endpoint),
+            ("Credential file", &mut storage.credential_path),
+        ],
+        _ => vec![("Data directory", &mut storage.data_home)],
+    }
+}
+
+/// Resolve `${VAR}` placeholders the same way loading the written file will
+fn resolved<T: Serialize + DeserializeOwned>(config: &T) -> crate::error::Result<T> {
+    let content = toml::to_string(config).context(crate::error::TomlSerializationSnafu {
+        message: "Failed to render config".to_string(),
+    })?;
+    let value: toml::Value = toml::from_str(&resolve_env_vars(&content)?).context(crate::error::TomlParsingSnafu {
+        message: "Failed to parse config".to_string(),
+    })?;
+    redact::register_config_secrets(&value);
+    value.try_into().context(crate::error::TomlParsingSnafu {
+        message: "Failed to parse config".to_string(),
+    })
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigParser, ParseOptions};
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_wizard_file_datanode() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_home = dir.path().join("data");
+        let output = dir.path().join("datanode.toml");
+        std::fs::create_dir(&data_home).unwrap();
+
+        // An invalid choice is asked again; the unreachable metasrv is kept on request
+        let answers = format!(
+            "datanode\nhdfs\nfile\n\n3\n\n{}\n127.0.0.1:1\nn\n{}\n",
+            data_home.display(),
+            output.display()
+        );
+        let mut prompts = Vec::new();
+        let path = Wizard::new(Cursor::new(answers), &mut prompts).run().await.unwrap();
+        assert_eq!(path, output.display().to_string());
+
+        let prompts = String::from_utf8(prompts).unwrap();
+        assert!(prompts.contains("Please answer one of: file, s3, oss, azblob, gcs"));
+
+        let config = ConfigParser::parse_datanode_config(&output, &ParseOptions::default()).unwrap();
+        assert_eq!(config.node_id, Some(3));
+        assert_eq!(config.storage.unwrap().data_home, Some(data_home.display().to_string()));
+        assert_eq!(config.meta_client.unwrap().metasrv_addrs, vec!["127.0.0.1:1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_wizard_input_ended() {
+        let mut prompts = Vec::new();
+        let result = Wizard::new(Cursor::new("frontend\n"), &mut prompts).run().await;
+        assert!(result.is_err());
+    }
+}